@@ -11,20 +11,23 @@ use crate::tag_utils::SwfSlice;
 
 #[cfg(test)]
 #[macro_use]
-mod test_utils;
+pub(crate) mod test_utils;
 
 pub mod activation;
 pub mod debug;
 pub mod error;
+mod external_interface;
 mod fscommand;
 pub mod function;
 pub mod globals;
+mod local_connection;
 pub mod object;
 mod property;
 mod scope;
 mod string;
 mod timer;
 mod value;
+mod xml_socket;
 
 #[cfg(test)]
 mod tests;
@@ -33,7 +36,9 @@ use crate::avm1::activation::{Activation, ActivationIdentifier};
 use crate::avm1::error::Error;
 use crate::avm1::globals::as_broadcaster;
 use crate::avm1::globals::as_broadcaster::BroadcasterFunctions;
+pub use external_interface::ExternalCallbacks;
 pub use globals::SystemPrototypes;
+pub use local_connection::LocalConnections;
 pub use object::script_object::ScriptObject;
 pub use object::sound_object::SoundObject;
 pub use object::stage_object::StageObject;
@@ -43,6 +48,7 @@ use smallvec::alloc::borrow::Cow;
 pub use string::AvmString;
 pub use timer::Timers;
 pub use value::Value;
+pub use xml_socket::XmlSockets;
 
 macro_rules! avm_debug {
     ($avm: expr, $($arg:tt)*) => (
@@ -54,13 +60,14 @@ macro_rules! avm_debug {
 
 #[macro_export]
 macro_rules! avm_warn {
-    ($activation: ident, $($arg:tt)*) => (
-        if cfg!(feature = "avm_debug") {
-            log::warn!("{} -- in {}", format!($($arg)*), $activation.id)
+    ($activation: ident, $($arg:tt)*) => ({
+        let message = if cfg!(feature = "avm_debug") {
+            format!("{} -- in {}", format!($($arg)*), $activation.id)
         } else {
-            log::warn!($($arg)*)
-        }
-    )
+            format!($($arg)*)
+        };
+        $activation.log_warning(&message);
+    })
 }
 
 #[macro_export]
@@ -391,6 +398,11 @@ impl<'gc> Avm1<'gc> {
         value
     }
 
+    /// The values on the operand stack, bottommost first.
+    pub fn stack(&self) -> &[Value<'gc>] {
+        &self.stack
+    }
+
     /// Obtain the value of `_global`.
     pub fn global_object(&self) -> Value<'gc> {
         Value::Object(self.globals)
@@ -439,7 +451,7 @@ pub fn root_error_handler<'gc>(activation: &mut Activation<'_, 'gc, '_>, error:
         let string = error
             .coerce_to_string(activation)
             .unwrap_or_else(|_| "undefined".into());
-        log::info!(target: "avm_trace", "{}", string);
+        activation.log_trace(&string);
     } else {
         log::error!("{}", error);
     }
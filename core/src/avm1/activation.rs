@@ -6,10 +6,13 @@ use crate::avm1::scope::Scope;
 use crate::avm1::{
     fscommand, globals, scope, skip_actions, start_drag, AvmString, ScriptObject, Value,
 };
+use crate::backend::log::{AvmVersion, LogSource};
 use crate::backend::navigator::{NavigationMethod, RequestOptions};
 use crate::context::UpdateContext;
+use crate::debugger::Debugger;
 use crate::display_object::{DisplayObject, MovieClip, TDisplayObject};
 use crate::ecma_conversions::f64_to_wrapping_u32;
+use crate::security::Security;
 use crate::tag_utils::SwfSlice;
 use crate::{avm_error, avm_warn};
 use enumset::EnumSet;
@@ -20,6 +23,7 @@ use std::borrow::Cow;
 use std::cell::{Ref, RefMut};
 use std::collections::HashMap;
 use std::fmt;
+use std::sync::Arc;
 use swf::avm1::read::Reader;
 use swf::avm1::types::{Action, CatchVar, Function, TryBlock};
 use url::form_urlencoded;
@@ -165,6 +169,14 @@ impl<'a> ActivationIdentifier<'a> {
     pub fn depth(&self) -> u16 {
         self.depth
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn parent(&self) -> Option<&ActivationIdentifier<'a>> {
+        self.parent
+    }
 }
 
 unsafe impl<'gc> gc_arena::Collect for ActivationIdentifier<'gc> {
@@ -426,6 +438,10 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         data: &SwfSlice,
         reader: &mut Reader<'_>,
     ) -> Result<FrameControl<'gc>, Error<'gc>> {
+        if self.context.debugger.is_some() && reader.pos() < (data.end - data.start) {
+            Debugger::before_action(self, data, reader.pos());
+        }
+
         if reader.pos() >= (data.end - data.start) {
             //Executing beyond the end of a function constitutes an implicit return.
             Ok(FrameControl::Return(ReturnType::Implicit))
@@ -1148,7 +1164,16 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             let url = url.to_string();
             match target[6..].parse::<u32>() {
                 Ok(level_id) => {
-                    let fetch = self.context.navigator.fetch(&url, RequestOptions::get());
+                    let movie = self
+                        .base_clip()
+                        .movie()
+                        .unwrap_or_else(|| self.context.swf.clone());
+                    let fetch = Security::fetch_movie(
+                        &mut self.context,
+                        &movie,
+                        &url,
+                        RequestOptions::get(),
+                    );
                     let level = self.resolve_level(level_id);
 
                     let process = self.context.load_manager.load_movie_into_clip(
@@ -1172,7 +1197,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         }
 
         if let Some(fscommand) = fscommand::parse(url) {
-            fscommand::handle(fscommand, self)?;
+            fscommand::handle(fscommand, target, self)?;
         } else {
             self.context
                 .navigator
@@ -1195,7 +1220,8 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         let url = url_val.coerce_to_string(self)?;
 
         if let Some(fscommand) = fscommand::parse(&url) {
-            fscommand::handle(fscommand, self)?;
+            let args = target.coerce_to_string(self)?;
+            fscommand::handle(fscommand, &args, self)?;
             return Ok(FrameControl::Continue);
         }
 
@@ -1222,7 +1248,11 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                     Cow::Borrowed(&url),
                     NavigationMethod::from_send_vars_method(swf_method),
                 );
-                let fetch = self.context.navigator.fetch(&url, opts);
+                let movie = self
+                    .base_clip()
+                    .movie()
+                    .unwrap_or_else(|| self.context.swf.clone());
+                let fetch = Security::fetch_data(&mut self.context, &movie, &url, opts);
                 let process = self.context.load_manager.load_form_into_object(
                     self.context.player.clone().unwrap(),
                     target_obj,
@@ -1239,7 +1269,11 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                     Cow::Borrowed(&url),
                     NavigationMethod::from_send_vars_method(swf_method),
                 );
-                let fetch = self.context.navigator.fetch(&url, opts);
+                let movie = self
+                    .base_clip()
+                    .movie()
+                    .unwrap_or_else(|| self.context.swf.clone());
+                let fetch = Security::fetch_movie(&mut self.context, &movie, &url, opts);
                 let process = self.context.load_manager.load_movie_into_clip(
                     self.context.player.clone().unwrap(),
                     clip_target,
@@ -1255,7 +1289,16 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             // target of `_level#` indicates a `loadMovieNum` call.
             match window_target[6..].parse::<u32>() {
                 Ok(level_id) => {
-                    let fetch = self.context.navigator.fetch(&url, RequestOptions::get());
+                    let movie = self
+                        .base_clip()
+                        .movie()
+                        .unwrap_or_else(|| self.context.swf.clone());
+                    let fetch = Security::fetch_movie(
+                        &mut self.context,
+                        &movie,
+                        &url,
+                        RequestOptions::get(),
+                    );
                     let level = self.resolve_level(level_id);
 
                     let process = self.context.load_manager.load_movie_into_clip(
@@ -1786,7 +1829,11 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         } else {
             avm_warn!(self, "SetTarget failed: {} not found", target);
             // TODO: Emulate AVM1 trace error message.
-            log::info!(target: "avm_trace", "Target not found: Target=\"{}\" Base=\"{}\"", target, base_clip.path());
+            self.log_trace(&format!(
+                "Target not found: Target=\"{}\" Base=\"{}\"",
+                target,
+                base_clip.path()
+            ));
 
             // When SetTarget has an invalid target, subsequent GetVariables act
             // as if they are targeting root, but subsequent Play/Stop/etc.
@@ -2043,7 +2090,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         } else {
             val.coerce_to_string(self)?
         };
-        log::info!(target: "avm_trace", "{}", out);
+        self.log_trace(&out);
         Ok(FrameControl::Continue)
     }
 
@@ -2057,12 +2104,15 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
 
     fn action_wait_for_frame(
         &mut self,
-        _frame: u16,
+        frame: u16,
         num_actions_to_skip: u8,
         r: &mut Reader<'_>,
     ) -> Result<FrameControl<'gc>, Error<'gc>> {
-        // TODO(Herschel): Always true for now.
-        let loaded = true;
+        let loaded = match self.target_clip().and_then(|clip| clip.as_movie_clip()) {
+            // The frame is 0-based, not 1-based.
+            Some(clip) => Self::is_frame_loaded(clip, i32::from(frame) + 1),
+            None => true,
+        };
         if !loaded {
             // Note that the offset is given in # of actions, NOT in bytes.
             // Read the actions and toss them away.
@@ -2076,9 +2126,16 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         num_actions_to_skip: u8,
         r: &mut Reader<'_>,
     ) -> Result<FrameControl<'gc>, Error<'gc>> {
-        // TODO(Herschel): Always true for now.
-        let _frame_num = self.context.avm1.pop().coerce_to_f64(self)? as u16;
-        let loaded = true;
+        // Param can either be a frame number or a frame label.
+        let frame = self.context.avm1.pop();
+        let loaded = match self.target_clip().and_then(|clip| clip.as_movie_clip()) {
+            Some(clip) => match globals::movie_clip::resolve_frame(clip, self, frame)? {
+                Some((clip, frame)) => Self::is_frame_loaded(clip, frame),
+                // Labels are only known once their frame has loaded.
+                None => clip.frames_loaded() >= clip.total_frames(),
+            },
+            None => true,
+        };
         if !loaded {
             // Note that the offset is given in # of actions, NOT in bytes.
             // Read the actions and toss them away.
@@ -2087,6 +2144,13 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         Ok(FrameControl::Continue)
     }
 
+    /// Whether the given 1-based frame of `clip` has loaded.
+    ///
+    /// Frames past the end are loaded once the whole clip has.
+    fn is_frame_loaded(clip: MovieClip<'gc>, frame: i32) -> bool {
+        frame.min(i32::from(clip.total_frames())) <= i32::from(clip.frames_loaded())
+    }
+
     #[allow(unused_variables)]
     fn action_throw(&mut self) -> Result<FrameControl<'gc>, Error<'gc>> {
         let value = self.context.avm1.pop();
@@ -2106,7 +2170,9 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             // Undefined/null with is ignored.
             Value::Undefined | Value::Null => {
                 // Mimic Flash's error output.
-                log::info!(target: "avm_trace", "Error: A 'with' action failed because the specified object did not exist.\n");
+                self.log_trace(
+                    "Error: A 'with' action failed because the specified object did not exist.\n",
+                );
                 Ok(FrameControl::Continue)
             }
 
@@ -2422,6 +2488,22 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             };
         }
 
+        // Movies may only reach into other movies that allow it.
+        let accessor = self.base_clip().movie();
+        let target = object.as_display_object().and_then(|o| o.movie());
+        if let (Some(accessor), Some(target)) = (accessor, target) {
+            if !Arc::ptr_eq(&accessor, &target)
+                && !self.context.security.can_script(&accessor, &target)
+            {
+                avm_warn!(
+                    self,
+                    "Security sandbox does not permit scripting {}",
+                    target.url().unwrap_or("<unknown>")
+                );
+                return Ok(None);
+            }
+        }
+
         Ok(Some(object))
     }
 
@@ -2733,6 +2815,36 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         self.base_clip
     }
 
+    /// Where log output of this activation comes from.
+    fn log_source(&self) -> LogSource {
+        LogSource {
+            movie_url: self
+                .base_clip
+                .movie()
+                .and_then(|movie| movie.url().map(str::to_owned)),
+            avm: AvmVersion::Avm1,
+            frame: self
+                .base_clip
+                .as_movie_clip()
+                .map(|clip| clip.current_frame()),
+        }
+    }
+
+    /// Output a message as if it was passed to `trace`.
+    pub fn log_trace(&mut self, message: &str) {
+        let source = self.log_source();
+        self.context.log.trace(&source, message);
+        if let Some(debugger) = self.context.debugger.as_mut() {
+            debugger.trace(message);
+        }
+    }
+
+    /// Report invalid or unsupported ActionScript to the log backend.
+    pub fn log_warning(&mut self, message: &str) {
+        let source = self.log_source();
+        self.context.log.avm_warning(&source, message);
+    }
+
     /// Gets the current target clip of this stack frame.
     /// This is the movie clip to which `GotoFrame` and other actions apply.
     /// Changed via `ActionSetTarget`/`ActionSetTarget2`.
@@ -0,0 +1,246 @@
+//! Callbacks exposed to the host with `ExternalInterface.addCallback`, and
+//! conversion of AVM1 values to and from the form they take outside the
+//! player.
+
+use crate::avm1::{
+    Activation, ActivationIdentifier, AvmString, Object, ScriptObject, TObject, Value,
+};
+use crate::backend::external_interface::ExternalValue;
+use crate::context::UpdateContext;
+use enumset::EnumSet;
+use gc_arena::Collect;
+use std::collections::{BTreeMap, HashMap};
+
+/// The callbacks that the movie exposed to the host, keyed by name.
+pub struct ExternalCallbacks<'gc> {
+    callbacks: HashMap<String, Callback<'gc>>,
+}
+
+#[derive(Clone, Collect)]
+#[collect(no_drop)]
+struct Callback<'gc> {
+    /// The `this` object the method is invoked on.
+    this: Value<'gc>,
+
+    method: Object<'gc>,
+}
+
+impl<'gc> ExternalCallbacks<'gc> {
+    pub fn new() -> Self {
+        Self {
+            callbacks: HashMap::new(),
+        }
+    }
+
+    /// Expose `method` to the host under `name`, replacing any callback that
+    /// already had that name.
+    ///
+    /// Returns `false`, leaving the callbacks unchanged, if the host refuses
+    /// the callback.
+    pub fn add_callback(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        name: String,
+        this: Value<'gc>,
+        method: Object<'gc>,
+    ) -> bool {
+        if !context.external_interface.on_callback_available(&name) {
+            return false;
+        }
+        context
+            .external_callbacks
+            .callbacks
+            .insert(name, Callback { this, method });
+        true
+    }
+
+    /// Invoke the callback the movie exposed under `name`.
+    ///
+    /// Returns `None` if there is no such callback.
+    pub fn call_callback(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        name: &str,
+        args: &[ExternalValue],
+    ) -> Option<ExternalValue> {
+        let callback = context.external_callbacks.callbacks.get(name)?.clone();
+
+        let version = context.swf.header().version;
+        let globals = context.avm1.global_object_cell();
+        let level0 = context.levels.get(&0).copied()?;
+
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[ExternalInterface]"),
+            version,
+            globals,
+            level0,
+        );
+
+        let args: Vec<_> = args
+            .iter()
+            .map(|arg| from_external_value(&mut activation, arg))
+            .collect();
+        let this = callback.this.coerce_to_object(&mut activation);
+        let result = callback
+            .method
+            .call(name, &mut activation, this, None, &args)
+            .unwrap_or(Value::Undefined);
+
+        Some(to_external_value(&mut activation, &result, 0))
+    }
+}
+
+impl Default for ExternalCallbacks<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<'gc> Collect for ExternalCallbacks<'gc> {
+    fn trace(&self, cc: gc_arena::CollectionContext) {
+        for callback in self.callbacks.values() {
+            callback.trace(cc);
+        }
+    }
+}
+
+/// The deepest level of nested objects that will leave the player.
+const MAX_DEPTH: usize = 32;
+
+/// Convert an AVM1 value into a form that can be passed to the host, or sent
+/// to another player.
+///
+/// Functions can't be passed and become `undefined`, as do cyclic references
+/// past `MAX_DEPTH`.
+pub fn to_external_value<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: &Value<'gc>,
+    depth: usize,
+) -> ExternalValue {
+    match value {
+        Value::Undefined => ExternalValue::Undefined,
+        Value::Null => ExternalValue::Null,
+        Value::Bool(b) => ExternalValue::Bool(*b),
+        Value::Number(n) => ExternalValue::Number(*n),
+        Value::String(s) => ExternalValue::String(s.to_string()),
+        Value::Object(object) => {
+            if depth >= MAX_DEPTH || object.as_executable().is_some() {
+                return ExternalValue::Undefined;
+            }
+
+            let array_proto = activation.context.avm1.prototypes.array;
+            if object
+                .is_instance_of(activation, *object, array_proto)
+                .unwrap_or_default()
+            {
+                let elements = object.array();
+                ExternalValue::List(
+                    elements
+                        .iter()
+                        .map(|element| to_external_value(activation, element, depth + 1))
+                        .collect(),
+                )
+            } else {
+                let mut properties = BTreeMap::new();
+                for key in object.get_keys(activation) {
+                    if let Ok(property) = object.get(&key, activation) {
+                        let property = to_external_value(activation, &property, depth + 1);
+                        properties.insert(key, property);
+                    }
+                }
+                ExternalValue::Object(properties)
+            }
+        }
+    }
+}
+
+/// Convert a value received from the host or another player into an AVM1
+/// value.
+pub fn from_external_value<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    value: &ExternalValue,
+) -> Value<'gc> {
+    match value {
+        ExternalValue::Undefined => Value::Undefined,
+        ExternalValue::Null => Value::Null,
+        ExternalValue::Bool(b) => Value::Bool(*b),
+        ExternalValue::Number(n) => Value::Number(*n),
+        ExternalValue::String(s) => AvmString::new(activation.context.gc_context, s.clone()).into(),
+        ExternalValue::List(elements) => {
+            let array = ScriptObject::array(
+                activation.context.gc_context,
+                Some(activation.context.avm1.prototypes.array),
+            );
+            for (i, element) in elements.iter().enumerate() {
+                let element = from_external_value(activation, element);
+                array.set_array_element(i, element, activation.context.gc_context);
+            }
+            array.into()
+        }
+        ExternalValue::Object(properties) => {
+            let object = ScriptObject::object(
+                activation.context.gc_context,
+                Some(activation.context.avm1.prototypes.object),
+            );
+            for (key, property) in properties {
+                let property = from_external_value(activation, property);
+                object.define_value(
+                    activation.context.gc_context,
+                    key,
+                    property,
+                    EnumSet::empty(),
+                );
+            }
+            object.into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::error::Error;
+    use crate::avm1::test_utils::with_avm_and_external_interface;
+    use crate::backend::external_interface::{
+        FunctionExternalInterfaceProvider, NullExternalInterfaceProvider,
+    };
+
+    #[test]
+    fn add_callback_refused_by_host() {
+        let mut provider = NullExternalInterfaceProvider::new();
+        with_avm_and_external_interface(
+            8,
+            &mut provider,
+            |activation, root| -> Result<(), Error> {
+                let context = &mut activation.context;
+                assert!(!ExternalCallbacks::add_callback(
+                    context,
+                    "f".to_string(),
+                    Value::Undefined,
+                    root
+                ));
+                assert!(context.external_callbacks.callbacks.is_empty());
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn add_callback_accepted_by_host() {
+        let mut provider = FunctionExternalInterfaceProvider::new();
+        with_avm_and_external_interface(
+            8,
+            &mut provider,
+            |activation, root| -> Result<(), Error> {
+                let context = &mut activation.context;
+                assert!(ExternalCallbacks::add_callback(
+                    context,
+                    "f".to_string(),
+                    Value::Undefined,
+                    root
+                ));
+                assert!(context.external_callbacks.callbacks.contains_key("f"));
+                Ok(())
+            },
+        );
+    }
+}
@@ -2,7 +2,6 @@
 
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
-use crate::avm_warn;
 
 /// Parse an FSCommand URL.
 pub fn parse(url: &str) -> Option<&str> {
@@ -14,13 +13,17 @@ pub fn parse(url: &str) -> Option<&str> {
     }
 }
 
-/// TODO: FSCommand URL handling
+/// Queue an FSCommand to be handled by the player once the current update is
+/// done.
 pub fn handle<'gc>(
     fscommand: &str,
+    args: &str,
     activation: &mut Activation<'_, 'gc, '_>,
 ) -> Result<(), Error<'gc>> {
-    avm_warn!(activation, "Unhandled FSCommand: {}", fscommand);
+    activation
+        .context
+        .fs_commands
+        .push((fscommand.to_string(), args.to_string()));
 
-    //This should be an error.
     Ok(())
 }
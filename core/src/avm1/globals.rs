@@ -19,9 +19,11 @@ pub(crate) mod context_menu;
 pub(crate) mod context_menu_item;
 pub(crate) mod display_object;
 pub(crate) mod error;
+mod external_interface;
 mod function;
 mod key;
 mod load_vars;
+mod local_connection;
 mod math;
 mod matrix;
 pub(crate) mod mouse;
@@ -42,6 +44,7 @@ pub(crate) mod system_security;
 pub(crate) mod text_field;
 mod text_format;
 mod xml;
+mod xml_socket;
 
 pub fn random<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
@@ -313,6 +316,7 @@ pub struct SystemPrototypes<'gc> {
     pub array: Object<'gc>,
     pub array_constructor: Object<'gc>,
     pub xml_node: Object<'gc>,
+    pub xml_constructor: Object<'gc>,
     pub string: Object<'gc>,
     pub number: Object<'gc>,
     pub boolean: Object<'gc>,
@@ -366,6 +370,8 @@ pub fn create_globals<'gc>(
         xml::create_xmlnode_proto(gc_context, object_proto, function_proto);
 
     let xml_proto: Object<'gc> = xml::create_xml_proto(gc_context, xmlnode_proto, function_proto);
+    let xml_socket_proto: Object<'gc> =
+        xml_socket::create_proto(gc_context, object_proto, function_proto);
 
     let string_proto: Object<'gc> = string::create_proto(gc_context, object_proto, function_proto);
     let number_proto: Object<'gc> = number::create_proto(gc_context, object_proto, function_proto);
@@ -373,6 +379,8 @@ pub fn create_globals<'gc>(
         boolean::create_proto(gc_context, object_proto, function_proto);
     let load_vars_proto: Object<'gc> =
         load_vars::create_proto(gc_context, object_proto, function_proto);
+    let local_connection_proto: Object<'gc> =
+        local_connection::create_proto(gc_context, object_proto, function_proto);
     let matrix_proto: Object<'gc> = matrix::create_proto(gc_context, object_proto, function_proto);
     let point_proto: Object<'gc> = point::create_proto(gc_context, object_proto, function_proto);
     let rectangle_proto: Object<'gc> =
@@ -435,6 +443,12 @@ pub fn create_globals<'gc>(
         Some(function_proto),
         load_vars_proto,
     );
+    let local_connection = FunctionObject::constructor(
+        gc_context,
+        Executable::Native(local_connection::constructor),
+        Some(function_proto),
+        local_connection_proto,
+    );
     let movie_clip = FunctionObject::constructor(
         gc_context,
         Executable::Native(movie_clip::constructor),
@@ -473,6 +487,12 @@ pub fn create_globals<'gc>(
         Some(function_proto),
         xml_proto,
     );
+    let xml_socket = FunctionObject::constructor(
+        gc_context,
+        Executable::Native(xml_socket::constructor),
+        Some(function_proto),
+        xml_socket_proto,
+    );
     let string = string::create_string_object(gc_context, string_proto, Some(function_proto));
     let number = number::create_number_object(gc_context, number_proto, Some(function_proto));
     let boolean = boolean::create_boolean_object(gc_context, boolean_proto, Some(function_proto));
@@ -485,7 +505,21 @@ pub fn create_globals<'gc>(
     let rectangle =
         rectangle::create_rectangle_object(gc_context, rectangle_proto, Some(function_proto));
 
+    let external = ScriptObject::object(gc_context, Some(object_proto));
+    let external_interface = external_interface::create_external_interface_object(
+        gc_context,
+        object_proto,
+        function_proto,
+    );
+
     flash.define_value(gc_context, "geom", geom.into(), EnumSet::empty());
+    flash.define_value(gc_context, "external", external.into(), EnumSet::empty());
+    external.define_value(
+        gc_context,
+        "ExternalInterface",
+        external_interface.into(),
+        EnumSet::empty(),
+    );
     geom.define_value(gc_context, "Matrix", matrix.into(), EnumSet::empty());
     geom.define_value(gc_context, "Point", point.into(), EnumSet::empty());
     geom.define_value(gc_context, "Rectangle", rectangle.into(), EnumSet::empty());
@@ -517,6 +551,12 @@ pub fn create_globals<'gc>(
     globals.define_value(gc_context, "Object", object.into(), DontEnum.into());
     globals.define_value(gc_context, "Function", function.into(), DontEnum.into());
     globals.define_value(gc_context, "LoadVars", load_vars.into(), DontEnum.into());
+    globals.define_value(
+        gc_context,
+        "LocalConnection",
+        local_connection.into(),
+        DontEnum.into(),
+    );
     globals.define_value(gc_context, "MovieClip", movie_clip.into(), DontEnum.into());
     globals.define_value(
         gc_context,
@@ -534,6 +574,7 @@ pub fn create_globals<'gc>(
     );
     globals.define_value(gc_context, "XMLNode", xmlnode.into(), DontEnum.into());
     globals.define_value(gc_context, "XML", xml.into(), DontEnum.into());
+    globals.define_value(gc_context, "XMLSocket", xml_socket.into(), DontEnum.into());
     globals.define_value(gc_context, "String", string.into(), DontEnum.into());
     globals.define_value(gc_context, "Number", number.into(), DontEnum.into());
     globals.define_value(gc_context, "Boolean", boolean.into(), DontEnum.into());
@@ -735,6 +776,7 @@ pub fn create_globals<'gc>(
             array: array_proto,
             array_constructor: array,
             xml_node: xmlnode_proto,
+            xml_constructor: xml,
             string: string_proto,
             number: number_proto,
             boolean: boolean_proto,
@@ -0,0 +1,123 @@
+//! AVM1 ExternalInterface object
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::external_interface::{from_external_value, to_external_value, ExternalCallbacks};
+use crate::avm1::function::{Executable, FunctionObject};
+use crate::avm1::property::Attribute;
+use crate::avm1::{Object, ScriptObject, TObject, Value};
+use gc_arena::MutationContext;
+
+/// Implements `ExternalInterface`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    // No-op constructor
+    Ok(Value::Undefined)
+}
+
+pub fn create_external_interface_object<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    use Attribute::*;
+
+    let prototype = ScriptObject::object(gc_context, Some(proto));
+    let external_interface = FunctionObject::constructor(
+        gc_context,
+        Executable::Native(constructor),
+        Some(fn_proto),
+        prototype.into(),
+    );
+    let mut object = external_interface.as_script_object().unwrap();
+
+    object.add_property(
+        gc_context,
+        "available",
+        FunctionObject::function(
+            gc_context,
+            Executable::Native(get_available),
+            Some(fn_proto),
+            fn_proto,
+        ),
+        None,
+        DontDelete | DontEnum | ReadOnly,
+    );
+
+    object.force_set_function(
+        "addCallback",
+        add_callback,
+        gc_context,
+        DontDelete | DontEnum | ReadOnly,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "call",
+        call,
+        gc_context,
+        DontDelete | DontEnum | ReadOnly,
+        Some(fn_proto),
+    );
+
+    external_interface
+}
+
+fn get_available<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(activation.context.external_interface.available().into())
+}
+
+fn add_callback<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if args.len() < 3 || !activation.context.external_interface.available() {
+        return Ok(false.into());
+    }
+
+    let name = args[0].coerce_to_string(activation)?.to_string();
+    let method = match &args[2] {
+        Value::Object(method) if method.as_executable().is_some() => *method,
+        _ => return Ok(false.into()),
+    };
+
+    Ok(
+        ExternalCallbacks::add_callback(&mut activation.context, name, args[1].clone(), method)
+            .into(),
+    )
+}
+
+fn call<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let name = match args.get(0) {
+        Some(name) => name.coerce_to_string(activation)?.to_string(),
+        None => return Ok(Value::Null),
+    };
+
+    let external_args: Vec<_> = args
+        .get(1..)
+        .unwrap_or_default()
+        .iter()
+        .map(|arg| to_external_value(activation, arg, 0))
+        .collect();
+
+    match activation
+        .context
+        .external_interface
+        .call(&name, &external_args)
+    {
+        Some(result) => Ok(from_external_value(activation, &result)),
+        None => Ok(Value::Null),
+    }
+}
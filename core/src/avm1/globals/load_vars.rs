@@ -7,6 +7,8 @@ use crate::avm1::property::Attribute;
 use crate::avm1::{AvmString, Object, ScriptObject, TObject, Value};
 use crate::avm_warn;
 use crate::backend::navigator::{NavigationMethod, RequestOptions};
+use crate::display_object::TDisplayObject;
+use crate::security::Security;
 use gc_arena::MutationContext;
 use std::borrow::Cow;
 
@@ -330,7 +332,11 @@ fn spawn_load_var_fetch<'gc>(
         (Cow::Borrowed(url.as_str()), RequestOptions::get())
     };
 
-    let fetch = activation.context.navigator.fetch(&url, request_options);
+    let movie = activation
+        .base_clip()
+        .movie()
+        .unwrap_or_else(|| activation.context.swf.clone());
+    let fetch = Security::fetch_data(&mut activation.context, &movie, &url, request_options);
     let process = activation.context.load_manager.load_form_into_load_vars(
         activation.context.player.clone().unwrap(),
         loader_object,
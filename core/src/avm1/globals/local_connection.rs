@@ -0,0 +1,129 @@
+//! AVM1 LocalConnection object
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::local_connection::LocalConnections;
+use crate::avm1::property::Attribute;
+use crate::avm1::{AvmString, Object, ScriptObject, Value};
+use crate::backend::navigator::url_domain;
+use crate::display_object::TDisplayObject;
+use gc_arena::MutationContext;
+
+/// Implements `LocalConnection`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    // No-op constructor
+    Ok(Value::Undefined)
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    use Attribute::*;
+
+    let mut object = ScriptObject::object(gc_context, Some(proto));
+
+    object.force_set_function(
+        "connect",
+        connect,
+        gc_context,
+        DontDelete | DontEnum | ReadOnly,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "send",
+        send,
+        gc_context,
+        DontDelete | DontEnum | ReadOnly,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "close",
+        close,
+        gc_context,
+        DontDelete | DontEnum | ReadOnly,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "domain",
+        domain,
+        gc_context,
+        DontDelete | DontEnum | ReadOnly,
+        Some(fn_proto),
+    );
+
+    object.into()
+}
+
+/// Returns the domain of the movie that the currently executing code belongs to.
+fn movie_domain(activation: &mut Activation<'_, '_, '_>) -> String {
+    let movie = activation
+        .base_clip()
+        .movie()
+        .unwrap_or_else(|| activation.context.swf.clone());
+    url_domain(movie.url())
+}
+
+fn connect<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let name = match args.get(0) {
+        Some(Value::String(name)) => name.to_string(),
+        _ => return Ok(false.into()),
+    };
+
+    let domain = movie_domain(activation);
+    Ok(LocalConnections::connect(&mut activation.context, this, &name, &domain).into())
+}
+
+fn send<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let (connection_name, method_name) = match (args.get(0), args.get(1)) {
+        (Some(Value::String(connection_name)), Some(Value::String(method_name))) => {
+            (connection_name.to_string(), method_name.to_string())
+        }
+        _ => return Ok(false.into()),
+    };
+
+    let domain = movie_domain(activation);
+    let connection_name = LocalConnections::qualify_name(&connection_name, &domain);
+    let args = args.get(2..).unwrap_or_default().to_vec();
+    let queued =
+        activation
+            .context
+            .local_connections
+            .send(this, connection_name, method_name, domain, args);
+
+    Ok(queued.into())
+}
+
+fn close<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    LocalConnections::close(&mut activation.context, this);
+    Ok(Value::Undefined)
+}
+
+fn domain<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let domain = movie_domain(activation);
+    Ok(AvmString::new(activation.context.gc_context, domain).into())
+}
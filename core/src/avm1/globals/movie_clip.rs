@@ -12,6 +12,7 @@ use crate::backend::navigator::NavigationMethod;
 use crate::display_object::{DisplayObject, EditText, MovieClip, TDisplayObject};
 use crate::ecma_conversions::f64_to_wrapping_i32;
 use crate::prelude::*;
+use crate::security::Security;
 use crate::shape_utils::DrawCommand;
 use crate::tag_utils::SwfSlice;
 use gc_arena::MutationContext;
@@ -633,21 +634,19 @@ pub fn duplicate_movie_clip_with_bias<'gc>(
 }
 
 fn get_bytes_loaded<'gc>(
-    _movie_clip: MovieClip<'gc>,
+    movie_clip: MovieClip<'gc>,
     _activation: &mut Activation<'_, 'gc, '_>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    // TODO find a correct value
-    Ok(1.0.into())
+    Ok(movie_clip.bytes_loaded().into())
 }
 
 fn get_bytes_total<'gc>(
-    _movie_clip: MovieClip<'gc>,
+    movie_clip: MovieClip<'gc>,
     _activation: &mut Activation<'_, 'gc, '_>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    // TODO find a correct value
-    Ok(1.0.into())
+    Ok(movie_clip.bytes_total().into())
 }
 
 fn get_next_highest_depth<'gc>(
@@ -692,9 +691,28 @@ pub fn goto_frame<'gc>(
     stop: bool,
     scene_offset: u16,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let mut call_frame = None;
+    let frame = args.get(0).cloned().unwrap_or(Value::Undefined);
+    if let Some((clip, mut frame)) = resolve_frame(movie_clip, activation, frame)? {
+        frame = frame.wrapping_sub(1);
+        frame = frame.wrapping_add(i32::from(scene_offset));
+        frame = frame.saturating_add(1);
+        if frame > 0 {
+            clip.goto_frame(&mut activation.context, frame as u16, stop);
+        }
+    }
+    Ok(Value::Undefined)
+}
 
-    match args.get(0).cloned().unwrap_or(Value::Undefined) {
+/// Resolves a frame number, frame label or `clip:frame` path to a clip and
+/// a 1-based frame of that clip.
+///
+/// Returns `None` if no clip has such a frame.
+pub fn resolve_frame<'gc>(
+    movie_clip: MovieClip<'gc>,
+    activation: &mut Activation<'_, 'gc, '_>,
+    frame: Value<'gc>,
+) -> Result<Option<(MovieClip<'gc>, i32)>, Error<'gc>> {
+    match frame {
         // A direct goto only runs if n is an integer
         Value::Number(n) if n.fract() == 0.0 => {
             // Frame #
@@ -704,7 +722,7 @@ pub fn goto_frame<'gc>(
             // TODO: -1 +1 here to match Flash's behavior.
             // We probably want to change our frame representation to 0-based.
             // Scene offset is only used by GotoFrame2 global opcode.
-            call_frame = Some((movie_clip, f64_to_wrapping_i32(n)));
+            Ok(Some((movie_clip, f64_to_wrapping_i32(n))))
         }
         val => {
             // Coerce to string and search for a frame label.
@@ -716,25 +734,16 @@ pub fn goto_frame<'gc>(
                 if let Some(clip) = clip.as_display_object().and_then(|o| o.as_movie_clip()) {
                     if let Ok(frame) = frame.parse().map(f64_to_wrapping_i32) {
                         // First try to parse as a frame number.
-                        call_frame = Some((clip, frame));
+                        return Ok(Some((clip, frame)));
                     } else if let Some(frame) = clip.frame_label_to_number(&frame) {
                         // Otherwise, it's a frame label.
-                        call_frame = Some((clip, frame as i32));
+                        return Ok(Some((clip, frame as i32)));
                     }
                 }
             }
+            Ok(None)
         }
     }
-
-    if let Some((clip, mut frame)) = call_frame {
-        frame = frame.wrapping_sub(1);
-        frame = frame.wrapping_add(i32::from(scene_offset));
-        frame = frame.saturating_add(1);
-        if frame > 0 {
-            clip.goto_frame(&mut activation.context, frame as u16, stop);
-        }
-    }
-    Ok(Value::Undefined)
 }
 
 fn next_frame<'gc>(
@@ -973,7 +982,11 @@ pub fn get_url<'gc>(
     if let Some(url_val) = args.get(0) {
         let url = url_val.coerce_to_string(activation)?;
         if let Some(fscommand) = fscommand::parse(&url) {
-            fscommand::handle(fscommand, activation);
+            let fscommand_args = match args.get(1) {
+                Some(fscommand_args) => fscommand_args.coerce_to_string(activation)?.to_string(),
+                None => String::new(),
+            };
+            fscommand::handle(fscommand, &fscommand_args, activation);
             return Ok(Value::Undefined);
         }
 
@@ -1041,7 +1054,11 @@ fn load_movie<'gc>(
     let method = args.get(1).cloned().unwrap_or(Value::Undefined);
     let method = NavigationMethod::from_method_str(&method.coerce_to_string(activation)?);
     let (url, opts) = activation.locals_into_request_options(Cow::Borrowed(&url), method);
-    let fetch = activation.context.navigator.fetch(&url, opts);
+    let movie = activation
+        .base_clip()
+        .movie()
+        .unwrap_or_else(|| activation.context.swf.clone());
+    let fetch = Security::fetch_movie(&mut activation.context, &movie, &url, opts);
     let process = activation.context.load_manager.load_movie_into_clip(
         activation.context.player.clone().unwrap(),
         DisplayObject::MovieClip(target),
@@ -1065,7 +1082,11 @@ fn load_variables<'gc>(
     let method = args.get(1).cloned().unwrap_or(Value::Undefined);
     let method = NavigationMethod::from_method_str(&method.coerce_to_string(activation)?);
     let (url, opts) = activation.locals_into_request_options(Cow::Borrowed(&url), method);
-    let fetch = activation.context.navigator.fetch(&url, opts);
+    let movie = activation
+        .base_clip()
+        .movie()
+        .unwrap_or_else(|| activation.context.swf.clone());
+    let fetch = Security::fetch_data(&mut activation.context, &movie, &url, opts);
     let target = target.object().coerce_to_object(activation);
     let process = activation.context.load_manager.load_form_into_object(
         activation.context.player.clone().unwrap(),
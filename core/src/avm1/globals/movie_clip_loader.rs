@@ -9,6 +9,7 @@ use crate::avm1::property::Attribute;
 use crate::avm1::{Object, Value};
 use crate::backend::navigator::RequestOptions;
 use crate::display_object::{DisplayObject, TDisplayObject};
+use crate::security::Security;
 use enumset::EnumSet;
 use gc_arena::MutationContext;
 
@@ -46,10 +47,12 @@ pub fn load_clip<'gc>(
             .as_display_object()
             .and_then(|dobj| dobj.as_movie_clip())
         {
-            let fetch = activation
-                .context
-                .navigator
-                .fetch(&url, RequestOptions::get());
+            let movie = activation
+                .base_clip()
+                .movie()
+                .unwrap_or_else(|| activation.context.swf.clone());
+            let fetch =
+                Security::fetch_movie(&mut activation.context, &movie, &url, RequestOptions::get());
             let process = activation.context.load_manager.load_movie_into_clip(
                 activation.context.player.clone().unwrap(),
                 DisplayObject::MovieClip(movieclip),
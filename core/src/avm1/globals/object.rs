@@ -159,16 +159,17 @@ pub fn register_class<'gc>(
                     movie_clip.set_avm1_constructor(activation.context.gc_context, None);
                 }
             } else {
-                log::warn!(
+                avm_warn!(
+                    activation,
                     "Tried to register_class on an unknown export {}",
                     class_name
                 );
             }
         } else {
-            log::warn!("Tried to register_class on an unknown movie");
+            avm_warn!(activation, "Tried to register_class on an unknown movie");
         }
     } else {
-        log::warn!("Tried to register_class with an unknown class");
+        avm_warn!(activation, "Tried to register_class with an unknown class");
     }
     Ok(Value::Undefined)
 }
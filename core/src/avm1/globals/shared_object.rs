@@ -3,6 +3,10 @@ use crate::avm1::error::Error;
 use crate::avm1::function::{Executable, FunctionObject};
 use crate::avm1::{AvmString, Object, TObject, Value};
 use crate::avm_warn;
+use crate::backend::storage::{
+    shared_object_domain, shared_object_key, shared_object_key_domain, DEFAULT_DOMAIN_QUOTA,
+};
+use crate::display_object::TDisplayObject;
 use enumset::EnumSet;
 use gc_arena::MutationContext;
 
@@ -22,10 +26,25 @@ pub fn delete_all<'gc>(
 pub fn get_disk_usage<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(activation, "SharedObject.getDiskUsage() not implemented");
-    Ok(Value::Undefined)
+    let url = match args.get(0) {
+        Some(url) => Some(url.coerce_to_string(activation)?.to_string()),
+        None => movie_url(activation),
+    };
+    let domain = shared_object_domain(url.as_deref());
+
+    Ok(activation.context.storage.get_domain_usage(&domain).into())
+}
+
+/// Returns the URL of the movie that the currently executing code belongs to.
+fn movie_url<'gc>(activation: &mut Activation<'_, 'gc, '_>) -> Option<String> {
+    activation
+        .base_clip()
+        .movie()
+        .unwrap_or_else(|| activation.context.swf.clone())
+        .url()
+        .map(|url| url.to_string())
 }
 
 /// Serialize an Object and any children to a JSON object
@@ -143,18 +162,27 @@ pub fn get_local<'gc>(
         .coerce_to_string(activation)?
         .to_string();
 
+    let local_path = match args.get(1) {
+        None | Some(Value::Undefined) | Some(Value::Null) => None,
+        Some(local_path) => Some(local_path.coerce_to_string(activation)?.to_string()),
+    };
+
+    let secure = args
+        .get(2)
+        .map(|secure| secure.as_bool(activation.current_swf_version()))
+        .unwrap_or(false);
+
+    let url = movie_url(activation);
+    let name = match shared_object_key(url.as_deref(), &name, local_path.as_deref(), secure) {
+        Some(key) => key,
+        None => return Ok(Value::Null),
+    };
+
     //Check if this is referencing an existing shared object
     if let Some(so) = activation.context.shared_objects.get(&name) {
         return Ok(Value::Object(*so));
     }
 
-    if args.len() > 1 {
-        avm_warn!(
-            activation,
-            "SharedObject.getLocal() doesn't support localPath or secure yet"
-        );
-    }
-
     // Data property only should exist when created with getLocal/Remote
     let constructor = activation.context.avm1.prototypes.shared_object_constructor;
     let this = constructor.construct(activation, &[])?;
@@ -331,33 +359,60 @@ pub fn connect<'gc>(
     Ok(Value::Undefined)
 }
 
-pub fn flush<'gc>(
+/// Serialize the `data` object of a shared object into the string that is
+/// written to storage.
+fn serialize_data<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     this: Object<'gc>,
-    _args: &[Value<'gc>],
-) -> Result<Value<'gc>, Error<'gc>> {
+) -> Result<String, Error<'gc>> {
     let data = this.get("data", activation)?.coerce_to_object(activation);
 
     let mut data_json = JsonValue::new_object();
     recursive_serialize(activation, data, &mut data_json);
 
+    Ok(data_json.dump())
+}
+
+pub fn flush<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let serialized = serialize_data(activation, this)?;
+
     let this_obj = this.as_shared_object().unwrap();
     let name = this_obj.get_name();
 
+    // Flash would prompt the user to raise the quota here; we simply refuse.
+    let min_disk_space = match args.get(0) {
+        Some(min_disk_space) => min_disk_space.coerce_to_f64(activation)?.max(0.0) as usize,
+        None => 0,
+    };
+    let domain = shared_object_key_domain(&name);
+    let previous_size = activation.context.storage.get_size(&name).unwrap_or(0);
+    let domain_usage = activation
+        .context
+        .storage
+        .get_domain_usage(domain)
+        .saturating_sub(previous_size);
+    if domain_usage + serialized.len().max(min_disk_space) > DEFAULT_DOMAIN_QUOTA {
+        return Ok(false.into());
+    }
+
     Ok(activation
         .context
         .storage
-        .put_string(&name, data_json.dump())
+        .put_string(&name, serialized)
         .into())
 }
 
 pub fn get_size<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
-    _this: Object<'gc>,
+    this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(activation, "SharedObject.getSize() not implemented");
-    Ok(Value::Undefined)
+    let serialized = serialize_data(activation, this)?;
+    Ok(serialized.len().into())
 }
 
 pub fn send<'gc>(
@@ -1,13 +1,12 @@
 //! Stage object
-//!
-//! TODO: This is a very rough stub with not much implementation.
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
 use crate::avm1::function::{Executable, FunctionObject};
 use crate::avm1::globals::as_broadcaster::BroadcasterFunctions;
 use crate::avm1::property::Attribute;
-use crate::avm1::{Object, ScriptObject, TObject, Value};
-use crate::avm_warn;
+use crate::avm1::{AvmString, Object, ScriptObject, TObject, Value};
+use crate::player::{StageAlign, StageScaleMode};
+use crate::prelude::*;
 use gc_arena::MutationContext;
 
 pub fn create_stage_object<'gc>(
@@ -109,25 +108,39 @@ fn align<'gc>(
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(activation, "Stage.align: unimplemented");
-    Ok("".into())
+    let align = StageAlign::to_name(*activation.context.stage_align);
+    Ok(AvmString::new(activation.context.gc_context, align).into())
 }
 
 fn set_align<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(activation, "Stage.align: unimplemented");
+    let align = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .to_owned()
+        .coerce_to_string(activation)?;
+    *activation.context.stage_align = StageAlign::from_name(&align);
     Ok(Value::Undefined)
 }
 
+/// The size of the stage, which follows the viewport in `noScale` mode.
+fn stage_size(activation: &Activation<'_, '_, '_>) -> (Twips, Twips) {
+    if *activation.context.scale_mode == StageScaleMode::NoScale {
+        activation.context.viewport_size
+    } else {
+        activation.context.stage_size
+    }
+}
+
 fn height<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    Ok(activation.context.stage_size.1.to_pixels().into())
+    Ok(stage_size(activation).1.to_pixels().into())
 }
 
 fn scale_mode<'gc>(
@@ -135,16 +148,24 @@ fn scale_mode<'gc>(
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(activation, "Stage.scaleMode: unimplemented");
-    Ok("noScale".into())
+    let scale_mode = activation.context.scale_mode.to_string();
+    Ok(AvmString::new(activation.context.gc_context, scale_mode).into())
 }
 
 fn set_scale_mode<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(activation, "Stage.scaleMode: unimplemented");
+    let name = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .to_owned()
+        .coerce_to_string(activation)?;
+    // Unknown modes are ignored.
+    if let Some(scale_mode) = StageScaleMode::from_name(&name) {
+        *activation.context.scale_mode = scale_mode;
+    }
     Ok(Value::Undefined)
 }
 
@@ -153,16 +174,20 @@ fn show_menu<'gc>(
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(activation, "Stage.showMenu: unimplemented");
-    Ok(true.into())
+    Ok((*activation.context.show_menu).into())
 }
 
 fn set_show_menu<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(activation, "Stage.showMenu: unimplemented");
+    let show_menu = args
+        .get(0)
+        .unwrap_or(&Value::Bool(true))
+        .to_owned()
+        .as_bool(activation.current_swf_version());
+    *activation.context.show_menu = show_menu;
     Ok(Value::Undefined)
 }
 
@@ -171,5 +196,5 @@ fn width<'gc>(
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    Ok(activation.context.stage_size.0.to_pixels().into())
+    Ok(stage_size(activation).0.to_pixels().into())
 }
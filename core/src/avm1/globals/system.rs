@@ -30,6 +30,7 @@ impl fmt::Display for CpuArchitecture {
 }
 
 /// Available type of sandbox for a given SWF
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum SandboxType {
     Remote,
     LocalWithFile,
@@ -264,8 +265,6 @@ pub struct SystemProperties {
     pub manufacturer: Manufacturer,
     /// The os of the host
     pub os: OperatingSystem,
-    /// The type of the player sandbox
-    pub sandbox_type: SandboxType,
     /// The cpu architecture of the platform
     pub cpu_architecture: CpuArchitecture,
     /// The highest supported h264 decoder level
@@ -391,7 +390,6 @@ impl Default for SystemProperties {
             dpi: 1_f32,
             manufacturer: Manufacturer::Linux,
             os: OperatingSystem::Linux,
-            sandbox_type: SandboxType::LocalTrusted,
             cpu_architecture: CpuArchitecture::X86,
             idc_level: "5.1".into(),
         }
@@ -4,40 +4,82 @@ use crate::avm1::function::{Executable, FunctionObject};
 use crate::avm1::object::Object;
 use crate::avm1::{AvmString, ScriptObject, TObject, Value};
 use crate::avm_warn;
+use crate::backend::navigator::{url_domain, url_from_relative_url};
+use crate::display_object::TDisplayObject;
 use enumset::EnumSet;
 use gc_arena::MutationContext;
 use std::convert::Into;
+use url::Url;
 
 fn allow_domain<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(activation, "System.security.allowDomain() not implemented");
+    allow_domains(activation, args, false)?;
     Ok(Value::Undefined)
 }
 
 fn allow_insecure_domain<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(
-        activation,
-        "System.security.allowInsecureDomain() not implemented"
-    );
+    allow_domains(activation, args, true)?;
     Ok(Value::Undefined)
 }
 
+/// Permit movies from each domain in `args` to script the calling movie.
+///
+/// Domains may also be given as the URL of a movie from that domain.
+fn allow_domains<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    args: &[Value<'gc>],
+    insecure: bool,
+) -> Result<(), Error<'gc>> {
+    let movie = activation
+        .base_clip()
+        .movie()
+        .unwrap_or_else(|| activation.context.swf.clone());
+    for arg in args {
+        let domain = arg.coerce_to_string(activation)?;
+        let domain = match Url::parse(&domain) {
+            Ok(url) if url.has_host() => url_domain(Some(&domain)),
+            _ => domain.to_string(),
+        };
+        activation
+            .context
+            .security
+            .allow_domain(&movie, &domain, insecure);
+    }
+    Ok(())
+}
+
 fn load_policy_file<'gc>(
     activation: &mut Activation<'_, 'gc, '_>,
     _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm_warn!(
-        activation,
-        "System.security.allowInsecureDomain() not implemented"
-    );
+    let url = match args.get(0) {
+        Some(url) => url.coerce_to_string(activation)?,
+        None => return Ok(Value::Undefined),
+    };
+    let movie = activation
+        .base_clip()
+        .movie()
+        .unwrap_or_else(|| activation.context.swf.clone());
+    let url = match movie.url() {
+        Some(base) => url_from_relative_url(base, &url),
+        None => Url::parse(&url),
+    };
+
+    match url {
+        Ok(url) => activation
+            .context
+            .security
+            .load_policy_file(url.into_string()),
+        Err(e) => avm_warn!(activation, "System.security.loadPolicyFile: {}", e),
+    }
     Ok(Value::Undefined)
 }
 
@@ -55,11 +97,12 @@ fn get_sandbox_type<'gc>(
     _this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    Ok(AvmString::new(
-        activation.context.gc_context,
-        activation.context.system.sandbox_type.to_string(),
-    )
-    .into())
+    let movie = activation
+        .base_clip()
+        .movie()
+        .unwrap_or_else(|| activation.context.swf.clone());
+    let sandbox_type = activation.context.security.sandbox_type(&movie);
+    Ok(AvmString::new(activation.context.gc_context, sandbox_type.to_string()).into())
 }
 
 fn get_choose_local_swf_path<'gc>(
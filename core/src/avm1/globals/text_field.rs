@@ -144,6 +144,82 @@ pub fn set_border<'gc>(
     Ok(Value::Undefined)
 }
 
+pub fn get_scroll<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(display_object) = this.as_display_object() {
+        if let Some(text_field) = display_object.as_edit_text() {
+            return Ok((text_field.scroll() as f64).into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn set_scroll<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(display_object) = this.as_display_object() {
+        if let Some(text_field) = display_object.as_edit_text() {
+            if let Some(value) = args.get(0) {
+                let scroll = value.coerce_to_f64(activation)?;
+                if scroll.is_finite() {
+                    text_field.set_scroll(scroll.max(1.0) as usize, activation.context.gc_context);
+                }
+            }
+        }
+    }
+    Ok(Value::Undefined)
+}
+
+pub fn get_maxscroll<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(display_object) = this.as_display_object() {
+        if let Some(text_field) = display_object.as_edit_text() {
+            return Ok((text_field.maxscroll() as f64).into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn get_mouse_wheel_enabled<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(display_object) = this.as_display_object() {
+        if let Some(text_field) = display_object.as_edit_text() {
+            return Ok(text_field.is_mouse_wheel_enabled().into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn set_mouse_wheel_enabled<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(display_object) = this.as_display_object() {
+        if let Some(text_field) = display_object.as_edit_text() {
+            if let Some(value) = args.get(0) {
+                let is_enabled = value.as_bool(activation.current_swf_version());
+                text_field.set_mouse_wheel_enabled(activation.context.gc_context, is_enabled);
+            }
+        }
+    }
+    Ok(Value::Undefined)
+}
+
 pub fn get_embed_fonts<'gc>(
     _activation: &mut Activation<'_, 'gc, '_>,
     this: Object<'gc>,
@@ -613,6 +689,52 @@ pub fn attach_virtual_properties<'gc>(
         )),
         ReadOnly.into(),
     );
+    object.add_property(
+        gc_context,
+        "scroll",
+        FunctionObject::function(
+            gc_context,
+            Executable::Native(get_scroll),
+            Some(fn_proto),
+            fn_proto,
+        ),
+        Some(FunctionObject::function(
+            gc_context,
+            Executable::Native(set_scroll),
+            Some(fn_proto),
+            fn_proto,
+        )),
+        ReadOnly.into(),
+    );
+    object.add_property(
+        gc_context,
+        "maxscroll",
+        FunctionObject::function(
+            gc_context,
+            Executable::Native(get_maxscroll),
+            Some(fn_proto),
+            fn_proto,
+        ),
+        None,
+        ReadOnly.into(),
+    );
+    object.add_property(
+        gc_context,
+        "mouseWheelEnabled",
+        FunctionObject::function(
+            gc_context,
+            Executable::Native(get_mouse_wheel_enabled),
+            Some(fn_proto),
+            fn_proto,
+        ),
+        Some(FunctionObject::function(
+            gc_context,
+            Executable::Native(set_mouse_wheel_enabled),
+            Some(fn_proto),
+            fn_proto,
+        )),
+        ReadOnly.into(),
+    );
 }
 
 fn get_new_text_format<'gc>(
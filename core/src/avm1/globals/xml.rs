@@ -9,6 +9,8 @@ use crate::avm1::property::Attribute::*;
 use crate::avm1::{AvmString, Object, TObject, Value};
 use crate::avm_warn;
 use crate::backend::navigator::RequestOptions;
+use crate::display_object::TDisplayObject;
+use crate::security::Security;
 use crate::xml;
 use crate::xml::{XMLDocument, XMLNode};
 use enumset::EnumSet;
@@ -909,10 +911,12 @@ pub fn xml_load<'gc>(
 
         this.set("loaded", false.into(), activation)?;
 
-        let fetch = activation
-            .context
-            .navigator
-            .fetch(&url, RequestOptions::get());
+        let movie = activation
+            .base_clip()
+            .movie()
+            .unwrap_or_else(|| activation.context.swf.clone());
+        let fetch =
+            Security::fetch_data(&mut activation.context, &movie, &url, RequestOptions::get());
         let target_clip = activation.target_clip_or_root();
         let process = activation.context.load_manager.load_xml_into_node(
             activation.context.player.clone().unwrap(),
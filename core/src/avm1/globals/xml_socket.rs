@@ -0,0 +1,138 @@
+//! AVM1 XMLSocket object
+
+use crate::avm1::activation::Activation;
+use crate::avm1::error::Error;
+use crate::avm1::property::Attribute;
+use crate::avm1::xml_socket::XmlSockets;
+use crate::avm1::{Object, ScriptObject, TObject, Value};
+use crate::backend::navigator::url_domain;
+use crate::display_object::TDisplayObject;
+use gc_arena::MutationContext;
+
+/// Implements `XMLSocket`
+pub fn constructor<'gc>(
+    _activation: &mut Activation<'_, 'gc, '_>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    // No-op constructor
+    Ok(Value::Undefined)
+}
+
+pub fn create_proto<'gc>(
+    gc_context: MutationContext<'gc, '_>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    use Attribute::*;
+
+    let mut object = ScriptObject::object(gc_context, Some(proto));
+
+    object.force_set_function(
+        "connect",
+        connect,
+        gc_context,
+        DontDelete | DontEnum | ReadOnly,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "send",
+        send,
+        gc_context,
+        DontDelete | DontEnum | ReadOnly,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "close",
+        close,
+        gc_context,
+        DontDelete | DontEnum | ReadOnly,
+        Some(fn_proto),
+    );
+
+    object.force_set_function(
+        "onData",
+        on_data,
+        gc_context,
+        DontDelete | DontEnum,
+        Some(fn_proto),
+    );
+
+    object.into()
+}
+
+fn connect<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let movie = activation
+        .base_clip()
+        .movie()
+        .unwrap_or_else(|| activation.context.swf.clone());
+
+    // A null host means the host that served the movie.
+    let host = match args.get(0) {
+        None | Some(Value::Undefined) | Some(Value::Null) => url_domain(movie.url()),
+        Some(host) => host.coerce_to_string(activation)?.to_string(),
+    };
+
+    let port = args
+        .get(1)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_f64(activation)?;
+    if host.is_empty() || !(1.0..=65535.0).contains(&port) {
+        return Ok(false.into());
+    }
+
+    let connecting = XmlSockets::connect(&mut activation.context, this, &movie, &host, port as u16);
+
+    Ok(connecting.into())
+}
+
+fn send<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let message = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation)?;
+
+    // Messages are terminated by a null byte.
+    let mut data = message.as_bytes().to_vec();
+    data.push(0);
+    XmlSockets::send(&mut activation.context, this, data);
+
+    Ok(Value::Undefined)
+}
+
+fn close<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    XmlSockets::close(&mut activation.context, this);
+    Ok(Value::Undefined)
+}
+
+/// Default `onData` handler, which parses the message and forwards it to
+/// `onXML`.
+fn on_data<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let src = args.get(0).cloned().unwrap_or(Value::Undefined);
+
+    // Flash always parses with the built-in `XML`, even if a movie replaced it.
+    let xml_constructor = activation.context.avm1.prototypes.xml_constructor;
+    let xml = xml_constructor.construct(activation, &[src])?;
+
+    this.call_method("onXML", &[xml.into()], activation)?;
+
+    Ok(Value::Undefined)
+}
@@ -0,0 +1,478 @@
+//! Message bus for `LocalConnection` objects.
+//!
+//! Messages are queued when `LocalConnection.send` is called and delivered on
+//! the next frame, as in the Flash Player. Receivers inside this player are
+//! resolved directly; all other messages are handed to the host's
+//! `LocalConnectionBackend`, which may deliver them to another player.
+
+use crate::avm1::external_interface::{from_external_value, to_external_value};
+use crate::avm1::{
+    Activation, ActivationIdentifier, AvmString, Object, ScriptObject, TObject, Value,
+};
+use crate::backend::local_connection::LocalConnectionMessage;
+use crate::context::UpdateContext;
+use enumset::EnumSet;
+use gc_arena::Collect;
+use std::collections::{HashMap, VecDeque};
+
+/// Method names that may never be invoked through a `LocalConnection`.
+const RESERVED_METHODS: &[&str] = &[
+    "send",
+    "connect",
+    "close",
+    "domain",
+    "allowDomain",
+    "allowInsecureDomain",
+    "onStatus",
+];
+
+/// Manages the `LocalConnection` objects that are listening for messages, and
+/// the messages that are waiting to be delivered.
+pub struct LocalConnections<'gc> {
+    /// The listening connections, keyed by their fully qualified name.
+    connections: HashMap<String, Receiver<'gc>>,
+
+    /// Messages sent from this player that haven't been delivered yet.
+    queue: VecDeque<PendingMessage<'gc>>,
+}
+
+/// A `LocalConnection` object that called `connect`.
+#[derive(Clone, Collect)]
+#[collect(no_drop)]
+struct Receiver<'gc> {
+    object: Object<'gc>,
+
+    /// The domain of the movie that created the receiving connection.
+    domain: String,
+}
+
+/// A message sent with `LocalConnection.send`.
+#[derive(Collect)]
+#[collect(no_drop)]
+struct PendingMessage<'gc> {
+    /// The `LocalConnection` object that sent this message.
+    sender: Object<'gc>,
+
+    /// The fully qualified name of the receiving connection.
+    connection_name: String,
+
+    method_name: String,
+
+    /// The domain of the movie that sent this message.
+    sender_domain: String,
+
+    args: Vec<Value<'gc>>,
+}
+
+impl<'gc> LocalConnections<'gc> {
+    pub fn new() -> Self {
+        Self {
+            connections: HashMap::new(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Qualify a connection name with the domain of the movie using it.
+    ///
+    /// Names starting with an underscore are global and are left alone, as are
+    /// names that already include a domain.
+    pub fn qualify_name(name: &str, domain: &str) -> String {
+        if name.starts_with('_') || name.contains(':') {
+            name.to_string()
+        } else {
+            format!("{}:{}", domain, name)
+        }
+    }
+
+    /// Start listening for messages on behalf of `object`.
+    ///
+    /// Returns `false` if the name is already in use, either by this player or
+    /// by another one known to the host, or if `object` is already connected.
+    pub fn connect(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        object: Object<'gc>,
+        name: &str,
+        domain: &str,
+    ) -> bool {
+        if name.is_empty() || name.contains(':') {
+            return false;
+        }
+
+        let connections = &context.local_connections.connections;
+        if connections
+            .values()
+            .any(|receiver| Object::ptr_eq(receiver.object, object))
+        {
+            return false;
+        }
+
+        let qualified_name = Self::qualify_name(name, domain);
+        if connections.contains_key(&qualified_name)
+            || !context.local_connection.connect(&qualified_name)
+        {
+            return false;
+        }
+
+        context.local_connections.connections.insert(
+            qualified_name,
+            Receiver {
+                object,
+                domain: domain.to_string(),
+            },
+        );
+        true
+    }
+
+    /// Stop listening for messages on behalf of `object`.
+    ///
+    /// Returns `false` if `object` wasn't connected.
+    pub fn close(context: &mut UpdateContext<'_, 'gc, '_>, object: Object<'gc>) -> bool {
+        let name = context
+            .local_connections
+            .connections
+            .iter()
+            .find(|(_, receiver)| Object::ptr_eq(receiver.object, object))
+            .map(|(name, _)| name.clone());
+
+        if let Some(name) = name {
+            context.local_connections.connections.remove(&name);
+            context.local_connection.close(&name);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Queue a message to be delivered on the next frame.
+    ///
+    /// `connection_name` must already be qualified with `qualify_name`.
+    /// Returns `false` if the name is malformed or `method_name` may not be
+    /// invoked remotely, in which case nothing is queued.
+    pub fn send(
+        &mut self,
+        sender: Object<'gc>,
+        connection_name: String,
+        method_name: String,
+        sender_domain: String,
+        args: Vec<Value<'gc>>,
+    ) -> bool {
+        // Both the domain and the name itself must be present.
+        if connection_name.starts_with(':')
+            || connection_name.ends_with(':')
+            || connection_name.is_empty()
+            || method_name.is_empty()
+            || RESERVED_METHODS.contains(&method_name.as_str())
+        {
+            return false;
+        }
+
+        self.queue.push_back(PendingMessage {
+            sender,
+            connection_name,
+            method_name,
+            sender_domain,
+            args,
+        });
+        true
+    }
+
+    /// Deliver all queued messages, including those sent by other players.
+    ///
+    /// Nothing is delivered until a root movie exists to run the handlers in.
+    pub fn update_connections(context: &mut UpdateContext<'_, 'gc, '_>) {
+        let level0 = match context.levels.get(&0).copied() {
+            Some(level0) => level0,
+            None => return,
+        };
+
+        let incoming = context.local_connection.poll();
+        if context.local_connections.queue.is_empty() && incoming.is_empty() {
+            return;
+        }
+
+        let version = context.swf.header().version;
+        let globals = context.avm1.global_object_cell();
+
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[LocalConnection]"),
+            version,
+            globals,
+            level0,
+        );
+
+        // Messages sent during delivery wait until the next frame.
+        let pending: Vec<_> = activation
+            .context
+            .local_connections
+            .queue
+            .drain(..)
+            .collect();
+        for message in pending {
+            let receiver = activation
+                .context
+                .local_connections
+                .connections
+                .get(&message.connection_name)
+                .cloned();
+
+            let delivered = match receiver {
+                Some(receiver) => Self::deliver(
+                    &mut activation,
+                    receiver,
+                    &message.method_name,
+                    &message.sender_domain,
+                    &message.args,
+                ),
+                None => {
+                    let args = message
+                        .args
+                        .iter()
+                        .map(|arg| to_external_value(&mut activation, arg, 0))
+                        .collect();
+                    activation
+                        .context
+                        .local_connection
+                        .send(LocalConnectionMessage {
+                            connection_name: message.connection_name,
+                            method_name: message.method_name,
+                            sender_domain: message.sender_domain,
+                            args,
+                        })
+                }
+            };
+
+            let info = ScriptObject::object(
+                activation.context.gc_context,
+                Some(activation.context.avm1.prototypes.object),
+            );
+            let level = if delivered { "status" } else { "error" };
+            info.define_value(
+                activation.context.gc_context,
+                "level",
+                level.into(),
+                EnumSet::empty(),
+            );
+            let _ = message
+                .sender
+                .call_method("onStatus", &[info.into()], &mut activation);
+        }
+
+        for message in incoming {
+            let receiver = activation
+                .context
+                .local_connections
+                .connections
+                .get(&message.connection_name)
+                .cloned();
+
+            if let Some(receiver) = receiver {
+                let args: Vec<_> = message
+                    .args
+                    .iter()
+                    .map(|arg| from_external_value(&mut activation, arg))
+                    .collect();
+                Self::deliver(
+                    &mut activation,
+                    receiver,
+                    &message.method_name,
+                    &message.sender_domain,
+                    &args,
+                );
+            }
+        }
+    }
+
+    /// Invoke a method on a receiving connection, checking that the receiver
+    /// accepts messages from the sender's domain first.
+    ///
+    /// Returns `false` if the message was refused.
+    fn deliver(
+        activation: &mut Activation<'_, 'gc, '_>,
+        receiver: Receiver<'gc>,
+        method_name: &str,
+        sender_domain: &str,
+        args: &[Value<'gc>],
+    ) -> bool {
+        if RESERVED_METHODS.contains(&method_name) {
+            return false;
+        }
+
+        if receiver.domain != sender_domain {
+            let sender_domain =
+                AvmString::new(activation.context.gc_context, sender_domain.to_string());
+            let allowed = receiver
+                .object
+                .call_method("allowDomain", &[sender_domain.into()], activation)
+                .map(|allowed| allowed.as_bool(activation.current_swf_version()))
+                .unwrap_or(false);
+            if !allowed {
+                return false;
+            }
+        }
+
+        let _ = receiver.object.call_method(method_name, args, activation);
+        true
+    }
+}
+
+impl Default for LocalConnections<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<'gc> Collect for LocalConnections<'gc> {
+    fn trace(&self, cc: gc_arena::CollectionContext) {
+        for receiver in self.connections.values() {
+            receiver.trace(cc);
+        }
+        for message in &self.queue {
+            message.trace(cc);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::error::Error;
+    use crate::avm1::test_utils::with_avm;
+
+    /// Stores the first argument of a message in `received`.
+    fn receive<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        this: Object<'gc>,
+        args: &[Value<'gc>],
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        let value = args.get(0).cloned().unwrap_or(Value::Undefined);
+        this.set("received", value, activation)?;
+        Ok(Value::Undefined)
+    }
+
+    fn allow_domain<'gc>(
+        _activation: &mut Activation<'_, 'gc, '_>,
+        _this: Object<'gc>,
+        _args: &[Value<'gc>],
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        Ok(true.into())
+    }
+
+    fn connection<'gc>(activation: &mut Activation<'_, 'gc, '_>) -> Object<'gc> {
+        let gc_context = activation.context.gc_context;
+        let fn_proto = Some(activation.context.avm1.prototypes.function);
+        let mut object =
+            ScriptObject::object(gc_context, Some(activation.context.avm1.prototypes.object));
+        object.force_set_function("receive", receive, gc_context, EnumSet::empty(), fn_proto);
+        object.into()
+    }
+
+    /// Send a message from `localhost` to `example.com:test`, and deliver it.
+    fn send<'gc>(activation: &mut Activation<'_, 'gc, '_>, sender: Object<'gc>, method_name: &str) {
+        activation.context.local_connections.send(
+            sender,
+            "example.com:test".to_string(),
+            method_name.to_string(),
+            "localhost".to_string(),
+            vec![true.into()],
+        );
+        LocalConnections::update_connections(&mut activation.context);
+    }
+
+    #[test]
+    fn delivers_messages_on_update() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let sender = connection(activation);
+            let receiver = connection(activation);
+            assert!(LocalConnections::connect(
+                &mut activation.context,
+                receiver,
+                "test",
+                "localhost"
+            ));
+            assert!(!LocalConnections::connect(
+                &mut activation.context,
+                sender,
+                "test",
+                "localhost"
+            ));
+
+            assert!(activation.context.local_connections.send(
+                sender,
+                LocalConnections::qualify_name("test", "localhost"),
+                "receive".to_string(),
+                "localhost".to_string(),
+                vec![5.0.into()],
+            ));
+            assert_eq!(receiver.get("received", activation)?, Value::Undefined);
+
+            LocalConnections::update_connections(&mut activation.context);
+            assert_eq!(receiver.get("received", activation)?, 5.0.into());
+
+            assert!(LocalConnections::close(&mut activation.context, receiver));
+            assert!(!LocalConnections::close(&mut activation.context, receiver));
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn refuses_other_domains_unless_allowed() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let sender = connection(activation);
+            let receiver = connection(activation);
+            assert!(LocalConnections::connect(
+                &mut activation.context,
+                receiver,
+                "test",
+                "example.com"
+            ));
+
+            send(activation, sender, "receive");
+            assert_eq!(receiver.get("received", activation)?, Value::Undefined);
+
+            receiver.as_script_object().unwrap().force_set_function(
+                "allowDomain",
+                allow_domain,
+                activation.context.gc_context,
+                EnumSet::empty(),
+                Some(activation.context.avm1.prototypes.function),
+            );
+            send(activation, sender, "connect");
+            assert_eq!(receiver.get("received", activation)?, Value::Undefined);
+            send(activation, sender, "receive");
+            assert_eq!(receiver.get("received", activation)?, true.into());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn refuses_invalid_messages() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let sender = connection(activation);
+            for (connection_name, method_name) in &[
+                ("localhost:", "receive"),
+                (":test", "receive"),
+                ("", "receive"),
+                ("localhost:test", ""),
+                ("localhost:test", "close"),
+                ("localhost:test", "allowDomain"),
+            ] {
+                assert!(!activation.context.local_connections.send(
+                    sender,
+                    connection_name.to_string(),
+                    method_name.to_string(),
+                    "localhost".to_string(),
+                    vec![],
+                ));
+            }
+            assert!(activation.context.local_connections.send(
+                sender,
+                "_test".to_string(),
+                "receive".to_string(),
+                "localhost".to_string(),
+                vec![],
+            ));
+            Ok(())
+        });
+    }
+}
@@ -842,18 +842,25 @@ mod tests {
     use crate::avm1::function::Executable;
     use crate::avm1::globals::system::SystemProperties;
     use crate::avm1::property::Attribute::*;
-    use crate::avm1::{Avm1, Timers};
+    use crate::avm1::{Avm1, ExternalCallbacks, LocalConnections, Timers, XmlSockets};
     use crate::avm2::Avm2;
     use crate::backend::audio::NullAudioBackend;
+    use crate::backend::external_interface::NullExternalInterfaceProvider;
     use crate::backend::input::NullInputBackend;
+    use crate::backend::local_connection::NullLocalConnectionBackend;
+    use crate::backend::log::NullLogBackend;
     use crate::backend::navigator::NullNavigatorBackend;
     use crate::backend::render::NullRenderer;
+    use crate::backend::socket::NullSocketBackend;
     use crate::backend::storage::MemoryStorageBackend;
     use crate::context::UpdateContext;
+    use crate::context_menu::ContextMenuState;
     use crate::display_object::MovieClip;
     use crate::library::Library;
     use crate::loader::LoadManager;
+    use crate::player::StageScaleMode;
     use crate::prelude::*;
+    use crate::security::Security;
     use crate::tag_utils::{SwfMovie, SwfSlice};
     use gc_arena::rootless_arena;
     use rand::{rngs::SmallRng, SeedableRng};
@@ -900,14 +907,29 @@ mod tests {
                 mouse_position: &(Twips::new(0), Twips::new(0)),
                 drag_object: &mut None,
                 stage_size: (Twips::from_pixels(550.0), Twips::from_pixels(400.0)),
+                viewport_size: (Twips::from_pixels(550.0), Twips::from_pixels(400.0)),
                 player: None,
                 load_manager: &mut LoadManager::new(),
                 system: &mut SystemProperties::default(),
+                security: &mut Security::new(),
                 instance_counter: &mut 0,
                 storage: &mut MemoryStorageBackend::default(),
+                local_connection: &mut NullLocalConnectionBackend::new(),
+                socket: &mut NullSocketBackend::new(),
+                external_interface: &mut NullExternalInterfaceProvider::new(),
+                log: &mut NullLogBackend::new(),
                 shared_objects: &mut HashMap::new(),
                 unbound_text_fields: &mut Vec::new(),
                 timers: &mut Timers::new(),
+                local_connections: &mut LocalConnections::new(),
+                xml_sockets: &mut XmlSockets::new(),
+                external_callbacks: &mut ExternalCallbacks::new(),
+                context_menu: &mut ContextMenuState::new(),
+                fs_commands: &mut Vec::new(),
+                show_menu: &mut true,
+                scale_mode: &mut StageScaleMode::ShowAll,
+                stage_align: &mut EnumSet::empty(),
+                debugger: &mut None,
                 needs_render: &mut false,
                 avm1: &mut avm1,
                 avm2: &mut avm2,
@@ -1,19 +1,31 @@
 use crate::avm1::activation::{Activation, ActivationIdentifier};
 use crate::avm1::error::Error;
 use crate::avm1::globals::system::SystemProperties;
-use crate::avm1::{Avm1, Object, Timers, UpdateContext};
+use crate::avm1::{
+    Avm1, ExternalCallbacks, LocalConnections, Object, Timers, UpdateContext, XmlSockets,
+};
 use crate::avm2::Avm2;
 use crate::backend::audio::NullAudioBackend;
+use crate::backend::external_interface::{
+    ExternalInterfaceProvider, NullExternalInterfaceProvider,
+};
 use crate::backend::input::NullInputBackend;
+use crate::backend::local_connection::NullLocalConnectionBackend;
+use crate::backend::log::NullLogBackend;
 use crate::backend::navigator::NullNavigatorBackend;
 use crate::backend::render::NullRenderer;
+use crate::backend::socket::NullSocketBackend;
 use crate::backend::storage::MemoryStorageBackend;
 use crate::context::ActionQueue;
+use crate::context_menu::ContextMenuState;
 use crate::display_object::{MovieClip, TDisplayObject};
 use crate::library::Library;
 use crate::loader::LoadManager;
+use crate::player::StageScaleMode;
 use crate::prelude::*;
+use crate::security::Security;
 use crate::tag_utils::{SwfMovie, SwfSlice};
+use enumset::EnumSet;
 use gc_arena::{rootless_arena, MutationContext};
 use rand::{rngs::SmallRng, SeedableRng};
 use std::collections::{BTreeMap, HashMap};
@@ -23,8 +35,23 @@ pub fn with_avm<F>(swf_version: u8, test: F)
 where
     F: for<'a, 'gc> FnOnce(&mut Activation<'_, 'gc, '_>, Object<'gc>) -> Result<(), Error<'gc>>,
 {
-    fn in_the_arena<'a, 'gc: 'a, F>(swf_version: u8, test: F, gc_context: MutationContext<'gc, '_>)
-    where
+    with_avm_and_external_interface(swf_version, &mut NullExternalInterfaceProvider::new(), test)
+}
+
+/// Like `with_avm`, with `external_interface` as the host's provider.
+pub fn with_avm_and_external_interface<F>(
+    swf_version: u8,
+    external_interface: &mut dyn ExternalInterfaceProvider,
+    test: F,
+) where
+    F: for<'a, 'gc> FnOnce(&mut Activation<'_, 'gc, '_>, Object<'gc>) -> Result<(), Error<'gc>>,
+{
+    fn in_the_arena<'a, 'gc: 'a, F>(
+        swf_version: u8,
+        external_interface: &mut dyn ExternalInterfaceProvider,
+        test: F,
+        gc_context: MutationContext<'gc, '_>,
+    ) where
         F: FnOnce(&mut Activation<'_, 'gc, '_>, Object<'gc>) -> Result<(), Error<'gc>>,
     {
         let mut avm1 = Avm1::new(gc_context, swf_version);
@@ -61,14 +88,29 @@ where
             mouse_position: &(Twips::new(0), Twips::new(0)),
             drag_object: &mut None,
             stage_size: (Twips::from_pixels(550.0), Twips::from_pixels(400.0)),
+            viewport_size: (Twips::from_pixels(550.0), Twips::from_pixels(400.0)),
             player: None,
             load_manager: &mut LoadManager::new(),
             system: &mut SystemProperties::default(),
+            security: &mut Security::new(),
             instance_counter: &mut 0,
             storage: &mut MemoryStorageBackend::default(),
+            local_connection: &mut NullLocalConnectionBackend::new(),
+            socket: &mut NullSocketBackend::new(),
+            external_interface,
+            log: &mut NullLogBackend::new(),
             shared_objects: &mut HashMap::new(),
             unbound_text_fields: &mut Vec::new(),
             timers: &mut Timers::new(),
+            local_connections: &mut LocalConnections::new(),
+            xml_sockets: &mut XmlSockets::new(),
+            external_callbacks: &mut ExternalCallbacks::new(),
+            context_menu: &mut ContextMenuState::new(),
+            fs_commands: &mut Vec::new(),
+            show_menu: &mut true,
+            scale_mode: &mut StageScaleMode::ShowAll,
+            stage_align: &mut EnumSet::empty(),
+            debugger: &mut None,
             needs_render: &mut false,
             avm1: &mut avm1,
             avm2: &mut avm2,
@@ -103,7 +145,7 @@ where
         run_test(&mut activation, root, test)
     }
 
-    rootless_arena(|gc_context| in_the_arena(swf_version, test, gc_context))
+    rootless_arena(|gc_context| in_the_arena(swf_version, external_interface, test, gc_context))
 }
 
 macro_rules! test_method {
@@ -0,0 +1,399 @@
+//! Connection handling for `XMLSocket` objects.
+//!
+//! Socket events are polled from the `SocketBackend` once per frame. Incoming
+//! data is buffered until a null byte terminates a message, which is then
+//! handed to the `onData` method of the owning `XMLSocket`.
+//!
+//! Unless the movie is trusted, a socket policy file must grant it access
+//! before the actual connection is made. Policy files are requested by sending
+//! `<policy-file-request/>` to each location given by
+//! `Security::socket_policy_locations` in turn.
+
+use crate::avm1::{Activation, ActivationIdentifier, AvmString, Object, TObject};
+use crate::backend::socket::{SocketEvent, SocketHandle};
+use crate::context::UpdateContext;
+use crate::security::{Access, MetaPolicy, PolicyFile, MASTER_SOCKET_POLICY_PORT};
+use crate::tag_utils::SwfMovie;
+use gc_arena::Collect;
+use std::collections::{HashMap, VecDeque};
+
+/// What is sent to a host to ask it for its socket policy file.
+const POLICY_FILE_REQUEST: &[u8] = b"<policy-file-request/>\0";
+
+/// Manages the open sockets of all `XMLSocket` objects.
+pub struct XmlSockets<'gc> {
+    sockets: HashMap<SocketHandle, XmlSocket<'gc>>,
+}
+
+struct XmlSocket<'gc> {
+    /// The `XMLSocket` object that owns this socket.
+    object: Object<'gc>,
+
+    /// Received bytes that aren't terminated by a null byte yet.
+    buffer: Vec<u8>,
+
+    /// Whether this is the connection the movie asked for, or one that
+    /// fetches a policy file first.
+    state: SocketState,
+}
+
+enum SocketState {
+    /// Fetching a socket policy file that may grant access to `host` and
+    /// `port`.
+    Policy {
+        host: String,
+        port: u16,
+
+        /// The domain that the policy must allow, or `None` for local movies.
+        requester: Option<String>,
+
+        /// The locations that haven't been asked for a policy yet.
+        locations: VecDeque<(String, u16)>,
+
+        /// Whether the policy is being fetched from the master location.
+        is_master: bool,
+
+        /// Messages sent by the movie before the connection was made.
+        pending: Vec<u8>,
+    },
+
+    /// Connected, or connecting, to the host the movie asked for.
+    Open,
+}
+
+impl<'gc> XmlSockets<'gc> {
+    pub fn new() -> Self {
+        Self {
+            sockets: HashMap::new(),
+        }
+    }
+
+    /// Open a connection on behalf of `object`, closing any connection it
+    /// already had.
+    ///
+    /// Returns `false` if the sandbox of `movie` forbids sockets. If a policy
+    /// file is required, the connection is only made once one grants access.
+    pub fn connect(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        object: Object<'gc>,
+        movie: &SwfMovie,
+        host: &str,
+        port: u16,
+    ) -> bool {
+        Self::close(context, object);
+
+        match context.security.socket_access(movie) {
+            Access::Allowed => {
+                let handle = context.socket.connect(host, port);
+                context.xml_sockets.sockets.insert(
+                    handle,
+                    XmlSocket {
+                        object,
+                        buffer: vec![],
+                        state: SocketState::Open,
+                    },
+                );
+                true
+            }
+            Access::RequiresPolicy(requester) => {
+                let locations = context.security.socket_policy_locations(host, port).into();
+                let state = SocketState::Policy {
+                    host: host.to_string(),
+                    port,
+                    requester,
+                    locations,
+                    is_master: false,
+                    pending: vec![],
+                };
+                Self::request_policy(context, object, state);
+                true
+            }
+            Access::Denied => {
+                log::warn!(
+                    "Movie in the {} sandbox may not connect to {}:{}",
+                    context.security.sandbox_type(movie),
+                    host,
+                    port
+                );
+                false
+            }
+        }
+    }
+
+    /// Ask the next location of `state` for a policy file.
+    ///
+    /// Returns `false` if every location was tried already.
+    fn request_policy(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        object: Object<'gc>,
+        mut state: SocketState,
+    ) -> bool {
+        let location = match &mut state {
+            SocketState::Policy {
+                locations,
+                is_master,
+                ..
+            } => {
+                let location = locations.pop_front();
+                *is_master =
+                    location.as_ref().map(|(_, port)| *port) == Some(MASTER_SOCKET_POLICY_PORT);
+                location
+            }
+            SocketState::Open => None,
+        };
+        let (host, port) = match location {
+            Some(location) => location,
+            None => return false,
+        };
+
+        let handle = context.socket.connect(&host, port);
+        context.socket.send(handle, POLICY_FILE_REQUEST.to_vec());
+        context.xml_sockets.sockets.insert(
+            handle,
+            XmlSocket {
+                object,
+                buffer: vec![],
+                state,
+            },
+        );
+        true
+    }
+
+    /// Send a message over the connection owned by `object`.
+    ///
+    /// Messages sent while a policy file is being fetched are sent once the
+    /// connection is made. Returns `false` if `object` has no connection, or
+    /// if it was closed.
+    pub fn send(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        object: Object<'gc>,
+        data: Vec<u8>,
+    ) -> bool {
+        let handle = match context.xml_sockets.handle_of(object) {
+            Some(handle) => handle,
+            None => return false,
+        };
+
+        if let Some(XmlSocket {
+            state: SocketState::Policy { pending, .. },
+            ..
+        }) = context.xml_sockets.sockets.get_mut(&handle)
+        {
+            pending.extend(data);
+            true
+        } else {
+            context.socket.send(handle, data)
+        }
+    }
+
+    /// Close the connection owned by `object`, if any.
+    pub fn close(context: &mut UpdateContext<'_, 'gc, '_>, object: Object<'gc>) {
+        if let Some(handle) = context.xml_sockets.handle_of(object) {
+            context.xml_sockets.sockets.remove(&handle);
+            context.socket.close(handle);
+        }
+    }
+
+    fn handle_of(&self, object: Object<'gc>) -> Option<SocketHandle> {
+        self.sockets
+            .iter()
+            .find(|(_, socket)| Object::ptr_eq(socket.object, object))
+            .map(|(handle, _)| *handle)
+    }
+
+    /// Dispatch every socket event that happened since the last frame.
+    ///
+    /// Nothing is dispatched until a root movie exists to run the handlers in.
+    pub fn update_sockets(context: &mut UpdateContext<'_, 'gc, '_>) {
+        let level0 = match context.levels.get(&0).copied() {
+            Some(level0) => level0,
+            None => return,
+        };
+
+        let events = context.socket.poll();
+        if events.is_empty() {
+            return;
+        }
+
+        let version = context.swf.header().version;
+        let globals = context.avm1.global_object_cell();
+
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[XMLSocket]"),
+            version,
+            globals,
+            level0,
+        );
+
+        for (handle, event) in events {
+            let (object, is_policy) = match activation.context.xml_sockets.sockets.get(&handle) {
+                Some(socket) => (
+                    socket.object,
+                    matches!(socket.state, SocketState::Policy { .. }),
+                ),
+                // Events may still arrive for sockets that were closed by script.
+                None => continue,
+            };
+
+            if is_policy {
+                if let Some(success) = Self::update_policy(&mut activation.context, handle, event) {
+                    let _ = object.call_method("onConnect", &[success.into()], &mut activation);
+                }
+                continue;
+            }
+
+            match event {
+                SocketEvent::Connected(success) => {
+                    if !success {
+                        activation.context.xml_sockets.sockets.remove(&handle);
+                        activation.context.socket.close(handle);
+                    }
+                    let _ = object.call_method("onConnect", &[success.into()], &mut activation);
+                }
+                SocketEvent::Data(data) => {
+                    let messages = match activation.context.xml_sockets.sockets.get_mut(&handle) {
+                        Some(socket) => socket.receive(&data),
+                        None => continue,
+                    };
+                    for message in messages {
+                        let message = AvmString::new(
+                            activation.context.gc_context,
+                            String::from_utf8_lossy(&message).into_owned(),
+                        );
+                        let _ = object.call_method("onData", &[message.into()], &mut activation);
+                    }
+                }
+                SocketEvent::Closed => {
+                    activation.context.xml_sockets.sockets.remove(&handle);
+                    activation.context.socket.close(handle);
+                    let _ = object.call_method("onClose", &[], &mut activation);
+                }
+            }
+        }
+    }
+
+    /// Handle an event of a connection that fetches a policy file.
+    ///
+    /// Once a policy grants access, the actual connection is made. Returns
+    /// `Some(false)` if no location had a policy that grants access, so that
+    /// the owning `XMLSocket` can be told that it failed to connect.
+    fn update_policy(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        handle: SocketHandle,
+        event: SocketEvent,
+    ) -> Option<bool> {
+        let policy = match event {
+            SocketEvent::Connected(true) => return None,
+            SocketEvent::Data(data) => {
+                let socket = context.xml_sockets.sockets.get_mut(&handle)?;
+                match socket.receive(&data).into_iter().next() {
+                    Some(policy) => PolicyFile::parse(&policy),
+                    None => return None,
+                }
+            }
+            SocketEvent::Connected(false) | SocketEvent::Closed => None,
+        };
+
+        let socket = context.xml_sockets.sockets.remove(&handle)?;
+        context.socket.close(handle);
+
+        let object = socket.object;
+        let mut state = socket.state;
+        if let SocketState::Policy {
+            host,
+            port,
+            requester,
+            locations,
+            is_master,
+            pending,
+        } = &mut state
+        {
+            if let Some(policy) = policy {
+                if policy.allows_socket(requester.as_deref(), *port) {
+                    let handle = context.socket.connect(host, *port);
+                    if !pending.is_empty() {
+                        context.socket.send(handle, std::mem::take(pending));
+                    }
+                    context.xml_sockets.sockets.insert(
+                        handle,
+                        XmlSocket {
+                            object,
+                            buffer: vec![],
+                            state: SocketState::Open,
+                        },
+                    );
+                    return None;
+                }
+
+                // The master policy decides whether the others are honoured.
+                if *is_master && policy.meta_policy() != MetaPolicy::All {
+                    locations.clear();
+                }
+            }
+
+            if Self::request_policy(context, object, state) {
+                return None;
+            }
+        }
+
+        log::warn!("No socket policy file permits this connection");
+        Some(false)
+    }
+}
+
+impl XmlSocket<'_> {
+    /// Buffer received bytes, and return every message that is now complete.
+    fn receive(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        self.buffer.extend_from_slice(data);
+
+        let mut messages = vec![];
+        while let Some(end) = self.buffer.iter().position(|b| *b == 0) {
+            let mut message: Vec<u8> = self.buffer.drain(..=end).collect();
+            message.pop();
+            messages.push(message);
+        }
+        messages
+    }
+}
+
+impl Default for XmlSockets<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<'gc> Collect for XmlSockets<'gc> {
+    fn trace(&self, cc: gc_arena::CollectionContext) {
+        for socket in self.sockets.values() {
+            socket.object.trace(cc);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::error::Error;
+    use crate::avm1::test_utils::with_avm;
+
+    #[test]
+    fn receive_splits_messages_on_null() {
+        with_avm(8, |_activation, root| -> Result<(), Error> {
+            let mut socket = XmlSocket {
+                object: root,
+                buffer: vec![],
+                state: SocketState::Open,
+            };
+            assert!(socket.receive(b"<a/").is_empty());
+            assert_eq!(
+                socket.receive(b">\0<b/>\0\0<c"),
+                vec![b"<a/>".to_vec(), b"<b/>".to_vec(), vec![]]
+            );
+            assert_eq!(socket.buffer, b"<c");
+            assert_eq!(socket.receive(b"/>\0"), vec![b"<c/>".to_vec()]);
+            assert!(socket.buffer.is_empty());
+            Ok(())
+        });
+    }
+}
@@ -1,6 +1,6 @@
 //! ActionScript Virtual Machine 2 (AS3) support
 
-use crate::avm2::activation::Activation;
+pub use crate::avm2::activation::Activation;
 use crate::avm2::globals::SystemPrototypes;
 use crate::avm2::object::{Object, ScriptObject, TObject};
 use crate::avm2::scope::Scope;
@@ -10,7 +10,10 @@ use crate::avm2::script::Script;
 use crate::avm2::string::AvmString;
 use crate::avm2::value::Value;
 use crate::avm2::{value, Avm2, Error};
+use crate::backend::log::{AvmVersion, LogSource};
 use crate::context::UpdateContext;
+use crate::debugger::Debugger;
+use crate::display_object::TDisplayObject;
 use gc_arena::{Collect, Gc, GcCell, MutationContext};
 use smallvec::SmallVec;
 use std::io::Cursor;
@@ -100,6 +103,9 @@ pub struct Activation<'a, 'gc: 'a, 'gc_context: 'a> {
     /// This will not be available if this is not a method call.
     base_proto: Option<Object<'gc>>,
 
+    /// The source file of the running code, as set by `DebugFile`.
+    debug_file: Option<AvmString<'gc>>,
+
     pub context: UpdateContext<'a, 'gc, 'gc_context>,
 }
 
@@ -124,6 +130,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             local_scope: ScriptObject::bare_object(context.gc_context),
             scope: None,
             base_proto: None,
+            debug_file: None,
             context,
         }
     }
@@ -158,6 +165,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             local_scope: ScriptObject::bare_object(context.gc_context),
             scope,
             base_proto: None,
+            debug_file: None,
             context,
         })
     }
@@ -203,6 +211,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             local_scope: ScriptObject::bare_object(context.gc_context),
             scope,
             base_proto,
+            debug_file: None,
             context,
         })
     }
@@ -279,6 +288,41 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         self.context.avm2
     }
 
+    /// The source file of the running code, if it was compiled with debug
+    /// info.
+    pub fn debug_file(&self) -> Option<AvmString<'gc>> {
+        self.debug_file
+    }
+
+    /// Where log output of this activation comes from.
+    fn log_source(&self) -> LogSource {
+        LogSource {
+            movie_url: self.context.swf.url().map(str::to_owned),
+            avm: AvmVersion::Avm2,
+            frame: self
+                .context
+                .levels
+                .get(&0)
+                .and_then(|root| root.as_movie_clip())
+                .map(|clip| clip.current_frame()),
+        }
+    }
+
+    /// Output a message as if it was passed to `trace`.
+    pub fn log_trace(&mut self, message: &str) {
+        let source = self.log_source();
+        self.context.log.trace(&source, message);
+        if let Some(debugger) = self.context.debugger.as_mut() {
+            debugger.trace(message);
+        }
+    }
+
+    /// Report invalid or unsupported ActionScript to the log backend.
+    pub fn log_warning(&mut self, message: &str) {
+        let source = self.log_source();
+        self.context.log.avm_warning(&source, message);
+    }
+
     /// Set the return value.
     pub fn set_return_value(&mut self, value: Value<'gc>) {
         self.return_value = Some(value);
@@ -388,14 +432,24 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
             .ok_or_else(|| "Cannot execute non-native method without body".into());
         let mut read = Reader::new(Cursor::new(body?.code.as_ref()));
 
-        loop {
+        if let Some(debugger) = self.context.debugger.as_mut() {
+            debugger.enter_avm2_method();
+        }
+
+        let result = loop {
             let result = self.do_next_opcode(method, &mut read);
             match result {
                 Ok(FrameControl::Return(value)) => break Ok(value),
                 Ok(FrameControl::Continue) => {}
                 Err(e) => break Err(e),
             }
+        };
+
+        if let Some(debugger) = self.context.debugger.as_mut() {
+            debugger.exit_avm2_method();
         }
+
+        result
     }
 
     /// Run a single action from a given action reader.
@@ -518,20 +572,20 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
                     register,
                 } => self.op_debug(method, is_local_register, register_name, register),
                 Op::DebugFile { file_name } => self.op_debug_file(method, file_name),
-                Op::DebugLine { line_num } => self.op_debug_line(line_num),
+                Op::DebugLine { line_num } => self.op_debug_line(method, line_num),
                 _ => self.unknown_op(op),
             };
 
             if let Err(e) = result {
-                log::error!("AVM2 error: {}", e);
+                self.log_warning(&format!("AVM2 error: {}", e));
                 return Err(e);
             }
             result
         } else if let Ok(None) = op {
-            log::error!("Unknown opcode!");
+            self.log_warning("Unknown opcode!");
             Err("Unknown opcode!".into())
         } else if let Err(e) = op {
-            log::error!("Parse error: {:?}", e);
+            self.log_warning(&format!("Parse error: {:?}", e));
             Err(e.into())
         } else {
             unreachable!();
@@ -539,7 +593,7 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
     }
 
     fn unknown_op(&mut self, op: swf::avm2::types::Op) -> Result<FrameControl<'gc>, Error> {
-        log::error!("Unknown AVM2 opcode: {:?}", op);
+        self.log_warning(&format!("Unknown AVM2 opcode: {:?}", op));
         Err("Unknown op".into())
     }
 
@@ -1859,34 +1913,32 @@ impl<'a, 'gc, 'gc_context> Activation<'a, 'gc, 'gc_context> {
         Ok(FrameControl::Continue)
     }
 
-    #[allow(unused_variables)]
-    #[cfg(avm_debug)]
     fn op_debug_file(
         &mut self,
         method: Gc<'gc, BytecodeMethod<'gc>>,
         file_name: Index<String>,
     ) -> Result<FrameControl<'gc>, Error> {
-        let file_name = self.pool_string(method, file_name)?;
+        let file_name = self.pool_string(&method, file_name, self.context.gc_context)?;
 
         avm_debug!(self.avm2(), "File: {}", file_name);
+        self.debug_file = Some(file_name);
 
         Ok(FrameControl::Continue)
     }
 
-    #[allow(unused_variables)]
-    #[cfg(not(avm_debug))]
-    fn op_debug_file(
+    fn op_debug_line(
         &mut self,
         method: Gc<'gc, BytecodeMethod<'gc>>,
-        file_name: Index<String>,
+        line_num: u32,
     ) -> Result<FrameControl<'gc>, Error> {
-        Ok(FrameControl::Continue)
-    }
-
-    #[allow(unused_variables)]
-    fn op_debug_line(&mut self, line_num: u32) -> Result<FrameControl<'gc>, Error> {
         avm_debug!(self.avm2(), "Line: {}", line_num);
 
+        if self.context.debugger.is_some() {
+            let name = method.method().name.clone();
+            let name = self.pool_string(&method, name, self.context.gc_context)?;
+            Debugger::on_avm2_line(self, &name, line_num);
+        }
+
         Ok(FrameControl::Continue)
     }
 }
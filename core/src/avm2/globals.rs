@@ -30,7 +30,8 @@ fn trace<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error> {
     if let Some(s) = args.get(0) {
-        log::info!(target: "avm_trace", "{}", s.clone().coerce_to_string(activation)?);
+        let message = s.clone().coerce_to_string(activation)?;
+        activation.log_trace(&message);
     }
 
     Ok(Value::Undefined)
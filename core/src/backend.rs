@@ -1,5 +1,9 @@
 pub mod audio;
+pub mod external_interface;
 pub mod input;
+pub mod local_connection;
+pub mod log;
 pub mod navigator;
 pub mod render;
+pub mod socket;
 pub mod storage;
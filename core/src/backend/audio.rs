@@ -1,6 +1,8 @@
+use downcast_rs::Downcast;
 use generational_arena::{Arena, Index};
 
 pub mod decoders;
+pub mod offline;
 pub mod swf {
     pub use swf::{
         read, AudioCompression, CharacterId, Sound, SoundEnvelope, SoundEnvelopePoint, SoundEvent,
@@ -14,7 +16,7 @@ pub type SoundInstanceHandle = Index;
 
 type Error = Box<dyn std::error::Error>;
 
-pub trait AudioBackend {
+pub trait AudioBackend: Downcast {
     fn prime_audio(&mut self) {}
     fn register_sound(&mut self, swf_sound: &swf::Sound) -> Result<SoundHandle, Error>;
     fn preload_sound_stream_head(
@@ -86,6 +88,7 @@ pub trait AudioBackend {
     /// implementing it.
     fn set_frame_rate(&mut self, _frame_rate: f64) {}
 }
+impl_downcast!(AudioBackend);
 
 /// Audio backend that ignores all audio.
 pub struct NullAudioBackend {
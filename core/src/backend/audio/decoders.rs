@@ -264,12 +264,14 @@ impl Iterator for StreamTagReader {
                     if tag_len >= skip_len {
                         *audio_data = SwfSlice {
                             movie: std::sync::Arc::clone(&reader.get_ref().get_ref().movie),
+                            buffer: std::sync::Arc::clone(&reader.get_ref().get_ref().buffer),
                             start: pos + skip_len,
                             end: pos + tag_len,
                         };
                     } else {
                         *audio_data = SwfSlice {
                             movie: std::sync::Arc::clone(&reader.get_ref().get_ref().movie),
+                            buffer: std::sync::Arc::clone(&reader.get_ref().get_ref().buffer),
                             start: pos,
                             end: pos + tag_len,
                         };
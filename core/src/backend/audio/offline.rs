@@ -0,0 +1,501 @@
+//! Audio backend that mixes sounds in step with the movie's frames, rather
+//! than in real time.
+//!
+//! This is used to capture a movie's audio when exporting it, so the output
+//! only depends on the frames that have been run.
+
+use super::decoders;
+use super::{swf, AudioBackend, AudioStreamHandle, SoundHandle, SoundInstanceHandle};
+use crate::tag_utils::SwfSlice;
+use generational_arena::Arena;
+use std::sync::Arc;
+use swf::AudioCompression;
+
+type Error = Box<dyn std::error::Error>;
+
+/// The sample rate that sound envelopes and in/out points are measured in.
+const ENVELOPE_SAMPLE_RATE: u32 = 44100;
+
+/// A decoded `DefineSound`.
+struct Sound {
+    sample_rate: u16,
+
+    /// The decoded sample frames, without the MP3 encoder delay.
+    sample_frames: Arc<Vec<[i16; 2]>>,
+
+    /// The number of sample frames declared by the SWF, which the duration is
+    /// based on.
+    num_sample_frames: u32,
+}
+
+/// A playing event or stream sound.
+struct SoundInstance {
+    /// The sound this is an instance of, or `None` for stream sounds.
+    handle: Option<SoundHandle>,
+
+    source: Resampler,
+
+    envelope: Option<Envelope>,
+
+    /// The number of sample frames mixed so far, at the output sample rate.
+    position: u64,
+}
+
+pub struct OfflineAudioBackend {
+    sounds: Arena<Sound>,
+    sound_instances: Arena<SoundInstance>,
+    sample_rate: u32,
+    frame_rate: f64,
+
+    /// The number of frames mixed since the frame rate was last set.
+    frames_mixed: u64,
+
+    /// The number of sample frames mixed before the frame rate was last set.
+    samples_before_frame_rate: u64,
+
+    /// The total number of sample frames mixed.
+    samples_mixed: u64,
+}
+
+impl OfflineAudioBackend {
+    /// Creates a backend that mixes stereo audio at `sample_rate` Hz.
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sounds: Arena::new(),
+            sound_instances: Arena::new(),
+            sample_rate,
+            frame_rate: 12.0,
+            frames_mixed: 0,
+            samples_before_frame_rate: 0,
+            samples_mixed: 0,
+        }
+    }
+
+    /// The sample rate of the mixed audio.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Mixes the audio of one frame of the movie, appending it to `output`.
+    ///
+    /// This should be called once after each `Player::run_frame`. The number
+    /// of sample frames is rounded so that the audio never drifts from the
+    /// frames, however many are mixed.
+    pub fn mix_frame(&mut self, output: &mut Vec<[i16; 2]>) {
+        self.frames_mixed += 1;
+        let end = self.samples_before_frame_rate
+            + (self.frames_mixed as f64 * f64::from(self.sample_rate) / self.frame_rate).round()
+                as u64;
+        let num_sample_frames = end.saturating_sub(self.samples_mixed);
+        self.samples_mixed = end.max(self.samples_mixed);
+
+        output.reserve(num_sample_frames as usize);
+        for _ in 0..num_sample_frames {
+            let mut mixed = [0i32; 2];
+            for (_, instance) in self.sound_instances.iter_mut() {
+                if let Some(frame) = instance.next(self.sample_rate) {
+                    mixed[0] += i32::from(frame[0]);
+                    mixed[1] += i32::from(frame[1]);
+                }
+            }
+            output.push([clamp_sample(mixed[0]), clamp_sample(mixed[1])]);
+        }
+
+        self.sound_instances
+            .retain(|_, instance| !instance.source.is_exhausted());
+    }
+}
+
+impl AudioBackend for OfflineAudioBackend {
+    fn register_sound(&mut self, swf_sound: &swf::Sound) -> Result<SoundHandle, Error> {
+        // Slice off latency seek for MP3 data.
+        let (skip_sample_frames, data) =
+            if swf_sound.format.compression == AudioCompression::Mp3 && swf_sound.data.len() >= 2 {
+                let skip_sample_frames =
+                    u16::from(swf_sound.data[0]) | (u16::from(swf_sound.data[1]) << 8);
+                (skip_sample_frames, &swf_sound.data[2..])
+            } else {
+                (0, &swf_sound.data[..])
+            };
+
+        let decoder = decoders::make_decoder(&swf_sound.format, data)?;
+        let sample_rate = decoder.sample_rate();
+        let sample_frames = decoder.skip(skip_sample_frames.into()).collect();
+        Ok(self.sounds.insert(Sound {
+            sample_rate,
+            sample_frames: Arc::new(sample_frames),
+            num_sample_frames: swf_sound.num_samples,
+        }))
+    }
+
+    fn start_sound(
+        &mut self,
+        sound_handle: SoundHandle,
+        settings: &swf::SoundInfo,
+    ) -> Result<SoundInstanceHandle, Error> {
+        let sound = self.sounds.get(sound_handle).ok_or("Unknown sound")?;
+        let event_sound = EventSound::new(sound, settings);
+        let source = Resampler::new(
+            Box::new(event_sound),
+            sound.sample_rate.into(),
+            self.sample_rate,
+        );
+        Ok(self.sound_instances.insert(SoundInstance {
+            handle: Some(sound_handle),
+            source,
+            envelope: settings
+                .envelope
+                .as_ref()
+                .map(|points| Envelope::new(points)),
+            position: 0,
+        }))
+    }
+
+    fn start_stream(
+        &mut self,
+        _clip_id: swf::CharacterId,
+        _clip_frame: u16,
+        clip_data: SwfSlice,
+        stream_info: &swf::SoundStreamHead,
+    ) -> Result<AudioStreamHandle, Error> {
+        let format = &stream_info.stream_format;
+        let decoder = decoders::make_stream_decoder(format, clip_data)?;
+        let source = Resampler::new(
+            Box::new(decoder),
+            format.sample_rate.into(),
+            self.sample_rate,
+        );
+        Ok(self.sound_instances.insert(SoundInstance {
+            handle: None,
+            source,
+            envelope: None,
+            position: 0,
+        }))
+    }
+
+    fn stop_sound(&mut self, sound: SoundInstanceHandle) {
+        self.sound_instances.remove(sound);
+    }
+
+    fn stop_stream(&mut self, stream: AudioStreamHandle) {
+        self.sound_instances.remove(stream);
+    }
+
+    fn stop_all_sounds(&mut self) {
+        self.sound_instances.clear();
+    }
+
+    fn stop_sounds_with_handle(&mut self, handle: SoundHandle) {
+        let handle = Some(handle);
+        self.sound_instances
+            .retain(|_, instance| instance.handle != handle);
+    }
+
+    fn is_sound_playing_with_handle(&mut self, handle: SoundHandle) -> bool {
+        let handle = Some(handle);
+        self.sound_instances
+            .iter()
+            .any(|(_, instance)| instance.handle == handle)
+    }
+
+    fn get_sound_duration(&self, sound: SoundHandle) -> Option<u32> {
+        let sound = self.sounds.get(sound)?;
+        let ms = u64::from(sound.num_sample_frames) * 1000 / u64::from(sound.sample_rate);
+        Some(ms as u32)
+    }
+
+    fn set_frame_rate(&mut self, frame_rate: f64) {
+        if frame_rate > 0.0 {
+            self.frame_rate = frame_rate;
+            self.frames_mixed = 0;
+            self.samples_before_frame_rate = self.samples_mixed;
+        }
+    }
+}
+
+impl Default for OfflineAudioBackend {
+    fn default() -> Self {
+        OfflineAudioBackend::new(44100)
+    }
+}
+
+impl SoundInstance {
+    /// Returns the next sample frame, at the output sample rate.
+    fn next(&mut self, sample_rate: u32) -> Option<[i16; 2]> {
+        let frame = self.source.next()?;
+        let frame = match &mut self.envelope {
+            Some(envelope) => {
+                let position =
+                    self.position * u64::from(ENVELOPE_SAMPLE_RATE) / u64::from(sample_rate);
+                let [left, right] = envelope.volume(position as u32);
+                [
+                    (f32::from(frame[0]) * left) as i16,
+                    (f32::from(frame[1]) * right) as i16,
+                ]
+            }
+            None => frame,
+        };
+        self.position += 1;
+        Some(frame)
+    }
+}
+
+/// The sample frames of an event sound, between its in and out points and
+/// repeated for each loop.
+struct EventSound {
+    sample_frames: Arc<Vec<[i16; 2]>>,
+    start: usize,
+    end: usize,
+    position: usize,
+    loops_left: u16,
+}
+
+impl EventSound {
+    fn new(sound: &Sound, settings: &swf::SoundInfo) -> Self {
+        let len = sound.sample_frames.len();
+        let sample_divisor = (ENVELOPE_SAMPLE_RATE / u32::from(sound.sample_rate.max(1))).max(1);
+        let to_sample_frame = |sample: u32| ((sample / sample_divisor) as usize).min(len);
+        let start = settings.in_sample.map(to_sample_frame).unwrap_or(0);
+        let end = settings.out_sample.map(to_sample_frame).unwrap_or(len);
+        Self {
+            sample_frames: Arc::clone(&sound.sample_frames),
+            start,
+            end: end.max(start),
+            position: start,
+            loops_left: settings.num_loops,
+        }
+    }
+}
+
+impl Iterator for EventSound {
+    type Item = [i16; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.loops_left == 0 || self.start == self.end {
+            return None;
+        }
+        let frame = self.sample_frames[self.position];
+        self.position += 1;
+        if self.position >= self.end {
+            self.position = self.start;
+            self.loops_left -= 1;
+        }
+        Some(frame)
+    }
+}
+
+/// Converts a sound to the output sample rate, by linear interpolation.
+struct Resampler {
+    source: Box<dyn Iterator<Item = [i16; 2]> + Send>,
+
+    /// How far to step through the source for each output sample frame.
+    step: f64,
+
+    /// The position between `current` and `next`, from 0 to 1.
+    position: f64,
+    current: Option<[i16; 2]>,
+    next: Option<[i16; 2]>,
+}
+
+impl Resampler {
+    fn new(
+        mut source: Box<dyn Iterator<Item = [i16; 2]> + Send>,
+        from_rate: u32,
+        to_rate: u32,
+    ) -> Self {
+        let current = source.next();
+        let next = source.next();
+        Self {
+            source,
+            step: f64::from(from_rate.max(1)) / f64::from(to_rate.max(1)),
+            position: 0.0,
+            current,
+            next,
+        }
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.current.is_none()
+    }
+}
+
+impl Iterator for Resampler {
+    type Item = [i16; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.current?;
+        let next = self.next.unwrap_or(current);
+        let position = self.position;
+        let lerp = |a: i16, b: i16| {
+            (f64::from(a) + (f64::from(b) - f64::from(a)) * position).round() as i16
+        };
+        let frame = [lerp(current[0], next[0]), lerp(current[1], next[1])];
+
+        self.position += self.step;
+        while self.position >= 1.0 && self.current.is_some() {
+            self.position -= 1.0;
+            self.current = self.next;
+            self.next = self.source.next();
+        }
+        Some(frame)
+    }
+}
+
+/// The volume of each channel of an event sound over time.
+struct Envelope {
+    points: Vec<swf::SoundEnvelopePoint>,
+
+    /// The index of the last point at or before the current position.
+    index: usize,
+}
+
+impl Envelope {
+    fn new(points: &[swf::SoundEnvelopePoint]) -> Self {
+        Self {
+            points: points.to_vec(),
+            index: 0,
+        }
+    }
+
+    /// The volume of each channel at `position`, in 44.1kHz sample frames.
+    /// The positions must not decrease between calls.
+    fn volume(&mut self, position: u32) -> [f32; 2] {
+        let points = &self.points;
+        let first = match points.first() {
+            Some(first) => first,
+            None => return [1.0, 1.0],
+        };
+        if position <= first.sample {
+            return [first.left_volume, first.right_volume];
+        }
+        while self.index + 1 < points.len() && points[self.index + 1].sample <= position {
+            self.index += 1;
+        }
+        let prev = &points[self.index];
+        match points.get(self.index + 1) {
+            Some(next) if next.sample > prev.sample => {
+                let lerp = (position - prev.sample) as f32 / (next.sample - prev.sample) as f32;
+                [
+                    prev.left_volume + (next.left_volume - prev.left_volume) * lerp,
+                    prev.right_volume + (next.right_volume - prev.right_volume) * lerp,
+                ]
+            }
+            _ => [prev.left_volume, prev.right_volume],
+        }
+    }
+}
+
+fn clamp_sample(sample: i32) -> i16 {
+    sample.max(i32::from(i16::MIN)).min(i32::from(i16::MAX)) as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pcm_sound(sample_rate: u16, samples: &[i16]) -> swf::Sound {
+        swf::Sound {
+            id: 1,
+            format: swf::SoundFormat {
+                compression: AudioCompression::Uncompressed,
+                sample_rate,
+                is_stereo: false,
+                is_16_bit: true,
+            },
+            num_samples: samples.len() as u32,
+            data: samples
+                .iter()
+                .flat_map(|s| s.to_le_bytes().to_vec())
+                .collect(),
+        }
+    }
+
+    fn sound_info(num_loops: u16) -> swf::SoundInfo {
+        swf::SoundInfo {
+            event: swf::SoundEvent::Event,
+            in_sample: None,
+            out_sample: None,
+            num_loops,
+            envelope: None,
+        }
+    }
+
+    #[test]
+    fn frames_are_aligned() {
+        let mut audio = OfflineAudioBackend::new(44100);
+        audio.set_frame_rate(30.0);
+        let mut output = vec![];
+        for frame in 1..=7 {
+            audio.mix_frame(&mut output);
+            assert_eq!(
+                output.len(),
+                (frame as f64 * 44100.0 / 30.0).round() as usize
+            );
+        }
+        assert!(output.iter().all(|&frame| frame == [0, 0]));
+    }
+
+    #[test]
+    fn event_sounds_loop() {
+        let mut audio = OfflineAudioBackend::new(44100);
+        audio.set_frame_rate(44100.0 / 8.0);
+        let sound = audio
+            .register_sound(&pcm_sound(44100, &[100, 200, 300]))
+            .unwrap();
+        audio.start_sound(sound, &sound_info(2)).unwrap();
+        assert!(audio.is_sound_playing_with_handle(sound));
+
+        let mut output = vec![];
+        audio.mix_frame(&mut output);
+        let left: Vec<i16> = output.iter().map(|frame| frame[0]).collect();
+        assert_eq!(left, [100, 200, 300, 100, 200, 300, 0, 0]);
+        assert!(!audio.is_sound_playing_with_handle(sound));
+    }
+
+    #[test]
+    fn sounds_are_resampled_and_mixed() {
+        let mut audio = OfflineAudioBackend::new(44100);
+        audio.set_frame_rate(44100.0 / 4.0);
+        let low = audio
+            .register_sound(&pcm_sound(22050, &[1000, 2000]))
+            .unwrap();
+        let high = audio.register_sound(&pcm_sound(44100, &[10, 10])).unwrap();
+        audio.start_sound(low, &sound_info(1)).unwrap();
+        audio.start_sound(high, &sound_info(1)).unwrap();
+
+        let mut output = vec![];
+        audio.mix_frame(&mut output);
+        let left: Vec<i16> = output.iter().map(|frame| frame[0]).collect();
+        assert_eq!(left, [1010, 1510, 2000, 2000]);
+    }
+
+    #[test]
+    fn envelopes_set_the_volume() {
+        let mut audio = OfflineAudioBackend::new(44100);
+        audio.set_frame_rate(44100.0 / 3.0);
+        let sound = audio
+            .register_sound(&pcm_sound(44100, &[1000, 1000, 1000]))
+            .unwrap();
+        let envelope = vec![
+            swf::SoundEnvelopePoint {
+                sample: 0,
+                left_volume: 0.0,
+                right_volume: 1.0,
+            },
+            swf::SoundEnvelopePoint {
+                sample: 2,
+                left_volume: 1.0,
+                right_volume: 0.0,
+            },
+        ];
+        let settings = swf::SoundInfo {
+            envelope: Some(envelope),
+            ..sound_info(1)
+        };
+        audio.start_sound(sound, &settings).unwrap();
+
+        let mut output = vec![];
+        audio.mix_frame(&mut output);
+        assert_eq!(output, [[0, 1000], [500, 500], [1000, 0]]);
+    }
+}
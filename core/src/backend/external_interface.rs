@@ -0,0 +1,153 @@
+//! Communication with the application hosting the player, as used by
+//! `ExternalInterface`.
+
+use std::collections::{BTreeMap, HashMap};
+
+/// A value that leaves the player, either to be passed to its host or to be
+/// sent to another player in a `LocalConnection` message.
+///
+/// AVM values can't leave the garbage collected arena of the player, so they
+/// are converted to and from this form at the boundary.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExternalValue {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    List(Vec<ExternalValue>),
+    Object(BTreeMap<String, ExternalValue>),
+}
+
+impl Default for ExternalValue {
+    fn default() -> Self {
+        ExternalValue::Undefined
+    }
+}
+
+pub trait ExternalInterfaceProvider {
+    /// Whether the host is able to receive calls from the movie, and allows
+    /// it to make them.
+    fn available(&self) -> bool;
+
+    /// Call a function provided by the host.
+    ///
+    /// Returns `None` if the host has no function with the given name.
+    fn call(&mut self, name: &str, args: &[ExternalValue]) -> Option<ExternalValue>;
+
+    /// Called when the movie exposes a callback with `addCallback`.
+    ///
+    /// The host may then invoke it with `Player::call_external_callback`.
+    /// Returns `false` if the host refuses the callback.
+    fn on_callback_available(&mut self, name: &str) -> bool;
+}
+
+/// External interface provider for players that have no host to talk to.
+pub struct NullExternalInterfaceProvider;
+
+impl NullExternalInterfaceProvider {
+    pub fn new() -> Self {
+        NullExternalInterfaceProvider
+    }
+}
+
+impl Default for NullExternalInterfaceProvider {
+    fn default() -> Self {
+        NullExternalInterfaceProvider::new()
+    }
+}
+
+impl ExternalInterfaceProvider for NullExternalInterfaceProvider {
+    fn available(&self) -> bool {
+        false
+    }
+
+    fn call(&mut self, _name: &str, _args: &[ExternalValue]) -> Option<ExternalValue> {
+        None
+    }
+
+    fn on_callback_available(&mut self, _name: &str) -> bool {
+        false
+    }
+}
+
+/// A function that movies can call with `ExternalInterface.call`.
+pub type HostFunction = Box<dyn FnMut(&[ExternalValue]) -> ExternalValue>;
+
+/// External interface provider backed by a registry of Rust functions.
+///
+/// Embedding code registers functions with `add_method`, and calls into the
+/// movie with `Player::call_external_callback`.
+pub struct FunctionExternalInterfaceProvider {
+    methods: HashMap<String, HostFunction>,
+}
+
+impl FunctionExternalInterfaceProvider {
+    pub fn new() -> Self {
+        Self {
+            methods: HashMap::new(),
+        }
+    }
+
+    /// Make `method` callable from movies under `name`.
+    pub fn add_method(&mut self, name: &str, method: HostFunction) {
+        self.methods.insert(name.to_string(), method);
+    }
+}
+
+impl Default for FunctionExternalInterfaceProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExternalInterfaceProvider for FunctionExternalInterfaceProvider {
+    fn available(&self) -> bool {
+        true
+    }
+
+    fn call(&mut self, name: &str, args: &[ExternalValue]) -> Option<ExternalValue> {
+        match self.methods.get_mut(name) {
+            Some(method) => Some(method(args)),
+            None => {
+                log::warn!("Movie called unknown external method {}", name);
+                None
+            }
+        }
+    }
+
+    fn on_callback_available(&mut self, name: &str) -> bool {
+        log::info!("Movie exposed external callback {}", name);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn call_method() {
+        let mut provider = FunctionExternalInterfaceProvider::new();
+        provider.add_method(
+            "sum",
+            Box::new(|args| {
+                let sum = args
+                    .iter()
+                    .map(|arg| match arg {
+                        ExternalValue::Number(n) => *n,
+                        _ => 0.0,
+                    })
+                    .sum();
+                ExternalValue::Number(sum)
+            }),
+        );
+
+        let args = [ExternalValue::Number(1.0), ExternalValue::Number(2.0)];
+        assert_eq!(
+            provider.call("sum", &args),
+            Some(ExternalValue::Number(3.0))
+        );
+        assert_eq!(provider.call("missing", &args), None);
+    }
+}
@@ -0,0 +1,233 @@
+//! Transport for `LocalConnection` messages between player instances.
+//!
+//! Messages between movies running in the same `Player` never touch this
+//! backend. It is only consulted when a connection name isn't registered
+//! locally, so that hosts can route messages to other players they own.
+
+use crate::backend::external_interface::ExternalValue;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// A message sent with `LocalConnection.send`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalConnectionMessage {
+    /// The fully qualified name of the receiving connection.
+    pub connection_name: String,
+
+    /// The name of the method to invoke on the receiver.
+    pub method_name: String,
+
+    /// The domain of the movie that sent this message.
+    pub sender_domain: String,
+
+    /// The arguments to pass to the method.
+    pub args: Vec<ExternalValue>,
+}
+
+pub trait LocalConnectionBackend {
+    /// Claim a connection name for this player.
+    ///
+    /// Returns `false` if another player already listens on that name.
+    fn connect(&mut self, connection_name: &str) -> bool;
+
+    /// Release a connection name previously claimed with `connect`.
+    fn close(&mut self, connection_name: &str);
+
+    /// Deliver a message to whichever player listens on its connection name.
+    ///
+    /// Returns `false` if no other player listens on that name.
+    fn send(&mut self, message: LocalConnectionMessage) -> bool;
+
+    /// Take all messages that other players sent to names claimed by this
+    /// player.
+    fn poll(&mut self) -> Vec<LocalConnectionMessage>;
+}
+
+/// A transport that doesn't know about any other player.
+///
+/// Messages can still be exchanged between movies inside a single player.
+pub struct NullLocalConnectionBackend;
+
+impl NullLocalConnectionBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for NullLocalConnectionBackend {
+    fn default() -> Self {
+        NullLocalConnectionBackend::new()
+    }
+}
+
+impl LocalConnectionBackend for NullLocalConnectionBackend {
+    fn connect(&mut self, _connection_name: &str) -> bool {
+        true
+    }
+
+    fn close(&mut self, _connection_name: &str) {}
+
+    fn send(&mut self, _message: LocalConnectionMessage) -> bool {
+        false
+    }
+
+    fn poll(&mut self) -> Vec<LocalConnectionMessage> {
+        vec![]
+    }
+}
+
+#[derive(Default)]
+struct HubData {
+    /// The ID of the next transport created from this hub.
+    next_id: usize,
+
+    /// The transport that claimed each connection name.
+    owners: HashMap<String, usize>,
+
+    /// Messages waiting to be picked up by each transport.
+    queues: HashMap<usize, VecDeque<LocalConnectionMessage>>,
+}
+
+/// Routes `LocalConnection` messages between any number of players in the
+/// same process.
+///
+/// Create one hub, and hand each player its own transport from `transport`.
+#[derive(Clone, Default)]
+pub struct LocalConnectionHub(Arc<Mutex<HubData>>);
+
+impl LocalConnectionHub {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a transport for a new player connected to this hub.
+    pub fn transport(&self) -> HubLocalConnectionBackend {
+        let mut hub = self.0.lock().unwrap();
+        let id = hub.next_id;
+        hub.next_id += 1;
+        hub.queues.insert(id, VecDeque::new());
+        HubLocalConnectionBackend {
+            hub: self.clone(),
+            id,
+            names: HashSet::new(),
+        }
+    }
+}
+
+/// A player's endpoint on a `LocalConnectionHub`.
+pub struct HubLocalConnectionBackend {
+    hub: LocalConnectionHub,
+    id: usize,
+
+    /// The connection names claimed by this transport.
+    names: HashSet<String>,
+}
+
+impl LocalConnectionBackend for HubLocalConnectionBackend {
+    fn connect(&mut self, connection_name: &str) -> bool {
+        let mut hub = self.hub.0.lock().unwrap();
+        match hub.owners.get(connection_name) {
+            Some(owner) if *owner != self.id => false,
+            _ => {
+                hub.owners.insert(connection_name.to_string(), self.id);
+                self.names.insert(connection_name.to_string());
+                true
+            }
+        }
+    }
+
+    fn close(&mut self, connection_name: &str) {
+        let mut hub = self.hub.0.lock().unwrap();
+        if hub.owners.get(connection_name) == Some(&self.id) {
+            hub.owners.remove(connection_name);
+        }
+        self.names.remove(connection_name);
+    }
+
+    fn send(&mut self, message: LocalConnectionMessage) -> bool {
+        let mut hub = self.hub.0.lock().unwrap();
+        let owner = match hub.owners.get(&message.connection_name) {
+            Some(owner) if *owner != self.id => *owner,
+            _ => return false,
+        };
+        if let Some(queue) = hub.queues.get_mut(&owner) {
+            queue.push_back(message);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn poll(&mut self) -> Vec<LocalConnectionMessage> {
+        let mut hub = self.hub.0.lock().unwrap();
+        hub.queues
+            .get_mut(&self.id)
+            .map(|queue| queue.drain(..).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for HubLocalConnectionBackend {
+    fn drop(&mut self) {
+        if let Ok(mut hub) = self.hub.0.lock() {
+            for name in self.names.drain() {
+                if hub.owners.get(&name) == Some(&self.id) {
+                    hub.owners.remove(&name);
+                }
+            }
+            hub.queues.remove(&self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(connection_name: &str) -> LocalConnectionMessage {
+        LocalConnectionMessage {
+            connection_name: connection_name.to_string(),
+            method_name: "receive".to_string(),
+            sender_domain: "localhost".to_string(),
+            args: vec![
+                ExternalValue::Number(1.0),
+                ExternalValue::String("a".into()),
+            ],
+        }
+    }
+
+    #[test]
+    fn hub_routes_messages_to_owner() {
+        let hub = LocalConnectionHub::new();
+        let mut a = hub.transport();
+        let mut b = hub.transport();
+
+        assert!(a.connect("localhost:test"));
+        assert!(!b.connect("localhost:test"));
+
+        assert!(b.send(message("localhost:test")));
+        assert!(!b.send(message("localhost:other")));
+        // A player's own connections are resolved without the transport.
+        assert!(!a.send(message("localhost:test")));
+
+        assert_eq!(a.poll(), vec![message("localhost:test")]);
+        assert!(a.poll().is_empty());
+        assert!(b.poll().is_empty());
+    }
+
+    #[test]
+    fn hub_releases_names() {
+        let hub = LocalConnectionHub::new();
+        let mut a = hub.transport();
+        let mut b = hub.transport();
+
+        assert!(a.connect("localhost:test"));
+        a.close("localhost:test");
+        assert!(b.connect("localhost:test"));
+        assert!(!a.connect("localhost:test"));
+
+        drop(b);
+        assert!(a.connect("localhost:test"));
+        assert!(!a.send(message("localhost:test")));
+    }
+}
@@ -0,0 +1,166 @@
+//! Output of `trace` calls and ActionScript warnings
+
+use downcast_rs::Downcast;
+
+/// The ActionScript virtual machine that produced a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AvmVersion {
+    Avm1,
+    Avm2,
+}
+
+/// Where a message was logged from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogSource {
+    /// The URL of the movie whose code logged the message.
+    pub movie_url: Option<String>,
+
+    pub avm: AvmVersion,
+
+    /// The current frame of the clip running the code, if known.
+    pub frame: Option<u16>,
+}
+
+pub trait LogBackend: Downcast {
+    /// Called when the movie calls `trace`.
+    fn trace(&mut self, source: &LogSource, message: &str);
+
+    /// Called when ActionScript does something invalid or unsupported.
+    fn avm_warning(&mut self, source: &LogSource, message: &str);
+
+    /// Lines of text to draw over the movie, oldest first, such as the last
+    /// messages of a trace console.
+    fn overlay(&self) -> &[String] {
+        &[]
+    }
+}
+impl_downcast!(LogBackend);
+
+/// Log backend that discards every message.
+#[derive(Default)]
+pub struct NullLogBackend;
+
+impl NullLogBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl LogBackend for NullLogBackend {
+    fn trace(&mut self, _source: &LogSource, _message: &str) {}
+
+    fn avm_warning(&mut self, _source: &LogSource, _message: &str) {}
+}
+
+/// Log backend that passes every message on to the `log` crate, with traces
+/// logged under the `avm_trace` target.
+///
+/// This is what the player uses unless the host sets another backend.
+#[derive(Default)]
+pub struct ForwardingLogBackend;
+
+impl ForwardingLogBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl LogBackend for ForwardingLogBackend {
+    fn trace(&mut self, _source: &LogSource, message: &str) {
+        log::info!(target: "avm_trace", "{}", message);
+    }
+
+    fn avm_warning(&mut self, _source: &LogSource, message: &str) {
+        log::warn!("{}", message);
+    }
+}
+
+/// Log backend that prints traces to stdout, and passes warnings on to the
+/// `log` crate.
+#[derive(Default)]
+pub struct StdoutLogBackend;
+
+impl StdoutLogBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl LogBackend for StdoutLogBackend {
+    fn trace(&mut self, _source: &LogSource, message: &str) {
+        println!("{}", message);
+    }
+
+    fn avm_warning(&mut self, _source: &LogSource, message: &str) {
+        log::warn!("{}", message);
+    }
+}
+
+/// A message kept by a `MemoryLogBackend`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogEntry {
+    Trace(LogSource, String),
+    AvmWarning(LogSource, String),
+}
+
+/// Log backend that keeps every message, so that tests can check them.
+#[derive(Default)]
+pub struct MemoryLogBackend {
+    entries: Vec<LogEntry>,
+}
+
+impl MemoryLogBackend {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Every message logged so far, in order.
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+
+    /// The output of every `trace` call so far, one per line.
+    pub fn trace_output(&self) -> String {
+        let mut output = String::new();
+        for entry in &self.entries {
+            if let LogEntry::Trace(_, message) = entry {
+                output.push_str(message);
+                output.push('\n');
+            }
+        }
+        output
+    }
+}
+
+impl LogBackend for MemoryLogBackend {
+    fn trace(&mut self, source: &LogSource, message: &str) {
+        self.entries
+            .push(LogEntry::Trace(source.clone(), message.to_string()));
+    }
+
+    fn avm_warning(&mut self, source: &LogSource, message: &str) {
+        self.entries
+            .push(LogEntry::AvmWarning(source.clone(), message.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_backend_collects_traces() {
+        let source = LogSource {
+            movie_url: None,
+            avm: AvmVersion::Avm1,
+            frame: Some(1),
+        };
+        let mut log = MemoryLogBackend::new();
+        log.trace(&source, "hello");
+        log.avm_warning(&source, "Unimplemented property _quality");
+        log.trace(&source, "world");
+
+        assert_eq!(log.trace_output(), "hello\nworld\n");
+        assert_eq!(log.entries().len(), 3);
+    }
+}
@@ -2,12 +2,14 @@
 
 use crate::loader::Error;
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
 use std::fs;
 use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::ptr::null;
+use std::rc::Rc;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
 use std::time::Duration;
@@ -67,6 +69,16 @@ pub fn url_from_relative_url(base: &str, relative: &str) -> Result<Url, ParseErr
     parsed
 }
 
+/// Returns the domain that content loaded from `url` belongs to.
+///
+/// Content without a host, such as local files or movies loaded from raw
+/// data, belongs to `localhost`.
+pub fn url_domain(url: Option<&str>) -> String {
+    url.and_then(|url| Url::parse(url).ok())
+        .and_then(|url| url.host_str().map(|host| host.to_string()))
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
 /// Enumerates all possible navigation methods.
 #[derive(Copy, Clone)]
 pub enum NavigationMethod {
@@ -140,6 +152,112 @@ impl RequestOptions {
 /// result of type `Result<T, E>`.
 pub type OwnedFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + 'static>>;
 
+/// A response body that is received in chunks.
+///
+/// Backends produce the chunks from a future that is handed a `ChunkSink` to
+/// send them through. That future runs whenever the consumer waits on
+/// `next_chunk`, so the stream need not be spawned separately.
+pub struct ChunkStream {
+    /// The future producing the chunks, until it completes.
+    body: Option<OwnedFuture<(), Error>>,
+
+    /// The outcome of the body future, once it has completed.
+    result: Result<(), Error>,
+
+    sink: ChunkSink,
+}
+
+impl ChunkStream {
+    /// Construct a stream whose chunks are sent by the future returned from
+    /// `body`.
+    pub fn new<F>(body: F) -> Self
+    where
+        F: FnOnce(ChunkSink) -> OwnedFuture<(), Error>,
+    {
+        let sink = ChunkSink(Rc::new(RefCell::new(ChunkQueue::default())));
+        Self {
+            body: Some(body(sink.clone())),
+            result: Ok(()),
+            sink,
+        }
+    }
+
+    /// The length of the whole body, if the backend knows it.
+    pub fn total_length(&self) -> Option<usize> {
+        self.sink.0.borrow().total_length
+    }
+
+    /// Wait for the next chunk of the body.
+    ///
+    /// Yields `None` once the whole body has been received.
+    pub fn next_chunk(&mut self) -> NextChunk<'_> {
+        NextChunk(self)
+    }
+}
+
+/// Treat a fetch of a whole body as a stream of one chunk.
+impl From<OwnedFuture<Vec<u8>, Error>> for ChunkStream {
+    fn from(fetch: OwnedFuture<Vec<u8>, Error>) -> Self {
+        Self::new(|sink| {
+            Box::pin(async move {
+                let data = fetch.await?;
+                sink.set_total_length(data.len());
+                sink.send(data);
+                Ok(())
+            })
+        })
+    }
+}
+
+/// The future returned by `ChunkStream::next_chunk`.
+pub struct NextChunk<'a>(&'a mut ChunkStream);
+
+impl<'a> Future for NextChunk<'a> {
+    type Output = Result<Option<Vec<u8>>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let stream = &mut *self.get_mut().0;
+        if let Some(body) = &mut stream.body {
+            if let Poll::Ready(result) = body.as_mut().poll(cx) {
+                stream.body = None;
+                stream.result = result;
+            }
+        }
+
+        // Chunks sent before the body failed are still handed out first.
+        if let Some(chunk) = stream.sink.0.borrow_mut().chunks.pop_front() {
+            Poll::Ready(Ok(Some(chunk)))
+        } else if stream.body.is_some() {
+            Poll::Pending
+        } else {
+            Poll::Ready(std::mem::replace(&mut stream.result, Ok(())).map(|()| None))
+        }
+    }
+}
+
+/// The end of a `ChunkStream` that backends send chunks through.
+#[derive(Clone)]
+pub struct ChunkSink(Rc<RefCell<ChunkQueue>>);
+
+impl ChunkSink {
+    /// Send the next chunk of the body.
+    pub fn send(&self, chunk: Vec<u8>) {
+        self.0.borrow_mut().chunks.push_back(chunk);
+    }
+
+    /// Report the length of the whole body, such as from a `Content-Length`
+    /// header.
+    pub fn set_total_length(&self, total_length: usize) {
+        self.0.borrow_mut().total_length = Some(total_length);
+    }
+}
+
+#[derive(Default)]
+struct ChunkQueue {
+    chunks: VecDeque<Vec<u8>>,
+    total_length: Option<usize>,
+}
+
 /// A backend interacting with a browser environment.
 pub trait NavigatorBackend {
     /// Cause a browser navigation to a given URL.
@@ -174,6 +292,15 @@ pub trait NavigatorBackend {
     /// Fetch data at a given URL and return it some time in the future.
     fn fetch(&self, url: &str, request_options: RequestOptions) -> OwnedFuture<Vec<u8>, Error>;
 
+    /// Fetch data at a given URL, yielding it in chunks as it arrives.
+    ///
+    /// This lets movies start playing before they have fully downloaded. The
+    /// default implementation yields the whole response as a single chunk
+    /// once `fetch` completes.
+    fn fetch_stream(&self, url: &str, request_options: RequestOptions) -> ChunkStream {
+        self.fetch(url, request_options).into()
+    }
+
     /// Get the amount of time since the SWF was launched.
     /// Used by the `getTimer` ActionScript call.
     fn time_since_launch(&mut self) -> Duration;
@@ -378,3 +505,97 @@ impl NavigatorBackend for NullNavigatorBackend {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A future that is pending the first time it is polled.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                Poll::Pending
+            }
+        }
+    }
+
+    fn poll_chunk(stream: &mut ChunkStream) -> Poll<Result<Option<Vec<u8>>, Error>> {
+        let waker = unsafe { Waker::from_raw(NullExecutor::raw_waker()) };
+        let mut context = Context::from_waker(&waker);
+        let mut next_chunk = stream.next_chunk();
+        Pin::new(&mut next_chunk).poll(&mut context)
+    }
+
+    #[test]
+    fn chunks_arrive_in_order() {
+        let mut stream = ChunkStream::new(|sink| {
+            Box::pin(async move {
+                sink.set_total_length(3);
+                sink.send(b"a".to_vec());
+                YieldOnce(false).await;
+                sink.send(b"b".to_vec());
+                sink.send(b"c".to_vec());
+                YieldOnce(false).await;
+                Ok(())
+            })
+        });
+        assert_eq!(stream.total_length(), None);
+
+        assert!(matches!(poll_chunk(&mut stream), Poll::Ready(Ok(Some(chunk))) if chunk == b"a"));
+        assert_eq!(stream.total_length(), Some(3));
+        assert!(matches!(poll_chunk(&mut stream), Poll::Ready(Ok(Some(chunk))) if chunk == b"b"));
+        assert!(matches!(poll_chunk(&mut stream), Poll::Ready(Ok(Some(chunk))) if chunk == b"c"));
+        assert!(matches!(poll_chunk(&mut stream), Poll::Ready(Ok(None))));
+    }
+
+    #[test]
+    fn pending_until_a_chunk_arrives() {
+        let mut stream = ChunkStream::new(|sink| {
+            Box::pin(async move {
+                YieldOnce(false).await;
+                sink.send(b"a".to_vec());
+                Ok(())
+            })
+        });
+        assert!(matches!(poll_chunk(&mut stream), Poll::Pending));
+        assert!(matches!(poll_chunk(&mut stream), Poll::Ready(Ok(Some(chunk))) if chunk == b"a"));
+        assert!(matches!(poll_chunk(&mut stream), Poll::Ready(Ok(None))));
+    }
+
+    #[test]
+    fn chunks_before_an_error() {
+        let mut stream = ChunkStream::new(|sink| {
+            Box::pin(async move {
+                sink.send(b"a".to_vec());
+                sink.send(b"b".to_vec());
+                Err(Error::FetchError("test".to_string()))
+            })
+        });
+        assert!(matches!(poll_chunk(&mut stream), Poll::Ready(Ok(Some(chunk))) if chunk == b"a"));
+        assert!(matches!(poll_chunk(&mut stream), Poll::Ready(Ok(Some(chunk))) if chunk == b"b"));
+        assert!(matches!(
+            poll_chunk(&mut stream),
+            Poll::Ready(Err(Error::FetchError(_)))
+        ));
+        // The error is only reported once.
+        assert!(matches!(poll_chunk(&mut stream), Poll::Ready(Ok(None))));
+    }
+
+    #[test]
+    fn whole_fetch_as_one_chunk() {
+        let fetch: OwnedFuture<Vec<u8>, Error> = Box::pin(async { Ok(vec![1, 2, 3]) });
+        let mut stream = ChunkStream::from(fetch);
+        assert!(
+            matches!(poll_chunk(&mut stream), Poll::Ready(Ok(Some(chunk))) if chunk == [1, 2, 3])
+        );
+        assert_eq!(stream.total_length(), Some(3));
+        assert!(matches!(poll_chunk(&mut stream), Poll::Ready(Ok(None))));
+    }
+}
@@ -39,6 +39,9 @@ pub trait RenderBackend: Downcast {
     fn push_mask(&mut self);
     fn activate_mask(&mut self);
     fn pop_mask(&mut self);
+
+    /// Change how smoothly shapes and bitmaps are drawn.
+    fn set_quality(&mut self, quality: StageQuality);
 }
 impl_downcast!(RenderBackend);
 
@@ -58,6 +61,14 @@ pub struct BitmapInfo {
     pub height: u16,
 }
 
+/// The rendering quality of the stage, as chosen in the context menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StageQuality {
+    Low,
+    Medium,
+    High,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Letterbox {
     None,
@@ -141,6 +152,7 @@ impl RenderBackend for NullRenderer {
     fn push_mask(&mut self) {}
     fn activate_mask(&mut self) {}
     fn pop_mask(&mut self) {}
+    fn set_quality(&mut self, _quality: StageQuality) {}
 }
 
 /// The format of image data in a DefineBitsJpeg2/3 tag.
@@ -512,3 +524,149 @@ pub fn srgb_to_linear(color: [f32; 4]) -> [f32; 4] {
         color[3],
     ]
 }
+
+/// Converts an RGBA color from linear color space to sRGB space.
+pub fn linear_to_srgb(color: [f32; 4]) -> [f32; 4] {
+    fn to_srgb_channel(n: f32) -> f32 {
+        if n <= 0.003_130_8 {
+            n * 12.92
+        } else {
+            1.055 * f32::powf(n, 1.0 / 2.4) - 0.055
+        }
+    }
+    [
+        to_srgb_channel(color[0]),
+        to_srgb_channel(color[1]),
+        to_srgb_channel(color[2]),
+        color[3],
+    ]
+}
+
+/// The number of entries in a gradient lookup table.
+///
+/// Flash bakes gradients into 256 colors, one for each possible ratio.
+pub const GRADIENT_SIZE: usize = 256;
+
+/// Bakes the colors of `gradient` into a `GRADIENT_SIZE`x1 RGBA texture.
+///
+/// Colors are interpolated between the records by ratio, and padded with the
+/// first and last record. Linear RGB gradients are interpolated in linear
+/// color space before being converted back to sRGB.
+pub fn gradient_lookup_table(gradient: &swf::Gradient) -> Vec<u8> {
+    let is_linear = gradient.interpolation == swf::GradientInterpolation::LinearRGB;
+    let stops: Vec<(f32, [f32; 4])> = gradient
+        .records
+        .iter()
+        .map(|record| {
+            let color = [
+                f32::from(record.color.r) / 255.0,
+                f32::from(record.color.g) / 255.0,
+                f32::from(record.color.b) / 255.0,
+                f32::from(record.color.a) / 255.0,
+            ];
+            let color = if is_linear {
+                srgb_to_linear(color)
+            } else {
+                color
+            };
+            (f32::from(record.ratio), color)
+        })
+        .collect();
+
+    let mut lookup_table = Vec::with_capacity(GRADIENT_SIZE * 4);
+    for i in 0..GRADIENT_SIZE {
+        let t = i as f32;
+        let color = match stops.iter().position(|(ratio, _)| *ratio >= t) {
+            None => stops.last().map(|(_, color)| *color),
+            Some(0) => stops.first().map(|(_, color)| *color),
+            Some(j) => {
+                let (ratio0, color0) = stops[j - 1];
+                let (ratio1, color1) = stops[j];
+                let a = (t - ratio0) / (ratio1 - ratio0);
+                let mut color = [0.0; 4];
+                for (c, (c0, c1)) in color.iter_mut().zip(color0.iter().zip(&color1)) {
+                    *c = c0 + (c1 - c0) * a;
+                }
+                Some(color)
+            }
+        }
+        .unwrap_or([0.0; 4]);
+        let color = if is_linear {
+            linear_to_srgb(color)
+        } else {
+            color
+        };
+        lookup_table.extend(
+            color
+                .iter()
+                .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8),
+        );
+    }
+    lookup_table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swf::{Gradient, GradientInterpolation, GradientRecord, GradientSpread, Matrix};
+
+    fn gradient(interpolation: GradientInterpolation, records: &[(u8, Color)]) -> Gradient {
+        Gradient {
+            matrix: Matrix::identity(),
+            spread: GradientSpread::Pad,
+            interpolation,
+            records: records
+                .iter()
+                .map(|(ratio, color)| GradientRecord {
+                    ratio: *ratio,
+                    color: color.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn entry(lookup_table: &[u8], i: usize) -> &[u8] {
+        &lookup_table[i * 4..i * 4 + 4]
+    }
+
+    #[test]
+    fn lookup_table_pads_and_interpolates() {
+        let black = Color::from_rgb(0x000000, 255);
+        let white = Color::from_rgb(0xFFFFFF, 255);
+        let table = gradient_lookup_table(&gradient(
+            GradientInterpolation::RGB,
+            &[(64, black), (192, white)],
+        ));
+        assert_eq!(table.len(), GRADIENT_SIZE * 4);
+        assert_eq!(entry(&table, 0), [0, 0, 0, 255]);
+        assert_eq!(entry(&table, 64), [0, 0, 0, 255]);
+        assert_eq!(entry(&table, 128), [128, 128, 128, 255]);
+        assert_eq!(entry(&table, 192), [255, 255, 255, 255]);
+        assert_eq!(entry(&table, 255), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn lookup_table_has_no_stop_limit() {
+        let records: Vec<_> = (0..=50u8)
+            .map(|i| (i * 5, Color::from_rgb(u32::from(i) * 5, 255)))
+            .collect();
+        let table = gradient_lookup_table(&gradient(GradientInterpolation::RGB, &records));
+        for (ratio, color) in &records {
+            assert_eq!(entry(&table, usize::from(*ratio))[2], color.b);
+        }
+    }
+
+    #[test]
+    fn lookup_table_interpolates_linear_rgb() {
+        let black = Color::from_rgb(0x000000, 255);
+        let white = Color::from_rgb(0xFFFFFF, 255);
+        let table = gradient_lookup_table(&gradient(
+            GradientInterpolation::LinearRGB,
+            &[(0, black), (255, white)],
+        ));
+        // Halfway in linear space is much brighter than halfway in sRGB.
+        assert!(entry(&table, 128)[0] > 180);
+        assert_eq!(entry(&table, 0), [0, 0, 0, 255]);
+        assert_eq!(entry(&table, 255), [255, 255, 255, 255]);
+    }
+}
@@ -0,0 +1,86 @@
+//! Raw TCP sockets, as used by `XMLSocket`.
+//!
+//! The player checks the sandbox of a movie and fetches socket policy files
+//! through this backend before it connects anywhere on the movie's behalf, so
+//! backends don't need to restrict the connections they make.
+
+use generational_arena::{Arena, Index};
+
+pub type SocketHandle = Index;
+
+/// Something that happened to a socket since it was last polled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SocketEvent {
+    /// The connection attempt finished, successfully or not.
+    Connected(bool),
+
+    /// Bytes were received from the remote end.
+    Data(Vec<u8>),
+
+    /// The connection was closed by the remote end, or failed.
+    Closed,
+}
+
+pub trait SocketBackend {
+    /// Start connecting to the given host and port.
+    ///
+    /// The connection is made asynchronously; the outcome is reported by
+    /// `poll` as a `SocketEvent::Connected`.
+    fn connect(&mut self, host: &str, port: u16) -> SocketHandle;
+
+    /// Queue bytes to be sent over a socket.
+    ///
+    /// Bytes sent while the socket is still connecting are sent once it
+    /// connects. Returns `false`, dropping the bytes, if the socket failed to
+    /// connect or was closed.
+    fn send(&mut self, handle: SocketHandle, data: Vec<u8>) -> bool;
+
+    /// Close a socket. No further events will be reported for it.
+    fn close(&mut self, handle: SocketHandle);
+
+    /// Take every event that happened on any socket since the last call.
+    fn poll(&mut self) -> Vec<(SocketHandle, SocketEvent)>;
+}
+
+/// Socket backend that can't connect anywhere.
+///
+/// Every connection attempt fails.
+pub struct NullSocketBackend {
+    sockets: Arena<()>,
+    events: Vec<(SocketHandle, SocketEvent)>,
+}
+
+impl NullSocketBackend {
+    pub fn new() -> Self {
+        Self {
+            sockets: Arena::new(),
+            events: vec![],
+        }
+    }
+}
+
+impl Default for NullSocketBackend {
+    fn default() -> Self {
+        NullSocketBackend::new()
+    }
+}
+
+impl SocketBackend for NullSocketBackend {
+    fn connect(&mut self, _host: &str, _port: u16) -> SocketHandle {
+        let handle = self.sockets.insert(());
+        self.events.push((handle, SocketEvent::Connected(false)));
+        handle
+    }
+
+    fn send(&mut self, _handle: SocketHandle, _data: Vec<u8>) -> bool {
+        false
+    }
+
+    fn close(&mut self, handle: SocketHandle) {
+        self.sockets.remove(handle);
+    }
+
+    fn poll(&mut self) -> Vec<(SocketHandle, SocketEvent)> {
+        std::mem::take(&mut self.events)
+    }
+}
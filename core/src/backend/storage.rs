@@ -1,5 +1,112 @@
+//! Persistent storage for local shared objects
+
+use crate::backend::navigator::url_domain;
 use downcast_rs::Downcast;
 use std::collections::HashMap;
+use url::Url;
+
+/// The amount of storage, in bytes, that a single domain may use before
+/// `SharedObject.flush` starts failing.
+///
+/// This is the default value of the Flash Player settings manager.
+pub const DEFAULT_DOMAIN_QUOTA: usize = 100 * 1024;
+
+/// Characters that Flash Player refuses to accept in a shared object name.
+const INVALID_NAME_CHARS: &[char] = &[
+    '~', '%', '&', '\\', ';', ':', '"', '\'', ',', '<', '>', '?', '#', ' ',
+];
+
+/// Split a movie URL into the domain and path used to scope its shared objects.
+///
+/// Movies without a URL, or with a URL that cannot be parsed, are treated as
+/// living at the root of the local domain.
+fn movie_scope(movie_url: Option<&str>) -> (String, String) {
+    let domain = url_domain(movie_url);
+    let path = movie_url
+        .and_then(|url| Url::parse(url).ok())
+        .map(|url| url.path().trim_end_matches('/').to_string())
+        .unwrap_or_default();
+    (domain, path)
+}
+
+/// Returns the domain that a movie's shared objects are stored under, and
+/// whose quota they count against.
+pub fn shared_object_domain(movie_url: Option<&str>) -> String {
+    url_domain(movie_url)
+}
+
+/// Build the storage key of a local shared object, following the rules of
+/// `SharedObject.getLocal(name, localPath, secure)`.
+///
+/// The key is made of the domain of the movie, followed by `local_path` (or the
+/// full path of the movie if no path is given) and the object name, separated
+/// by `/`. Keys of secure objects are prefixed with `#` so that they never
+/// collide with the insecure object of the same name.
+///
+/// Returns `None` if the object may not be accessed:
+///
+/// * `name` is empty or contains characters that Flash rejects.
+/// * `local_path` is not a prefix of the movie's own path.
+/// * `secure` was requested by a movie that was not loaded over HTTPS.
+pub fn shared_object_key(
+    movie_url: Option<&str>,
+    name: &str,
+    local_path: Option<&str>,
+    secure: bool,
+) -> Option<String> {
+    if name.contains(INVALID_NAME_CHARS)
+        || name
+            .split('/')
+            .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+    {
+        return None;
+    }
+
+    if secure {
+        let is_https = movie_url
+            .and_then(|url| Url::parse(url).ok())
+            .map(|url| url.scheme() == "https")
+            .unwrap_or(false);
+        if !is_https {
+            return None;
+        }
+    }
+
+    let (domain, movie_path) = movie_scope(movie_url);
+    let path = match local_path {
+        Some(local_path) => {
+            let local_path = local_path.trim_end_matches('/');
+            if local_path.split('/').any(|segment| segment == "..") {
+                return None;
+            }
+
+            let is_prefix = local_path.is_empty()
+                || (local_path.starts_with('/')
+                    && movie_path.starts_with(local_path)
+                    && movie_path[local_path.len()..]
+                        .chars()
+                        .next()
+                        .map(|c| c == '/')
+                        .unwrap_or(true));
+            if !is_prefix {
+                return None;
+            }
+
+            local_path
+        }
+        None => &movie_path,
+    };
+
+    let prefix = if secure { "#" } else { "" };
+    Some(format!("{}{}{}/{}", prefix, domain, path, name))
+}
+
+/// Returns the domain that a shared object key counts against for quota
+/// purposes.
+pub fn shared_object_key_domain(key: &str) -> &str {
+    let key = key.trim_start_matches('#');
+    key.split('/').next().unwrap_or(key)
+}
 
 pub trait StorageBackend: Downcast {
     fn get_string(&self, name: &str) -> Option<String>;
@@ -11,6 +118,16 @@ pub trait StorageBackend: Downcast {
     }
 
     fn remove_key(&mut self, name: &str);
+
+    /// Returns the total size, in bytes, of all values whose key begins with
+    /// `prefix`.
+    fn get_usage(&self, prefix: &str) -> usize;
+
+    /// Returns the total size, in bytes, of all shared objects stored under
+    /// the given domain, including secure objects.
+    fn get_domain_usage(&self, domain: &str) -> usize {
+        self.get_usage(&format!("{}/", domain)) + self.get_usage(&format!("#{}/", domain))
+    }
 }
 impl_downcast!(StorageBackend);
 
@@ -39,4 +156,98 @@ impl StorageBackend for MemoryStorageBackend {
     fn remove_key(&mut self, name: &str) {
         self.map.remove(name);
     }
+
+    fn get_usage(&self, prefix: &str) -> usize {
+        self.map
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .map(|(_, value)| value.len())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_uses_movie_host_and_path() {
+        assert_eq!(
+            shared_object_key(
+                Some("http://www.example.com/games/foo.swf"),
+                "save",
+                None,
+                false
+            ),
+            Some("www.example.com/games/foo.swf/save".to_string())
+        );
+        assert_eq!(
+            shared_object_key(Some("file:///home/user/foo.swf"), "save", None, false),
+            Some("localhost/home/user/foo.swf/save".to_string())
+        );
+        assert_eq!(
+            shared_object_key(None, "save", None, false),
+            Some("localhost/save".to_string())
+        );
+    }
+
+    #[test]
+    fn key_local_path_must_be_prefix() {
+        let url = Some("http://example.com/games/foo.swf");
+        assert_eq!(
+            shared_object_key(url, "save", Some("/"), false),
+            Some("example.com/save".to_string())
+        );
+        assert_eq!(
+            shared_object_key(url, "save", Some("/games"), false),
+            Some("example.com/games/save".to_string())
+        );
+        assert_eq!(
+            shared_object_key(url, "save", Some("/games/"), false),
+            Some("example.com/games/save".to_string())
+        );
+        assert_eq!(shared_object_key(url, "save", Some("/gam"), false), None);
+        assert_eq!(shared_object_key(url, "save", Some("/other"), false), None);
+        assert_eq!(
+            shared_object_key(url, "save", Some("/games/.."), false),
+            None
+        );
+    }
+
+    #[test]
+    fn key_rejects_invalid_names() {
+        let url = Some("http://example.com/foo.swf");
+        assert_eq!(shared_object_key(url, "", None, false), None);
+        assert_eq!(shared_object_key(url, "my save", None, false), None);
+        assert_eq!(shared_object_key(url, "a:b", None, false), None);
+        assert_eq!(
+            shared_object_key(url, "dir/save", None, false),
+            Some("example.com/foo.swf/dir/save".to_string())
+        );
+    }
+
+    #[test]
+    fn key_secure_requires_https() {
+        assert_eq!(
+            shared_object_key(Some("http://example.com/foo.swf"), "save", None, true),
+            None
+        );
+        assert_eq!(
+            shared_object_key(Some("https://example.com/foo.swf"), "save", None, true),
+            Some("#example.com/foo.swf/save".to_string())
+        );
+        assert_eq!(
+            shared_object_key_domain("#example.com/foo.swf/save"),
+            "example.com"
+        );
+    }
+
+    #[test]
+    fn memory_backend_domain_usage() {
+        let mut storage = MemoryStorageBackend::default();
+        storage.put_string("example.com/a.swf/save", "1234".to_string());
+        storage.put_string("#example.com/a.swf/save", "12".to_string());
+        storage.put_string("example.com.evil/a.swf/save", "123".to_string());
+        assert_eq!(storage.get_domain_usage("example.com"), 6);
+    }
 }
@@ -2,19 +2,27 @@
 use crate::avm1;
 
 use crate::avm1::globals::system::SystemProperties;
-use crate::avm1::{Avm1, Object, Timers, Value};
+use crate::avm1::{Avm1, ExternalCallbacks, LocalConnections, Object, Timers, Value, XmlSockets};
 use crate::avm2::Avm2;
+use crate::backend::external_interface::ExternalInterfaceProvider;
 use crate::backend::input::InputBackend;
+use crate::backend::local_connection::LocalConnectionBackend;
+use crate::backend::log::LogBackend;
+use crate::backend::socket::SocketBackend;
 use crate::backend::storage::StorageBackend;
 use crate::backend::{audio::AudioBackend, navigator::NavigatorBackend, render::RenderBackend};
+use crate::context_menu::ContextMenuState;
+use crate::debugger::Debugger;
 use crate::display_object::EditText;
 use crate::library::Library;
 use crate::loader::LoadManager;
-use crate::player::Player;
+use crate::player::{Player, StageAlign, StageScaleMode};
 use crate::prelude::*;
+use crate::security::Security;
 use crate::tag_utils::{SwfMovie, SwfSlice};
 use crate::transform::TransformStack;
 use core::fmt;
+use enumset::EnumSet;
 use gc_arena::{Collect, CollectionContext, MutationContext};
 use rand::rngs::SmallRng;
 use std::collections::{BTreeMap, HashMap, VecDeque};
@@ -53,7 +61,7 @@ pub struct UpdateContext<'a, 'gc, 'gc_context> {
     pub swf: &'a Arc<SwfMovie>,
 
     /// The audio backend, used by display objects and AVM to play audio.
-    pub audio: &'a mut (dyn AudioBackend + 'a),
+    pub audio: &'a mut dyn AudioBackend,
 
     /// The navigator backend, used by the AVM to make HTTP requests and visit webpages.
     pub navigator: &'a mut (dyn NavigatorBackend + 'a),
@@ -67,6 +75,20 @@ pub struct UpdateContext<'a, 'gc, 'gc_context> {
     /// The storage backend, used for storing persistent state
     pub storage: &'a mut dyn StorageBackend,
 
+    /// The local connection backend, used to exchange `LocalConnection`
+    /// messages with other players.
+    pub local_connection: &'a mut dyn LocalConnectionBackend,
+
+    /// The socket backend, used by `XMLSocket` to talk to servers.
+    pub socket: &'a mut dyn SocketBackend,
+
+    /// The external interface provider, used by `ExternalInterface` to talk to
+    /// the host application.
+    pub external_interface: &'a mut dyn ExternalInterfaceProvider,
+
+    /// The log backend, which receives `trace` output and AVM warnings.
+    pub log: &'a mut dyn LogBackend,
+
     /// The RNG, used by the AVM `RandomNumber` opcode,  `Math.random(),` and `random()`.
     pub rng: &'a mut SmallRng,
 
@@ -89,6 +111,9 @@ pub struct UpdateContext<'a, 'gc, 'gc_context> {
     /// The dimensions of the stage.
     pub stage_size: (Twips, Twips),
 
+    /// The dimensions of the viewport the stage is shown in.
+    pub viewport_size: (Twips, Twips),
+
     /// Weak reference to the player.
     ///
     /// Recipients of an update context may upgrade the reference to ensure
@@ -104,6 +129,9 @@ pub struct UpdateContext<'a, 'gc, 'gc_context> {
     /// The system properties
     pub system: &'a mut SystemProperties,
 
+    /// The security sandbox state shared by all movies.
+    pub security: &'a mut Security,
+
     /// The current instance ID. Used to generate default `instanceN` names.
     pub instance_counter: &'a mut i32,
 
@@ -116,6 +144,35 @@ pub struct UpdateContext<'a, 'gc, 'gc_context> {
     /// Timed callbacks created with `setInterval`/`setTimeout`.
     pub timers: &'a mut Timers<'gc>,
 
+    /// Listening `LocalConnection` objects and their undelivered messages.
+    pub local_connections: &'a mut LocalConnections<'gc>,
+
+    /// Open `XMLSocket` connections.
+    pub xml_sockets: &'a mut XmlSockets<'gc>,
+
+    /// Callbacks exposed to the host with `ExternalInterface.addCallback`.
+    pub external_callbacks: &'a mut ExternalCallbacks<'gc>,
+
+    /// The context menu that is currently shown by the host.
+    pub context_menu: &'a mut ContextMenuState<'gc>,
+
+    /// FSCommands sent by the movie, as pairs of command and arguments.
+    ///
+    /// These are handled by the player once the current update is done.
+    pub fs_commands: &'a mut Vec<(String, String)>,
+
+    /// Whether the full context menu is shown, as set by `Stage.showMenu`.
+    pub show_menu: &'a mut bool,
+
+    /// How the stage is scaled to fit the viewport, as set by `Stage.scaleMode`.
+    pub scale_mode: &'a mut StageScaleMode,
+
+    /// How the stage is aligned in the viewport, as set by `Stage.align`.
+    pub stage_align: &'a mut EnumSet<StageAlign>,
+
+    /// The debugger that pauses execution at breakpoints, if one is attached.
+    pub debugger: &'a mut Option<Debugger>,
+
     /// The AVM1 global state.
     pub avm1: &'a mut Avm1<'gc>,
 
@@ -136,6 +193,9 @@ unsafe impl<'a, 'gc, 'gc_context> Collect for UpdateContext<'a, 'gc, 'gc_context
         self.renderer.trace(cc);
         self.input.trace(cc);
         self.storage.trace(cc);
+        self.local_connection.trace(cc);
+        self.socket.trace(cc);
+        self.external_interface.trace(cc);
         self.rng.trace(cc);
         self.levels.trace(cc);
         self.system_prototypes.trace(cc);
@@ -144,10 +204,17 @@ unsafe impl<'a, 'gc, 'gc_context> Collect for UpdateContext<'a, 'gc, 'gc_context
         self.drag_object.trace(cc);
         self.load_manager.trace(cc);
         self.system.trace(cc);
+        self.security.trace(cc);
         self.instance_counter.trace(cc);
         self.shared_objects.trace(cc);
         self.unbound_text_fields.trace(cc);
         self.timers.trace(cc);
+        self.local_connections.trace(cc);
+        self.xml_sockets.trace(cc);
+        self.external_callbacks.trace(cc);
+        self.context_menu.trace(cc);
+        self.fs_commands.trace(cc);
+        self.show_menu.trace(cc);
         self.avm1.trace(cc);
         self.avm2.trace(cc);
     }
@@ -178,6 +245,10 @@ impl<'a, 'gc, 'gc_context> UpdateContext<'a, 'gc, 'gc_context> {
             renderer: self.renderer,
             input: self.input,
             storage: self.storage,
+            local_connection: self.local_connection,
+            socket: self.socket,
+            external_interface: self.external_interface,
+            log: self.log,
             rng: self.rng,
             levels: self.levels,
             system_prototypes: self.system_prototypes.clone(),
@@ -185,13 +256,24 @@ impl<'a, 'gc, 'gc_context> UpdateContext<'a, 'gc, 'gc_context> {
             mouse_position: self.mouse_position,
             drag_object: self.drag_object,
             stage_size: self.stage_size,
+            viewport_size: self.viewport_size,
             player: self.player.clone(),
             load_manager: self.load_manager,
             system: self.system,
+            security: self.security,
             instance_counter: self.instance_counter,
             shared_objects: self.shared_objects,
             unbound_text_fields: self.unbound_text_fields,
             timers: self.timers,
+            local_connections: self.local_connections,
+            xml_sockets: self.xml_sockets,
+            external_callbacks: self.external_callbacks,
+            context_menu: self.context_menu,
+            fs_commands: self.fs_commands,
+            show_menu: self.show_menu,
+            scale_mode: self.scale_mode,
+            stage_align: self.stage_align,
+            debugger: self.debugger,
             avm1: self.avm1,
             avm2: self.avm2,
         }
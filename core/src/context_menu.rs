@@ -0,0 +1,1095 @@
+//! The context menu shown when the user right-clicks the player.
+//!
+//! The player builds a list of `ContextMenuItem`s for the host to display, and
+//! remembers what each item does until the host reports the user's choice.
+//! Hosts without native menus can have the player draw the menu itself, as laid
+//! out by `DrawnContextMenu`.
+
+use crate::avm1::activation::{Activation, ActivationIdentifier};
+use crate::avm1::{Object, TObject, Value};
+use crate::backend::render::{RenderBackend, ShapeHandle, StageQuality};
+use crate::context::UpdateContext;
+use crate::events::{KeyCode, PlayerEvent};
+use crate::font::{EvalParameters, Font};
+use crate::prelude::*;
+use crate::shape_utils::{DistilledShape, DrawCommand, DrawPath};
+use crate::transform::Transform;
+use gc_arena::Collect;
+
+/// An item of the context menu, as displayed by the host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextMenuItem {
+    /// The text shown for this item.
+    pub caption: String,
+
+    /// Whether the item can be chosen.
+    pub enabled: bool,
+
+    /// Whether the item is shown with a check mark.
+    pub checked: bool,
+
+    /// Whether a separator is drawn above this item.
+    pub separator_before: bool,
+}
+
+impl ContextMenuItem {
+    fn new(caption: &str) -> Self {
+        Self {
+            caption: caption.to_string(),
+            enabled: true,
+            checked: false,
+            separator_before: false,
+        }
+    }
+
+    fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    fn checked(mut self, checked: bool) -> Self {
+        self.checked = checked;
+        self
+    }
+
+    fn separator_before(mut self) -> Self {
+        self.separator_before = true;
+        self
+    }
+}
+
+/// The built-in items of the context menu that are handled by the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Collect)]
+#[collect(require_static)]
+pub enum BuiltInItem {
+    ZoomIn,
+    ZoomOut,
+    ShowAll,
+    Quality(StageQuality),
+    Play,
+    Loop,
+    Rewind,
+    Forward,
+    Back,
+}
+
+/// What happens when a context menu item is chosen.
+#[derive(Clone, Collect)]
+#[collect(no_drop)]
+enum ContextMenuCallback<'gc> {
+    BuiltIn(BuiltInItem),
+
+    /// A `ContextMenuItem` from an AVM1 `ContextMenu`, whose `onSelect` is
+    /// called with the object that owns the menu.
+    Avm1 {
+        item: Object<'gc>,
+        owner: Value<'gc>,
+    },
+}
+
+/// The state of the context menu that is currently shown by the host.
+pub struct ContextMenuState<'gc> {
+    items: Vec<ContextMenuItem>,
+    callbacks: Vec<ContextMenuCallback<'gc>>,
+}
+
+/// The player settings that decide how the built-in items are shown.
+#[derive(Debug, Clone, Copy)]
+pub struct BuiltInState {
+    /// Whether the full menu is shown, as set by `Stage.showMenu`.
+    pub show_menu: bool,
+
+    /// Whether the stage is zoomed in.
+    pub is_zoomed: bool,
+
+    pub quality: StageQuality,
+}
+
+impl<'gc> ContextMenuState<'gc> {
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            callbacks: Vec::new(),
+        }
+    }
+
+    /// The items of the menu, in display order.
+    pub fn items(&self) -> &[ContextMenuItem] {
+        &self.items
+    }
+
+    fn push(&mut self, item: ContextMenuItem, callback: ContextMenuCallback<'gc>) {
+        self.items.push(item);
+        self.callbacks.push(callback);
+    }
+
+    fn push_built_in(&mut self, item: ContextMenuItem, built_in: BuiltInItem) {
+        self.push(item, ContextMenuCallback::BuiltIn(built_in));
+    }
+
+    /// Build the menu for a right-click on `target`.
+    ///
+    /// The `menu` property of `target` or its closest ancestor decides which
+    /// built-in items are shown, and adds its custom items to the top of the
+    /// menu. The menu's `onSelect` handler is called first, so that it may
+    /// update the items.
+    pub fn build(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        target: Option<DisplayObject<'gc>>,
+        state: BuiltInState,
+    ) -> Self {
+        let mut menu = Self::new();
+
+        let root = match context.levels.get(&0).copied() {
+            Some(root) => root,
+            None => return menu,
+        };
+        let globals = context.avm1.global_object_cell();
+        let mut activation = Activation::from_nothing(
+            context.reborrow(),
+            ActivationIdentifier::root("[Context Menu]"),
+            root.swf_version(),
+            globals,
+            root,
+        );
+
+        let mut shown = BuiltInItems::all();
+        if let Some((owner, context_menu)) = find_menu(&mut activation, target) {
+            if let Ok(on_select) = context_menu.get("onSelect", &mut activation) {
+                let _ = on_select.call(
+                    "[Context Menu]",
+                    &mut activation,
+                    context_menu,
+                    None,
+                    &[owner.clone(), context_menu.into()],
+                );
+            }
+
+            shown = BuiltInItems::from_menu(&mut activation, context_menu);
+
+            if let Ok(Value::Object(custom_items)) =
+                context_menu.get("customItems", &mut activation)
+            {
+                for i in 0..custom_items.length() {
+                    if let Value::Object(item) = custom_items.array_element(i) {
+                        if let Some(menu_item) = custom_item(&mut activation, item) {
+                            let owner = owner.clone();
+                            menu.push(menu_item, ContextMenuCallback::Avm1 { item, owner });
+                        }
+                    }
+                }
+            }
+        }
+
+        if !state.show_menu {
+            return menu;
+        }
+
+        let root_clip = root.as_movie_clip();
+        if shown.zoom {
+            menu.push_built_in(
+                ContextMenuItem::new("Zoom In").separator_before(),
+                BuiltInItem::ZoomIn,
+            );
+            menu.push_built_in(
+                ContextMenuItem::new("Zoom Out").enabled(state.is_zoomed),
+                BuiltInItem::ZoomOut,
+            );
+            menu.push_built_in(
+                ContextMenuItem::new("Show All").enabled(state.is_zoomed),
+                BuiltInItem::ShowAll,
+            );
+        }
+
+        if shown.quality {
+            for (i, (caption, quality)) in [
+                ("Quality: Low", StageQuality::Low),
+                ("Quality: Medium", StageQuality::Medium),
+                ("Quality: High", StageQuality::High),
+            ]
+            .iter()
+            .enumerate()
+            {
+                let mut item = ContextMenuItem::new(caption).checked(state.quality == *quality);
+                if i == 0 {
+                    item = item.separator_before();
+                }
+                menu.push_built_in(item, BuiltInItem::Quality(*quality));
+            }
+        }
+
+        // Timeline controls are only useful for movies with more than one frame.
+        if let Some(root_clip) = root_clip.filter(|clip| clip.total_frames() > 1) {
+            if shown.play {
+                menu.push_built_in(
+                    ContextMenuItem::new("Play")
+                        .checked(root_clip.playing())
+                        .separator_before(),
+                    BuiltInItem::Play,
+                );
+            }
+            if shown.loop_ {
+                let item = ContextMenuItem::new("Loop").checked(root_clip.is_looping());
+                let item = if shown.play {
+                    item
+                } else {
+                    item.separator_before()
+                };
+                menu.push_built_in(item, BuiltInItem::Loop);
+            }
+            if shown.rewind {
+                menu.push_built_in(
+                    ContextMenuItem::new("Rewind")
+                        .enabled(root_clip.current_frame() > 1)
+                        .separator_before(),
+                    BuiltInItem::Rewind,
+                );
+            }
+            if shown.forward_back {
+                let item = ContextMenuItem::new("Forward")
+                    .enabled(root_clip.current_frame() < root_clip.total_frames());
+                let item = if shown.rewind {
+                    item
+                } else {
+                    item.separator_before()
+                };
+                menu.push_built_in(item, BuiltInItem::Forward);
+                menu.push_built_in(
+                    ContextMenuItem::new("Back").enabled(root_clip.current_frame() > 1),
+                    BuiltInItem::Back,
+                );
+            }
+        }
+
+        // The first item never needs a separator.
+        if let Some(first) = menu.items.first_mut() {
+            first.separator_before = false;
+        }
+
+        menu
+    }
+
+    /// Run the action of the item at `index`.
+    ///
+    /// Built-in items that change the player itself are returned for the
+    /// player to handle.
+    pub fn run_callback(
+        &self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        index: usize,
+    ) -> Option<BuiltInItem> {
+        if !self.items.get(index)?.enabled {
+            return None;
+        }
+
+        match self.callbacks.get(index)?.clone() {
+            ContextMenuCallback::BuiltIn(built_in) => {
+                let root_clip = context.levels.get(&0).and_then(|root| root.as_movie_clip());
+                let root_clip = match root_clip {
+                    Some(root_clip) => root_clip,
+                    None => return Some(built_in),
+                };
+                match built_in {
+                    BuiltInItem::Play => {
+                        if root_clip.playing() {
+                            root_clip.stop(context);
+                        } else {
+                            root_clip.play(context);
+                        }
+                    }
+                    BuiltInItem::Loop => {
+                        root_clip.set_looping(context.gc_context, !root_clip.is_looping())
+                    }
+                    BuiltInItem::Rewind => root_clip.goto_frame(context, 1, true),
+                    BuiltInItem::Forward => root_clip.next_frame(context),
+                    BuiltInItem::Back => root_clip.prev_frame(context),
+                    _ => return Some(built_in),
+                }
+                None
+            }
+            ContextMenuCallback::Avm1 { item, owner } => {
+                let root = context.levels.get(&0).copied()?;
+                let globals = context.avm1.global_object_cell();
+                let mut activation = Activation::from_nothing(
+                    context.reborrow(),
+                    ActivationIdentifier::root("[Context Menu Callback]"),
+                    root.swf_version(),
+                    globals,
+                    root,
+                );
+                if let Ok(on_select) = item.get("onSelect", &mut activation) {
+                    let _ = on_select.call(
+                        "[Context Menu Callback]",
+                        &mut activation,
+                        item,
+                        None,
+                        &[owner, item.into()],
+                    );
+                }
+                None
+            }
+        }
+    }
+}
+
+impl Default for ContextMenuState<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<'gc> Collect for ContextMenuState<'gc> {
+    fn trace(&self, cc: gc_arena::CollectionContext) {
+        self.callbacks.trace(cc);
+    }
+}
+
+/// The height of each item of a drawn context menu, in pixels.
+const DRAWN_ITEM_HEIGHT: f64 = 20.0;
+
+/// The height of the gap that a separator is drawn in, in pixels.
+const DRAWN_SEPARATOR_HEIGHT: f64 = 8.0;
+
+/// The space to the left and right of the caption of each item, in pixels.
+///
+/// The left one leaves room for check marks.
+const DRAWN_ITEM_PADDING: f64 = 20.0;
+
+/// The narrowest that a drawn context menu can be, in pixels.
+const DRAWN_MIN_WIDTH: f64 = 120.0;
+
+/// The size of the captions of a drawn context menu, in pixels.
+const DRAWN_FONT_SIZE: f64 = 12.0;
+
+/// How the captions of a drawn context menu are laid out.
+fn drawn_caption_params() -> EvalParameters {
+    EvalParameters::from_parts(Twips::from_pixels(DRAWN_FONT_SIZE), Twips::new(0), false)
+}
+
+/// What the player should do after the drawn context menu handles an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawnMenuResponse {
+    /// The event didn't change the menu.
+    None,
+
+    /// A different item is highlighted, so the menu must be drawn again.
+    Redraw,
+
+    /// The menu was dismissed without choosing an item.
+    Dismiss,
+
+    /// The item at the given index was chosen.
+    Choose(usize),
+}
+
+/// A context menu that the player draws over the movie itself, for hosts that
+/// can't show a native menu.
+///
+/// All positions and sizes are in viewport pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawnContextMenu {
+    items: Vec<ContextMenuItem>,
+
+    /// The top of each item, relative to the top of the menu.
+    item_tops: Vec<f64>,
+
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+
+    /// The item under the mouse cursor, if it can be chosen.
+    hovered: Option<usize>,
+}
+
+impl DrawnContextMenu {
+    /// Lay out `items` with their top-left corner at `position`.
+    ///
+    /// The menu opens to the left of or above `position` instead if it would
+    /// not fit in the viewport otherwise. `caption_width` measures the width of
+    /// a caption.
+    pub fn new(
+        items: Vec<ContextMenuItem>,
+        position: (f64, f64),
+        viewport_size: (f64, f64),
+        caption_width: impl Fn(&str) -> f64,
+    ) -> Self {
+        let mut item_tops = Vec::with_capacity(items.len());
+        let mut height = 0.0;
+        for item in &items {
+            if item.separator_before {
+                height += DRAWN_SEPARATOR_HEIGHT;
+            }
+            item_tops.push(height);
+            height += DRAWN_ITEM_HEIGHT;
+        }
+
+        let width = items
+            .iter()
+            .map(|item| caption_width(&item.caption) + 2.0 * DRAWN_ITEM_PADDING)
+            .fold(DRAWN_MIN_WIDTH, f64::max);
+
+        let fit = |position: f64, size: f64, viewport_size: f64| {
+            if position + size > viewport_size {
+                (position - size).max(0.0)
+            } else {
+                position
+            }
+        };
+
+        Self {
+            items,
+            item_tops,
+            x: fit(position.0, width, viewport_size.0),
+            y: fit(position.1, height, viewport_size.1),
+            width,
+            height,
+            hovered: None,
+        }
+    }
+
+    /// Lay out `items` as with `new`, measuring captions in `font`.
+    pub fn with_font(
+        items: Vec<ContextMenuItem>,
+        position: (f64, f64),
+        viewport_size: (f64, f64),
+        font: Option<Font<'_>>,
+    ) -> Self {
+        Self::new(items, position, viewport_size, |caption| match font {
+            Some(font) => font
+                .measure(caption, drawn_caption_params(), false)
+                .0
+                .to_pixels(),
+            None => 0.0,
+        })
+    }
+
+    /// Register the shape that `render` draws the menu with: a white square of
+    /// one pixel, which is scaled and tinted to draw each part.
+    pub fn register_rect(renderer: &mut dyn RenderBackend) -> ShapeHandle {
+        let fill_style = swf::FillStyle::Color(Color {
+            r: 255,
+            g: 255,
+            b: 255,
+            a: 255,
+        });
+        let size = Twips::from_pixels(1.0);
+        let bounds = BoundingBox {
+            x_min: Twips::new(0),
+            y_min: Twips::new(0),
+            x_max: size,
+            y_max: size,
+            valid: true,
+        };
+        let commands = vec![
+            DrawCommand::MoveTo {
+                x: Twips::new(0),
+                y: Twips::new(0),
+            },
+            DrawCommand::LineTo {
+                x: size,
+                y: Twips::new(0),
+            },
+            DrawCommand::LineTo { x: size, y: size },
+            DrawCommand::LineTo {
+                x: Twips::new(0),
+                y: size,
+            },
+            DrawCommand::LineTo {
+                x: Twips::new(0),
+                y: Twips::new(0),
+            },
+        ];
+        let shape = DistilledShape {
+            paths: vec![DrawPath::Fill {
+                style: &fill_style,
+                commands,
+            }],
+            shape_bounds: bounds.clone(),
+            edge_bounds: bounds,
+            id: 0,
+        };
+        renderer.register_shape(shape)
+    }
+
+    /// The items of the menu, in display order.
+    pub fn items(&self) -> &[ContextMenuItem] {
+        &self.items
+    }
+
+    /// The bounds of the whole menu, as `(x, y, width, height)`.
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        (self.x, self.y, self.width, self.height)
+    }
+
+    /// The bounds of the item at `index`, as `(x, y, width, height)`.
+    pub fn item_bounds(&self, index: usize) -> Option<(f64, f64, f64, f64)> {
+        let top = self.item_tops.get(index)?;
+        Some((self.x, self.y + top, self.width, DRAWN_ITEM_HEIGHT))
+    }
+
+    /// The vertical center of the separator above the item at `index`, if it
+    /// has one.
+    pub fn separator_y(&self, index: usize) -> Option<f64> {
+        if !self.items.get(index)?.separator_before {
+            return None;
+        }
+        let top = self.item_tops.get(index)?;
+        Some(self.y + top - DRAWN_SEPARATOR_HEIGHT / 2.0)
+    }
+
+    /// The space to the left of each caption.
+    pub fn caption_offset(&self) -> f64 {
+        DRAWN_ITEM_PADDING
+    }
+
+    /// Whether the given position is inside the menu.
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// The index of the item at the given position, if it can be chosen.
+    pub fn item_at(&self, x: f64, y: f64) -> Option<usize> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        let y = y - self.y;
+        let index = self
+            .item_tops
+            .iter()
+            .position(|top| y >= *top && y < top + DRAWN_ITEM_HEIGHT)?;
+        if self.items[index].enabled {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// The highlighted item, which is the one under the mouse cursor.
+    pub fn hovered(&self) -> Option<usize> {
+        self.hovered
+    }
+
+    /// Highlight the item at the given position.
+    ///
+    /// Returns `true` if a different item is highlighted now.
+    pub fn hover(&mut self, x: f64, y: f64) -> bool {
+        let hovered = self.item_at(x, y);
+        let changed = hovered != self.hovered;
+        self.hovered = hovered;
+        changed
+    }
+
+    /// Handle an input event, which the menu takes all of while it's open.
+    ///
+    /// An item is chosen by releasing the mouse button over it. Clicking
+    /// outside of the menu or pressing Escape dismisses it.
+    pub fn handle_event(&mut self, event: &PlayerEvent) -> DrawnMenuResponse {
+        match *event {
+            PlayerEvent::MouseMove { x, y } if self.hover(x, y) => DrawnMenuResponse::Redraw,
+            PlayerEvent::MouseDown { x, y, .. } if !self.contains(x, y) => {
+                DrawnMenuResponse::Dismiss
+            }
+            PlayerEvent::MouseUp { x, y, .. } => match self.item_at(x, y) {
+                Some(index) => DrawnMenuResponse::Choose(index),
+                None => DrawnMenuResponse::None,
+            },
+            PlayerEvent::KeyDown {
+                key_code: KeyCode::Escape,
+            } => DrawnMenuResponse::Dismiss,
+            _ => DrawnMenuResponse::None,
+        }
+    }
+
+    /// Draw the menu over the movie, with the hovered item highlighted and
+    /// disabled items greyed out.
+    ///
+    /// `rect` is the shape registered with `register_rect`.
+    pub fn render(
+        &self,
+        renderer: &mut dyn RenderBackend,
+        font: Option<Font<'_>>,
+        rect: ShapeHandle,
+    ) {
+        const BORDER: [f32; 3] = [0.4, 0.4, 0.4];
+        const BACKGROUND: [f32; 3] = [0.94, 0.94, 0.94];
+        const HIGHLIGHT: [f32; 3] = [0.2, 0.4, 0.8];
+        const TEXT: [f32; 3] = [0.0, 0.0, 0.0];
+        const DISABLED_TEXT: [f32; 3] = [0.6, 0.6, 0.6];
+        const HIGHLIGHTED_TEXT: [f32; 3] = [1.0, 1.0, 1.0];
+
+        let (x, y, width, height) = self.bounds();
+        render_rect(
+            renderer,
+            rect,
+            (x - 1.0, y - 1.0, width + 2.0, height + 2.0),
+            BORDER,
+        );
+        render_rect(renderer, rect, (x, y, width, height), BACKGROUND);
+
+        let params = drawn_caption_params();
+        let caption_offset = self.caption_offset();
+        for (i, item) in self.items.iter().enumerate() {
+            let bounds = match self.item_bounds(i) {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+            let (item_x, item_y, item_width, item_height) = bounds;
+
+            if let Some(separator_y) = self.separator_y(i) {
+                render_rect(
+                    renderer,
+                    rect,
+                    (item_x + 4.0, separator_y, item_width - 8.0, 1.0),
+                    DISABLED_TEXT,
+                );
+            }
+
+            let text_color = if self.hovered == Some(i) {
+                render_rect(renderer, rect, bounds, HIGHLIGHT);
+                HIGHLIGHTED_TEXT
+            } else if item.enabled {
+                TEXT
+            } else {
+                DISABLED_TEXT
+            };
+
+            if item.checked {
+                let size = 6.0;
+                render_rect(
+                    renderer,
+                    rect,
+                    (
+                        item_x + (caption_offset - size) / 2.0,
+                        item_y + (item_height - size) / 2.0,
+                        size,
+                        size,
+                    ),
+                    text_color,
+                );
+            }
+
+            if let Some(font) = font {
+                let mut transform = Transform::default();
+                transform.matrix.tx = Twips::from_pixels(item_x + caption_offset);
+                transform.matrix.ty =
+                    Twips::from_pixels(item_y + (item_height - DRAWN_FONT_SIZE) / 2.0 - 2.0);
+                transform.color_transform.r_mult = text_color[0];
+                transform.color_transform.g_mult = text_color[1];
+                transform.color_transform.b_mult = text_color[2];
+                font.evaluate(
+                    &item.caption,
+                    transform,
+                    params,
+                    |transform, glyph, _advance| renderer.render_shape(glyph.shape, transform),
+                );
+            }
+        }
+    }
+}
+
+/// Draw `rect`, the shape registered by `DrawnContextMenu::register_rect`,
+/// stretched over the given bounds in viewport pixels and tinted with `color`.
+fn render_rect(
+    renderer: &mut dyn RenderBackend,
+    rect: ShapeHandle,
+    (x, y, width, height): (f64, f64, f64, f64),
+    color: [f32; 3],
+) {
+    let mut transform = Transform::default();
+    transform.matrix.a = width as f32;
+    transform.matrix.d = height as f32;
+    transform.matrix.tx = Twips::from_pixels(x);
+    transform.matrix.ty = Twips::from_pixels(y);
+    transform.color_transform.r_mult = color[0];
+    transform.color_transform.g_mult = color[1];
+    transform.color_transform.b_mult = color[2];
+    renderer.render_shape(rect, &transform);
+}
+
+/// Which built-in items a `ContextMenu` object asks for.
+struct BuiltInItems {
+    zoom: bool,
+    quality: bool,
+    play: bool,
+    loop_: bool,
+    rewind: bool,
+    forward_back: bool,
+}
+
+impl BuiltInItems {
+    fn all() -> Self {
+        Self {
+            zoom: true,
+            quality: true,
+            play: true,
+            loop_: true,
+            rewind: true,
+            forward_back: true,
+        }
+    }
+
+    fn from_menu<'gc>(activation: &mut Activation<'_, 'gc, '_>, context_menu: Object<'gc>) -> Self {
+        let built_in_items = match context_menu.get("builtInItems", activation) {
+            Ok(Value::Object(built_in_items)) => built_in_items,
+            _ => return Self::all(),
+        };
+
+        let mut is_shown = |name: &str| match built_in_items.get(name, activation) {
+            Ok(Value::Undefined) | Err(_) => true,
+            Ok(value) => value.as_bool(activation.current_swf_version()),
+        };
+
+        Self {
+            zoom: is_shown("zoom"),
+            quality: is_shown("quality"),
+            play: is_shown("play"),
+            loop_: is_shown("loop"),
+            rewind: is_shown("rewind"),
+            forward_back: is_shown("forward_back"),
+        }
+    }
+}
+
+/// Find the `ContextMenu` for a right-click on `target`, by looking at the
+/// `menu` property of `target` and its ancestors.
+///
+/// Returns the object owning the menu, along with the menu itself.
+fn find_menu<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    target: Option<DisplayObject<'gc>>,
+) -> Option<(Value<'gc>, Object<'gc>)> {
+    let mut node = target;
+    while let Some(display_object) = node {
+        if let Value::Object(object) = display_object.object() {
+            if let Ok(Value::Object(context_menu)) = object.get("menu", activation) {
+                return Some((object.into(), context_menu));
+            }
+        }
+        node = display_object.parent();
+    }
+    None
+}
+
+/// Convert an AVM1 `ContextMenuItem` to a menu item, if it is visible.
+fn custom_item<'gc>(
+    activation: &mut Activation<'_, 'gc, '_>,
+    item: Object<'gc>,
+) -> Option<ContextMenuItem> {
+    let swf_version = activation.current_swf_version();
+    let mut flag = |name: &str, default: bool| match item.get(name, activation) {
+        Ok(Value::Undefined) | Err(_) => default,
+        Ok(value) => value.as_bool(swf_version),
+    };
+
+    if !flag("visible", true) {
+        return None;
+    }
+    let enabled = flag("enabled", true);
+    let separator_before = flag("separatorBefore", false);
+
+    let caption = item
+        .get("caption", activation)
+        .and_then(|caption| caption.coerce_to_string(activation))
+        .ok()?
+        .to_string();
+
+    Some(ContextMenuItem {
+        caption,
+        enabled,
+        checked: false,
+        separator_before,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::error::Error;
+    use crate::avm1::function::FunctionObject;
+    use crate::avm1::test_utils::with_avm;
+    use crate::avm1::{AvmString, ScriptObject};
+
+    const STATE: BuiltInState = BuiltInState {
+        show_menu: true,
+        is_zoomed: false,
+        quality: StageQuality::Medium,
+    };
+
+    fn captions<'a>(menu: &'a ContextMenuState<'_>) -> Vec<&'a str> {
+        menu.items()
+            .iter()
+            .map(|item| item.caption.as_str())
+            .collect()
+    }
+
+    fn mark_selected<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        this: Object<'gc>,
+        _args: &[Value<'gc>],
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        this.set("selected", true.into(), activation)?;
+        Ok(Value::Undefined)
+    }
+
+    /// Give `root` a `ContextMenu` without quality items, with a custom item
+    /// of each of the given captions.
+    fn add_menu<'gc>(
+        activation: &mut Activation<'_, 'gc, '_>,
+        root: Object<'gc>,
+        captions: &[&str],
+    ) -> Result<Vec<Object<'gc>>, Error<'gc>> {
+        let gc_context = activation.context.gc_context;
+        let prototypes = activation.context.avm1.prototypes().clone();
+
+        let built_in_items = ScriptObject::object(gc_context, Some(prototypes.object));
+        built_in_items.set("quality", false.into(), activation)?;
+
+        let custom_items = ScriptObject::array(gc_context, Some(prototypes.array));
+        let mut items = vec![];
+        for (i, caption) in captions.iter().enumerate() {
+            let item: Object<'gc> =
+                ScriptObject::object(gc_context, Some(prototypes.object)).into();
+            let caption = AvmString::new(gc_context, caption.to_string());
+            item.set("caption", caption.into(), activation)?;
+            let on_select = FunctionObject::function(
+                gc_context,
+                mark_selected as crate::avm1::function::NativeFunction<'gc>,
+                Some(prototypes.function),
+                prototypes.function,
+            );
+            item.set("onSelect", on_select.into(), activation)?;
+            custom_items.set_array_element(i, item.into(), gc_context);
+            items.push(item);
+        }
+
+        let context_menu = ScriptObject::object(gc_context, Some(prototypes.object));
+        context_menu.set("builtInItems", built_in_items.into(), activation)?;
+        context_menu.set("customItems", custom_items.into(), activation)?;
+        root.set("menu", context_menu.into(), activation)?;
+        Ok(items)
+    }
+
+    #[test]
+    fn built_in_items() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let menu = ContextMenuState::build(&mut activation.context, None, STATE);
+            assert_eq!(
+                captions(&menu),
+                vec![
+                    "Zoom In",
+                    "Zoom Out",
+                    "Show All",
+                    "Quality: Low",
+                    "Quality: Medium",
+                    "Quality: High"
+                ]
+            );
+
+            let items = menu.items();
+            assert!(!items[0].separator_before);
+            assert!(items[0].enabled);
+            assert!(!items[1].enabled);
+            assert!(!items[2].enabled);
+            assert!(items[3].separator_before);
+            assert_eq!(
+                items.iter().map(|item| item.checked).collect::<Vec<_>>(),
+                vec![false, false, false, false, true, false]
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn hidden_menu_has_no_built_in_items() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let state = BuiltInState {
+                show_menu: false,
+                ..STATE
+            };
+            let menu = ContextMenuState::build(&mut activation.context, None, state);
+            assert!(menu.items().is_empty());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn custom_items() {
+        with_avm(8, |activation, root| -> Result<(), Error> {
+            let items = add_menu(activation, root, &["First", "Second"])?;
+            items[1].set("enabled", false.into(), activation)?;
+            items[1].set("separatorBefore", true.into(), activation)?;
+
+            let target = root.as_display_object();
+            let menu = ContextMenuState::build(&mut activation.context, target, STATE);
+            assert_eq!(
+                captions(&menu),
+                vec!["First", "Second", "Zoom In", "Zoom Out", "Show All"]
+            );
+            assert!(menu.items()[1].separator_before);
+            assert!(!menu.items()[1].enabled);
+            assert!(menu.items()[2].separator_before);
+
+            // Hidden items are left out.
+            items[1].set("visible", false.into(), activation)?;
+            let menu = ContextMenuState::build(&mut activation.context, target, STATE);
+            assert_eq!(
+                captions(&menu),
+                vec!["First", "Zoom In", "Zoom Out", "Show All"]
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn run_callback() {
+        with_avm(8, |activation, root| -> Result<(), Error> {
+            let items = add_menu(activation, root, &["Enabled", "Disabled"])?;
+            items[1].set("enabled", false.into(), activation)?;
+
+            let target = root.as_display_object();
+            let state = BuiltInState {
+                is_zoomed: true,
+                ..STATE
+            };
+            let menu = ContextMenuState::build(&mut activation.context, target, state);
+
+            assert_eq!(menu.run_callback(&mut activation.context, 0), None);
+            assert_eq!(items[0].get("selected", activation)?, true.into());
+
+            // Disabled items do nothing.
+            assert_eq!(menu.run_callback(&mut activation.context, 1), None);
+            assert_eq!(items[1].get("selected", activation)?, Value::Undefined);
+
+            // Built-in items that change the player are returned to it.
+            assert_eq!(
+                menu.run_callback(&mut activation.context, 2),
+                Some(BuiltInItem::ZoomIn)
+            );
+            assert_eq!(
+                menu.run_callback(&mut activation.context, 4),
+                Some(BuiltInItem::ShowAll)
+            );
+            assert_eq!(menu.run_callback(&mut activation.context, 5), None);
+            Ok(())
+        });
+    }
+
+    fn item(caption: &str, enabled: bool, separator_before: bool) -> ContextMenuItem {
+        ContextMenuItem {
+            caption: caption.to_string(),
+            enabled,
+            checked: false,
+            separator_before,
+        }
+    }
+
+    #[test]
+    fn drawn_menu_layout() {
+        let items = vec![
+            item("First", true, false),
+            item("Disabled", false, false),
+            item("After separator", true, true),
+        ];
+        let menu = DrawnContextMenu::new(items, (10.0, 20.0), (550.0, 400.0), |caption| {
+            caption.len() as f64 * 10.0
+        });
+
+        // The longest caption is wider than the minimum width.
+        assert_eq!(menu.bounds(), (10.0, 20.0, 190.0, 68.0));
+        assert_eq!(menu.item_bounds(0), Some((10.0, 20.0, 190.0, 20.0)));
+        assert_eq!(menu.item_bounds(1), Some((10.0, 40.0, 190.0, 20.0)));
+        assert_eq!(menu.item_bounds(2), Some((10.0, 68.0, 190.0, 20.0)));
+        assert_eq!(menu.item_bounds(3), None);
+        assert_eq!(menu.separator_y(1), None);
+        assert_eq!(menu.separator_y(2), Some(64.0));
+
+        assert_eq!(menu.item_at(15.0, 25.0), Some(0));
+        assert_eq!(menu.item_at(15.0, 45.0), None);
+        assert_eq!(menu.item_at(15.0, 62.0), None);
+        assert_eq!(menu.item_at(199.0, 87.0), Some(2));
+        assert_eq!(menu.item_at(200.0, 87.0), None);
+        assert_eq!(menu.item_at(15.0, 88.0), None);
+        assert!(menu.contains(15.0, 62.0));
+        assert!(!menu.contains(5.0, 25.0));
+    }
+
+    #[test]
+    fn drawn_menu_stays_in_viewport() {
+        let items = vec![item("First", true, false), item("Second", true, false)];
+        let menu = DrawnContextMenu::new(items.clone(), (500.0, 390.0), (550.0, 400.0), |_| 0.0);
+        assert_eq!(menu.bounds(), (380.0, 350.0, 120.0, 40.0));
+
+        // Menus that don't fit either way are moved into the corner.
+        let menu = DrawnContextMenu::new(items, (50.0, 30.0), (100.0, 50.0), |_| 0.0);
+        assert_eq!(menu.bounds(), (0.0, 0.0, 120.0, 40.0));
+    }
+
+    #[test]
+    fn drawn_menu_hover() {
+        let items = vec![item("First", true, false), item("Disabled", false, false)];
+        let mut menu = DrawnContextMenu::new(items, (0.0, 0.0), (550.0, 400.0), |_| 0.0);
+        assert_eq!(menu.hovered(), None);
+        assert!(menu.hover(5.0, 5.0));
+        assert_eq!(menu.hovered(), Some(0));
+        assert!(!menu.hover(6.0, 6.0));
+        assert!(menu.hover(5.0, 25.0));
+        assert_eq!(menu.hovered(), None);
+        assert!(!menu.hover(500.0, 300.0));
+    }
+
+    #[test]
+    fn drawn_menu_events() {
+        use crate::events::MouseButton;
+
+        let items = vec![item("First", true, false), item("Disabled", false, false)];
+        let mut menu = DrawnContextMenu::new(items, (0.0, 0.0), (550.0, 400.0), |_| 0.0);
+        let button = MouseButton::Left;
+        assert_eq!(
+            menu.handle_event(&PlayerEvent::MouseMove { x: 5.0, y: 5.0 }),
+            DrawnMenuResponse::Redraw
+        );
+        assert_eq!(
+            menu.handle_event(&PlayerEvent::MouseMove { x: 6.0, y: 6.0 }),
+            DrawnMenuResponse::None
+        );
+        assert_eq!(
+            menu.handle_event(&PlayerEvent::MouseDown {
+                x: 5.0,
+                y: 5.0,
+                button
+            }),
+            DrawnMenuResponse::None
+        );
+        assert_eq!(
+            menu.handle_event(&PlayerEvent::MouseUp {
+                x: 5.0,
+                y: 25.0,
+                button
+            }),
+            DrawnMenuResponse::None
+        );
+        assert_eq!(
+            menu.handle_event(&PlayerEvent::MouseUp {
+                x: 5.0,
+                y: 5.0,
+                button
+            }),
+            DrawnMenuResponse::Choose(0)
+        );
+        assert_eq!(
+            menu.handle_event(&PlayerEvent::MouseDown {
+                x: 500.0,
+                y: 300.0,
+                button
+            }),
+            DrawnMenuResponse::Dismiss
+        );
+        assert_eq!(
+            menu.handle_event(&PlayerEvent::KeyDown {
+                key_code: KeyCode::Escape
+            }),
+            DrawnMenuResponse::Dismiss
+        );
+    }
+}
@@ -0,0 +1,968 @@
+//! An interactive debugger for ActionScript code.
+//!
+//! A `Debugger` is attached to the player with `Player::set_debugger`.
+//! Before each AVM1 action, and each source line of AVM2 code compiled with
+//! debug info, it checks its breakpoints and whether a step has finished.
+//! When execution pauses, its `DebuggerFrontend` is handed a `DebugSession`
+//! to inspect the paused code, and decides how to resume.
+
+use crate::avm1::activation::{Activation, ActivationIdentifier};
+use crate::avm1::debug::VariableDumper;
+use crate::avm1::{AvmString, Value};
+use crate::avm2::Activation as Avm2Activation;
+use crate::prelude::*;
+use crate::tag_utils::{SwfMovie, SwfSlice};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::io::Cursor;
+use swf::avm1::read::Reader;
+
+type Error = Box<dyn std::error::Error>;
+
+/// Receives control whenever the debugger pauses execution.
+pub trait DebuggerFrontend {
+    /// Called when execution pauses before an action.
+    ///
+    /// The player is blocked until this returns, so the frontend may take as
+    /// long as it likes to inspect the paused code through `session`.
+    fn on_pause(&mut self, session: &mut DebugSession<'_, '_, '_, '_>) -> DebugCommand;
+
+    /// Called by the player between frames, so that the frontend can change
+    /// breakpoints while the movie is running.
+    fn poll(&mut self, _state: &mut DebuggerState) {}
+
+    /// Called when the movie calls `trace`.
+    fn on_trace(&mut self, _message: &str) {}
+}
+
+/// How execution resumes after a pause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugCommand {
+    /// Run until the next breakpoint.
+    Continue,
+
+    /// Pause before the next action, following calls into functions.
+    StepIn,
+
+    /// Pause before the next action of the current function or its callers.
+    StepOver,
+
+    /// Pause once the current function has returned.
+    StepOut,
+}
+
+/// A place where execution pauses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Pause before the action at `offset` bytes into the uncompressed body
+    /// of a SWF, after its header. If `url` is set, only that SWF matches.
+    Action { url: Option<String>, offset: usize },
+
+    /// Pause at the start of the script of `frame` (counting from 1) of the
+    /// clip at `path`, such as `_level0.menu`.
+    Frame { path: String, frame: u16 },
+
+    /// Pause at `line` of the source file `file`, which requires line info
+    /// for AVM1 code, or AVM2 code compiled with debug info. `file` may leave
+    /// out the directories of the source file.
+    Line { file: String, line: u32 },
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Breakpoint::Action {
+                url: Some(url),
+                offset,
+            } => write!(f, "action at {:#x} in {}", offset, url),
+            Breakpoint::Action { url: None, offset } => write!(f, "action at {:#x}", offset),
+            Breakpoint::Frame { path, frame } => write!(f, "frame {} of {}", frame, path),
+            Breakpoint::Line { file, line } => write!(f, "{}:{}", file, line),
+        }
+    }
+}
+
+/// Why execution paused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseReason {
+    /// The first action ran after the debugger was asked to pause on start.
+    Start,
+
+    /// A breakpoint was hit.
+    Breakpoint,
+
+    /// A step has finished.
+    Step,
+}
+
+/// A line of a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLine {
+    pub file: String,
+    pub line: u32,
+}
+
+impl SourceLine {
+    /// Whether `file` names this source file, possibly without directories.
+    fn matches_file(&self, file: &str) -> bool {
+        if let Some(prefix) = self.file.strip_suffix(file) {
+            // AVM2 debug info separates the package of a file with `;`.
+            prefix.is_empty() || prefix.ends_with(|c| c == '/' || c == '\\' || c == ';')
+        } else {
+            false
+        }
+    }
+}
+
+impl fmt::Display for SourceLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// Where execution is paused.
+#[derive(Debug, Clone)]
+pub struct Location {
+    /// The URL of the SWF containing the code.
+    pub url: Option<String>,
+
+    /// The offset of the next AVM1 action into the uncompressed body of the
+    /// SWF.
+    pub offset: Option<usize>,
+
+    /// The name of the running activation, such as a function name.
+    pub function: String,
+
+    /// The path of the clip running AVM1 code.
+    pub clip: Option<String>,
+
+    /// The current frame of that clip, if it is a movie clip.
+    pub frame: Option<u16>,
+
+    /// The source line of the next action, if line info is known.
+    pub line: Option<SourceLine>,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.function)?;
+        if let Some(clip) = &self.clip {
+            write!(f, " in {}", clip)?;
+        }
+        if let Some(frame) = self.frame {
+            write!(f, " (frame {})", frame)?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " at {:#x}", offset)?;
+        }
+        if let Some(line) = &self.line {
+            write!(f, ", {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Source line information for a SWF, as written by the Flash authoring tool
+/// to a SWD file alongside the SWF.
+#[derive(Debug, Clone, Default)]
+pub struct LineInfo {
+    /// The URL of the SWF this line info belongs to.
+    url: Option<String>,
+
+    /// The length of the SWF header, as SWD offsets count from the start of
+    /// the SWF file rather than its body.
+    header_length: usize,
+
+    /// The name and text of each source file, by script ID.
+    scripts: HashMap<u32, (String, String)>,
+
+    /// The script ID and line of each action, by SWD offset.
+    lines: BTreeMap<usize, (u32, u32)>,
+}
+
+impl LineInfo {
+    const SWD_SCRIPT: u32 = 0;
+    const SWD_OFFSET: u32 = 1;
+    const SWD_BREAKPOINT: u32 = 2;
+    const SWD_ID: u32 = 3;
+    const SWD_REGISTERS: u32 = 5;
+
+    /// Read the line info for `movie` from the contents of a SWD file.
+    pub fn from_swd(swd: &[u8], movie: &SwfMovie) -> Result<Self, Error> {
+        let mut line_info = Self {
+            url: movie.url().map(str::to_owned),
+            header_length: movie.header_length(),
+            ..Default::default()
+        };
+
+        if swd.len() < 4 || &swd[..3] != b"FWD" {
+            return Err("Not a SWD file".into());
+        }
+        let mut input = &swd[4..];
+        while !input.is_empty() {
+            match read_u32(&mut input)? {
+                Self::SWD_SCRIPT => {
+                    let id = read_u32(&mut input)?;
+                    let _bitmap = read_u32(&mut input)?;
+                    let name = read_c_string(&mut input)?;
+                    let text = read_c_string(&mut input)?;
+                    line_info.scripts.insert(id, (name, text));
+                }
+                Self::SWD_OFFSET => {
+                    let id = read_u32(&mut input)?;
+                    let line = read_u32(&mut input)?;
+                    let offset = read_u32(&mut input)?;
+                    line_info.lines.insert(offset as usize, (id, line));
+                }
+                Self::SWD_BREAKPOINT => {
+                    let _offset = read_u32(&mut input)?;
+                }
+                Self::SWD_ID => {
+                    read_bytes(&mut input, 16)?;
+                }
+                Self::SWD_REGISTERS => {
+                    let _offset = read_u32(&mut input)?;
+                    let count = read_bytes(&mut input, 1)?[0];
+                    for _ in 0..count {
+                        read_bytes(&mut input, 1)?;
+                        read_c_string(&mut input)?;
+                    }
+                }
+                tag => return Err(format!("Unknown SWD tag {}", tag).into()),
+            }
+        }
+
+        Ok(line_info)
+    }
+
+    /// The name and text of each source file, in the order of the SWD file.
+    pub fn source_files(&self) -> Vec<(&str, &str)> {
+        let mut ids: Vec<_> = self.scripts.keys().collect();
+        ids.sort();
+        ids.into_iter()
+            .map(|id| {
+                let (name, text) = &self.scripts[id];
+                (&name[..], &text[..])
+            })
+            .collect()
+    }
+
+    /// Whether this line info belongs to the SWF of `code`.
+    fn applies_to(&self, code: &SwfSlice) -> bool {
+        self.url.as_deref() == code.movie.url()
+    }
+
+    /// The source line that the action at `offset` into the SWF body is on.
+    pub fn line(&self, offset: usize) -> Option<SourceLine> {
+        let (_, (id, line)) = self
+            .lines
+            .range(..=offset + self.header_length)
+            .next_back()?;
+        self.source_line(*id, *line)
+    }
+
+    /// The source line that starts with the action at `offset` into the SWF
+    /// body, if any.
+    fn line_start(&self, offset: usize) -> Option<SourceLine> {
+        let (id, line) = self.lines.get(&(offset + self.header_length))?;
+        self.source_line(*id, *line)
+    }
+
+    fn source_line(&self, id: u32, line: u32) -> Option<SourceLine> {
+        Some(SourceLine {
+            file: self.scripts.get(&id)?.0.clone(),
+            line,
+        })
+    }
+}
+
+fn read_bytes<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if input.len() < len {
+        return Err("Unexpected end of SWD file".into());
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}
+
+fn read_u32(input: &mut &[u8]) -> Result<u32, Error> {
+    let bytes = read_bytes(input, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_c_string(input: &mut &[u8]) -> Result<String, Error> {
+    let len = input
+        .iter()
+        .position(|&c| c == 0)
+        .ok_or("Unterminated string in SWD file")?;
+    let string = String::from_utf8_lossy(read_bytes(input, len)?).into_owned();
+    read_bytes(input, 1)?;
+    Ok(string)
+}
+
+/// The state of an unfinished step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Start,
+    In,
+    Over(u16),
+    Out(u16),
+}
+
+impl Step {
+    fn is_done(self, depth: u16) -> bool {
+        match self {
+            Step::Start | Step::In => true,
+            Step::Over(from) => depth <= from,
+            Step::Out(from) => depth < from,
+        }
+    }
+}
+
+/// The breakpoints and stepping state of a debugger.
+#[derive(Default)]
+pub struct DebuggerState {
+    breakpoints: Vec<Breakpoint>,
+    line_info: Vec<LineInfo>,
+    step: Option<Step>,
+
+    /// How many AVM2 methods are running, as AVM2 activations don't track
+    /// their own depth.
+    avm2_depth: u16,
+}
+
+impl DebuggerState {
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    /// Remove the breakpoint at `index` of `breakpoints`.
+    pub fn remove_breakpoint(&mut self, index: usize) -> Option<Breakpoint> {
+        if index < self.breakpoints.len() {
+            Some(self.breakpoints.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn add_line_info(&mut self, line_info: LineInfo) {
+        self.line_info.push(line_info);
+    }
+
+    fn line_info(&self, code: &SwfSlice) -> Option<&LineInfo> {
+        self.line_info.iter().find(|info| info.applies_to(code))
+    }
+
+    /// Decide whether to pause before the action at `pos` bytes into `code`.
+    fn pause_reason(
+        &self,
+        activation: &Activation<'_, '_, '_>,
+        code: &SwfSlice,
+        pos: usize,
+    ) -> Option<PauseReason> {
+        match self.step {
+            Some(Step::Start) => return Some(PauseReason::Start),
+            Some(step) if step.is_done(activation.id.depth()) => return Some(PauseReason::Step),
+            _ => (),
+        }
+
+        let offset = code.start + pos;
+        let is_hit = |breakpoint: &Breakpoint| match breakpoint {
+            Breakpoint::Action { url, offset: at } => {
+                *at == offset && (url.is_none() || url.as_deref() == code.movie.url())
+            }
+            Breakpoint::Frame { path, frame } => {
+                let clip = activation.base_clip();
+                pos == 0
+                    && is_frame_script(&activation.id)
+                    && clip.as_movie_clip().map(|clip| clip.current_frame()) == Some(*frame)
+                    && clip.path() == *path
+            }
+            Breakpoint::Line { file, line } => self
+                .line_info(code)
+                .and_then(|info| info.line_start(offset))
+                .map(|start| start.line == *line && start.matches_file(file))
+                .unwrap_or(false),
+        };
+        if self.breakpoints.iter().any(is_hit) {
+            Some(PauseReason::Breakpoint)
+        } else {
+            None
+        }
+    }
+
+    /// Decide whether to pause at the start of `line` of AVM2 code.
+    fn avm2_pause_reason(&self, line: Option<&SourceLine>) -> Option<PauseReason> {
+        match self.step {
+            Some(Step::Start) => return Some(PauseReason::Start),
+            Some(step) if step.is_done(self.avm2_depth) => return Some(PauseReason::Step),
+            _ => (),
+        }
+
+        let line = line?;
+        let is_hit = |breakpoint: &Breakpoint| match breakpoint {
+            Breakpoint::Line { file, line: at } => *at == line.line && line.matches_file(file),
+            _ => false,
+        };
+        if self.breakpoints.iter().any(is_hit) {
+            Some(PauseReason::Breakpoint)
+        } else {
+            None
+        }
+    }
+
+    fn resume(&mut self, command: DebugCommand, depth: u16) {
+        self.step = match command {
+            DebugCommand::Continue => None,
+            DebugCommand::StepIn => Some(Step::In),
+            DebugCommand::StepOver => Some(Step::Over(depth)),
+            DebugCommand::StepOut => Some(Step::Out(depth)),
+        };
+    }
+}
+
+/// Whether an activation runs the actions of a frame.
+fn is_frame_script(id: &ActivationIdentifier<'_>) -> bool {
+    id.name() == "[Frame]"
+}
+
+/// An ActionScript debugger, which pauses execution at breakpoints and steps.
+pub struct Debugger {
+    frontend: Box<dyn DebuggerFrontend>,
+    state: DebuggerState,
+}
+
+impl Debugger {
+    pub fn new(frontend: Box<dyn DebuggerFrontend>) -> Self {
+        Self {
+            frontend,
+            state: DebuggerState::default(),
+        }
+    }
+
+    /// Pause before the first action that runs.
+    pub fn pause_on_start(&mut self) {
+        self.state.step = Some(Step::Start);
+    }
+
+    pub fn state(&self) -> &DebuggerState {
+        &self.state
+    }
+
+    pub fn state_mut(&mut self) -> &mut DebuggerState {
+        &mut self.state
+    }
+
+    /// Let the frontend handle requests made while the movie is running.
+    pub fn poll(&mut self) {
+        self.frontend.poll(&mut self.state);
+    }
+
+    /// Forward the output of `trace` to the frontend.
+    pub fn trace(&mut self, message: &str) {
+        self.frontend.on_trace(message);
+    }
+
+    /// Called by AVM1 before it runs the action at `pos` bytes into `code`.
+    pub(crate) fn before_action(
+        activation: &mut Activation<'_, '_, '_>,
+        code: &SwfSlice,
+        pos: usize,
+    ) {
+        // The debugger is taken out of the context while paused, so that code
+        // run by the frontend, such as getters, doesn't pause again.
+        let mut debugger = match activation.context.debugger.take() {
+            Some(debugger) => debugger,
+            None => return,
+        };
+
+        if let Some(reason) = debugger.state.pause_reason(activation, code, pos) {
+            let depth = activation.id.depth();
+            let clip = activation.base_clip();
+            let location = Location {
+                url: code.movie.url().map(str::to_owned),
+                offset: Some(code.start + pos),
+                function: activation.id.name().to_string(),
+                clip: Some(clip.path()),
+                frame: clip.as_movie_clip().map(|clip| clip.current_frame()),
+                line: debugger
+                    .state
+                    .line_info(code)
+                    .and_then(|info| info.line(code.start + pos)),
+            };
+
+            let mut session = DebugSession {
+                paused: Paused::Avm1 {
+                    activation,
+                    code,
+                    pos,
+                },
+                state: &mut debugger.state,
+                location,
+                reason,
+            };
+            let command = debugger.frontend.on_pause(&mut session);
+            debugger.state.resume(command, depth);
+        }
+
+        *activation.context.debugger = Some(debugger);
+    }
+
+    /// Called by AVM2 when the method `function` reaches the start of `line`
+    /// of its source file.
+    pub(crate) fn on_avm2_line(
+        activation: &mut Avm2Activation<'_, '_, '_>,
+        function: &str,
+        line: u32,
+    ) {
+        let mut debugger = match activation.context.debugger.take() {
+            Some(debugger) => debugger,
+            None => return,
+        };
+
+        let line = activation.debug_file().map(|file| SourceLine {
+            file: file.to_string(),
+            line,
+        });
+        if let Some(reason) = debugger.state.avm2_pause_reason(line.as_ref()) {
+            let depth = debugger.state.avm2_depth;
+            let location = Location {
+                url: activation.context.swf.url().map(str::to_owned),
+                offset: None,
+                function: function.to_string(),
+                clip: None,
+                frame: None,
+                line,
+            };
+
+            let mut session = DebugSession {
+                paused: Paused::Avm2 { activation },
+                state: &mut debugger.state,
+                location,
+                reason,
+            };
+            let command = debugger.frontend.on_pause(&mut session);
+            debugger.state.resume(command, depth);
+        }
+
+        *activation.context.debugger = Some(debugger);
+    }
+
+    /// Called by AVM2 when a method starts running.
+    pub(crate) fn enter_avm2_method(&mut self) {
+        self.state.avm2_depth = self.state.avm2_depth.saturating_add(1);
+    }
+
+    /// Called by AVM2 when a method has finished running.
+    pub(crate) fn exit_avm2_method(&mut self) {
+        self.state.avm2_depth = self.state.avm2_depth.saturating_sub(1);
+    }
+}
+
+/// The code that is paused.
+enum Paused<'s, 'a, 'gc, 'gc_context> {
+    /// AVM1 code, paused before the action at `pos` bytes into `code`.
+    Avm1 {
+        activation: &'s mut Activation<'a, 'gc, 'gc_context>,
+        code: &'s SwfSlice,
+        pos: usize,
+    },
+
+    /// AVM2 code, paused at the start of a source line.
+    Avm2 {
+        activation: &'s mut Avm2Activation<'a, 'gc, 'gc_context>,
+    },
+}
+
+/// What the session methods report for AVM2 code, which can only be paused
+/// and stepped through.
+const AVM2_UNSUPPORTED: &str = "Not available for ActionScript 3 code.\n";
+
+/// Paused code, as seen by a `DebuggerFrontend`.
+pub struct DebugSession<'s, 'a, 'gc, 'gc_context> {
+    paused: Paused<'s, 'a, 'gc, 'gc_context>,
+    state: &'s mut DebuggerState,
+    location: Location,
+    reason: PauseReason,
+}
+
+impl<'s, 'a, 'gc, 'gc_context> DebugSession<'s, 'a, 'gc, 'gc_context> {
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    pub fn reason(&self) -> PauseReason {
+        self.reason
+    }
+
+    /// The breakpoints and stepping state of the debugger.
+    pub fn state(&mut self) -> &mut DebuggerState {
+        self.state
+    }
+
+    /// Whether the paused code is AVM2 code.
+    pub fn is_avm2(&self) -> bool {
+        matches!(self.paused, Paused::Avm2 { .. })
+    }
+
+    fn avm1(&mut self) -> Option<&mut Activation<'a, 'gc, 'gc_context>> {
+        match &mut self.paused {
+            Paused::Avm1 { activation, .. } => Some(&mut **activation),
+            Paused::Avm2 { .. } => None,
+        }
+    }
+
+    /// The next AVM1 action to run.
+    pub fn action(&self) -> Option<String> {
+        if let Paused::Avm1 {
+            activation,
+            code,
+            pos,
+        } = &self.paused
+        {
+            let mut reader = Reader::new(&code.data()[*pos..], activation.swf_version());
+            Some(match reader.read_action() {
+                Ok(Some(action)) => format!("{:?}", action),
+                Ok(None) => "End".to_string(),
+                Err(e) => format!("<invalid action: {}>", e),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// The names of the running activations, innermost first.
+    ///
+    /// Only the paused function is known for AVM2 code.
+    pub fn call_stack(&self) -> Vec<String> {
+        let mut call_stack = vec![];
+        match &self.paused {
+            Paused::Avm1 { activation, .. } => {
+                let mut id = Some(&activation.id);
+                while let Some(current) = id {
+                    call_stack.push(current.name().to_string());
+                    id = current.parent();
+                }
+            }
+            Paused::Avm2 { .. } => call_stack.push(self.location.function.clone()),
+        }
+        call_stack
+    }
+
+    /// The variables of each scope of the scope chain, innermost first.
+    pub fn scope_chain(&mut self) -> String {
+        let activation = match self.avm1() {
+            Some(activation) => activation,
+            None => return AVM2_UNSUPPORTED.to_string(),
+        };
+        let mut dumper = VariableDumper::new("  ");
+        let mut scope = Some(activation.scope_cell());
+        let mut index = 0;
+        while let Some(current) = scope {
+            let (class, locals) = {
+                let current = current.read();
+                (current.class(), current.locals_cell())
+            };
+            dumper.print_variables(
+                &format!("Scope #{} ({:?}):", index, class),
+                &format!("scope{}", index),
+                &locals,
+                activation,
+            );
+            scope = current.read().parent_cell();
+            index += 1;
+        }
+        dumper.output().to_string()
+    }
+
+    /// The variables of the innermost scope.
+    pub fn locals(&mut self) -> String {
+        let activation = match self.avm1() {
+            Some(activation) => activation,
+            None => return AVM2_UNSUPPORTED.to_string(),
+        };
+        let mut dumper = VariableDumper::new("  ");
+        let locals = activation.scope().locals_cell();
+        dumper.print_variables("Locals:", "local", &locals, activation);
+        dumper.output().to_string()
+    }
+
+    /// The registers used by the running code.
+    ///
+    /// Functions defined with `DefineFunction2` have their own registers;
+    /// everything else shares the four global registers.
+    pub fn registers(&mut self) -> String {
+        let activation = match self.avm1() {
+            Some(activation) => activation,
+            None => return AVM2_UNSUPPORTED.to_string(),
+        };
+        let ids: Vec<u8> = if activation.has_local_register(0) {
+            (0..=255)
+                .take_while(|&id| activation.has_local_register(id))
+                .collect()
+        } else {
+            (0..4).collect()
+        };
+
+        let mut output = String::new();
+        for id in ids {
+            let value = activation.current_register(id);
+            let value = VariableDumper::dump(&value, "  ", activation);
+            output.push_str(&format!("r{} = {}\n", id, value));
+        }
+        output
+    }
+
+    /// The values on the operand stack, topmost first.
+    pub fn stack(&mut self) -> String {
+        let activation = match self.avm1() {
+            Some(activation) => activation,
+            None => return AVM2_UNSUPPORTED.to_string(),
+        };
+        let values: Vec<Value<'gc>> = activation.context.avm1.stack().to_vec();
+        let mut output = String::new();
+        for (i, value) in values.iter().rev().enumerate() {
+            let value = VariableDumper::dump(value, "  ", activation);
+            output.push_str(&format!("#{} = {}\n", i, value));
+        }
+        output
+    }
+
+    /// Evaluate `expression` in the scope of the paused code.
+    ///
+    /// Only literals and variable paths, such as `_root.menu._x` or
+    /// `/menu:count`, are supported. Getters are called as they would be by the
+    /// code itself. Expressions with operators or function calls are refused
+    /// with an error, rather than being looked up as a variable.
+    pub fn evaluate(&mut self, expression: &str) -> Result<String, String> {
+        let activation = self
+            .avm1()
+            .ok_or_else(|| AVM2_UNSUPPORTED.trim_end().to_string())?;
+        let expression = expression.trim();
+        let value = match expression {
+            "" => return Err("Nothing to evaluate".to_string()),
+            "undefined" => Value::Undefined,
+            "null" => Value::Null,
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => {
+                let quoted = expression
+                    .strip_prefix('"')
+                    .and_then(|s| s.strip_suffix('"'));
+                if let Some(string) = quoted {
+                    AvmString::new(activation.context.gc_context, string.to_string()).into()
+                } else if let Ok(number) = expression.parse::<f64>() {
+                    number.into()
+                } else if !is_variable_path(expression) {
+                    return Err(format!(
+                        "Can't evaluate {}: only literals and variable paths are supported",
+                        expression
+                    ));
+                } else {
+                    activation
+                        .get_variable(expression)
+                        .map_err(|e| e.to_string())?
+                }
+            }
+        };
+        Ok(VariableDumper::dump(&value, "  ", activation))
+    }
+}
+
+/// Whether `expression` looks like a variable path, made of identifiers
+/// separated by `.`, `/` or `:`.
+fn is_variable_path(expression: &str) -> bool {
+    expression
+        .chars()
+        .all(|c| c.is_alphanumeric() || "_$./:".contains(c))
+}
+
+/// Read the top-level tags of `movie`, stopping at the first error.
+fn read_tags(movie: &SwfMovie) -> impl Iterator<Item = swf::Tag> {
+    let data = movie.data();
+    let version = movie.version();
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let mut reader = swf::read::Reader::new(&data[pos..], version);
+        let tag = match reader.read_tag() {
+            Ok(swf::Tag::End) | Err(_) => return None,
+            Ok(tag) => tag,
+        };
+        pos = data.len() - reader.get_ref().len();
+        Some(tag)
+    })
+}
+
+/// Whether `movie` allows debugging, and the MD5-crypt hash of the password
+/// it asks for, as set by its `EnableDebugger` tag.
+///
+/// An empty hash means that no password is needed. Returns `None` if the movie
+/// does not allow debugging.
+pub fn debugger_password_hash(movie: &SwfMovie) -> Option<String> {
+    read_tags(movie).find_map(|tag| match tag {
+        swf::Tag::EnableDebugger(hash) => Some(hash),
+        _ => None,
+    })
+}
+
+/// The source files named by the `DebugFile` instructions of the AVM2 code in
+/// `movie`, in the order they are first used.
+pub fn avm2_source_files(movie: &SwfMovie) -> Vec<String> {
+    let mut files = vec![];
+    let mut seen = BTreeSet::new();
+    for tag in read_tags(movie) {
+        let data = match tag {
+            swf::Tag::DoAbc(do_abc) => do_abc.data,
+            _ => continue,
+        };
+        let abc = match swf::avm2::read::Reader::new(Cursor::new(&data[..])).read() {
+            Ok(abc) => abc,
+            Err(_) => continue,
+        };
+        for body in &abc.method_bodies {
+            let mut reader = swf::avm2::read::Reader::new(Cursor::new(&body.code[..]));
+            while let Ok(Some(op)) = reader.read_op() {
+                if let swf::avm2::types::Op::DebugFile { file_name } = op {
+                    // String 0 is the empty string, so the pool starts at 1.
+                    let name = (file_name.0 as usize)
+                        .checked_sub(1)
+                        .and_then(|i| abc.constant_pool.strings.get(i));
+                    if let Some(name) = name {
+                        if seen.insert(name.clone()) {
+                            files.push(name.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swd(records: &[&[u8]]) -> Vec<u8> {
+        let mut swd = b"FWD\x07".to_vec();
+        for record in records {
+            swd.extend_from_slice(record);
+        }
+        swd
+    }
+
+    fn u32s(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|v| v.to_le_bytes().to_vec())
+            .collect()
+    }
+
+    /// A movie with no tags, written with a stage rectangle of `num_bits`
+    /// bits per field.
+    fn movie_with_rect_bits(num_bits: u8) -> SwfMovie {
+        let mut data = b"FWS\x08\0\0\0\0".to_vec();
+        // The stage rectangle, with every field set to 0.
+        let rect_bytes = (5 + 4 * usize::from(num_bits) + 7) / 8;
+        data.push(num_bits << 3);
+        data.extend(std::iter::repeat(0).take(rect_bytes - 1));
+        // Frame rate, frame count and the end tag.
+        data.extend_from_slice(&[0, 24, 1, 0, 0, 0]);
+        let len = data.len() as u32;
+        data[4..8].copy_from_slice(&len.to_le_bytes());
+        SwfMovie::from_data(&data, None).unwrap()
+    }
+
+    #[test]
+    fn line_info_uses_header_length() {
+        // The stage rectangle may be written with more bits than it needs,
+        // which moves the offsets of every action.
+        for &num_bits in &[0, 15, 31] {
+            let movie = movie_with_rect_bits(num_bits);
+            let header_length = movie.header_length();
+            assert_eq!(
+                header_length,
+                8 + (5 + 4 * usize::from(num_bits) + 7) / 8 + 4
+            );
+
+            let mut script = u32s(&[LineInfo::SWD_SCRIPT, 1, 0]);
+            script.extend_from_slice(b"Main.as\0text\0");
+            let data = swd(&[
+                &script,
+                &u32s(&[LineInfo::SWD_OFFSET, 1, 7, header_length as u32]),
+            ]);
+            let line_info = LineInfo::from_swd(&data, &movie).unwrap();
+            assert_eq!(
+                line_info.line_start(0),
+                Some(SourceLine {
+                    file: "Main.as".to_string(),
+                    line: 7
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn line_info_from_swd() {
+        let movie = movie_with_rect_bits(15);
+        let header_length = movie.header_length();
+        let mut script = u32s(&[LineInfo::SWD_SCRIPT, 1, 0]);
+        script.extend_from_slice(b"C:\\src\\Main.as\0text\0");
+        let offset = |line, offset| u32s(&[LineInfo::SWD_OFFSET, 1, line, offset]);
+        let first = header_length as u32 + 10;
+        let data = swd(&[
+            &script,
+            &u32s(&[LineInfo::SWD_ID, 0, 0, 0, 0]),
+            &offset(3, first),
+            &offset(4, first + 8),
+            &u32s(&[LineInfo::SWD_BREAKPOINT, first]),
+        ]);
+
+        let line_info = LineInfo::from_swd(&data, &movie).unwrap();
+        let line = |file: &str, line| {
+            Some(SourceLine {
+                file: file.to_string(),
+                line,
+            })
+        };
+        assert_eq!(line_info.line(9), None);
+        assert_eq!(line_info.line(10), line("C:\\src\\Main.as", 3));
+        assert_eq!(line_info.line(12), line("C:\\src\\Main.as", 3));
+        assert_eq!(line_info.line(18), line("C:\\src\\Main.as", 4));
+        assert_eq!(line_info.line_start(12), None);
+
+        let start = line_info.line_start(18).unwrap();
+        assert!(start.matches_file("Main.as"));
+        assert!(start.matches_file("src\\Main.as"));
+        assert!(!start.matches_file("ain.as"));
+
+        assert!(LineInfo::from_swd(b"FWS\x07", &movie).is_err());
+        assert!(LineInfo::from_swd(&data[..data.len() - 1], &movie).is_err());
+    }
+
+    #[test]
+    fn variable_paths() {
+        assert!(is_variable_path("_root.menu._x"));
+        assert!(is_variable_path("/menu:count"));
+        assert!(is_variable_path("$version"));
+        assert!(!is_variable_path("a + b"));
+        assert!(!is_variable_path("getTimer()"));
+        assert!(!is_variable_path("items[0]"));
+    }
+
+    #[test]
+    fn step_depths() {
+        assert!(Step::In.is_done(3));
+        assert!(Step::Over(2).is_done(1));
+        assert!(Step::Over(2).is_done(2));
+        assert!(!Step::Over(2).is_done(3));
+        assert!(Step::Out(2).is_done(1));
+        assert!(!Step::Out(2).is_done(2));
+    }
+}
@@ -112,6 +112,57 @@ pub struct EditTextData<'gc> {
 
     /// Whether this text field is firing is variable binding (to prevent infinite loops).
     firing_variable_binding: bool,
+
+    /// The line shown at the top of the text field, starting from 1.
+    scroll: usize,
+
+    /// Whether the mouse wheel scrolls this text field.
+    is_mouse_wheel_enabled: bool,
+}
+
+impl<'gc> EditTextData<'gc> {
+    /// The top of each line of laid-out text, in order.
+    fn line_tops(&self) -> Vec<Twips> {
+        let mut tops: Vec<Twips> = self
+            .layout
+            .iter()
+            .filter(|lbox| lbox.is_text_box() || lbox.is_bullet())
+            .map(|lbox| lbox.bounds().offset_y())
+            .collect();
+        tops.sort();
+        tops.dedup();
+        tops
+    }
+
+    /// The height available for text inside the field.
+    fn visible_height(&self) -> Twips {
+        self.bounds.height() - Twips::from_pixels(EditText::INTERNAL_PADDING * 2.0)
+    }
+
+    /// The highest line that can be scrolled to the top of the field, which
+    /// is the first line from which all of the remaining text fits.
+    fn maxscroll(&self) -> usize {
+        let bottom = self
+            .layout
+            .iter()
+            .map(|lbox| lbox.bounds().extent_y())
+            .max()
+            .unwrap_or_default();
+        let visible_height = self.visible_height();
+        let tops = self.line_tops();
+        tops.iter()
+            .position(|&top| bottom - top <= visible_height)
+            .unwrap_or_else(|| tops.len().saturating_sub(1))
+            + 1
+    }
+
+    /// How far the text is moved up to show the `scroll` line at the top.
+    fn scroll_offset(&self) -> Twips {
+        self.line_tops()
+            .get(self.scroll - 1)
+            .copied()
+            .unwrap_or_default()
+    }
 }
 
 impl<'gc> EditText<'gc> {
@@ -193,6 +244,8 @@ impl<'gc> EditText<'gc> {
                 variable,
                 bound_stage_object: None,
                 firing_variable_binding: false,
+                scroll: 1,
+                is_mouse_wheel_enabled: true,
             },
         ));
 
@@ -601,6 +654,8 @@ impl<'gc> EditText<'gc> {
 
         edit_text.layout = new_layout;
         edit_text.intrinsic_bounds = intrinsic_bounds;
+        let maxscroll = edit_text.maxscroll();
+        edit_text.scroll = edit_text.scroll.min(maxscroll);
 
         match autosize {
             AutoSizeMode::None => {}
@@ -642,6 +697,34 @@ impl<'gc> EditText<'gc> {
         }
     }
 
+    /// The line shown at the top of the text field, starting from 1.
+    pub fn scroll(self) -> usize {
+        self.0.read().scroll
+    }
+
+    /// Scroll the text field so that `scroll` is the top visible line.
+    ///
+    /// The line is clamped so that the text field never scrolls past the
+    /// point where the last line becomes visible.
+    pub fn set_scroll(self, scroll: usize, context: MutationContext<'gc, '_>) {
+        let mut edit_text = self.0.write(context);
+        let maxscroll = edit_text.maxscroll();
+        edit_text.scroll = scroll.max(1).min(maxscroll);
+    }
+
+    /// The highest value `scroll` can take.
+    pub fn maxscroll(self) -> usize {
+        self.0.read().maxscroll()
+    }
+
+    pub fn is_mouse_wheel_enabled(self) -> bool {
+        self.0.read().is_mouse_wheel_enabled
+    }
+
+    pub fn set_mouse_wheel_enabled(self, context: MutationContext<'gc, '_>, is_enabled: bool) {
+        self.0.write(context).is_mouse_wheel_enabled = is_enabled;
+    }
+
     /// Measure the width and height of the `EditText`'s current text load.
     ///
     /// The returned tuple should be interpreted as width, then height.
@@ -994,18 +1077,27 @@ impl<'gc> TDisplayObject<'gc> for EditText<'gc> {
 
         // TODO: Where does this come from? How is this different than INTERNAL_PADDING? Does this apply to y as well?
         // If this is actually right, offset the border in `redraw_border` instead of doing an extra push.
+        let edit_text = self.0.read();
+        let scroll_offset = edit_text.scroll_offset();
         context.transform_stack.push(&Transform {
             matrix: Matrix {
                 tx: Twips::from_pixels(Self::INTERNAL_PADDING),
-                ty: Twips::from_pixels(Self::INTERNAL_PADDING),
+                ty: Twips::from_pixels(Self::INTERNAL_PADDING) - scroll_offset,
                 ..Default::default()
             },
             ..Default::default()
         });
 
-        for layout_box in self.0.read().layout.iter() {
-            self.render_layout_box(context, layout_box);
+        // Only lines that start inside the field are shown, though the top
+        // line always is, even if the field is too short to fit it.
+        let visible_bottom = scroll_offset + edit_text.visible_height();
+        for layout_box in edit_text.layout.iter() {
+            let top = layout_box.bounds().offset_y();
+            if top == scroll_offset || (top > scroll_offset && top < visible_bottom) {
+                self.render_layout_box(context, layout_box);
+            }
         }
+        drop(edit_text);
 
         context.transform_stack.pop();
         context.transform_stack.pop();
@@ -91,6 +91,7 @@ impl<'gc> MovieClip<'gc> {
                         total_frames: num_frames,
                         audio_stream_info: None,
                         frame_labels: HashMap::new(),
+                        preload_progress: Default::default(),
                     },
                 ),
                 tag_stream_pos: 0,
@@ -132,6 +133,11 @@ impl<'gc> MovieClip<'gc> {
             .replace_with_movie(gc_context, movie)
     }
 
+    /// Preload the tags of this clip, registering the characters they define.
+    ///
+    /// If the movie is still downloading, only the tags that have loaded are
+    /// preloaded. Calling this again once more has loaded carries on from
+    /// there.
     pub fn preload(
         self,
         context: &mut UpdateContext<'_, 'gc, '_>,
@@ -141,10 +147,20 @@ impl<'gc> MovieClip<'gc> {
         // TODO: Re-creating static data because preload step occurs after construction.
         // Should be able to hoist this up somewhere, or use MaybeUninit.
         let mut static_data = (&*self.0.read().static_data).clone();
-        let data = self.0.read().static_data.swf.clone();
-        let mut reader = data.read_from(self.0.read().tag_stream_pos);
-        let mut cur_frame = 1;
-        let mut ids = fnv::FnvHashMap::default();
+        if static_data.preload_progress.complete {
+            return;
+        }
+        if static_data.id == 0 {
+            // The root clip's slice only sees the data that had loaded when it
+            // was made, so pick up anything that has arrived since.
+            static_data.swf = SwfSlice::from(Arc::clone(&static_data.swf.movie));
+            self.0.write(context.gc_context).static_data =
+                Gc::allocate(context.gc_context, static_data.clone());
+        }
+        let data = static_data.swf.clone();
+        let mut reader = data.read_from(static_data.preload_progress.next_tag_pos);
+        let mut cur_frame = static_data.preload_progress.cur_frame;
+        let mut ids = std::mem::take(&mut static_data.preload_progress.ids);
         let version = reader.version();
         let tag_callback = |reader: &mut SwfStream<&[u8]>, tag_code, tag_len| {
             let data = *reader.get_inner().get_ref();
@@ -348,12 +364,24 @@ impl<'gc> MovieClip<'gc> {
                 _ => Ok(()),
             }
         };
-        let _ = tag_utils::decode_tags(&mut reader, tag_callback, TagCode::End);
+        let result = tag_utils::decode_tags(&mut reader, tag_callback, TagCode::End);
+        // Tags that stop early are only worth retrying once more has loaded.
+        // The root clip's slice keeps growing until the whole movie has, even
+        // past the length given in the header.
+        let complete = result.is_ok()
+            || data.movie.is_loaded()
+            || (static_data.id != 0 && data.data().len() == data.end - data.start);
+        static_data.preload_progress = PreloadProgress {
+            next_tag_pos: reader.get_ref().position(),
+            cur_frame,
+            ids,
+            complete,
+        };
         self.0.write(context.gc_context).static_data =
             Gc::allocate(context.gc_context, static_data);
 
         // Finalize audio stream.
-        if self.0.read().static_data.audio_stream_info.is_some() {
+        if complete && self.0.read().static_data.audio_stream_info.is_some() {
             context.audio.preload_sound_stream_end(self.0.read().id());
         }
     }
@@ -435,11 +463,24 @@ impl<'gc> MovieClip<'gc> {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn playing(self) -> bool {
         self.0.read().playing()
     }
 
+    /// Whether this clip goes back to its first frame after its last one.
+    pub fn is_looping(self) -> bool {
+        !self.0.read().flags.contains(MovieClipFlags::StopAtEnd)
+    }
+
+    pub fn set_looping(self, gc_context: MutationContext<'gc, '_>, looping: bool) {
+        let mut mc = self.0.write(gc_context);
+        if looping {
+            mc.flags.remove(MovieClipFlags::StopAtEnd);
+        } else {
+            mc.flags.insert(MovieClipFlags::StopAtEnd);
+        }
+    }
+
     pub fn next_frame(self, context: &mut UpdateContext<'_, 'gc, '_>) {
         if self.current_frame() < self.total_frames() {
             self.goto_frame(context, self.current_frame() + 1, true);
@@ -494,8 +535,38 @@ impl<'gc> MovieClip<'gc> {
     }
 
     pub fn frames_loaded(self) -> FrameNumber {
-        // TODO(Herschel): root needs to progressively stream in frames.
-        self.0.read().static_data.total_frames
+        let mc = self.0.read();
+        let static_data = &mc.static_data;
+        if static_data.preload_progress.complete || static_data.swf.movie.is_loaded() {
+            static_data.total_frames
+        } else {
+            (static_data.preload_progress.cur_frame - 1).min(static_data.total_frames)
+        }
+    }
+
+    /// The number of uncompressed bytes of this clip that have loaded.
+    ///
+    /// For the root clip of a movie, this counts the whole movie. Other clips
+    /// are always fully loaded, and count the length of their tags.
+    pub fn bytes_loaded(self) -> usize {
+        let mc = self.0.read();
+        let static_data = &mc.static_data;
+        if static_data.id == 0 {
+            static_data.swf.movie.bytes_loaded()
+        } else {
+            static_data.swf.end - static_data.swf.start
+        }
+    }
+
+    /// The number of uncompressed bytes of this clip once it has loaded.
+    pub fn bytes_total(self) -> usize {
+        let mc = self.0.read();
+        let static_data = &mc.static_data;
+        if static_data.id == 0 {
+            static_data.swf.movie.bytes_total()
+        } else {
+            static_data.swf.end - static_data.swf.start
+        }
     }
 
     pub fn set_avm1_constructor(
@@ -678,15 +749,19 @@ impl<'gc> MovieClip<'gc> {
     ) {
         // Advance frame number.
         if self.current_frame() < self.total_frames() {
+            if self.current_frame() >= self.frames_loaded() {
+                // Wait for the next frame to load.
+                return;
+            }
             self.0.write(context.gc_context).current_frame += 1;
-        } else if self.total_frames() > 1 {
+        } else if self.total_frames() > 1 && self.is_looping() {
             // Looping acts exactly like a gotoAndPlay(1).
             // Specifically, object that existed on frame 1 should not be destroyed
             // and recreated.
             self.run_goto(self_display_object, context, 1);
             return;
         } else {
-            // Single frame clips do not play.
+            // Single frame clips do not play, and non-looping clips stop at the end.
             self.stop(context);
         }
 
@@ -857,12 +932,7 @@ impl<'gc> MovieClip<'gc> {
 
         let len = mc.tag_stream_len() as u64;
         // Sanity; let's make sure we don't seek way too far.
-        // TODO: This should be self.frames_loaded() when we implement that.
-        let clamped_frame = if frame <= mc.total_frames() {
-            frame
-        } else {
-            mc.total_frames()
-        };
+        let clamped_frame = frame.min(self.frames_loaded());
         drop(mc);
 
         while self.current_frame() < clamped_frame && frame_pos < len {
@@ -1296,6 +1366,7 @@ impl<'gc> MovieClipData<'gc> {
                 total_frames,
                 audio_stream_info: None,
                 frame_labels: HashMap::new(),
+                preload_progress: Default::default(),
             },
         );
         self.tag_stream_pos = 0;
@@ -1622,6 +1693,39 @@ impl<'gc, 'a> MovieClipData<'gc> {
         Ok(())
     }
 
+    /// Prepares the morph shape `id` to be drawn at `ratio`.
+    ///
+    /// Morph shapes defined by earlier preloads of a movie that is still
+    /// downloading are already in the library. Returns `false` if `id` isn't a
+    /// morph shape.
+    fn preload_morph_shape_ratio(
+        &self,
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        morph_shapes: &mut fnv::FnvHashMap<CharacterId, MorphShapeStatic>,
+        id: CharacterId,
+        ratio: Option<u16>,
+    ) -> bool {
+        if let Some(morph_shape) = morph_shapes.get_mut(&id) {
+            if let Some(ratio) = ratio {
+                morph_shape.register_ratio(context.renderer, ratio);
+            }
+            return true;
+        }
+
+        let morph_shape = match context
+            .library
+            .library_for_movie_mut(self.movie())
+            .get_character_by_id(id)
+        {
+            Some(Character::MorphShape(morph_shape)) => *morph_shape,
+            _ => return false,
+        };
+        if let Some(ratio) = ratio {
+            morph_shape.register_ratio(context, ratio);
+        }
+        true
+    }
+
     #[inline]
     fn preload_place_object(
         &mut self,
@@ -1640,29 +1744,18 @@ impl<'gc, 'a> MovieClipData<'gc> {
         }?;
         match place_object.action {
             PlaceObjectAction::Place(id) => {
-                if let Some(morph_shape) = morph_shapes.get_mut(&id) {
+                if self.preload_morph_shape_ratio(context, morph_shapes, id, place_object.ratio) {
                     ids.insert(place_object.depth.into(), id);
-                    if let Some(ratio) = place_object.ratio {
-                        morph_shape.register_ratio(context.renderer, ratio);
-                    }
                 }
             }
             PlaceObjectAction::Modify => {
                 if let Some(&id) = ids.get(&place_object.depth.into()) {
-                    if let Some(morph_shape) = morph_shapes.get_mut(&id) {
-                        ids.insert(place_object.depth.into(), id);
-                        if let Some(ratio) = place_object.ratio {
-                            morph_shape.register_ratio(context.renderer, ratio);
-                        }
-                    }
+                    self.preload_morph_shape_ratio(context, morph_shapes, id, place_object.ratio);
                 }
             }
             PlaceObjectAction::Replace(id) => {
-                if let Some(morph_shape) = morph_shapes.get_mut(&id) {
+                if self.preload_morph_shape_ratio(context, morph_shapes, id, place_object.ratio) {
                     ids.insert(place_object.depth.into(), id);
-                    if let Some(ratio) = place_object.ratio {
-                        morph_shape.register_ratio(context.renderer, ratio);
-                    }
                 } else {
                     ids.remove(&place_object.depth.into());
                 }
@@ -2384,6 +2477,7 @@ struct MovieClipStatic {
     frame_labels: HashMap<String, FrameNumber>,
     audio_stream_info: Option<swf::SoundStreamHead>,
     total_frames: FrameNumber,
+    preload_progress: PreloadProgress,
 }
 
 impl MovieClipStatic {
@@ -2394,6 +2488,41 @@ impl MovieClipStatic {
             total_frames: 1,
             frame_labels: HashMap::new(),
             audio_stream_info: None,
+            // There are no tags to preload.
+            preload_progress: PreloadProgress {
+                complete: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// How far the tags of a movie clip have been preloaded.
+///
+/// A root movie that is still downloading is preloaded a bit at a time, so
+/// this is kept to pick up where the last preload left off.
+#[derive(Clone)]
+struct PreloadProgress {
+    /// The position of the next tag to preload.
+    next_tag_pos: u64,
+
+    /// The frame that the next tag is in.
+    cur_frame: FrameNumber,
+
+    /// The characters placed at each depth, for finding morph shape ratios.
+    ids: fnv::FnvHashMap<Depth, CharacterId>,
+
+    /// Whether every tag has been preloaded.
+    complete: bool,
+}
+
+impl Default for PreloadProgress {
+    fn default() -> Self {
+        Self {
+            next_tag_pos: 0,
+            cur_frame: 1,
+            ids: fnv::FnvHashMap::default(),
+            complete: false,
         }
     }
 }
@@ -2521,6 +2650,10 @@ enum MovieClipFlags {
 
     /// Whether this `MovieClip` is playing or stopped.
     Playing,
+
+    /// Whether this `MovieClip` stops on its last frame instead of looping,
+    /// as toggled by the context menu.
+    StopAtEnd,
 }
 
 /// Actions that are attached to a `MovieClip` event in
@@ -2578,6 +2711,7 @@ impl ClipAction {
             },
             action_data: SwfSlice {
                 movie: Arc::clone(&movie),
+                buffer: movie.data(),
                 start: 0,
                 end: len,
             },
@@ -6,12 +6,68 @@ pub enum PlayerEvent {
     KeyDown { key_code: KeyCode },
     KeyUp { key_code: KeyCode },
     MouseMove { x: f64, y: f64 },
-    MouseUp { x: f64, y: f64 },
-    MouseDown { x: f64, y: f64 },
+    MouseUp { x: f64, y: f64, button: MouseButton },
+    MouseDown { x: f64, y: f64, button: MouseButton },
+    MouseWheel { delta: MouseWheelDelta },
     MouseLeft,
     TextInput { codepoint: char },
 }
 
+/// A mouse button.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+impl MouseButton {
+    /// The clip events that pressing and releasing this button dispatch, on
+    /// top of `MouseDown` and `MouseUp`.
+    pub fn clip_events(self) -> Option<(ClipEvent, ClipEvent)> {
+        match self {
+            MouseButton::Left => None,
+            MouseButton::Right => Some((ClipEvent::RightMouseDown, ClipEvent::RightMouseUp)),
+            MouseButton::Middle => Some((ClipEvent::MiddleMouseDown, ClipEvent::MiddleMouseUp)),
+        }
+    }
+
+    /// The key code that `Key.isDown` uses for this button.
+    pub fn key_code(self) -> KeyCode {
+        match self {
+            MouseButton::Left => KeyCode::LeftMouse,
+            MouseButton::Right => KeyCode::RightMouse,
+            MouseButton::Middle => KeyCode::MiddleMouse,
+        }
+    }
+}
+
+/// The distance scrolled by a mouse wheel.
+///
+/// Positive values scroll up, towards the start of the content.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MouseWheelDelta {
+    /// Scrolled by lines of text, as reported by most mouse wheels.
+    Lines(f64),
+
+    /// Scrolled by pixels, as reported by touchpads and browsers.
+    Pixels(f64),
+}
+
+impl MouseWheelDelta {
+    /// Browsers scroll about 100 pixels for each notch of a mouse wheel, which
+    /// Flash reports as 3 lines.
+    const PIXELS_PER_LINE: f64 = 100.0 / 3.0;
+
+    /// The distance scrolled in lines, as reported to `onMouseWheel`.
+    pub fn lines(self) -> f64 {
+        match self {
+            MouseWheelDelta::Lines(lines) => lines,
+            MouseWheelDelta::Pixels(pixels) => pixels / Self::PIXELS_PER_LINE,
+        }
+    }
+}
+
 /// Whether this button event was handled by some child.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum ClipEventResult {
@@ -37,11 +93,15 @@ pub enum ClipEvent {
     MouseUp,
     MouseDown,
     MouseMove,
+    MiddleMouseDown,
+    MiddleMouseUp,
     Press,
     RollOut,
     RollOver,
     Release,
     ReleaseOutside,
+    RightMouseDown,
+    RightMouseUp,
     Unload,
 }
 
@@ -61,7 +121,7 @@ impl ClipEvent {
     pub fn propagates(self) -> bool {
         matches!(
             self,
-            Self::MouseUp | Self::MouseDown | Self::MouseMove | Self::KeyPress { .. } | Self::KeyDown | Self::KeyUp
+            Self::MouseUp | Self::MouseDown | Self::MouseMove | Self::MiddleMouseDown | Self::MiddleMouseUp | Self::RightMouseDown | Self::RightMouseUp | Self::KeyPress { .. } | Self::KeyDown | Self::KeyUp
         )
     }
 
@@ -86,14 +146,30 @@ impl ClipEvent {
             ClipEvent::MouseDown => Some("onMouseDown"),
             ClipEvent::MouseMove => Some("onMouseMove"),
             ClipEvent::MouseUp => Some("onMouseUp"),
+            ClipEvent::MiddleMouseDown => None,
+            ClipEvent::MiddleMouseUp => None,
             ClipEvent::Press => Some("onPress"),
             ClipEvent::RollOut => Some("onRollOut"),
             ClipEvent::RollOver => Some("onRollOver"),
             ClipEvent::Release => Some("onRelease"),
             ClipEvent::ReleaseOutside => Some("onReleaseOutside"),
+            ClipEvent::RightMouseDown => None,
+            ClipEvent::RightMouseUp => None,
             ClipEvent::Unload => Some("onUnload"),
         }
     }
+
+    /// Returns the ActionScript 3 `MouseEvent` type of this event, for events
+    /// that only exist in ActionScript 3.
+    pub fn avm2_event_type(self) -> Option<&'static str> {
+        match self {
+            ClipEvent::MiddleMouseDown => Some("middleMouseDown"),
+            ClipEvent::MiddleMouseUp => Some("middleMouseUp"),
+            ClipEvent::RightMouseDown => Some("rightMouseDown"),
+            ClipEvent::RightMouseUp => Some("rightMouseUp"),
+            _ => None,
+        }
+    }
 }
 
 /// Flash virtual keycode.
@@ -101,6 +177,9 @@ impl ClipEvent {
 #[repr(u8)]
 pub enum KeyCode {
     Unknown = 0,
+    LeftMouse = 1,
+    RightMouse = 2,
+    MiddleMouse = 4,
     Backspace = 8,
     Return = 13,
     Shift = 16,
@@ -4,6 +4,17 @@ use crate::prelude::*;
 use crate::transform::Transform;
 use gc_arena::{Collect, Gc, MutationContext};
 
+/// The number of glyphs in each row of a font's glyph sheet.
+const GLYPH_SHEET_COLUMNS: usize = 16;
+
+/// The size of each glyph in a font's glyph sheet at a scale of 1, in pixels.
+pub const GLYPH_SHEET_CELL_SIZE: f32 = 64.0;
+
+/// The size of each glyph in a font's glyph sheet at `scale`, in pixels.
+fn glyph_sheet_cell_size(scale: f32) -> f32 {
+    (GLYPH_SHEET_CELL_SIZE * scale).ceil().max(1.0)
+}
+
 /// Certain Flash routines measure text by rounding down to the nearest whole pixel.
 pub fn round_down_to_pixel(t: Twips) -> Twips {
     Twips::from_pixels(t.to_pixels().floor())
@@ -32,8 +43,7 @@ pub struct EvalParameters {
 
 impl EvalParameters {
     /// Construct eval parameters from their individual parts.
-    #[allow(dead_code)]
-    fn from_parts(height: Twips, letter_spacing: Twips, kerning: bool) -> Self {
+    pub fn from_parts(height: Twips, letter_spacing: Twips, kerning: bool) -> Self {
         Self {
             height,
             letter_spacing,
@@ -154,6 +164,11 @@ impl<'gc> Font<'gc> {
         !self.0.glyphs.is_empty()
     }
 
+    /// Returns the number of glyphs in this font.
+    pub fn num_glyphs(self) -> usize {
+        self.0.glyphs.len()
+    }
+
     /// Returns a glyph entry by index.
     /// Used by `Text` display objects.
     pub fn get_glyph(self, i: usize) -> Option<Glyph> {
@@ -209,6 +224,50 @@ impl<'gc> Font<'gc> {
         self.0.scale
     }
 
+    /// The size in pixels of the sheet that `render_glyph_sheet` draws at
+    /// `scale`, or `None` if the font has no glyphs.
+    pub fn glyph_sheet_size(self, scale: f32) -> Option<(u32, u32)> {
+        let num_glyphs = self.num_glyphs();
+        if num_glyphs == 0 {
+            return None;
+        }
+        let cell_size = glyph_sheet_cell_size(scale);
+        let columns = num_glyphs.min(GLYPH_SHEET_COLUMNS);
+        let rows = (num_glyphs + GLYPH_SHEET_COLUMNS - 1) / GLYPH_SHEET_COLUMNS;
+        Some((
+            (columns as f32 * cell_size) as u32,
+            (rows as f32 * cell_size) as u32,
+        ))
+    }
+
+    /// Draw every glyph of this font in black, in rows from left to right.
+    pub fn render_glyph_sheet(self, renderer: &mut dyn RenderBackend, scale: f32) {
+        let num_glyphs = self.num_glyphs();
+        let cell_size = glyph_sheet_cell_size(scale);
+        let columns = num_glyphs.min(GLYPH_SHEET_COLUMNS);
+
+        // Fonts without layout information have no ascent, so put their
+        // baseline where most fonts would have it.
+        let cell_height = Twips::from_pixels(cell_size.into());
+        let mut baseline = self.get_baseline_for_height(cell_height);
+        if baseline.get() == 0 {
+            baseline = Twips::new(cell_height.get() * 4 / 5);
+        }
+
+        for (i, glyph) in self.0.glyphs.iter().enumerate() {
+            let mut transform = Transform::default();
+            transform.matrix.a = cell_height.get() as f32 / self.scale();
+            transform.matrix.d = transform.matrix.a;
+            transform.matrix.tx = Twips::from_pixels(f64::from((i % columns) as f32 * cell_size));
+            transform.matrix.ty =
+                Twips::from_pixels(f64::from((i / columns) as f32 * cell_size)) + baseline;
+            transform.color_transform.r_mult = 0.0;
+            transform.color_transform.g_mult = 0.0;
+            transform.color_transform.b_mult = 0.0;
+            renderer.render_shape(glyph.shape, &transform);
+        }
+    }
+
     /// Evaluate this font against a particular string on a glyph-by-glyph
     /// basis.
     ///
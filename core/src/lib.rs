@@ -21,6 +21,8 @@ mod character;
 mod collect;
 pub mod color_transform;
 mod context;
+pub mod context_menu;
+pub mod debugger;
 mod drawing;
 mod ecma_conversions;
 pub mod events;
@@ -31,6 +33,7 @@ pub mod loader;
 mod player;
 mod prelude;
 mod property_map;
+mod security;
 pub mod shape_utils;
 pub mod string_utils;
 pub mod tag_utils;
@@ -40,6 +43,8 @@ mod xml;
 pub mod backend;
 
 pub use events::PlayerEvent;
-pub use player::Player;
+pub use library::{LibrarySymbol, SymbolKind};
+pub use backend::render::StageQuality;
+pub use player::{Player, StageAlign, StageScaleMode};
 pub use swf;
 pub use swf::Color;
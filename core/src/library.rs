@@ -10,12 +10,41 @@ use std::sync::{Arc, Weak};
 use swf::CharacterId;
 use weak_table::PtrWeakKeyHashMap;
 
+/// What kind of character a library symbol is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SymbolKind {
+    Bitmap,
+    Button,
+    EditText,
+    Font,
+    Graphic,
+    MorphShape,
+    MovieClip,
+    Sound,
+    Text,
+}
+
+/// A character in a movie's library, as listed by `MovieLibrary::symbols`.
+#[derive(Debug, Clone)]
+pub struct LibrarySymbol {
+    pub id: CharacterId,
+    pub kind: SymbolKind,
+
+    /// The names this character is exported under.
+    pub export_names: Vec<String>,
+
+    /// How many frames of this character can be drawn: the frames of a movie
+    /// clip, the ratios of a morph shape that the movie uses, or 1.
+    pub num_frames: u16,
+}
+
 /// Symbol library for a single given SWF.
 #[derive(Collect)]
 #[collect(no_drop)]
 pub struct MovieLibrary<'gc> {
     characters: HashMap<CharacterId, Character<'gc>>,
     export_characters: HashMap<String, Character<'gc>>,
+    export_names: HashMap<CharacterId, Vec<String>>,
     jpeg_tables: Option<Vec<u8>>,
     device_font: Option<Font<'gc>>,
     fonts: HashMap<FontDescriptor, Font<'gc>>,
@@ -26,6 +55,7 @@ impl<'gc> MovieLibrary<'gc> {
         MovieLibrary {
             characters: HashMap::new(),
             export_characters: HashMap::new(),
+            export_names: HashMap::new(),
             jpeg_tables: None,
             device_font: None,
             fonts: HashMap::new(),
@@ -53,6 +83,10 @@ impl<'gc> MovieLibrary<'gc> {
             match self.export_characters.entry(export_name.to_string()) {
                 Entry::Vacant(e) => {
                     e.insert(character.clone());
+                    self.export_names
+                        .entry(id)
+                        .or_default()
+                        .push(export_name.to_string());
                 }
                 Entry::Occupied(_) => {
                     log::warn!(
@@ -84,6 +118,39 @@ impl<'gc> MovieLibrary<'gc> {
         self.export_characters.get(name)
     }
 
+    /// Lists the characters in this library, ordered by ID.
+    pub fn symbols(&self) -> Vec<LibrarySymbol> {
+        let mut symbols: Vec<_> = self
+            .characters
+            .iter()
+            .map(|(&id, character)| {
+                let (kind, num_frames) = match character {
+                    Character::Bitmap(_) => (SymbolKind::Bitmap, 1),
+                    Character::Button(_) => (SymbolKind::Button, 1),
+                    Character::EditText(_) => (SymbolKind::EditText, 1),
+                    Character::Font(_) => (SymbolKind::Font, 1),
+                    Character::Graphic(_) => (SymbolKind::Graphic, 1),
+                    Character::MorphShape(morph_shape) => {
+                        (SymbolKind::MorphShape, morph_shape.ratios().len() as u16)
+                    }
+                    Character::MovieClip(movie_clip) => {
+                        (SymbolKind::MovieClip, movie_clip.total_frames())
+                    }
+                    Character::Sound(_) => (SymbolKind::Sound, 1),
+                    Character::Text(_) => (SymbolKind::Text, 1),
+                };
+                LibrarySymbol {
+                    id,
+                    kind,
+                    export_names: self.export_names.get(&id).cloned().unwrap_or_default(),
+                    num_frames,
+                }
+            })
+            .collect();
+        symbols.sort_by_key(|symbol| symbol.id);
+        symbols
+    }
+
     /// Instantiates the library item with the given character ID into a display object.
     /// The object must then be post-instantiated before being used.
     pub fn instantiate_by_id(
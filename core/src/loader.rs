@@ -2,7 +2,7 @@
 
 use crate::avm1::activation::{Activation, ActivationIdentifier};
 use crate::avm1::{Avm1, AvmString, Object, TObject, Value};
-use crate::backend::navigator::OwnedFuture;
+use crate::backend::navigator::{ChunkStream, OwnedFuture};
 use crate::context::{ActionQueue, ActionType};
 use crate::display_object::{DisplayObject, MorphShape, TDisplayObject};
 use crate::player::{Player, NEWEST_PLAYER_VERSION};
@@ -12,6 +12,7 @@ use gc_arena::{Collect, CollectionContext, MutationContext};
 use generational_arena::{Arena, Index};
 use std::string::FromUtf8Error;
 use std::sync::{Arc, Mutex, Weak};
+use swf::read::SwfDecompressor;
 use thiserror::Error;
 use url::form_urlencoded;
 
@@ -46,6 +47,9 @@ pub enum Error {
     #[error("Invalid XML encoding")]
     InvalidXmlEncoding(#[from] FromUtf8Error),
 
+    #[error("Security sandbox does not permit loading {0}")]
+    SandboxViolation(String),
+
     #[error("Network error")]
     NetworkError(#[from] std::io::Error),
 
@@ -118,7 +122,7 @@ impl<'gc> LoadManager<'gc> {
     pub fn load_root_movie(
         &mut self,
         player: Weak<Mutex<Player>>,
-        fetch: OwnedFuture<Vec<u8>, Error>,
+        fetch: ChunkStream,
         url: String,
     ) -> OwnedFuture<(), Error> {
         let loader = Loader::RootMovie { self_handle: None };
@@ -137,7 +141,7 @@ impl<'gc> LoadManager<'gc> {
         &mut self,
         player: Weak<Mutex<Player>>,
         target_clip: DisplayObject<'gc>,
-        fetch: OwnedFuture<Vec<u8>, Error>,
+        fetch: ChunkStream,
         url: String,
         target_broadcaster: Option<Object<'gc>>,
     ) -> OwnedFuture<(), Error> {
@@ -352,10 +356,13 @@ impl<'gc> Loader<'gc> {
     }
 
     /// Construct a future for the root movie loader.
+    ///
+    /// The root movie starts playing as soon as its header has arrived, and
+    /// the rest of it is preloaded as it downloads.
     pub fn root_movie_loader(
         &mut self,
         player: Weak<Mutex<Player>>,
-        fetch: OwnedFuture<Vec<u8>, Error>,
+        mut fetch: ChunkStream,
         mut url: String,
     ) -> OwnedFuture<(), Error> {
         let _handle = match self {
@@ -379,16 +386,29 @@ impl<'gc> Loader<'gc> {
                     Ok(())
                 })?;
 
-            let data = (fetch.await)
-                .and_then(|data| Ok((data.len(), SwfMovie::from_data(&data, Some(url.clone()))?)));
-
-            if let Ok((_length, movie)) = data {
-                player.lock().unwrap().set_root_movie(Arc::new(movie));
+            let mut decompressor = SwfDecompressor::new();
+            let mut movie: Option<Arc<SwfMovie>> = None;
+            loop {
+                let chunk = match fetch.next_chunk().await {
+                    Ok(Some(chunk)) => chunk,
+                    Ok(None) => break,
+                    Err(_) => return Err(Error::FetchError(url)),
+                };
+                decompressor
+                    .push(&chunk)
+                    .map_err(|e| Error::InvalidSwf(e.into()))?;
+                stream_root_movie(&player, &decompressor, &mut movie, &url, false);
+            }
 
-                Ok(())
-            } else {
-                Err(Error::FetchError(url))
+            decompressor
+                .finish()
+                .map_err(|e| Error::InvalidSwf(e.into()))?;
+            for diagnostic in decompressor.diagnostics() {
+                log::warn!("{}", diagnostic);
             }
+            stream_root_movie(&player, &decompressor, &mut movie, &url, true);
+
+            Ok(())
         })
     }
 
@@ -402,7 +422,7 @@ impl<'gc> Loader<'gc> {
     pub fn movie_loader(
         &mut self,
         player: Weak<Mutex<Player>>,
-        fetch: OwnedFuture<Vec<u8>, Error>,
+        mut fetch: ChunkStream,
         mut url: String,
     ) -> OwnedFuture<(), Error> {
         let handle = match self {
@@ -451,7 +471,53 @@ impl<'gc> Loader<'gc> {
                     Ok(())
                 })?;
 
-            let data = (fetch.await)
+            let mut data = vec![];
+            let fetched = loop {
+                match fetch.next_chunk().await {
+                    Ok(Some(chunk)) => data.extend_from_slice(&chunk),
+                    Ok(None) => break Ok(data),
+                    Err(e) => break Err(e),
+                }
+
+                // The last progress event is sent once the movie has loaded.
+                let loaded = data.len();
+                let total = fetch.total_length().unwrap_or(0);
+                if loaded < total {
+                    player.lock().expect("Could not lock player!!").update(
+                        |uc| -> Result<(), Error> {
+                            let (clip, broadcaster) = match uc.load_manager.get_loader(handle) {
+                                Some(Loader::Movie {
+                                    target_clip,
+                                    target_broadcaster,
+                                    ..
+                                }) => (*target_clip, *target_broadcaster),
+                                None => return Err(Error::Cancelled),
+                                _ => unreachable!(),
+                            };
+
+                            if let Some(broadcaster) = broadcaster {
+                                Avm1::run_stack_frame_for_method(
+                                    clip,
+                                    broadcaster,
+                                    NEWEST_PLAYER_VERSION,
+                                    uc,
+                                    "broadcastMessage",
+                                    &[
+                                        "onLoadProgress".into(),
+                                        Value::Object(broadcaster),
+                                        loaded.into(),
+                                        total.into(),
+                                    ],
+                                );
+                            }
+
+                            Ok(())
+                        },
+                    )?;
+                }
+            };
+
+            let data = fetched
                 .and_then(|data| Ok((data.len(), SwfMovie::from_data(&data, Some(url.clone()))?)));
             if let Ok((length, movie)) = data {
                 let movie = Arc::new(movie);
@@ -817,3 +883,40 @@ impl<'gc> Loader<'gc> {
         })
     }
 }
+
+/// Hand the part of the root movie that has been decompressed so far to
+/// the player.
+///
+/// The player is given the movie once its header has arrived, and after
+/// that preloads each new part of it.
+fn stream_root_movie(
+    player: &Mutex<Player>,
+    decompressor: &SwfDecompressor,
+    movie: &mut Option<Arc<SwfMovie>>,
+    url: &str,
+    finished: bool,
+) {
+    let mut player = player.lock().expect("Could not lock player!!");
+    match movie {
+        Some(movie) => {
+            let loaded = movie.bytes_loaded() - movie.header_length();
+            movie.append_data(&decompressor.data()[loaded..]);
+            if finished {
+                movie.finish_loading();
+            }
+            player.preload();
+        }
+        None => {
+            if let Some(new_movie) =
+                SwfMovie::from_partial_data(decompressor, Some(url.to_string()), finished)
+            {
+                let new_movie = Arc::new(new_movie);
+                if finished {
+                    new_movie.finish_loading();
+                }
+                *movie = Some(new_movie.clone());
+                player.set_root_movie(new_movie);
+            }
+        }
+    }
+}
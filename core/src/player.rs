@@ -2,26 +2,47 @@ use crate::avm1::activation::{Activation, ActivationIdentifier};
 use crate::avm1::debug::VariableDumper;
 use crate::avm1::globals::system::SystemProperties;
 use crate::avm1::object::Object;
-use crate::avm1::{Avm1, AvmString, TObject, Timers, Value};
+use crate::avm1::{
+    Avm1, AvmString, ExternalCallbacks, LocalConnections, TObject, Timers, Value, XmlSockets,
+};
 use crate::avm2::Avm2;
+use crate::backend::external_interface::{
+    ExternalInterfaceProvider, ExternalValue, NullExternalInterfaceProvider,
+};
 use crate::backend::input::{InputBackend, MouseCursor};
+use crate::backend::local_connection::{LocalConnectionBackend, NullLocalConnectionBackend};
+use crate::backend::log::{ForwardingLogBackend, LogBackend};
 use crate::backend::navigator::{NavigatorBackend, RequestOptions};
+use crate::backend::socket::{NullSocketBackend, SocketBackend};
 use crate::backend::storage::StorageBackend;
-use crate::backend::{audio::AudioBackend, render::Letterbox, render::RenderBackend};
+use crate::backend::{
+    audio::AudioBackend, render::Letterbox, render::RenderBackend, render::ShapeHandle,
+    render::StageQuality,
+};
 use crate::context::{ActionQueue, ActionType, RenderContext, UpdateContext};
+use crate::context_menu::{
+    BuiltInItem, BuiltInState, ContextMenuItem, ContextMenuState, DrawnContextMenu,
+    DrawnMenuResponse,
+};
+use crate::debugger::Debugger;
 use crate::display_object::{EditText, MorphShape, MovieClip};
-use crate::events::{ButtonKeyCode, ClipEvent, ClipEventResult, KeyCode, PlayerEvent};
-use crate::library::Library;
+use crate::events::{
+    ButtonKeyCode, ClipEvent, ClipEventResult, KeyCode, MouseButton, MouseWheelDelta, PlayerEvent,
+};
+use crate::font::{EvalParameters, Font};
+use crate::library::{Library, LibrarySymbol};
 use crate::loader::LoadManager;
 use crate::prelude::*;
+use crate::security::Security;
 use crate::tag_utils::SwfMovie;
 use crate::transform::TransformStack;
-use enumset::EnumSet;
+use enumset::{EnumSet, EnumSetType};
 use gc_arena::{make_arena, ArenaParameters, Collect, GcCell};
 use log::info;
 use rand::{rngs::SmallRng, SeedableRng};
 use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
+use std::fmt;
 use std::ops::DerefMut;
 use std::sync::{Arc, Mutex, Weak};
 
@@ -31,6 +52,92 @@ pub static DEVICE_FONT_TAG: &[u8] = include_bytes!("../assets/noto-sans-definefo
 /// `player_version`.
 pub const NEWEST_PLAYER_VERSION: u8 = 32;
 
+/// The most the stage can be zoomed in with the context menu.
+const MAX_ZOOM: f32 = 20.0;
+
+/// How the movie is scaled to fit the viewport, as set by `Stage.scaleMode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StageScaleMode {
+    /// Scale the whole movie to fit, keeping its aspect ratio.
+    ShowAll,
+
+    /// Stretch the movie to fill the viewport.
+    ExactFit,
+
+    /// Scale the movie to fill the viewport, keeping its aspect ratio and
+    /// cropping whatever doesn't fit.
+    NoBorder,
+
+    /// Show the movie at its original size.
+    NoScale,
+}
+
+impl StageScaleMode {
+    /// Parse a scale mode name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "showall" => Some(StageScaleMode::ShowAll),
+            "exactfit" => Some(StageScaleMode::ExactFit),
+            "noborder" => Some(StageScaleMode::NoBorder),
+            "noscale" => Some(StageScaleMode::NoScale),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for StageScaleMode {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(match self {
+            StageScaleMode::ShowAll => "showAll",
+            StageScaleMode::ExactFit => "exactFit",
+            StageScaleMode::NoBorder => "noBorder",
+            StageScaleMode::NoScale => "noScale",
+        })
+    }
+}
+
+/// The edges of the viewport that the movie is aligned to, as set by
+/// `Stage.align`. The movie is centered on any axis without an edge.
+#[derive(Debug, EnumSetType)]
+pub enum StageAlign {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl StageAlign {
+    /// Parse an alignment string such as `"TL"`, ignoring case and any
+    /// unknown characters.
+    pub fn from_name(name: &str) -> EnumSet<Self> {
+        let mut align = EnumSet::empty();
+        for c in name.chars() {
+            match c.to_ascii_uppercase() {
+                'T' => align.insert(StageAlign::Top),
+                'B' => align.insert(StageAlign::Bottom),
+                'L' => align.insert(StageAlign::Left),
+                'R' => align.insert(StageAlign::Right),
+                _ => false,
+            };
+        }
+        align
+    }
+
+    /// The alignment string for `align`, such as `"TL"`.
+    pub fn to_name(align: EnumSet<Self>) -> String {
+        [
+            (StageAlign::Top, 'T'),
+            (StageAlign::Bottom, 'B'),
+            (StageAlign::Left, 'L'),
+            (StageAlign::Right, 'R'),
+        ]
+        .iter()
+        .filter(|(edge, _)| align.contains(*edge))
+        .map(|(_, c)| *c)
+        .collect()
+    }
+}
+
 #[derive(Collect)]
 #[collect(no_drop)]
 struct GcRoot<'gc>(GcCell<'gc, GcRootData<'gc>>);
@@ -71,6 +178,18 @@ struct GcRootData<'gc> {
 
     /// Timed callbacks created with `setInterval`/`setTimeout`.
     timers: Timers<'gc>,
+
+    /// Listening `LocalConnection` objects and their undelivered messages.
+    local_connections: LocalConnections<'gc>,
+
+    /// Open `XMLSocket` connections.
+    xml_sockets: XmlSockets<'gc>,
+
+    /// Callbacks exposed to the host with `ExternalInterface.addCallback`.
+    external_callbacks: ExternalCallbacks<'gc>,
+
+    /// The context menu that is currently shown by the host.
+    context_menu: ContextMenuState<'gc>,
 }
 
 impl<'gc> GcRootData<'gc> {
@@ -90,6 +209,10 @@ impl<'gc> GcRootData<'gc> {
         &mut HashMap<String, Object<'gc>>,
         &mut Vec<EditText<'gc>>,
         &mut Timers<'gc>,
+        &mut LocalConnections<'gc>,
+        &mut XmlSockets<'gc>,
+        &mut ExternalCallbacks<'gc>,
+        &mut ContextMenuState<'gc>,
     ) {
         (
             &mut self.levels,
@@ -102,6 +225,10 @@ impl<'gc> GcRootData<'gc> {
             &mut self.shared_objects,
             &mut self.unbound_text_fields,
             &mut self.timers,
+            &mut self.local_connections,
+            &mut self.xml_sockets,
+            &mut self.external_callbacks,
+            &mut self.context_menu,
         )
     }
 }
@@ -114,6 +241,15 @@ type Navigator = Box<dyn NavigatorBackend>;
 type Renderer = Box<dyn RenderBackend>;
 type Input = Box<dyn InputBackend>;
 type Storage = Box<dyn StorageBackend>;
+type LocalConnection = Box<dyn LocalConnectionBackend>;
+type Socket = Box<dyn SocketBackend>;
+type ExternalInterface = Box<dyn ExternalInterfaceProvider>;
+type Log = Box<dyn LogBackend>;
+
+/// A host callback for the FSCommands sent by the movie.
+///
+/// It is called with the command and its arguments.
+pub type FsCommandHandler = Box<dyn FnMut(&str, &str)>;
 
 pub struct Player {
     /// The version of the player we're emulating.
@@ -143,6 +279,63 @@ pub struct Player {
 
     storage: Storage,
 
+    local_connection: LocalConnection,
+
+    socket: Socket,
+
+    external_interface: ExternalInterface,
+
+    log: Log,
+
+    /// FSCommands sent by the movie that haven't been handled yet.
+    fs_commands: Vec<(String, String)>,
+
+    fs_command_handler: Option<FsCommandHandler>,
+
+    /// The debugger attached to the movie's ActionScript, if any.
+    debugger: Option<Debugger>,
+
+    /// How the movie is scaled to fit the viewport, as set by
+    /// `Stage.scaleMode` or the `allowscale` FSCommand.
+    scale_mode: StageScaleMode,
+
+    /// How the movie is aligned in the viewport, as set by `Stage.align`.
+    stage_align: EnumSet<StageAlign>,
+
+    /// Whether the full context menu is shown, as set by the `showmenu`
+    /// FSCommand or `Stage.showMenu`.
+    show_menu: bool,
+
+    /// How much the stage is zoomed in with the context menu.
+    zoom: f32,
+
+    /// The point of the stage that is centered in the viewport when zoomed in.
+    zoom_focus: (Twips, Twips),
+
+    quality: StageQuality,
+
+    /// The context menu drawn over the movie, for hosts without native menus.
+    drawn_context_menu: Option<DrawnContextMenu>,
+
+    /// A white square of one pixel, which is scaled and tinted to draw the
+    /// background of the drawn context menu.
+    context_menu_rect: Option<ShapeHandle>,
+
+    /// Whether all key presses should go to the movie, as set by the
+    /// `trapallkeys` FSCommand.
+    trap_all_keys: bool,
+
+    /// Whether the movie wants to be shown fullscreen, as set by the
+    /// `fullscreen` FSCommand.
+    is_fullscreen: bool,
+
+    /// Whether the movie asked to quit with the `quit` FSCommand.
+    quit_requested: bool,
+
+    /// Whether the player is a standalone projector, which handles the
+    /// projector FSCommands such as `fullscreen` and `quit` itself.
+    is_projector: bool,
+
     rng: SmallRng,
 
     gc_arena: GcArena,
@@ -165,6 +358,8 @@ pub struct Player {
 
     system: SystemProperties,
 
+    security: Security,
+
     /// The current instance ID. Used to generate default `instanceN` names.
     instance_counter: i32,
 
@@ -180,6 +375,11 @@ pub struct Player {
 }
 
 impl Player {
+    /// Create a player from the backends that every host provides.
+    ///
+    /// The player starts out without local connections, sockets, an external
+    /// interface or a log; hosts that support them install their backends with
+    /// the `set_*_backend` methods.
     pub fn new(
         renderer: Renderer,
         audio: Audio,
@@ -231,6 +431,10 @@ impl Player {
                         shared_objects: HashMap::new(),
                         unbound_text_fields: Vec::new(),
                         timers: Timers::new(),
+                        local_connections: LocalConnections::new(),
+                        xml_sockets: XmlSockets::new(),
+                        external_callbacks: ExternalCallbacks::new(),
+                        context_menu: ContextMenuState::new(),
                     },
                 ))
             }),
@@ -254,9 +458,29 @@ impl Player {
             input,
             self_reference: None,
             system: SystemProperties::default(),
+            security: Security::new(),
             instance_counter: 0,
             time_til_next_timer: None,
             storage,
+            local_connection: Box::new(NullLocalConnectionBackend::new()),
+            socket: Box::new(NullSocketBackend::new()),
+            external_interface: Box::new(NullExternalInterfaceProvider::new()),
+            log: Box::new(ForwardingLogBackend::new()),
+            fs_commands: Vec::new(),
+            fs_command_handler: None,
+            debugger: None,
+            scale_mode: StageScaleMode::ShowAll,
+            stage_align: EnumSet::empty(),
+            show_menu: true,
+            zoom: 1.0,
+            zoom_focus: (Twips::new(0), Twips::new(0)),
+            quality: StageQuality::High,
+            drawn_context_menu: None,
+            context_menu_rect: None,
+            trap_all_keys: false,
+            is_fullscreen: false,
+            quit_requested: false,
+            is_projector: false,
         };
 
         player.mutate_with_update_context(|context| Avm2::load_player_globals(context))?;
@@ -277,7 +501,9 @@ impl Player {
     /// off.
     pub fn fetch_root_movie(&mut self, movie_url: &str) {
         self.mutate_with_update_context(|context| {
-            let fetch = context.navigator.fetch(movie_url, RequestOptions::get());
+            let fetch = context
+                .navigator
+                .fetch_stream(movie_url, RequestOptions::get());
             let process = context.load_manager.load_root_movie(
                 context.player.clone().unwrap(),
                 fetch,
@@ -293,6 +519,9 @@ impl Player {
     /// This should only be called once, as it makes no attempt at removing
     /// previous stage contents. If you need to load a new root movie, you
     /// should destroy and recreate the player instance.
+    ///
+    /// The movie may still be downloading, in which case `preload` should be
+    /// called as more of it arrives.
     pub fn set_root_movie(&mut self, movie: Arc<SwfMovie>) {
         info!(
             "Loaded SWF version {}, with a resolution of {}x{}",
@@ -354,6 +583,10 @@ impl Player {
     }
 
     pub fn tick(&mut self, dt: f64) {
+        if let Some(debugger) = &mut self.debugger {
+            debugger.poll();
+        }
+
         // Don't run until preloading is complete.
         // TODO: Eventually we want to stream content similar to the Flash player.
         if !self.audio.is_loading_complete() {
@@ -416,6 +649,93 @@ impl Player {
         self.is_playing = v;
     }
 
+    /// Attach a debugger to the movie's ActionScript, or detach it with `None`.
+    pub fn set_debugger(&mut self, debugger: Option<Debugger>) {
+        self.debugger = debugger;
+    }
+
+    /// Set the callback that receives every FSCommand sent by the movie.
+    pub fn set_fs_command_handler(&mut self, handler: FsCommandHandler) {
+        self.fs_command_handler = Some(handler);
+    }
+
+    /// Make the player act as a standalone projector, which handles the
+    /// projector FSCommands itself instead of only passing them to the host.
+    pub fn set_is_projector(&mut self, is_projector: bool) {
+        self.is_projector = is_projector;
+    }
+
+    pub fn set_local_connection_backend(&mut self, local_connection: LocalConnection) {
+        self.local_connection = local_connection;
+    }
+
+    pub fn set_socket_backend(&mut self, socket: Socket) {
+        self.socket = socket;
+    }
+
+    pub fn set_external_interface_backend(&mut self, external_interface: ExternalInterface) {
+        self.external_interface = external_interface;
+    }
+
+    pub fn set_log_backend(&mut self, log: Log) {
+        self.log = log;
+    }
+
+    /// Set whether local movies are trusted, and so may load data from both
+    /// the filesystem and the network.
+    ///
+    /// Untrusted local movies are restricted to one or the other, depending on
+    /// the `UseNetwork` flag of their `FileAttributes` tag. Local movies are
+    /// untrusted until this is called.
+    pub fn set_trust_local_content(&mut self, trust_local_content: bool) {
+        self.security.set_trust_local_content(trust_local_content);
+    }
+
+    /// Whether the movie is scaled to fit the viewport.
+    pub fn allow_scale(&self) -> bool {
+        self.scale_mode != StageScaleMode::NoScale
+    }
+
+    pub fn scale_mode(&self) -> StageScaleMode {
+        self.scale_mode
+    }
+
+    pub fn stage_align(&self) -> EnumSet<StageAlign> {
+        self.stage_align
+    }
+
+    /// Whether the full context menu should be shown.
+    pub fn show_menu(&self) -> bool {
+        self.show_menu
+    }
+
+    /// The rendering quality chosen in the context menu.
+    pub fn quality(&self) -> StageQuality {
+        self.quality
+    }
+
+    /// Whether the host should pass every key press to the movie, including
+    /// those it would otherwise handle itself.
+    pub fn trap_all_keys(&self) -> bool {
+        self.trap_all_keys
+    }
+
+    /// Whether the movie wants to be shown fullscreen.
+    pub fn is_fullscreen(&self) -> bool {
+        self.is_fullscreen
+    }
+
+    /// Set whether the movie is shown fullscreen, such as when the user
+    /// leaves fullscreen mode.
+    pub fn set_is_fullscreen(&mut self, is_fullscreen: bool) {
+        self.is_fullscreen = is_fullscreen;
+    }
+
+    /// Whether the movie asked the host to quit.
+    pub fn quit_requested(&self) -> bool {
+        self.quit_requested
+    }
+
     pub fn needs_render(&self) -> bool {
         self.needs_render
     }
@@ -433,12 +753,35 @@ impl Player {
     }
 
     pub fn set_viewport_dimensions(&mut self, width: u32, height: u32) {
+        let is_resized = (width, height) != (self.viewport_width, self.viewport_height);
         self.viewport_width = width;
         self.viewport_height = height;
         self.build_matrices();
+
+        // The stage follows the viewport size in `noScale` mode, so the movie
+        // is told to lay itself out again.
+        if is_resized && self.scale_mode == StageScaleMode::NoScale {
+            self.update(|context| {
+                context.action_queue.queue_actions(
+                    *context.levels.get(&0).expect("root level"),
+                    ActionType::NotifyListeners {
+                        listener: "Stage",
+                        method: "onResize",
+                        args: vec![],
+                    },
+                    false,
+                );
+            });
+        }
     }
 
     pub fn handle_event(&mut self, event: PlayerEvent) {
+        // The drawn context menu takes all input while it's open.
+        if self.drawn_context_menu.is_some() {
+            self.handle_drawn_context_menu_event(event);
+            return;
+        }
+
         let mut needs_render = self.needs_render;
 
         if cfg!(feature = "avm_debug") {
@@ -505,8 +848,8 @@ impl Player {
 
         // Update mouse position from mouse events.
         if let PlayerEvent::MouseMove { x, y }
-        | PlayerEvent::MouseDown { x, y }
-        | PlayerEvent::MouseUp { x, y } = event
+        | PlayerEvent::MouseDown { x, y, .. }
+        | PlayerEvent::MouseUp { x, y, .. } = event
         {
             self.mouse_pos =
                 self.inverse_view_matrix * (Twips::from_pixels(x), Twips::from_pixels(y));
@@ -565,6 +908,13 @@ impl Player {
             _ => (None, None),
         };
 
+        // The right and middle buttons also have events of their own.
+        let button_clip_event = match event {
+            PlayerEvent::MouseDown { button, .. } => button.clip_events().map(|(down, _)| down),
+            PlayerEvent::MouseUp { button, .. } => button.clip_events().map(|(_, up)| up),
+            _ => None,
+        };
+
         if clip_event.is_some() || listener.is_some() {
             self.mutate_with_update_context(|context| {
                 let levels: Vec<DisplayObject<'_>> = context.levels.values().copied().collect();
@@ -573,6 +923,9 @@ impl Player {
                     if let Some(clip_event) = clip_event {
                         level.handle_clip_event(context, clip_event);
                     }
+                    if let Some(button_clip_event) = button_clip_event {
+                        level.handle_clip_event(context, button_clip_event);
+                    }
                 }
 
                 if let Some((listener_type, event_name)) = listener {
@@ -590,6 +943,7 @@ impl Player {
         }
 
         let mut is_mouse_down = self.is_mouse_down;
+        let mouse_pos = self.mouse_pos;
         self.mutate_with_update_context(|context| {
             if let Some(node) = context.mouse_hovered_object {
                 if node.removed() {
@@ -598,7 +952,10 @@ impl Player {
             }
 
             match event {
-                PlayerEvent::MouseDown { .. } => {
+                PlayerEvent::MouseDown {
+                    button: MouseButton::Left,
+                    ..
+                } => {
                     is_mouse_down = true;
                     needs_render = true;
                     if let Some(node) = context.mouse_hovered_object {
@@ -606,7 +963,10 @@ impl Player {
                     }
                 }
 
-                PlayerEvent::MouseUp { .. } => {
+                PlayerEvent::MouseUp {
+                    button: MouseButton::Left,
+                    ..
+                } => {
                     is_mouse_down = false;
                     needs_render = true;
                     if let Some(node) = context.mouse_hovered_object {
@@ -614,6 +974,11 @@ impl Player {
                     }
                 }
 
+                PlayerEvent::MouseWheel { delta } => {
+                    Self::scroll_wheel(context, mouse_pos, delta);
+                    needs_render = true;
+                }
+
                 _ => (),
             }
 
@@ -625,6 +990,92 @@ impl Player {
         }
     }
 
+    /// Scroll the text field under the mouse, and notify `Mouse` listeners
+    /// that the mouse wheel moved.
+    fn scroll_wheel<'gc>(
+        context: &mut UpdateContext<'_, 'gc, '_>,
+        mouse_pos: (Twips, Twips),
+        delta: MouseWheelDelta,
+    ) {
+        let lines = delta.lines();
+        let text_field = context
+            .levels
+            .values()
+            .rev()
+            .find_map(|level| Self::text_field_at(*level, mouse_pos));
+
+        let scroll_target = if let Some(text_field) = text_field {
+            if text_field.is_mouse_wheel_enabled() {
+                let scroll = text_field.scroll() as f64 - lines.round();
+                text_field.set_scroll(scroll.max(1.0) as usize, context.gc_context);
+            }
+            text_field.object()
+        } else {
+            context
+                .mouse_hovered_object
+                .map(|object| object.object())
+                .unwrap_or(Value::Undefined)
+        };
+
+        context.action_queue.queue_actions(
+            *context.levels.get(&0).expect("root level"),
+            ActionType::NotifyListeners {
+                listener: "Mouse",
+                method: "onMouseWheel",
+                args: vec![lines.into(), scroll_target],
+            },
+            false,
+        );
+    }
+
+    /// Find the topmost visible display object at `point` in the given
+    /// display object or its children.
+    fn object_at<'gc>(
+        object: DisplayObject<'gc>,
+        point: (Twips, Twips),
+    ) -> Option<DisplayObject<'gc>> {
+        if !object.visible() {
+            return None;
+        }
+
+        let child = object
+            .children()
+            .filter_map(|child| Self::object_at(child, point))
+            .last();
+        if child.is_some() {
+            return child;
+        }
+
+        if object.world_bounds().contains(point) {
+            Some(object)
+        } else {
+            None
+        }
+    }
+
+    /// Find the topmost visible text field at `point` in the given display
+    /// object or its children.
+    fn text_field_at<'gc>(
+        object: DisplayObject<'gc>,
+        point: (Twips, Twips),
+    ) -> Option<EditText<'gc>> {
+        if !object.visible() {
+            return None;
+        }
+
+        if let Some(text_field) = object.as_edit_text() {
+            if object.world_bounds().contains(point) {
+                return Some(text_field);
+            }
+            return None;
+        }
+
+        object
+            .children()
+            .filter_map(|child| Self::text_field_at(child, point))
+            .last()
+    }
+
     /// Update dragged object, if any.
     fn update_drag(&mut self) {
         let mouse_pos = self.mouse_pos;
@@ -711,9 +1162,10 @@ impl Player {
 
     /// Preload the first movie in the player.
     ///
-    /// This should only be called once. Further movie loads should preload the
-    /// specific `MovieClip` referenced.
-    fn preload(&mut self) {
+    /// While the movie is downloading, this should be called again as more of
+    /// it arrives. Further movie loads should preload the specific `MovieClip`
+    /// referenced.
+    pub fn preload(&mut self) {
         self.mutate_with_update_context(|context| {
             let mut morph_shapes = fnv::FnvHashMap::default();
             let root = *context.levels.get(&0).expect("root level");
@@ -743,6 +1195,9 @@ impl Player {
             for mut level in levels {
                 level.run_frame(update_context);
             }
+
+            LocalConnections::update_connections(update_context);
+            XmlSockets::update_sockets(update_context);
         });
         self.needs_render = true;
     }
@@ -781,10 +1236,140 @@ impl Player {
         transform_stack.pop();
 
         self.renderer.draw_letterbox(self.letterbox);
+
+        let overlay = self.log.overlay();
+        if !overlay.is_empty() {
+            let (renderer, swf) = (&mut self.renderer, &self.swf);
+            self.gc_arena.mutate(|_gc_context, gc_root| {
+                let root_data = gc_root.0.read();
+                let device_font = root_data
+                    .library
+                    .library_for_movie(swf.clone())
+                    .and_then(|library| library.device_font());
+                if let Some(font) = device_font {
+                    render_overlay(renderer.deref_mut(), font, overlay);
+                }
+            });
+        }
+
+        if let (Some(menu), Some(rect)) = (&self.drawn_context_menu, self.context_menu_rect) {
+            let (renderer, swf) = (&mut self.renderer, &self.swf);
+            self.gc_arena.mutate(|_gc_context, gc_root| {
+                let root_data = gc_root.0.read();
+                let device_font = root_data
+                    .library
+                    .library_for_movie(swf.clone())
+                    .and_then(|library| library.device_font());
+                menu.render(renderer.deref_mut(), device_font, rect);
+            });
+        }
         self.renderer.end_frame();
         self.needs_render = false;
     }
 
+    /// The current frame of the root movie's timeline, or `None` if no movie
+    /// has been loaded.
+    pub fn root_frame(&mut self) -> Option<u16> {
+        self.gc_arena.mutate(|_gc_context, gc_root| {
+            let root_data = gc_root.0.read();
+            let root = root_data.levels.get(&0)?.as_movie_clip()?;
+            Some(root.current_frame())
+        })
+    }
+
+    /// The frame number of a label on the root movie's timeline.
+    pub fn root_frame_label(&mut self, label: &str) -> Option<u16> {
+        self.gc_arena.mutate(|_gc_context, gc_root| {
+            let root_data = gc_root.0.read();
+            let root = root_data.levels.get(&0)?.as_movie_clip()?;
+            root.frame_label_to_number(label)
+        })
+    }
+
+    /// Lists the characters in the root movie's library.
+    pub fn library_symbols(&mut self) -> Vec<LibrarySymbol> {
+        let swf = self.swf.clone();
+        self.gc_arena.mutate(|_gc_context, gc_root| {
+            let root_data = gc_root.0.read();
+            root_data
+                .library
+                .library_for_movie(swf)
+                .map(|library| library.symbols())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Draws a character from the root movie's library on its own, resizing
+    /// the viewport to fit it at `scale`.
+    ///
+    /// `frame` picks the frame of a movie clip or the ratio of a morph shape,
+    /// counting from 0; movie clips reach it without running any scripts.
+    /// Fonts are drawn as a sheet of their glyphs. Returns the size of the
+    /// image in pixels, or `None` if the character can't be drawn.
+    pub fn render_symbol(&mut self, id: CharacterId, frame: u16, scale: f32) -> Option<(u32, u32)> {
+        self.mutate_with_update_context(|context| {
+            let library = context.library.library_for_movie_mut(context.swf.clone());
+            if let Some(font) = library.get_font(id) {
+                let (width, height) = font.glyph_sheet_size(scale)?;
+                context.renderer.set_viewport_dimensions(width, height);
+                context.renderer.begin_frame(TRANSPARENT);
+                font.render_glyph_sheet(context.renderer, scale);
+                context.renderer.end_frame();
+                return Some((width, height));
+            }
+
+            let mut display_object = library.instantiate_by_id(id, context.gc_context).ok()?;
+            display_object.post_instantiation(context, display_object, None, false);
+            if let Some(movie_clip) = display_object.as_movie_clip() {
+                movie_clip.goto_frame(context, frame.saturating_add(1), true);
+            }
+            if let Some(mut morph_shape) = display_object.as_morph_shape() {
+                let ratio = *morph_shape.ratios().get(usize::from(frame))?;
+                morph_shape.set_ratio(context.gc_context, ratio);
+            }
+
+            let bounds = display_object.bounds();
+            if !bounds.valid {
+                return None;
+            }
+            let to_pixels = |twips: Twips| (twips.to_pixels() as f32 * scale).ceil().max(1.0);
+            let width = to_pixels(bounds.x_max - bounds.x_min) as u32;
+            let height = to_pixels(bounds.y_max - bounds.y_min) as u32;
+            let view_bounds = BoundingBox {
+                x_min: Twips::new(0),
+                y_min: Twips::new(0),
+                x_max: Twips::from_pixels(width.into()),
+                y_max: Twips::from_pixels(height.into()),
+                valid: true,
+            };
+
+            context.renderer.set_viewport_dimensions(width, height);
+            context.renderer.begin_frame(TRANSPARENT);
+            let mut transform_stack = TransformStack::new();
+            transform_stack.push(&crate::transform::Transform {
+                matrix: Matrix {
+                    a: scale,
+                    d: scale,
+                    tx: Twips::new((-bounds.x_min.get() as f32 * scale).round() as i32),
+                    ty: Twips::new((-bounds.y_min.get() as f32 * scale).round() as i32),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            let mut render_context = RenderContext {
+                renderer: &mut *context.renderer,
+                library: &*context.library,
+                transform_stack: &mut transform_stack,
+                view_bounds,
+                clip_depth_stack: vec![],
+            };
+            display_object.render(&mut render_context);
+            context.renderer.end_frame();
+
+            Some((width, height))
+        })
+    }
+
     pub fn audio(&self) -> &Audio {
         &self.audio
     }
@@ -814,6 +1399,18 @@ impl Player {
         self.input.deref_mut()
     }
 
+    pub fn storage_mut(&mut self) -> &mut dyn StorageBackend {
+        self.storage.deref_mut()
+    }
+
+    pub fn log(&self) -> &dyn LogBackend {
+        &*self.log
+    }
+
+    pub fn log_mut(&mut self) -> &mut dyn LogBackend {
+        self.log.deref_mut()
+    }
+
     fn run_actions<'gc>(context: &mut UpdateContext<'_, 'gc, '_>) {
         // Note that actions can queue further actions, so a while loop is necessary here.
         while let Some(actions) = context.action_queue.pop_action() {
@@ -931,20 +1528,73 @@ impl Player {
         let (movie_width, movie_height) = (self.movie_width as f32, self.movie_height as f32);
         let (viewport_width, viewport_height) =
             (self.viewport_width as f32, self.viewport_height as f32);
-        let movie_aspect = movie_width / movie_height;
-        let viewport_aspect = viewport_width / viewport_height;
-        let (scale, margin_width, margin_height) = if viewport_aspect > movie_aspect {
-            let scale = viewport_height / movie_height;
-            (scale, (viewport_width - movie_width * scale) / 2.0, 0.0)
-        } else {
-            let scale = viewport_width / movie_width;
-            (scale, 0.0, (viewport_height - movie_height * scale) / 2.0)
+        let width_scale = viewport_width / movie_width;
+        let height_scale = viewport_height / movie_height;
+        let (mut scale_x, mut scale_y) = match self.scale_mode {
+            StageScaleMode::ShowAll => {
+                let scale = width_scale.min(height_scale);
+                (scale, scale)
+            }
+            StageScaleMode::NoBorder => {
+                let scale = width_scale.max(height_scale);
+                (scale, scale)
+            }
+            StageScaleMode::ExactFit => (width_scale, height_scale),
+            StageScaleMode::NoScale => (1.0, 1.0),
         };
+
+        // Place the movie within the space left over on each axis, according to
+        // the stage alignment. This space is negative when the movie is cropped.
+        let align = self.stage_align;
+        let align_margin = |extra: f32, start: StageAlign, end: StageAlign| {
+            if align.contains(start) {
+                0.0
+            } else if align.contains(end) {
+                extra
+            } else {
+                extra / 2.0
+            }
+        };
+        let mut margin_width = align_margin(
+            viewport_width - movie_width * scale_x,
+            StageAlign::Left,
+            StageAlign::Right,
+        );
+        let mut margin_height = align_margin(
+            viewport_height - movie_height * scale_y,
+            StageAlign::Top,
+            StageAlign::Bottom,
+        );
+
+        if self.zoom > 1.0 {
+            // Center the zoom focus in the viewport, without scrolling past the
+            // edges of the stage.
+            scale_x *= self.zoom;
+            scale_y *= self.zoom;
+            let zoomed_margin = |viewport: f32, movie: f32, focus: f32| {
+                let min_margin = viewport - movie;
+                if min_margin >= 0.0 {
+                    min_margin / 2.0
+                } else {
+                    (viewport / 2.0 - focus).max(min_margin).min(0.0)
+                }
+            };
+            margin_width = zoomed_margin(
+                viewport_width,
+                movie_width * scale_x,
+                self.zoom_focus.0.to_pixels() as f32 * scale_x,
+            );
+            margin_height = zoomed_margin(
+                viewport_height,
+                movie_height * scale_y,
+                self.zoom_focus.1.to_pixels() as f32 * scale_y,
+            );
+        }
         self.view_matrix = Matrix {
-            a: scale,
+            a: scale_x,
             b: 0.0,
             c: 0.0,
-            d: scale,
+            d: scale_y,
             tx: Twips::from_pixels(margin_width.into()),
             ty: Twips::from_pixels(margin_height.into()),
         };
@@ -952,11 +1602,16 @@ impl Player {
         self.inverse_view_matrix.invert();
 
         // Calculate letterbox dimensions.
+        // The letterbox is only drawn around a centered movie in `showAll` mode.
         // TODO: Letterbox should be an option; the original Flash Player defaults to showing content
         // in the extra margins.
-        self.letterbox = if margin_width > 0.0 {
+        let is_centered =
+            |start: StageAlign, end: StageAlign| !align.contains(start) && !align.contains(end);
+        self.letterbox = if self.scale_mode != StageScaleMode::ShowAll {
+            Letterbox::None
+        } else if margin_width > 0.0 && is_centered(StageAlign::Left, StageAlign::Right) {
             Letterbox::Pillarbox(margin_width)
-        } else if margin_height > 0.0 {
+        } else if margin_height > 0.0 && is_centered(StageAlign::Top, StageAlign::Bottom) {
             Letterbox::Letterbox(margin_height)
         } else {
             Letterbox::None
@@ -969,6 +1624,8 @@ impl Player {
     where
         F: for<'a, 'gc> FnOnce(&mut UpdateContext<'a, 'gc, '_>) -> R,
     {
+        let layout = (self.scale_mode, self.stage_align);
+
         // We have to do this piecewise borrowing of fields before the closure to avoid
         // completely borrowing `self`.
         let (
@@ -983,11 +1640,23 @@ impl Player {
             mouse_position,
             stage_width,
             stage_height,
+            viewport_width,
+            viewport_height,
             player,
             system_properties,
+            security,
             instance_counter,
             storage,
+            local_connection,
+            socket,
+            external_interface,
+            log,
+            fs_commands,
             needs_render,
+            show_menu,
+            scale_mode,
+            stage_align,
+            debugger,
         ) = (
             self.player_version,
             &self.swf,
@@ -1000,14 +1669,26 @@ impl Player {
             &self.mouse_pos,
             Twips::from_pixels(self.movie_width.into()),
             Twips::from_pixels(self.movie_height.into()),
+            Twips::from_pixels(self.viewport_width.into()),
+            Twips::from_pixels(self.viewport_height.into()),
             self.self_reference.clone(),
             &mut self.system,
+            &mut self.security,
             &mut self.instance_counter,
             self.storage.deref_mut(),
+            self.local_connection.deref_mut(),
+            self.socket.deref_mut(),
+            self.external_interface.deref_mut(),
+            self.log.deref_mut(),
+            &mut self.fs_commands,
             &mut self.needs_render,
+            &mut self.show_menu,
+            &mut self.scale_mode,
+            &mut self.stage_align,
+            &mut self.debugger,
         );
 
-        self.gc_arena.mutate(|gc_context, gc_root| {
+        let rval = self.gc_arena.mutate(|gc_context, gc_root| {
             let mut root_data = gc_root.0.write(gc_context);
             let mouse_hovered_object = root_data.mouse_hovered_object;
             let (
@@ -1021,6 +1702,10 @@ impl Player {
                 shared_objects,
                 unbound_text_fields,
                 timers,
+                local_connections,
+                xml_sockets,
+                external_callbacks,
+                context_menu,
             ) = root_data.update_context_params();
 
             let mut update_context = UpdateContext {
@@ -1040,16 +1725,31 @@ impl Player {
                 mouse_position,
                 drag_object,
                 stage_size: (stage_width, stage_height),
+                viewport_size: (viewport_width, viewport_height),
                 system_prototypes: avm1.prototypes().clone(),
                 player,
                 load_manager,
                 system: system_properties,
+                security,
                 instance_counter,
                 storage,
+                local_connection,
+                socket,
+                external_interface,
+                log,
                 shared_objects,
                 unbound_text_fields,
                 timers,
+                local_connections,
+                xml_sockets,
+                external_callbacks,
+                context_menu,
+                fs_commands,
                 needs_render,
+                show_menu,
+                scale_mode,
+                stage_align,
+                debugger,
                 avm1,
                 avm2,
             };
@@ -1059,7 +1759,61 @@ impl Player {
             // Hovered object may have been updated; copy it back to the GC root.
             root_data.mouse_hovered_object = update_context.mouse_hovered_object;
             ret
-        })
+        });
+
+        self.run_fs_commands();
+
+        if layout != (self.scale_mode, self.stage_align) {
+            self.build_matrices();
+            self.needs_render = true;
+        }
+
+        rval
+    }
+
+    /// Handle the FSCommands sent by the movie during the last update.
+    ///
+    /// Every command is passed on to the host's `FsCommandHandler`. When the
+    /// player is a standalone projector, it also acts on the projector
+    /// commands itself.
+    fn run_fs_commands(&mut self) {
+        for (command, args) in std::mem::take(&mut self.fs_commands) {
+            let handled = self.is_projector && self.run_projector_command(&command, &args);
+            match &mut self.fs_command_handler {
+                Some(handler) => handler(&command, &args),
+                None if !handled => log::warn!("Unhandled FSCommand: {} {}", command, args),
+                None => (),
+            }
+        }
+    }
+
+    /// Act on an FSCommand of the standalone projector.
+    ///
+    /// Returns `false` if the command isn't a projector command.
+    fn run_projector_command(&mut self, command: &str, args: &str) -> bool {
+        let enabled = args.eq_ignore_ascii_case("true");
+        match command.to_ascii_lowercase().as_str() {
+            "quit" => {
+                self.is_playing = false;
+                self.quit_requested = true;
+            }
+            "fullscreen" => self.is_fullscreen = enabled,
+            "allowscale" => {
+                self.scale_mode = if enabled {
+                    StageScaleMode::ShowAll
+                } else {
+                    StageScaleMode::NoScale
+                };
+                self.build_matrices();
+                self.needs_render = true;
+            }
+            "showmenu" => self.show_menu = enabled,
+            "trapallkeys" => self.trap_all_keys = enabled,
+            // Movies may not launch programs.
+            "exec" => log::warn!("Ignoring FSCommand exec: {}", args),
+            _ => return false,
+        }
+        true
     }
 
     /// Loads font data from the given buffer.
@@ -1117,6 +1871,140 @@ impl Player {
         });
     }
 
+    /// Invoke a callback that the movie exposed with
+    /// `ExternalInterface.addCallback`.
+    ///
+    /// Returns `None` if the movie has no callback with the given name.
+    pub fn call_external_callback(
+        &mut self,
+        name: &str,
+        args: &[ExternalValue],
+    ) -> Option<ExternalValue> {
+        self.update(|context| ExternalCallbacks::call_callback(context, name, args))
+    }
+
+    /// Build the context menu for a right-click at the current mouse position.
+    ///
+    /// The host should show the returned items, and then either pass the index
+    /// of the chosen item to `run_context_menu_callback`, or call
+    /// `clear_context_menu` if the menu was dismissed.
+    pub fn prepare_context_menu(&mut self) -> Vec<ContextMenuItem> {
+        let state = BuiltInState {
+            show_menu: self.show_menu,
+            is_zoomed: self.zoom > 1.0,
+            quality: self.quality,
+        };
+        let mouse_pos = self.mouse_pos;
+        self.update(|context| {
+            let target = context
+                .levels
+                .values()
+                .rev()
+                .find_map(|level| Self::object_at(*level, mouse_pos));
+            let menu = ContextMenuState::build(context, target, state);
+            let items = menu.items().to_vec();
+            *context.context_menu = menu;
+            items
+        })
+    }
+
+    /// Run the action of the context menu item at `index`, and close the menu.
+    pub fn run_context_menu_callback(&mut self, index: usize) {
+        let built_in = self.update(|context| {
+            let menu = std::mem::take(context.context_menu);
+            menu.run_callback(context, index)
+        });
+
+        match built_in {
+            Some(BuiltInItem::ZoomIn) => {
+                self.zoom = (self.zoom * 2.0).min(MAX_ZOOM);
+                self.zoom_focus = self.mouse_pos;
+            }
+            Some(BuiltInItem::ZoomOut) => self.zoom = (self.zoom / 2.0).max(1.0),
+            Some(BuiltInItem::ShowAll) => self.zoom = 1.0,
+            Some(BuiltInItem::Quality(quality)) => {
+                self.quality = quality;
+                self.renderer.set_quality(quality);
+            }
+            _ => (),
+        }
+        self.build_matrices();
+        self.needs_render = true;
+    }
+
+    /// Close the context menu without choosing an item.
+    pub fn clear_context_menu(&mut self) {
+        self.mutate_with_update_context(|context| {
+            *context.context_menu = ContextMenuState::new();
+        });
+    }
+
+    /// Build the context menu for a right-click at the current mouse position,
+    /// and draw it over the movie, for hosts that can't show a native menu.
+    ///
+    /// While the menu is open, it takes all input events until an item is
+    /// chosen, or the menu is dismissed by clicking outside of it or pressing
+    /// Escape.
+    pub fn show_drawn_context_menu(&mut self) {
+        let items = self.prepare_context_menu();
+        if items.is_empty() {
+            self.clear_context_menu();
+            return;
+        }
+
+        if self.context_menu_rect.is_none() {
+            self.context_menu_rect =
+                Some(DrawnContextMenu::register_rect(self.renderer.deref_mut()));
+        }
+
+        let (x, y) = self.view_matrix * self.mouse_pos;
+        // Open the menu just beside the cursor, so that releasing the button
+        // that opened it doesn't choose an item.
+        let position = (x.to_pixels() + 1.0, y.to_pixels() + 1.0);
+        let viewport_size = (
+            f64::from(self.viewport_width),
+            f64::from(self.viewport_height),
+        );
+        let swf = self.swf.clone();
+        let menu = self.gc_arena.mutate(|_gc_context, gc_root| {
+            let root_data = gc_root.0.read();
+            let device_font = root_data
+                .library
+                .library_for_movie(swf)
+                .and_then(|library| library.device_font());
+            DrawnContextMenu::with_font(items, position, viewport_size, device_font)
+        });
+        self.drawn_context_menu = Some(menu);
+        self.needs_render = true;
+    }
+
+    /// Whether the player is drawing a context menu over the movie.
+    pub fn is_drawn_context_menu_open(&self) -> bool {
+        self.drawn_context_menu.is_some()
+    }
+
+    /// Handle an input event while the drawn context menu is open.
+    fn handle_drawn_context_menu_event(&mut self, event: PlayerEvent) {
+        let response = match &mut self.drawn_context_menu {
+            Some(menu) => menu.handle_event(&event),
+            None => return,
+        };
+
+        match response {
+            DrawnMenuResponse::None => (),
+            DrawnMenuResponse::Redraw => self.needs_render = true,
+            DrawnMenuResponse::Dismiss => {
+                self.drawn_context_menu = None;
+                self.clear_context_menu();
+                self.needs_render = true;
+            }
+            DrawnMenuResponse::Choose(index) => {
+                self.drawn_context_menu = None;
+                self.run_context_menu_callback(index);
+            }
+        }
+    }
+
     /// Update all AVM-based timers (such as created via setInterval).
     /// Returns the approximate amount of time until the next timer tick.
     pub fn update_timers(&mut self, dt: f64) {
@@ -1125,6 +2013,42 @@ impl Player {
     }
 }
 
+/// The size of the text drawn over the movie by the log backend, in pixels.
+const OVERLAY_FONT_SIZE: f64 = 12.0;
+
+/// How text drawn over the movie is laid out.
+fn overlay_params() -> EvalParameters {
+    EvalParameters::from_parts(Twips::from_pixels(OVERLAY_FONT_SIZE), Twips::new(0), false)
+}
+
+/// Draw lines of text over the top-left corner of the viewport, with a shadow
+/// so that they can be read over any movie.
+fn render_overlay(renderer: &mut dyn RenderBackend, font: Font<'_>, lines: &[String]) {
+    let params = overlay_params();
+    for (i, line) in lines.iter().enumerate() {
+        let y = 4.0 + OVERLAY_FONT_SIZE * 1.25 * i as f64;
+        for &(offset, brightness) in &[(1.0, 0.0), (0.0, 1.0)] {
+            let mut transform = crate::transform::Transform::default();
+            transform.matrix.tx = Twips::from_pixels(4.0 + offset);
+            transform.matrix.ty = Twips::from_pixels(y + offset);
+            transform.color_transform.r_mult = brightness;
+            transform.color_transform.g_mult = brightness;
+            transform.color_transform.b_mult = brightness;
+            font.evaluate(line, transform, params, |transform, glyph, _advance| {
+                renderer.render_shape(glyph.shape, transform)
+            });
+        }
+    }
+}
+
+/// The background of characters drawn by `Player::render_symbol`.
+const TRANSPARENT: Color = Color {
+    r: 0,
+    g: 0,
+    b: 0,
+    a: 0,
+};
+
 pub struct DragObject<'gc> {
     /// The display object being dragged.
     pub display_object: DisplayObject<'gc>,
@@ -1141,3 +2065,641 @@ unsafe impl<'gc> gc_arena::Collect for DragObject<'gc> {
         self.display_object.trace(cc);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::error::Error;
+    use crate::avm1::test_utils::with_avm;
+    use crate::library::SymbolKind;
+
+    /// Add a text field at (10, 10) to the root clip, which shows about two of
+    /// its ten lines of text.
+    fn add_text_field<'gc>(context: &mut UpdateContext<'_, 'gc, '_>) -> EditText<'gc> {
+        let device_font =
+            Player::load_device_font(context.gc_context, DEVICE_FONT_TAG, context.renderer)
+                .unwrap();
+        let movie = context.swf.clone();
+        context
+            .library
+            .library_for_movie_mut(movie.clone())
+            .set_device_font(Some(device_font));
+
+        let text_field = EditText::new(context, movie, 10.0, 10.0, 200.0, 40.0);
+        text_field.set_multiline(true, context);
+        let lines: Vec<_> = (1..=10).map(|i| format!("Line {}", i)).collect();
+        text_field.set_text(lines.join("\n"), context).unwrap();
+
+        let mut display_object: DisplayObject<'gc> = text_field.into();
+        let mut root = context.levels.get(&0).unwrap().as_movie_clip().unwrap();
+        root.add_child_from_avm(context, display_object, 1);
+        display_object.post_instantiation(context, display_object, None, true);
+        text_field
+    }
+
+    #[test]
+    fn scroll_and_maxscroll() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let text_field = add_text_field(&mut activation.context);
+            let maxscroll = text_field.maxscroll();
+            assert!(maxscroll > 1 && maxscroll < 10);
+            assert_eq!(text_field.scroll(), 1);
+
+            let object = text_field.object().coerce_to_object(activation);
+            assert_eq!(
+                object.get("maxscroll", activation)?,
+                (maxscroll as f64).into()
+            );
+            object.set("scroll", 2.into(), activation)?;
+            assert_eq!(object.get("scroll", activation)?, 2.into());
+
+            // Scrolling is clamped to the lines that exist.
+            object.set("scroll", 100.into(), activation)?;
+            assert_eq!(text_field.scroll(), maxscroll);
+            object.set("scroll", 0.into(), activation)?;
+            assert_eq!(text_field.scroll(), 1);
+            object.set("scroll", f64::NAN.into(), activation)?;
+            assert_eq!(text_field.scroll(), 1);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn mouse_wheel_scrolls_text_field() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let context = &mut activation.context;
+            let text_field = add_text_field(context);
+            let inside = (Twips::from_pixels(50.0), Twips::from_pixels(20.0));
+            let outside = (Twips::from_pixels(300.0), Twips::from_pixels(20.0));
+
+            // Scrolling down moves later lines into view.
+            Player::scroll_wheel(context, inside, MouseWheelDelta::Lines(-2.0));
+            assert_eq!(text_field.scroll(), 3);
+            Player::scroll_wheel(context, inside, MouseWheelDelta::Lines(1.0));
+            assert_eq!(text_field.scroll(), 2);
+            Player::scroll_wheel(context, inside, MouseWheelDelta::Pixels(100.0));
+            assert_eq!(text_field.scroll(), 1);
+            Player::scroll_wheel(context, inside, MouseWheelDelta::Lines(-100.0));
+            assert_eq!(text_field.scroll(), text_field.maxscroll());
+
+            // Only the text field under the mouse scrolls.
+            text_field.set_scroll(1, context.gc_context);
+            Player::scroll_wheel(context, outside, MouseWheelDelta::Lines(-2.0));
+            assert_eq!(text_field.scroll(), 1);
+
+            text_field.set_mouse_wheel_enabled(context.gc_context, false);
+            Player::scroll_wheel(context, inside, MouseWheelDelta::Lines(-2.0));
+            assert_eq!(text_field.scroll(), 1);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn mouse_wheel_notifies_listeners() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let context = &mut activation.context;
+            let text_field = add_text_field(context);
+            while context.action_queue.pop_action().is_some() {}
+
+            let inside = (Twips::from_pixels(50.0), Twips::from_pixels(20.0));
+            Player::scroll_wheel(context, inside, MouseWheelDelta::Lines(-3.0));
+            match context
+                .action_queue
+                .pop_action()
+                .map(|action| action.action_type)
+            {
+                Some(ActionType::NotifyListeners {
+                    listener,
+                    method,
+                    args,
+                }) => {
+                    assert_eq!((listener, method), ("Mouse", "onMouseWheel"));
+                    assert_eq!(args, vec![(-3.0).into(), text_field.object()]);
+                }
+                _ => panic!("Expected onMouseWheel to be queued"),
+            }
+            Ok(())
+        });
+    }
+
+    fn create_player() -> Arc<Mutex<Player>> {
+        use crate::backend::{
+            audio::NullAudioBackend, input::NullInputBackend, navigator::NullNavigatorBackend,
+            render::NullRenderer, storage::MemoryStorageBackend,
+        };
+
+        let player = Player::new(
+            Box::new(NullRenderer::new()),
+            Box::new(NullAudioBackend::new()),
+            Box::new(NullNavigatorBackend::new()),
+            Box::new(NullInputBackend::new()),
+            Box::new(MemoryStorageBackend::default()),
+        )
+        .unwrap();
+        player
+            .lock()
+            .unwrap()
+            .set_root_movie(Arc::new(SwfMovie::empty(8)));
+        player
+    }
+
+    /// Create a player running one of the SWFs the `swf` crate is tested with.
+    fn create_player_with_test_swf(name: &str) -> Arc<Mutex<Player>> {
+        let player = create_player();
+        let path = format!(
+            "{}/../swf/tests/swfs/{}.swf",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        let movie = SwfMovie::from_path(&path).unwrap();
+        player.lock().unwrap().set_root_movie(Arc::new(movie));
+        player
+    }
+
+    /// Create a player running one of the SWFs the `swf` crate is tested
+    /// with, downloading it `chunk_size` bytes at a time.
+    ///
+    /// `on_chunk` is called after each chunk has been preloaded, except for
+    /// those that arrive before the movie's header.
+    fn stream_test_swf(
+        name: &str,
+        chunk_size: usize,
+        on_chunk: impl FnMut(&mut Player),
+    ) -> Arc<Mutex<Player>> {
+        stream_swf_data(&read_test_swf(name), chunk_size, on_chunk)
+    }
+
+    fn read_test_swf(name: &str) -> Vec<u8> {
+        let path = format!(
+            "{}/../swf/tests/swfs/{}.swf",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        std::fs::read(&path).unwrap()
+    }
+
+    fn stream_swf_data(
+        data: &[u8],
+        chunk_size: usize,
+        mut on_chunk: impl FnMut(&mut Player),
+    ) -> Arc<Mutex<Player>> {
+        let player = create_player();
+        let mut decompressor = swf::read::SwfDecompressor::new();
+        let mut movie: Option<Arc<SwfMovie>> = None;
+        for chunk in data.chunks(chunk_size) {
+            decompressor.push(chunk).unwrap();
+            let mut player = player.lock().unwrap();
+            match &movie {
+                Some(movie) => {
+                    let loaded = movie.bytes_loaded() - movie.header_length();
+                    movie.append_data(&decompressor.data()[loaded..]);
+                    player.preload();
+                }
+                None => match SwfMovie::from_partial_data(&decompressor, None, false) {
+                    Some(new_movie) => {
+                        let new_movie = Arc::new(new_movie);
+                        movie = Some(new_movie.clone());
+                        player.set_root_movie(new_movie);
+                    }
+                    None => continue,
+                },
+            }
+            on_chunk(&mut player);
+        }
+        decompressor.finish().unwrap();
+        movie.unwrap().finish_loading();
+        player.lock().unwrap().preload();
+        player
+    }
+
+    #[test]
+    fn drawn_context_menu_sets_quality() {
+        let player = create_player();
+        let mut player = player.lock().unwrap();
+        player.show_drawn_context_menu();
+        assert!(player.is_drawn_context_menu_open());
+
+        let menu = player.drawn_context_menu.clone().unwrap();
+        assert_eq!(menu.items()[3].caption, "Quality: Low");
+        let (x, y, _, _) = menu.item_bounds(3).unwrap();
+        player.handle_event(PlayerEvent::MouseMove {
+            x: x + 5.0,
+            y: y + 5.0,
+        });
+        assert_eq!(
+            player.drawn_context_menu.as_ref().unwrap().hovered(),
+            Some(3)
+        );
+        player.handle_event(PlayerEvent::MouseUp {
+            x: x + 5.0,
+            y: y + 5.0,
+            button: MouseButton::Left,
+        });
+        assert!(!player.is_drawn_context_menu_open());
+        assert_eq!(player.quality(), StageQuality::Low);
+    }
+
+    #[test]
+    fn drawn_context_menu_dismissed() {
+        let player = create_player();
+        let mut player = player.lock().unwrap();
+
+        player.show_drawn_context_menu();
+        let (x, y, width, height) = player.drawn_context_menu.as_ref().unwrap().bounds();
+        // Clicking inside the menu, but not on an item, keeps it open.
+        player.handle_event(PlayerEvent::MouseDown {
+            x: x + 5.0,
+            y: y + 5.0,
+            button: MouseButton::Left,
+        });
+        assert!(player.is_drawn_context_menu_open());
+        player.handle_event(PlayerEvent::MouseDown {
+            x: x + width + 5.0,
+            y: y + height + 5.0,
+            button: MouseButton::Left,
+        });
+        assert!(!player.is_drawn_context_menu_open());
+
+        player.show_drawn_context_menu();
+        player.handle_event(PlayerEvent::KeyDown {
+            key_code: KeyCode::Escape,
+        });
+        assert!(!player.is_drawn_context_menu_open());
+        assert_eq!(player.quality(), StageQuality::High);
+    }
+
+    /// Lay out a 550x400 movie in a viewport of the given size.
+    ///
+    /// Returns the scale and offset of the view matrix, in pixels, along with
+    /// the letterbox.
+    fn layout(
+        player: &mut Player,
+        viewport: (u32, u32),
+        scale_mode: StageScaleMode,
+        align: &str,
+    ) -> ((f32, f32, f64, f64), Letterbox) {
+        player.movie_width = 550;
+        player.movie_height = 400;
+        player.viewport_width = viewport.0;
+        player.viewport_height = viewport.1;
+        player.scale_mode = scale_mode;
+        player.stage_align = StageAlign::from_name(align);
+        player.build_matrices();
+
+        let matrix = player.view_matrix;
+        let (x, y) = player.inverse_view_matrix * (matrix.tx, matrix.ty);
+        assert_eq!((x, y), (Twips::new(0), Twips::new(0)));
+        (
+            (
+                matrix.a,
+                matrix.d,
+                matrix.tx.to_pixels(),
+                matrix.ty.to_pixels(),
+            ),
+            player.letterbox,
+        )
+    }
+
+    #[test]
+    fn show_all() {
+        let player = create_player();
+        let mut player = player.lock().unwrap();
+        let mode = StageScaleMode::ShowAll;
+        assert_eq!(
+            layout(&mut player, (1100, 600), mode, ""),
+            ((1.5, 1.5, 137.5, 0.0), Letterbox::Pillarbox(137.5))
+        );
+        assert_eq!(
+            layout(&mut player, (550, 800), mode, ""),
+            ((1.0, 1.0, 0.0, 200.0), Letterbox::Letterbox(200.0))
+        );
+
+        // Aligned movies have no letterbox.
+        assert_eq!(
+            layout(&mut player, (1100, 600), mode, "L"),
+            ((1.5, 1.5, 0.0, 0.0), Letterbox::None)
+        );
+        assert_eq!(
+            layout(&mut player, (1100, 600), mode, "R"),
+            ((1.5, 1.5, 275.0, 0.0), Letterbox::None)
+        );
+        assert_eq!(
+            layout(&mut player, (550, 800), mode, "T"),
+            ((1.0, 1.0, 0.0, 0.0), Letterbox::None)
+        );
+        assert_eq!(
+            layout(&mut player, (550, 800), mode, "B"),
+            ((1.0, 1.0, 0.0, 400.0), Letterbox::None)
+        );
+
+        // Alignment on the other axis doesn't affect the letterbox.
+        assert_eq!(
+            layout(&mut player, (1100, 600), mode, "T"),
+            ((1.5, 1.5, 137.5, 0.0), Letterbox::Pillarbox(137.5))
+        );
+    }
+
+    #[test]
+    fn no_border() {
+        let player = create_player();
+        let mut player = player.lock().unwrap();
+        let mode = StageScaleMode::NoBorder;
+        assert_eq!(
+            layout(&mut player, (1100, 600), mode, ""),
+            ((2.0, 2.0, 0.0, -100.0), Letterbox::None)
+        );
+        assert_eq!(
+            layout(&mut player, (1100, 600), mode, "T"),
+            ((2.0, 2.0, 0.0, 0.0), Letterbox::None)
+        );
+        assert_eq!(
+            layout(&mut player, (1100, 600), mode, "B"),
+            ((2.0, 2.0, 0.0, -200.0), Letterbox::None)
+        );
+        assert_eq!(
+            layout(&mut player, (550, 800), mode, "L"),
+            ((2.0, 2.0, 0.0, 0.0), Letterbox::None)
+        );
+        assert_eq!(
+            layout(&mut player, (550, 800), mode, "R"),
+            ((2.0, 2.0, -550.0, 0.0), Letterbox::None)
+        );
+    }
+
+    #[test]
+    fn exact_fit() {
+        let player = create_player();
+        let mut player = player.lock().unwrap();
+        let mode = StageScaleMode::ExactFit;
+        for align in &["", "TL", "BR"] {
+            assert_eq!(
+                layout(&mut player, (1100, 600), mode, align),
+                ((2.0, 1.5, 0.0, 0.0), Letterbox::None)
+            );
+        }
+    }
+
+    #[test]
+    fn no_scale() {
+        let player = create_player();
+        let mut player = player.lock().unwrap();
+        let mode = StageScaleMode::NoScale;
+        assert_eq!(
+            layout(&mut player, (1100, 600), mode, ""),
+            ((1.0, 1.0, 275.0, 100.0), Letterbox::None)
+        );
+        assert_eq!(
+            layout(&mut player, (1100, 600), mode, "TL"),
+            ((1.0, 1.0, 0.0, 0.0), Letterbox::None)
+        );
+        assert_eq!(
+            layout(&mut player, (1100, 600), mode, "BR"),
+            ((1.0, 1.0, 550.0, 200.0), Letterbox::None)
+        );
+        assert_eq!(
+            layout(&mut player, (1100, 600), mode, "TR"),
+            ((1.0, 1.0, 550.0, 0.0), Letterbox::None)
+        );
+        assert_eq!(
+            layout(&mut player, (1100, 600), mode, "BL"),
+            ((1.0, 1.0, 0.0, 200.0), Letterbox::None)
+        );
+        // The movie is cropped when the viewport is smaller.
+        assert_eq!(
+            layout(&mut player, (450, 300), mode, ""),
+            ((1.0, 1.0, -50.0, -50.0), Letterbox::None)
+        );
+    }
+
+    #[test]
+    fn zoom_keeps_focus_in_view() {
+        let player = create_player();
+        let mut player = player.lock().unwrap();
+        player.zoom = 2.0;
+        let mode = StageScaleMode::ShowAll;
+
+        player.zoom_focus = (Twips::from_pixels(100.0), Twips::from_pixels(100.0));
+        assert_eq!(
+            layout(&mut player, (550, 400), mode, ""),
+            ((2.0, 2.0, 0.0, 0.0), Letterbox::None)
+        );
+
+        player.zoom_focus = (Twips::from_pixels(400.0), Twips::from_pixels(300.0));
+        assert_eq!(
+            layout(&mut player, (550, 400), mode, ""),
+            ((2.0, 2.0, -525.0, -400.0), Letterbox::None)
+        );
+    }
+
+    /// The current frame, loaded frames and loaded bytes of the root clip.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct RootProgress {
+        current_frame: u16,
+        frames_loaded: u16,
+        total_frames: u16,
+        bytes_loaded: usize,
+        bytes_total: usize,
+    }
+
+    fn root_progress(player: &mut Player) -> RootProgress {
+        player.mutate_with_update_context(|context| {
+            let root = context.levels.get(&0).unwrap().as_movie_clip().unwrap();
+            RootProgress {
+                current_frame: root.current_frame(),
+                frames_loaded: root.frames_loaded(),
+                total_frames: root.total_frames(),
+                bytes_loaded: root.bytes_loaded(),
+                bytes_total: root.bytes_total(),
+            }
+        })
+    }
+
+    #[test]
+    fn stream_root_movie() {
+        let path = format!(
+            "{}/../swf/tests/swfs/DefineMorphShape-MX.swf",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let swf_length = std::fs::metadata(&path).unwrap().len() as usize;
+
+        for &chunk_size in &[1, 64, 1024] {
+            let mut progress = vec![];
+            let player = stream_test_swf("DefineMorphShape-MX", chunk_size, |player| {
+                progress.push(root_progress(player));
+            });
+            let last = root_progress(&mut player.lock().unwrap());
+
+            // Preloading carries on from where the last chunk left off.
+            assert!(progress.windows(2).all(|pair| {
+                pair[0].frames_loaded <= pair[1].frames_loaded
+                    && pair[0].bytes_loaded < pair[1].bytes_loaded
+            }));
+            for progress in &progress {
+                assert_eq!(progress.total_frames, 30);
+                assert_eq!(progress.bytes_total, swf_length);
+                assert!(progress.bytes_loaded <= swf_length);
+            }
+            if chunk_size < swf_length {
+                assert!(progress[0].frames_loaded < 30);
+            }
+            assert_eq!(last.frames_loaded, 30);
+            assert_eq!(last.bytes_loaded, swf_length);
+        }
+    }
+
+    #[test]
+    fn run_frame_waits_for_frames_to_load() {
+        let mut waited = false;
+        let player = stream_test_swf("DefineMorphShape-MX", 64, |player| {
+            for _ in 0..5 {
+                player.run_frame();
+                let progress = root_progress(player);
+                assert!(progress.current_frame <= progress.frames_loaded);
+                waited |= progress.current_frame == progress.frames_loaded
+                    && progress.frames_loaded < progress.total_frames;
+            }
+        });
+        assert!(waited);
+
+        // Once the movie has loaded, it plays to the end.
+        let mut player = player.lock().unwrap();
+        let start = root_progress(&mut player).current_frame;
+        for _ in start..30 {
+            player.run_frame();
+        }
+        assert_eq!(root_progress(&mut player).current_frame, 30);
+    }
+
+    #[test]
+    fn stream_root_movie_longer_than_header() {
+        // Tags past the length given in the header are still loaded.
+        let mut data = read_test_swf("DefineMorphShape-MX");
+        let swf_length = data.len();
+        let length = (swf_length as u32 / 2).to_le_bytes();
+        data[4..8].copy_from_slice(&length);
+
+        for &chunk_size in &[64, 1024] {
+            let player = stream_swf_data(&data, chunk_size, |player| {
+                let progress = root_progress(player);
+                assert!(progress.bytes_loaded <= progress.bytes_total);
+            });
+            let last = root_progress(&mut player.lock().unwrap());
+            assert_eq!(last.frames_loaded, 30);
+            assert_eq!(last.bytes_loaded, swf_length);
+            assert_eq!(last.bytes_total, swf_length);
+        }
+    }
+
+    #[test]
+    fn stream_morph_shape_ratios() {
+        // Ratios placed in frames that arrive after the morph shape is
+        // defined are still prepared.
+        for &chunk_size in &[64, 1024] {
+            let player = stream_test_swf("DefineMorphShape-MX", chunk_size, |_| ());
+            let symbols = player.lock().unwrap().library_symbols();
+            let frames: Vec<_> = symbols
+                .iter()
+                .map(|symbol| (symbol.id, symbol.kind, symbol.num_frames))
+                .collect();
+            assert_eq!(
+                frames,
+                vec![(1, SymbolKind::MorphShape, 30), (2, SymbolKind::Graphic, 1)]
+            );
+        }
+    }
+
+    #[test]
+    fn library_symbols() {
+        let symbols = |name| {
+            let player = create_player_with_test_swf(name);
+            let symbols = player.lock().unwrap().library_symbols();
+            symbols
+                .into_iter()
+                .map(|symbol| {
+                    (
+                        symbol.id,
+                        symbol.kind,
+                        symbol.export_names,
+                        symbol.num_frames,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            symbols("ExportAssets-CS6"),
+            vec![
+                (1, SymbolKind::Graphic, vec![], 1),
+                (
+                    2,
+                    SymbolKind::MovieClip,
+                    vec!["Test\u{1f4af}".to_string()],
+                    1
+                )
+            ]
+        );
+        assert_eq!(
+            symbols("DefineSprite"),
+            vec![(1, SymbolKind::MovieClip, vec![], 5)]
+        );
+        // A morph shape has a frame for each ratio the movie places it at.
+        assert_eq!(
+            symbols("DefineMorphShape-MX"),
+            vec![
+                (1, SymbolKind::MorphShape, vec![], 30),
+                (2, SymbolKind::Graphic, vec![], 1)
+            ]
+        );
+        assert_eq!(
+            symbols("DefineSound"),
+            vec![(1, SymbolKind::Sound, vec![], 1)]
+        );
+    }
+
+    #[test]
+    fn render_symbol() {
+        let player = create_player_with_test_swf("DefineShape");
+        let mut player = player.lock().unwrap();
+        assert_eq!(player.render_symbol(1, 0, 1.0), Some((20, 20)));
+        assert_eq!(player.render_symbol(1, 0, 2.0), Some((40, 40)));
+        assert_eq!(player.render_symbol(1, 0, 0.01), Some((1, 1)));
+        assert_eq!(player.render_symbol(2, 0, 1.0), None);
+
+        // Each frame of a morph shape is drawn at its ratio.
+        let player = create_player_with_test_swf("DefineMorphShape-MX");
+        let mut player = player.lock().unwrap();
+        assert_eq!(player.render_symbol(1, 0, 1.0), Some((40, 40)));
+        assert_eq!(player.render_symbol(1, 29, 1.0), Some((69, 51)));
+        assert_eq!(player.render_symbol(1, 30, 1.0), None);
+
+        // Empty clips and sounds can't be drawn.
+        let player = create_player_with_test_swf("DefineSprite");
+        assert_eq!(player.lock().unwrap().render_symbol(1, 0, 1.0), None);
+        let player = create_player_with_test_swf("DefineSound");
+        assert_eq!(player.lock().unwrap().render_symbol(1, 0, 1.0), None);
+    }
+
+    #[test]
+    fn render_glyph_sheet() {
+        let cell_size = crate::font::GLYPH_SHEET_CELL_SIZE as u32;
+
+        // One glyph.
+        let player = create_player_with_test_swf("DefineFont2-CS6");
+        let mut player = player.lock().unwrap();
+        assert_eq!(
+            player.render_symbol(1, 0, 1.0),
+            Some((cell_size, cell_size))
+        );
+        assert_eq!(
+            player.render_symbol(1, 0, 2.0),
+            Some((cell_size * 2, cell_size * 2))
+        );
+
+        // Two glyphs, side by side.
+        let player = create_player_with_test_swf("DefineText2-MX");
+        assert_eq!(
+            player.lock().unwrap().render_symbol(1, 0, 1.0),
+            Some((cell_size * 2, cell_size))
+        );
+    }
+}
@@ -0,0 +1,838 @@
+//! The Flash Player security sandbox and cross-domain policy files
+//!
+//! Every movie is placed in a sandbox based on where it was loaded from. The
+//! sandbox decides which other files the movie may load data from; loading
+//! data from another domain additionally requires a `crossdomain.xml` policy
+//! file on that domain which grants access to the movie's domain.
+
+use crate::avm1::globals::system::SandboxType;
+use crate::backend::navigator::{url_domain, ChunkStream, OwnedFuture, RequestOptions};
+use crate::context::UpdateContext;
+use crate::loader::Error;
+use crate::tag_utils::SwfMovie;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+/// Policy files that have been fetched so far, keyed by their URL.
+///
+/// Files that were missing or invalid are cached as `None`.
+type PolicyCache = Arc<Mutex<HashMap<String, Option<PolicyFile>>>>;
+
+/// Sandbox state shared by every movie in a player.
+pub struct Security {
+    /// Whether local movies are placed in the local-trusted sandbox.
+    ///
+    /// Local content is untrusted unless the host says otherwise. Standalone
+    /// players trust it, as does Flash Player once a user adds it to their
+    /// trusted locations.
+    trust_local_content: bool,
+
+    /// Custom policy file locations registered with `loadPolicyFile`.
+    policy_urls: Vec<String>,
+
+    /// Policy files fetched so far.
+    policy_files: PolicyCache,
+
+    /// Domains that movies have permitted to script them with `allowDomain`
+    /// and `allowInsecureDomain`, keyed by the domain of the permitting movie.
+    allowed_domains: HashMap<String, Vec<AllowedDomain>>,
+}
+
+struct AllowedDomain {
+    domain: String,
+
+    /// Whether movies served over HTTP may script movies served over HTTPS.
+    insecure: bool,
+}
+
+impl Security {
+    pub fn new() -> Self {
+        Self {
+            trust_local_content: false,
+            policy_urls: Vec::new(),
+            policy_files: Arc::new(Mutex::new(HashMap::new())),
+            allowed_domains: HashMap::new(),
+        }
+    }
+
+    pub fn set_trust_local_content(&mut self, trust_local_content: bool) {
+        self.trust_local_content = trust_local_content;
+    }
+
+    /// The sandbox that a movie is placed in.
+    pub fn sandbox_type(&self, movie: &SwfMovie) -> SandboxType {
+        classify(
+            movie.url(),
+            movie.use_network_sandbox(),
+            self.trust_local_content,
+        )
+    }
+
+    /// Register a custom policy file location, as with `loadPolicyFile`.
+    pub fn load_policy_file(&mut self, url: String) {
+        if !self.policy_urls.contains(&url) {
+            self.policy_urls.push(url);
+        }
+    }
+
+    /// Permit movies from `domain` to script movies from the domain of
+    /// `movie`, as with `allowDomain` and `allowInsecureDomain`.
+    pub fn allow_domain(&mut self, movie: &SwfMovie, domain: &str, insecure: bool) {
+        self.allowed_domains
+            .entry(url_domain(movie.url()))
+            .or_insert_with(Vec::new)
+            .push(AllowedDomain {
+                domain: domain.to_ascii_lowercase(),
+                insecure,
+            });
+    }
+
+    /// Whether code in `accessor` may script the display objects of `target`.
+    pub fn can_script(&self, accessor: &SwfMovie, target: &SwfMovie) -> bool {
+        let accessor_domain = url_domain(accessor.url());
+        let target_domain = url_domain(target.url());
+        if accessor_domain == target_domain
+            || self.sandbox_type(accessor) == SandboxType::LocalTrusted
+        {
+            return true;
+        }
+
+        let downgrade = is_secure(target.url()) && !is_secure(accessor.url());
+        self.allowed_domains
+            .get(&target_domain)
+            .map(|allowed| {
+                allowed.iter().any(|allowed| {
+                    domain_matches(&allowed.domain, &accessor_domain)
+                        && (allowed.insecure || !downgrade)
+                })
+            })
+            .unwrap_or(false)
+    }
+
+    /// Whether `movie` may open a socket, and which domain a socket policy
+    /// file must grant access to first.
+    pub fn socket_access(&self, movie: &SwfMovie) -> Access {
+        socket_access(self.sandbox_type(movie), movie.url())
+    }
+
+    /// The places to request a socket policy file from before connecting to
+    /// `host` and `port`, in order.
+    ///
+    /// The master policy on port 843 comes first, followed by locations
+    /// registered with `loadPolicyFile` and finally the port itself.
+    pub fn socket_policy_locations(&self, host: &str, port: u16) -> Vec<(String, u16)> {
+        let mut locations = vec![(host.to_string(), MASTER_SOCKET_POLICY_PORT)];
+        for custom_url in &self.policy_urls {
+            if let Ok(custom) = Url::parse(custom_url) {
+                if custom.scheme() == "xmlsocket"
+                    && custom.host_str().map(|h| h.eq_ignore_ascii_case(host)) == Some(true)
+                {
+                    if let Some(custom_port) = custom.port() {
+                        locations.push((host.to_string(), custom_port));
+                    }
+                }
+            }
+        }
+        locations.push((host.to_string(), port));
+
+        let mut unique = Vec::with_capacity(locations.len());
+        for location in locations {
+            if !unique.contains(&location) {
+                unique.push(location);
+            }
+        }
+        unique
+    }
+
+    /// Fetch data for `movie`, once its sandbox permits the load.
+    ///
+    /// Loads that the sandbox forbids, and cross-domain loads that no policy
+    /// file grants, fail with `Error::SandboxViolation`.
+    pub fn fetch_data(
+        context: &mut UpdateContext<'_, '_, '_>,
+        movie: &SwfMovie,
+        url: &str,
+        options: RequestOptions,
+    ) -> OwnedFuture<Vec<u8>, Error> {
+        let sandbox_type = context.security.sandbox_type(movie);
+        let target = resolve_url(movie, url);
+
+        let (target, requester) = match (
+            data_access(sandbox_type, movie.url(), target.as_ref()),
+            target,
+        ) {
+            (Access::Allowed, _) => return context.navigator.fetch(url, options),
+            (Access::RequiresPolicy(requester), Some(target)) => (target, requester),
+            (_, target) => {
+                let url = target
+                    .map(|target| target.to_string())
+                    .unwrap_or_else(|| url.to_string());
+                log::warn!("Movie in the {} sandbox may not load {}", sandbox_type, url);
+                return Box::pin(async move { Err(Error::SandboxViolation(url)) });
+            }
+        };
+
+        let secure = is_secure(movie.url());
+        let policies: Vec<_> = policy_urls_for(&target, &context.security.policy_urls)
+            .into_iter()
+            .map(|policy_url| {
+                let fetch = if context
+                    .security
+                    .policy_files
+                    .lock()
+                    .unwrap()
+                    .contains_key(&policy_url)
+                {
+                    None
+                } else {
+                    Some(context.navigator.fetch(&policy_url, RequestOptions::get()))
+                };
+                (policy_url, fetch)
+            })
+            .collect();
+        let cache = context.security.policy_files.clone();
+        // Fetches don't start until they are awaited, so the request is
+        // only sent once a policy file allows it.
+        let data = context.navigator.fetch(url, options);
+        let url = target.to_string();
+
+        Box::pin(async move {
+            let mut policies = policies.into_iter();
+
+            // The first policy is the master policy at the root of the
+            // domain, which controls whether the others are honoured.
+            let mut permitted = MetaPolicy::All;
+            let mut allowed = false;
+            if let Some((policy_url, fetch)) = policies.next() {
+                if let Some(policy) = load_policy(&cache, &policy_url, fetch).await {
+                    permitted = policy.meta_policy;
+                    allowed = policy.allows(requester.as_deref(), secure, &policy_url);
+                }
+            }
+
+            if permitted == MetaPolicy::All {
+                for (policy_url, fetch) in policies {
+                    if allowed {
+                        break;
+                    }
+                    if let Some(policy) = load_policy(&cache, &policy_url, fetch).await {
+                        allowed = policy.allows(requester.as_deref(), secure, &policy_url);
+                    }
+                }
+            }
+
+            if !allowed {
+                log::warn!("No policy file permits loading {}", url);
+                return Err(Error::SandboxViolation(url));
+            }
+
+            data.await
+        })
+    }
+
+    /// Stream a movie for `movie` to load, once its sandbox permits the load.
+    ///
+    /// Movies may be loaded from other domains without a policy file, so only
+    /// loads that the sandbox forbids outright fail, with
+    /// `Error::SandboxViolation`.
+    pub fn fetch_movie(
+        context: &mut UpdateContext<'_, '_, '_>,
+        movie: &SwfMovie,
+        url: &str,
+        options: RequestOptions,
+    ) -> ChunkStream {
+        let sandbox_type = context.security.sandbox_type(movie);
+        let target = resolve_url(movie, url);
+        if !movie_access(sandbox_type, movie.url(), target.as_ref()) {
+            let url = target
+                .map(|target| target.to_string())
+                .unwrap_or_else(|| url.to_string());
+            log::warn!("Movie in the {} sandbox may not load {}", sandbox_type, url);
+            return ChunkStream::new(|_| {
+                Box::pin(async move { Err(Error::SandboxViolation(url)) })
+            });
+        }
+
+        context.navigator.fetch_stream(url, options)
+    }
+}
+
+impl Default for Security {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The port that master socket policy files are served on.
+pub const MASTER_SOCKET_POLICY_PORT: u16 = 843;
+
+/// Whether a movie may load data from somewhere, or open a socket to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Access {
+    /// The data may be loaded, or the socket opened, straight away.
+    Allowed,
+
+    /// A policy file must grant access to the given domain first, or to every
+    /// domain for `None`.
+    RequiresPolicy(Option<String>),
+
+    /// The sandbox of the movie forbids it.
+    Denied,
+}
+
+/// Which policy files on a domain the master policy permits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MetaPolicy {
+    /// No policy files, including the master policy itself.
+    None,
+
+    /// Only the master policy at `/crossdomain.xml`.
+    MasterOnly,
+
+    /// Any policy file.
+    All,
+}
+
+/// A parsed `crossdomain.xml` policy file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyFile {
+    meta_policy: MetaPolicy,
+    rules: Vec<PolicyRule>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PolicyRule {
+    /// The domain pattern access is granted to, such as `*.example.com`.
+    domain: String,
+
+    /// Whether the rule only applies to movies served over HTTPS, when the
+    /// policy file itself was served over HTTPS.
+    secure: bool,
+
+    /// The ports that sockets may connect to, such as `507,516-523` or `*`.
+    ///
+    /// Only socket policy files specify ports.
+    to_ports: Option<String>,
+}
+
+impl PolicyFile {
+    /// Parse a policy file, returning `None` if it isn't one.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let data = String::from_utf8_lossy(data);
+        let mut reader = Reader::from_str(&data);
+        let mut buf = Vec::new();
+        let mut depth = 0;
+        let mut is_policy = false;
+        let mut policy = Self {
+            meta_policy: MetaPolicy::All,
+            rules: Vec::new(),
+        };
+
+        loop {
+            buf.clear();
+            let (element, is_empty) = match reader.read_event(&mut buf).ok()? {
+                Event::Start(element) => (element, false),
+                Event::Empty(element) => (element, true),
+                Event::End(_) => {
+                    depth -= 1;
+                    continue;
+                }
+                Event::Eof => break,
+                _ => continue,
+            };
+
+            match (depth, element.name()) {
+                (0, b"cross-domain-policy") => is_policy = true,
+                (0, _) => return None,
+                (1, b"allow-access-from") => {
+                    let mut domain = None;
+                    let mut secure = true;
+                    let mut to_ports = None;
+                    for attribute in element.attributes().filter_map(Result::ok) {
+                        let value = String::from_utf8_lossy(&attribute.value);
+                        match attribute.key {
+                            b"domain" => domain = Some(value.trim().to_ascii_lowercase()),
+                            b"secure" => secure = value.trim() != "false",
+                            b"to-ports" => to_ports = Some(value.trim().to_string()),
+                            _ => (),
+                        }
+                    }
+                    if let Some(domain) = domain {
+                        policy.rules.push(PolicyRule {
+                            domain,
+                            secure,
+                            to_ports,
+                        });
+                    }
+                }
+                (1, b"site-control") => {
+                    for attribute in element.attributes().filter_map(Result::ok) {
+                        if attribute.key == b"permitted-cross-domain-policies" {
+                            policy.meta_policy = match &attribute.value[..] {
+                                b"none" => MetaPolicy::None,
+                                b"master-only" => MetaPolicy::MasterOnly,
+                                _ => MetaPolicy::All,
+                            };
+                        }
+                    }
+                }
+                _ => (),
+            }
+
+            if !is_empty {
+                depth += 1;
+            }
+        }
+
+        if is_policy {
+            Some(policy)
+        } else {
+            None
+        }
+    }
+
+    /// Whether this policy, served from `policy_url`, grants access to movies
+    /// from `domain`.
+    ///
+    /// A `domain` of `None` stands for local movies, which are only granted
+    /// access by rules that allow every domain.
+    pub fn allows(&self, domain: Option<&str>, secure: bool, policy_url: &str) -> bool {
+        if self.meta_policy == MetaPolicy::None {
+            return false;
+        }
+
+        let https_policy = is_secure(Some(policy_url));
+        self.rules.iter().any(|rule| {
+            let matches = match domain {
+                Some(domain) => domain_matches(&rule.domain, domain),
+                None => rule.domain == "*",
+            };
+            matches && (secure || !rule.secure || !https_policy)
+        })
+    }
+
+    /// Whether this socket policy grants movies from `domain` access to
+    /// `port`.
+    ///
+    /// A `domain` of `None` stands for local movies, as with `allows`.
+    pub fn allows_socket(&self, domain: Option<&str>, port: u16) -> bool {
+        if self.meta_policy == MetaPolicy::None {
+            return false;
+        }
+
+        self.rules.iter().any(|rule| {
+            let matches = match domain {
+                Some(domain) => domain_matches(&rule.domain, domain),
+                None => rule.domain == "*",
+            };
+            matches
+                && rule
+                    .to_ports
+                    .as_deref()
+                    .map(|ports| ports_match(ports, port))
+                    .unwrap_or(false)
+        })
+    }
+
+    /// Which other policy files on the same host this policy permits.
+    pub fn meta_policy(&self) -> MetaPolicy {
+        self.meta_policy
+    }
+}
+
+/// Whether `port` is in a `to-ports` list such as `507,516-523`.
+fn ports_match(ports: &str, port: u16) -> bool {
+    ports.split(',').map(str::trim).any(|range| {
+        if range == "*" {
+            return true;
+        }
+        let mut bounds = range
+            .splitn(2, '-')
+            .map(|bound| bound.trim().parse::<u16>());
+        match (bounds.next(), bounds.next()) {
+            (Some(Ok(only)), None) => only == port,
+            (Some(Ok(start)), Some(Ok(end))) => (start..=end).contains(&port),
+            _ => false,
+        }
+    })
+}
+
+/// Place a movie loaded from `url` into a sandbox.
+///
+/// `use_network` is the `UseNetwork` flag from the movie's `FileAttributes`,
+/// which decides whether an untrusted local movie may use the network or the
+/// filesystem.
+pub fn classify(url: Option<&str>, use_network: bool, trust_local_content: bool) -> SandboxType {
+    let is_local = url
+        .and_then(|url| Url::parse(url).ok())
+        .map(|url| url.scheme() == "file")
+        .unwrap_or(true);
+
+    if !is_local {
+        SandboxType::Remote
+    } else if trust_local_content {
+        SandboxType::LocalTrusted
+    } else if use_network {
+        SandboxType::LocalWithNetwork
+    } else {
+        SandboxType::LocalWithFile
+    }
+}
+
+/// Whether a movie in `sandbox_type`, loaded from `movie_url`, may load data
+/// from `target`.
+///
+/// A `target` of `None` couldn't be resolved because the movie has no URL, so
+/// it can only be relative to wherever the movie came from, which is local.
+fn data_access(sandbox_type: SandboxType, movie_url: Option<&str>, target: Option<&Url>) -> Access {
+    let is_local = target
+        .map(|target| target.scheme() == "file")
+        .unwrap_or(true);
+    match (sandbox_type, is_local) {
+        (SandboxType::LocalTrusted, _) | (SandboxType::LocalWithFile, true) => Access::Allowed,
+        (SandboxType::Remote, false) => {
+            let domain = url_domain(movie_url);
+            if target.and_then(Url::host_str) == Some(domain.as_str()) {
+                Access::Allowed
+            } else {
+                Access::RequiresPolicy(Some(domain))
+            }
+        }
+        // Local movies are only granted network access by policy files that
+        // allow every domain.
+        (SandboxType::LocalWithNetwork, false) => Access::RequiresPolicy(None),
+        _ => Access::Denied,
+    }
+}
+
+/// Whether a movie in `sandbox_type`, loaded from `movie_url`, may load
+/// another movie from `target`.
+///
+/// The sandbox decides this just as for other data, except that no policy
+/// file is needed.
+fn movie_access(sandbox_type: SandboxType, movie_url: Option<&str>, target: Option<&Url>) -> bool {
+    data_access(sandbox_type, movie_url, target) != Access::Denied
+}
+
+/// Resolve `url` relative to the URL that `movie` was loaded from.
+fn resolve_url(movie: &SwfMovie, url: &str) -> Option<Url> {
+    movie
+        .url()
+        .and_then(|base| Url::parse(base).ok())
+        .and_then(|base| base.join(url).ok())
+        .or_else(|| Url::parse(url).ok())
+}
+
+/// Whether a movie in `sandbox_type`, loaded from `movie_url`, may open a
+/// socket.
+fn socket_access(sandbox_type: SandboxType, movie_url: Option<&str>) -> Access {
+    match sandbox_type {
+        SandboxType::LocalTrusted => Access::Allowed,
+        SandboxType::Remote => Access::RequiresPolicy(Some(url_domain(movie_url))),
+        // Local movies are only granted access by policies that allow every
+        // domain.
+        SandboxType::LocalWithNetwork => Access::RequiresPolicy(None),
+        SandboxType::LocalWithFile => Access::Denied,
+    }
+}
+
+/// Whether `domain` matches a domain pattern from a policy file or
+/// `allowDomain` call.
+///
+/// `*` matches every domain, and `*.example.com` matches `example.com` and all
+/// of its subdomains.
+pub fn domain_matches(pattern: &str, domain: &str) -> bool {
+    let domain = domain.to_ascii_lowercase();
+    if pattern == "*" {
+        true
+    } else if let Some(parent) = pattern.strip_prefix("*.") {
+        domain == parent || domain.ends_with(&format!(".{}", parent))
+    } else {
+        pattern == domain
+    }
+}
+
+fn is_secure(url: Option<&str>) -> bool {
+    url.and_then(|url| Url::parse(url).ok())
+        .map(|url| url.scheme() == "https")
+        .unwrap_or(false)
+}
+
+/// The policy files that may govern access to `target`, starting with the
+/// master policy at the root of its domain.
+///
+/// Custom policy files only govern their own directory and those below it.
+fn policy_urls_for(target: &Url, custom_urls: &[String]) -> Vec<String> {
+    let mut master = target.clone();
+    master.set_path("/crossdomain.xml");
+    master.set_query(None);
+    master.set_fragment(None);
+
+    let mut policy_urls = vec![master.to_string()];
+    for custom_url in custom_urls {
+        if let Ok(custom) = Url::parse(custom_url) {
+            let directory = &custom.path()[..custom.path().rfind('/').unwrap_or(0) + 1];
+            if custom.origin() == target.origin()
+                && target.path().starts_with(directory)
+                && !policy_urls.contains(custom_url)
+            {
+                policy_urls.push(custom_url.clone());
+            }
+        }
+    }
+    policy_urls
+}
+
+/// Get the policy file at `url`, from the cache or by awaiting `fetch`.
+async fn load_policy(
+    cache: &PolicyCache,
+    url: &str,
+    fetch: Option<OwnedFuture<Vec<u8>, Error>>,
+) -> Option<PolicyFile> {
+    if let Some(policy) = cache.lock().unwrap().get(url) {
+        return policy.clone();
+    }
+
+    let policy = match fetch {
+        Some(fetch) => fetch.await.ok().and_then(|data| PolicyFile::parse(&data)),
+        None => None,
+    };
+    cache
+        .lock()
+        .unwrap()
+        .insert(url.to_string(), policy.clone());
+    policy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_movies() {
+        assert_eq!(
+            classify(Some("https://example.com/movie.swf"), false, true),
+            SandboxType::Remote
+        );
+        assert_eq!(
+            classify(Some("file:///movie.swf"), false, true),
+            SandboxType::LocalTrusted
+        );
+        assert_eq!(
+            classify(Some("file:///movie.swf"), false, false),
+            SandboxType::LocalWithFile
+        );
+        assert_eq!(
+            classify(Some("file:///movie.swf"), true, false),
+            SandboxType::LocalWithNetwork
+        );
+        assert_eq!(classify(None, true, false), SandboxType::LocalWithNetwork);
+    }
+
+    #[test]
+    fn local_content_is_untrusted_by_default() {
+        assert!(!Security::new().trust_local_content);
+    }
+
+    #[test]
+    fn local_with_file_is_denied_network_access() {
+        let movie_url = Some("file:///movies/movie.swf");
+        let sandbox_type = classify(movie_url, false, false);
+        assert_eq!(sandbox_type, SandboxType::LocalWithFile);
+
+        let remote = Url::parse("http://example.com/data.txt").unwrap();
+        let local = Url::parse("file:///movies/data.txt").unwrap();
+        assert_eq!(
+            data_access(sandbox_type, movie_url, Some(&remote)),
+            Access::Denied
+        );
+        assert_eq!(
+            data_access(sandbox_type, movie_url, Some(&local)),
+            Access::Allowed
+        );
+        assert_eq!(socket_access(sandbox_type, movie_url), Access::Denied);
+    }
+
+    #[test]
+    fn local_with_network_is_denied_file_access() {
+        let movie_url = Some("file:///movies/movie.swf");
+        let sandbox_type = classify(movie_url, true, false);
+        assert_eq!(sandbox_type, SandboxType::LocalWithNetwork);
+
+        let remote = Url::parse("http://example.com/data.txt").unwrap();
+        let local = Url::parse("file:///movies/data.txt").unwrap();
+        assert_eq!(
+            data_access(sandbox_type, movie_url, Some(&remote)),
+            Access::RequiresPolicy(None)
+        );
+        assert_eq!(
+            data_access(sandbox_type, movie_url, Some(&local)),
+            Access::Denied
+        );
+        assert_eq!(data_access(sandbox_type, None, None), Access::Denied);
+        assert_eq!(
+            socket_access(sandbox_type, movie_url),
+            Access::RequiresPolicy(None)
+        );
+    }
+
+    #[test]
+    fn movie_loads_need_no_policy_file() {
+        let remote_url = Some("http://example.com/movie.swf");
+        let local_url = Some("file:///movies/movie.swf");
+        let remote = Url::parse("http://movies.com/other.swf").unwrap();
+        let local = Url::parse("file:///movies/other.swf").unwrap();
+
+        let remote_sandbox = classify(remote_url, false, false);
+        assert!(movie_access(remote_sandbox, remote_url, Some(&remote)));
+        assert!(!movie_access(remote_sandbox, remote_url, Some(&local)));
+
+        let local_with_file = classify(local_url, false, false);
+        assert!(!movie_access(local_with_file, local_url, Some(&remote)));
+        assert!(movie_access(local_with_file, local_url, Some(&local)));
+
+        let local_with_network = classify(local_url, true, false);
+        assert!(movie_access(local_with_network, local_url, Some(&remote)));
+        assert!(!movie_access(local_with_network, local_url, Some(&local)));
+    }
+
+    #[test]
+    fn remote_data_access() {
+        let movie_url = Some("http://example.com/movie.swf");
+        let sandbox_type = classify(movie_url, false, false);
+        assert_eq!(sandbox_type, SandboxType::Remote);
+
+        let same_domain = Url::parse("http://example.com/data.txt").unwrap();
+        let other_domain = Url::parse("http://data.com/data.txt").unwrap();
+        let local = Url::parse("file:///data.txt").unwrap();
+        assert_eq!(
+            data_access(sandbox_type, movie_url, Some(&same_domain)),
+            Access::Allowed
+        );
+        assert_eq!(
+            data_access(sandbox_type, movie_url, Some(&other_domain)),
+            Access::RequiresPolicy(Some("example.com".to_string()))
+        );
+        assert_eq!(
+            data_access(sandbox_type, movie_url, Some(&local)),
+            Access::Denied
+        );
+        assert_eq!(
+            socket_access(sandbox_type, movie_url),
+            Access::RequiresPolicy(Some("example.com".to_string()))
+        );
+    }
+
+    #[test]
+    fn match_domains() {
+        assert!(domain_matches("*", "example.com"));
+        assert!(domain_matches("example.com", "Example.com"));
+        assert!(domain_matches("*.example.com", "example.com"));
+        assert!(domain_matches("*.example.com", "www.example.com"));
+        assert!(!domain_matches("*.example.com", "badexample.com"));
+        assert!(!domain_matches("example.com", "www.example.com"));
+    }
+
+    #[test]
+    fn parse_policy_file() {
+        let policy = PolicyFile::parse(
+            br#"<?xml version="1.0"?>
+            <!DOCTYPE cross-domain-policy SYSTEM "http://www.adobe.com/xml/dtds/cross-domain-policy.dtd">
+            <cross-domain-policy>
+                <site-control permitted-cross-domain-policies="master-only"/>
+                <allow-access-from domain="*.example.com"/>
+                <allow-access-from domain="insecure.org" secure="false"/>
+            </cross-domain-policy>"#,
+        )
+        .unwrap();
+
+        assert_eq!(policy.meta_policy, MetaPolicy::MasterOnly);
+        let http = "http://data.com/crossdomain.xml";
+        let https = "https://data.com/crossdomain.xml";
+        assert!(policy.allows(Some("www.example.com"), false, http));
+        assert!(!policy.allows(Some("www.example.com"), false, https));
+        assert!(policy.allows(Some("www.example.com"), true, https));
+        assert!(policy.allows(Some("insecure.org"), false, https));
+        assert!(!policy.allows(Some("other.com"), true, http));
+        assert!(!policy.allows(None, true, http));
+
+        assert_eq!(PolicyFile::parse(b"<html></html>"), None);
+        assert_eq!(PolicyFile::parse(b"not xml at all"), None);
+    }
+
+    #[test]
+    fn parse_socket_policy_file() {
+        let policy = PolicyFile::parse(
+            br#"<cross-domain-policy>
+                <allow-access-from domain="*.example.com" to-ports="507,516-523"/>
+                <allow-access-from domain="*" to-ports="*"/>
+                <allow-access-from domain="noports.com"/>
+            </cross-domain-policy>"#,
+        )
+        .unwrap();
+        assert!(policy.allows_socket(Some("www.example.com"), 507));
+        assert!(policy.allows_socket(Some("www.example.com"), 520));
+        assert!(policy.allows_socket(None, 1));
+
+        let policy = PolicyFile::parse(
+            br#"<cross-domain-policy>
+                <allow-access-from domain="example.com" to-ports="507,516-523"/>
+                <allow-access-from domain="noports.com"/>
+            </cross-domain-policy>"#,
+        )
+        .unwrap();
+        assert!(policy.allows_socket(Some("example.com"), 516));
+        assert!(!policy.allows_socket(Some("example.com"), 508));
+        assert!(!policy.allows_socket(Some("example.com"), 524));
+        assert!(!policy.allows_socket(Some("noports.com"), 507));
+        assert!(!policy.allows_socket(None, 507));
+    }
+
+    #[test]
+    fn socket_policy_locations() {
+        let mut security = Security::new();
+        assert_eq!(
+            security.socket_policy_locations("example.com", 5000),
+            vec![
+                ("example.com".to_string(), 843),
+                ("example.com".to_string(), 5000)
+            ]
+        );
+        assert_eq!(
+            security.socket_policy_locations("example.com", 843),
+            vec![("example.com".to_string(), 843)]
+        );
+
+        security.load_policy_file("xmlsocket://example.com:1234".to_string());
+        security.load_policy_file("xmlsocket://other.com:1234".to_string());
+        security.load_policy_file("http://example.com/crossdomain.xml".to_string());
+        assert_eq!(
+            security.socket_policy_locations("example.com", 5000),
+            vec![
+                ("example.com".to_string(), 843),
+                ("example.com".to_string(), 1234),
+                ("example.com".to_string(), 5000)
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_policy_urls() {
+        let target = Url::parse("http://data.com/api/v1/items?id=1").unwrap();
+        let custom = vec![
+            "http://data.com/api/policy.xml".to_string(),
+            "http://data.com/other/policy.xml".to_string(),
+            "http://elsewhere.com/api/policy.xml".to_string(),
+        ];
+        assert_eq!(
+            policy_urls_for(&target, &custom),
+            vec![
+                "http://data.com/crossdomain.xml".to_string(),
+                "http://data.com/api/policy.xml".to_string(),
+            ]
+        );
+    }
+}
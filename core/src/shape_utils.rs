@@ -126,6 +126,205 @@ impl DrawCommand {
     }
 }
 
+/// An owned copy of a `DistilledShape`.
+///
+/// Render backends keep this around for shapes with strokes, so that they can
+/// tessellate the shape again when it is drawn at a different scale.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OwnedShape {
+    paths: Vec<OwnedDrawPath>,
+    shape_bounds: BoundingBox,
+    edge_bounds: BoundingBox,
+    id: CharacterId,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum OwnedDrawPath {
+    Stroke {
+        style: LineStyle,
+        is_closed: bool,
+        commands: Vec<DrawCommand>,
+    },
+    Fill {
+        style: FillStyle,
+        commands: Vec<DrawCommand>,
+    },
+}
+
+impl OwnedShape {
+    pub fn as_distilled(&self) -> DistilledShape<'_> {
+        DistilledShape {
+            paths: self
+                .paths
+                .iter()
+                .map(|path| match path {
+                    OwnedDrawPath::Stroke {
+                        style,
+                        is_closed,
+                        commands,
+                    } => DrawPath::Stroke {
+                        style,
+                        is_closed: *is_closed,
+                        commands: commands.clone(),
+                    },
+                    OwnedDrawPath::Fill { style, commands } => DrawPath::Fill {
+                        style,
+                        commands: commands.clone(),
+                    },
+                })
+                .collect(),
+            shape_bounds: self.shape_bounds.clone(),
+            edge_bounds: self.edge_bounds.clone(),
+            id: self.id,
+        }
+    }
+}
+
+impl From<&DistilledShape<'_>> for OwnedShape {
+    fn from(shape: &DistilledShape<'_>) -> Self {
+        Self {
+            paths: shape
+                .paths
+                .iter()
+                .map(|path| match path {
+                    DrawPath::Stroke {
+                        style,
+                        is_closed,
+                        commands,
+                    } => OwnedDrawPath::Stroke {
+                        style: (*style).clone(),
+                        is_closed: *is_closed,
+                        commands: commands.clone(),
+                    },
+                    DrawPath::Fill { style, commands } => OwnedDrawPath::Fill {
+                        style: (*style).clone(),
+                        commands: commands.clone(),
+                    },
+                })
+                .collect(),
+            shape_bounds: shape.shape_bounds.clone(),
+            edge_bounds: shape.edge_bounds.clone(),
+            id: shape.id,
+        }
+    }
+}
+
+/// The scale that the strokes of a shape are tessellated for, in device
+/// pixels per shape pixel along each axis.
+///
+/// Scales are rounded to steps of `1 / STROKE_SCALE_STEPS` octaves, so that a
+/// backend can cache one tessellation per step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StrokeScale {
+    x: i16,
+    y: i16,
+}
+
+const STROKE_SCALE_STEPS: f32 = 8.0;
+
+impl StrokeScale {
+    /// The scale that a shape is tessellated for when it is registered.
+    pub const IDENTITY: Self = Self { x: 0, y: 0 };
+
+    /// The scale of a shape drawn with `matrix`.
+    pub fn from_matrix(matrix: &swf::Matrix) -> Self {
+        fn step(scale: f32) -> i16 {
+            if scale > 0.0 && scale.is_finite() {
+                (scale.log2() * STROKE_SCALE_STEPS).round() as i16
+            } else {
+                0
+            }
+        }
+        Self {
+            x: step(f32::hypot(matrix.a, matrix.b)),
+            y: step(f32::hypot(matrix.c, matrix.d)),
+        }
+    }
+
+    pub fn x(self) -> f32 {
+        f32::exp2(f32::from(self.x) / STROKE_SCALE_STEPS)
+    }
+
+    pub fn y(self) -> f32 {
+        f32::exp2(f32::from(self.y) / STROKE_SCALE_STEPS)
+    }
+
+    fn average(self) -> f32 {
+        (self.x() + self.y()) / 2.0
+    }
+}
+
+/// The width of a stroke in shape pixels, when tessellated for `scale`.
+///
+/// The scale flags of `style` decide how the width follows the scale of the
+/// shape, and strokes are never thinner than one device pixel. Hairlines,
+/// which have a width of 0, are always exactly one device pixel wide.
+pub fn stroke_width(style: &LineStyle, scale: StrokeScale) -> f32 {
+    let width = style.width.to_pixels() as f32;
+    let device_width = match (style.allow_scale_x, style.allow_scale_y) {
+        (true, true) => width * scale.average(),
+        (true, false) => width * scale.x(),
+        (false, true) => width * scale.y(),
+        (false, false) => width,
+    };
+    device_width.max(1.0) / scale.average()
+}
+
+/// How the strokes of a shape depend on the scale that it is drawn at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeScaling {
+    /// Whether any stroke ignores the scale in some direction, or is a hairline.
+    has_fixed_strokes: bool,
+
+    /// The width of the thinnest stroke, in shape pixels.
+    min_width: Option<f32>,
+}
+
+impl StrokeScaling {
+    pub fn from_shape(shape: &DistilledShape<'_>) -> Self {
+        let mut scaling = Self {
+            has_fixed_strokes: false,
+            min_width: None,
+        };
+        for path in &shape.paths {
+            if let DrawPath::Stroke { style, .. } = path {
+                let width = style.width.to_pixels() as f32;
+                if width <= 0.0 || !style.allow_scale_x || !style.allow_scale_y {
+                    scaling.has_fixed_strokes = true;
+                }
+                scaling.min_width = Some(scaling.min_width.map_or(width, |w| w.min(width)));
+            }
+        }
+        scaling
+    }
+
+    /// Whether the shape has any strokes, and so may need to be tessellated again.
+    pub fn has_strokes(&self) -> bool {
+        self.min_width.is_some()
+    }
+
+    /// The scale to tessellate the shape for when it is drawn with `matrix`.
+    ///
+    /// Strokes that follow the scale of the shape and stay at least one device
+    /// pixel wide look the same at any scale, so these shapes use the
+    /// tessellation made at registration.
+    pub fn stroke_scale(&self, matrix: &swf::Matrix) -> StrokeScale {
+        let min_width = match self.min_width {
+            Some(min_width) => min_width,
+            None => return StrokeScale::IDENTITY,
+        };
+        let scale = StrokeScale::from_matrix(matrix);
+        if !self.has_fixed_strokes
+            && min_width >= 1.0
+            && min_width * scale.x().min(scale.y()) >= 1.0
+        {
+            StrokeScale::IDENTITY
+        } else {
+            scale
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 struct Point {
     x: Twips,
@@ -710,4 +909,64 @@ mod tests {
         }];
         assert_eq!(commands, expected);
     }
+
+    fn line_style(width: f64, allow_scale_x: bool, allow_scale_y: bool) -> LineStyle {
+        LineStyle {
+            allow_scale_x,
+            allow_scale_y,
+            ..LineStyle::new_v1(Twips::from_pixels(width), swf::Color::from_rgb(0, 255))
+        }
+    }
+
+    /// Strokes keep their width in device pixels according to their scale flags.
+    #[test]
+    fn stroke_width_scale_modes() {
+        let zoomed = StrokeScale::from_matrix(&swf::Matrix::scale(4.0, 4.0));
+        let normal = line_style(2.0, true, true);
+        let no_scale = line_style(2.0, false, false);
+        let hairline = line_style(0.0, true, true);
+
+        assert!((stroke_width(&normal, StrokeScale::IDENTITY) - 2.0).abs() < 0.001);
+        assert!((stroke_width(&normal, zoomed) - 2.0).abs() < 0.001);
+        assert!((stroke_width(&no_scale, zoomed) - 0.5).abs() < 0.001);
+        assert!((stroke_width(&hairline, StrokeScale::IDENTITY) - 1.0).abs() < 0.001);
+        assert!((stroke_width(&hairline, zoomed) - 0.25).abs() < 0.001);
+    }
+
+    /// Shapes whose strokes look the same at any scale don't need a new tessellation.
+    #[test]
+    fn stroke_scaling() {
+        let shrunk = swf::Matrix::scale(0.25, 0.25);
+        let zoomed = swf::Matrix::scale(4.0, 4.0);
+
+        let line_styles = [line_style(2.0, true, true)];
+        let shape = build_shape(vec![]);
+        let mut distilled = DistilledShape::from(&shape);
+        distilled.paths = vec![DrawPath::Stroke {
+            style: &line_styles[0],
+            is_closed: false,
+            commands: vec![],
+        }];
+        let scaling = StrokeScaling::from_shape(&distilled);
+        assert!(scaling.has_strokes());
+        assert_eq!(scaling.stroke_scale(&zoomed), StrokeScale::IDENTITY);
+        assert_ne!(scaling.stroke_scale(&shrunk), StrokeScale::IDENTITY);
+
+        let line_styles = [line_style(2.0, false, false)];
+        distilled.paths = vec![DrawPath::Stroke {
+            style: &line_styles[0],
+            is_closed: false,
+            commands: vec![],
+        }];
+        let scaling = StrokeScaling::from_shape(&distilled);
+        assert_eq!(
+            scaling.stroke_scale(&zoomed),
+            StrokeScale::from_matrix(&zoomed)
+        );
+
+        distilled.paths = vec![];
+        let scaling = StrokeScaling::from_shape(&distilled);
+        assert!(!scaling.has_strokes());
+        assert_eq!(scaling.stroke_scale(&shrunk), StrokeScale::IDENTITY);
+    }
 }
@@ -1,7 +1,7 @@
 use crate::backend::navigator::url_from_relative_path;
 use gc_arena::Collect;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use swf::{Header, TagCode};
 
 pub type Error = Box<dyn std::error::Error>;
@@ -17,10 +17,20 @@ pub struct SwfMovie {
     header: Header,
 
     /// Uncompressed SWF data.
-    data: Vec<u8>,
+    data: SwfData,
+
+    /// The length of the uncompressed SWF data according to the header.
+    expected_length: usize,
+
+    /// The length of the uncompressed SWF header, which precedes `data`.
+    header_length: usize,
 
     /// The URL the SWF was downloaded from.
     url: Option<String>,
+
+    /// Whether the `FileAttributes` tag asks for network access when the
+    /// movie is played locally.
+    use_network_sandbox: bool,
 }
 
 impl SwfMovie {
@@ -34,8 +44,11 @@ impl SwfMovie {
                 frame_rate: 1.0,
                 num_frames: 0,
             },
-            data: vec![],
+            data: vec![].into(),
+            expected_length: 0,
+            header_length: 0,
             url: None,
+            use_network_sandbox: false,
         }
     }
 
@@ -47,8 +60,11 @@ impl SwfMovie {
     pub fn from_movie_and_subdata(&self, data: Vec<u8>, source: &SwfMovie) -> Self {
         Self {
             header: self.header.clone(),
-            data,
+            expected_length: data.len(),
+            data: data.into(),
+            header_length: self.header_length,
             url: source.url.clone(),
+            use_network_sandbox: source.use_network_sandbox,
         }
     }
 
@@ -66,31 +82,81 @@ impl SwfMovie {
 
     /// Construct a movie based on the contents of the SWF datastream.
     pub fn from_data(swf_data: &[u8], url: Option<String>) -> Result<Self, Error> {
-        let swf_stream = swf::read::read_swf_header(&swf_data[..])?;
-        let header = swf_stream.header;
-        let mut reader = swf_stream.reader;
-
-        // Decompress the entire SWF in memory.
-        // Sometimes SWFs will have an incorrectly compressed stream,
-        // but will otherwise decompress fine up to the End tag.
+        // Sometimes SWFs will have an incorrectly compressed stream or
+        // header length, but will otherwise decompress fine up to the End tag.
         // So just warn on this case and try to continue gracefully.
-        let data = if header.compression == swf::Compression::Lzma {
-            // TODO: The LZMA decoder is still funky.
-            // It always errors, and doesn't return all the data if you use read_to_end,
-            // but read_exact at least returns the data... why?
-            // Does the decoder need to be flushed somehow?
-            let mut data = vec![0u8; swf_stream.uncompressed_length];
-            let _ = reader.get_mut().read_exact(&mut data);
-            data
-        } else {
-            let mut data = Vec::with_capacity(swf_stream.uncompressed_length);
-            if let Err(e) = reader.get_mut().read_to_end(&mut data) {
-                return Err(format!("Error decompressing SWF, may be corrupt: {}", e).into());
-            }
-            data
+        let swf_buf = swf::read::decompress_swf(&swf_data[..])?;
+        for diagnostic in &swf_buf.diagnostics {
+            log::warn!("{}", diagnostic);
+        }
+        let header = swf_buf.header;
+        let data = swf_buf.data;
+
+        let use_network_sandbox = read_file_attributes(&data, header.version)
+            .map(|attributes| attributes.use_network_sandbox)
+            .unwrap_or(false);
+
+        Ok(Self {
+            header,
+            expected_length: data.len(),
+            data: data.into(),
+            header_length: swf_buf.header_length,
+            url,
+            use_network_sandbox,
+        })
+    }
+
+    /// Construct a movie from the start of a SWF datastream that is still
+    /// being downloaded.
+    ///
+    /// The rest of the data is added with `append_data` as it arrives. Returns
+    /// `None` until enough of the movie has arrived to read its header and
+    /// `FileAttributes` tag, unless `finished` says that no more will arrive.
+    pub fn from_partial_data(
+        decompressor: &swf::read::SwfDecompressor,
+        url: Option<String>,
+        finished: bool,
+    ) -> Option<Self> {
+        let header = decompressor.header()?.clone();
+        let data = decompressor.data();
+        // A `FileAttributes` tag is 6 bytes long.
+        if data.len() < 6 && !finished {
+            return None;
+        }
+
+        let use_network_sandbox = read_file_attributes(data, header.version)
+            .map(|attributes| attributes.use_network_sandbox)
+            .unwrap_or(false);
+
+        let movie = Self {
+            header,
+            data: SwfData::with_capacity(decompressor.data_length()),
+            expected_length: decompressor.data_length(),
+            header_length: decompressor.header_length()?,
+            url,
+            use_network_sandbox,
         };
+        movie.append_data(data);
+        Some(movie)
+    }
+
+    /// Add more data to a movie that is being downloaded.
+    ///
+    /// All of the data is kept, even if the movie turns out to be longer than
+    /// its header says.
+    pub fn append_data(&self, data: &[u8]) {
+        self.data.append(data)
+    }
 
-        Ok(Self { header, data, url })
+    /// Mark a movie as fully downloaded, even if it has less data than its
+    /// header says.
+    pub fn finish_loading(&self) {
+        self.data.finish();
+    }
+
+    /// Whether all of the movie has been downloaded.
+    pub fn is_loaded(&self) -> bool {
+        self.data.is_finished()
     }
 
     pub fn header(&self) -> &Header {
@@ -102,8 +168,36 @@ impl SwfMovie {
         self.header.version
     }
 
-    pub fn data(&self) -> &[u8] {
-        &self.data
+    /// The uncompressed data of the movie that has been loaded so far.
+    ///
+    /// This is a snapshot: data that loads later isn't added to it.
+    pub fn data(&self) -> Arc<Vec<u8>> {
+        self.data.get()
+    }
+
+    /// The length of the uncompressed data of the whole movie, as far as is
+    /// known.
+    ///
+    /// This is the length given in the header, unless more data than that
+    /// has been received.
+    pub fn data_length(&self) -> usize {
+        self.expected_length.max(self.data.len())
+    }
+
+    /// The length of the uncompressed SWF header, which precedes `data`.
+    pub fn header_length(&self) -> usize {
+        self.header_length
+    }
+
+    /// The number of uncompressed bytes of the movie loaded so far, including
+    /// the header.
+    pub fn bytes_loaded(&self) -> usize {
+        self.header_length + self.data.len()
+    }
+
+    /// The uncompressed length of the whole movie, including the header.
+    pub fn bytes_total(&self) -> usize {
+        self.header_length + self.data_length()
     }
 
     pub fn width(&self) -> u32 {
@@ -118,6 +212,124 @@ impl SwfMovie {
     pub fn url(&self) -> Option<&str> {
         self.url.as_deref()
     }
+
+    /// Whether this SWF may use the network, rather than the filesystem, when
+    /// played locally without being trusted.
+    pub fn use_network_sandbox(&self) -> bool {
+        self.use_network_sandbox
+    }
+}
+
+/// Uncompressed SWF data that may still be arriving.
+///
+/// Readers take a snapshot of the data loaded so far, which never changes.
+/// Appending publishes a new, longer buffer instead of writing to one that may
+/// be in use. While snapshots of the published buffer are held, appended data
+/// is held back until it makes the buffer at least half as long again, so
+/// that streaming a movie only copies it a few times over.
+#[derive(Debug)]
+struct SwfData(Mutex<SwfDataState>);
+
+#[derive(Debug, Clone)]
+struct SwfDataState {
+    /// The data that readers can see.
+    published: Arc<Vec<u8>>,
+
+    /// Data that has been appended, but not published yet.
+    pending: Vec<u8>,
+
+    /// How long the data is expected to be, which the published buffer
+    /// reserves room for.
+    capacity: usize,
+
+    /// Whether no more data will be appended.
+    finished: bool,
+}
+
+impl SwfData {
+    fn with_capacity(capacity: usize) -> Self {
+        Self(Mutex::new(SwfDataState {
+            published: Arc::new(Vec::with_capacity(capacity)),
+            pending: vec![],
+            capacity,
+            finished: false,
+        }))
+    }
+
+    /// A snapshot of the data published so far.
+    fn get(&self) -> Arc<Vec<u8>> {
+        self.0.lock().unwrap().published.clone()
+    }
+
+    /// The length of all of the data appended so far, published or not.
+    fn len(&self) -> usize {
+        let state = self.0.lock().unwrap();
+        state.published.len() + state.pending.len()
+    }
+
+    fn append(&self, data: &[u8]) {
+        let mut state = self.0.lock().unwrap();
+        state.pending.extend_from_slice(data);
+        if let Some(published) = Arc::get_mut(&mut state.published) {
+            // Nobody else can see the buffer, so it can grow in place.
+            published.extend_from_slice(data);
+            state.pending.clear();
+        } else if state.pending.len() >= state.published.len() / 2 {
+            state.publish();
+        }
+    }
+
+    fn finish(&self) {
+        let mut state = self.0.lock().unwrap();
+        if !state.pending.is_empty() {
+            state.publish();
+        }
+        state.finished = true;
+    }
+
+    fn is_finished(&self) -> bool {
+        self.0.lock().unwrap().finished
+    }
+}
+
+impl SwfDataState {
+    /// Publish a new buffer holding the pending data.
+    fn publish(&mut self) {
+        let len = self.published.len() + self.pending.len();
+        let mut published = Vec::with_capacity(self.capacity.max(len));
+        published.extend_from_slice(&self.published);
+        published.append(&mut self.pending);
+        self.published = Arc::new(published);
+    }
+}
+
+impl From<Vec<u8>> for SwfData {
+    fn from(data: Vec<u8>) -> Self {
+        Self(Mutex::new(SwfDataState {
+            capacity: data.len(),
+            published: Arc::new(data),
+            pending: vec![],
+            finished: true,
+        }))
+    }
+}
+
+impl Clone for SwfData {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.0.lock().unwrap().clone()))
+    }
+}
+
+/// Read the `FileAttributes` tag, which must be the first tag of the movie if
+/// it is present.
+fn read_file_attributes(data: &[u8], version: u8) -> Option<swf::FileAttributes> {
+    let mut reader = SwfStream::new(std::io::Cursor::new(data), version);
+    let (tag_code, _) = reader.read_tag_code_and_length().ok()?;
+    if TagCode::from_u16(tag_code) == Some(TagCode::FileAttributes) {
+        reader.read_file_attributes().ok()
+    } else {
+        None
+    }
 }
 
 /// A shared-ownership reference to some portion of an SWF datastream.
@@ -125,16 +337,26 @@ impl SwfMovie {
 #[collect(no_drop)]
 pub struct SwfSlice {
     pub movie: Arc<SwfMovie>,
+
+    /// The data of `movie` that had loaded when this slice was made.
+    pub buffer: Arc<Vec<u8>>,
+
     pub start: usize,
     pub end: usize,
 }
 
 impl From<Arc<SwfMovie>> for SwfSlice {
+    /// Slice the whole movie, including any data that has yet to load.
+    ///
+    /// Only the data that has loaded so far can be read from the slice. Make
+    /// a new slice to read data that loads later.
     fn from(movie: Arc<SwfMovie>) -> Self {
-        let end = movie.data().len();
+        let buffer = movie.data();
+        let end = movie.data_length();
 
         Self {
             movie,
+            buffer,
             start: 0,
             end,
         }
@@ -144,7 +366,7 @@ impl From<Arc<SwfMovie>> for SwfSlice {
 impl AsRef<[u8]> for SwfSlice {
     #[inline]
     fn as_ref(&self) -> &[u8] {
-        &self.movie.data()[self.start..self.end]
+        self.data()
     }
 }
 
@@ -154,6 +376,7 @@ impl SwfSlice {
     pub fn empty(movie: Arc<SwfMovie>) -> Self {
         Self {
             movie,
+            buffer: Arc::new(vec![]),
             start: 0,
             end: 0,
         }
@@ -165,9 +388,11 @@ impl SwfSlice {
     /// reattach the SWF data to a fresh movie and return a new slice into it.
     pub fn owned_subslice(&self, data: Vec<u8>, source: &SwfMovie) -> Self {
         let len = data.len();
+        let movie = Arc::new(self.movie.from_movie_and_subdata(data, source));
 
         Self {
-            movie: Arc::new(self.movie.from_movie_and_subdata(data, source)),
+            buffer: movie.data(),
+            movie,
             start: 0,
             end: len,
         }
@@ -178,12 +403,13 @@ impl SwfSlice {
     /// This function returns None if the given slice is not a subslice of the
     /// current slice.
     pub fn to_subslice(&self, slice: &[u8]) -> Option<SwfSlice> {
-        let self_pval = self.movie.data().as_ptr() as usize;
+        let self_pval = self.buffer.as_ptr() as usize;
         let slice_pval = slice.as_ptr() as usize;
 
         if (self_pval + self.start) <= slice_pval && slice_pval < (self_pval + self.end) {
             Some(SwfSlice {
                 movie: self.movie.clone(),
+                buffer: self.buffer.clone(),
                 start: slice_pval - self_pval,
                 end: (slice_pval - self_pval) + slice.len(),
             })
@@ -203,21 +429,20 @@ impl SwfSlice {
     /// movie, or the given reader refers to a different underlying movie, this
     /// function returns None.
     pub fn resize_to_reader(&self, reader: &mut SwfStream<&[u8]>, size: usize) -> Option<SwfSlice> {
-        if self.movie.data().as_ptr() as usize <= reader.get_ref().get_ref().as_ptr() as usize
-            && (reader.get_ref().get_ref().as_ptr() as usize)
-                < self.movie.data().as_ptr() as usize + self.movie.data().len()
-        {
-            let outer_offset =
-                reader.get_ref().get_ref().as_ptr() as usize - self.movie.data().as_ptr() as usize;
+        let buffer_pval = self.buffer.as_ptr() as usize;
+        let reader_pval = reader.get_ref().get_ref().as_ptr() as usize;
+        if buffer_pval <= reader_pval && reader_pval < buffer_pval + self.buffer.len() {
+            let outer_offset = reader_pval - buffer_pval;
             let inner_offset = reader.get_ref().position() as usize;
             let new_start = outer_offset + inner_offset;
             let new_end = outer_offset + inner_offset + size;
 
-            let len = self.movie.data().len();
+            let len = self.buffer.len();
 
-            if new_start < len && new_end < len {
+            if new_start < len && new_end <= len {
                 Some(SwfSlice {
                     movie: self.movie.clone(),
+                    buffer: self.buffer.clone(),
                     start: new_start,
                     end: new_end,
                 })
@@ -240,15 +465,18 @@ impl SwfSlice {
         let new_end = self.start + end;
 
         if new_start <= new_end {
-            self.to_subslice(&self.movie.data().get(new_start..new_end)?)
+            self.to_subslice(self.buffer.get(new_start..new_end)?)
         } else {
             None
         }
     }
 
     /// Convert the SwfSlice into a standard data slice.
+    ///
+    /// Only the part of the slice that has loaded is returned.
     pub fn data(&self) -> &[u8] {
-        &self.movie.data()[self.start..self.end]
+        let len = self.buffer.len();
+        &self.buffer[self.start.min(len)..self.end.min(len)]
     }
 
     /// Get the version of the SWF this data comes from.
@@ -277,8 +505,22 @@ where
 {
     use std::io::{Seek, SeekFrom};
     loop {
-        let (tag_code, tag_len) = reader.read_tag_code_and_length()?;
+        // A tag that runs past the end of the data may not have loaded yet, so
+        // leave the reader at its start to try it again later.
+        let start_pos = reader.get_ref().position();
+        let data_len = reader.get_ref().get_ref().as_ref().len() as u64;
+        let (tag_code, tag_len) = match reader.read_tag_code_and_length() {
+            Ok(tag) => tag,
+            Err(e) => {
+                reader.get_mut().seek(SeekFrom::Start(start_pos))?;
+                return Err(e.into());
+            }
+        };
         let end_pos = reader.get_ref().position() + tag_len as u64;
+        if end_pos > data_len {
+            reader.get_mut().seek(SeekFrom::Start(start_pos))?;
+            return Err("Tag extends past the end of the data".into());
+        }
 
         let tag = TagCode::from_u16(tag_code);
         if let Some(tag) = tag {
@@ -301,3 +543,130 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_swf_data() {
+        let data = SwfData::with_capacity(4);
+        assert_eq!(data.get().as_slice(), b"");
+        data.append(b"ab");
+        assert_eq!(data.get().as_slice(), b"ab");
+        let loaded = data.get();
+
+        // Appending more doesn't change a snapshot that was taken already.
+        data.append(b"c");
+        assert_eq!(loaded.as_slice(), b"ab");
+        assert_eq!(data.get().as_slice(), b"abc");
+        assert_eq!(data.len(), 3);
+        assert!(!data.is_finished());
+
+        let copy = data.clone();
+        assert_eq!(copy.get().as_slice(), b"abc");
+        assert!(!copy.is_finished());
+
+        data.finish();
+        assert!(data.is_finished());
+        assert!(data.clone().is_finished());
+    }
+
+    #[test]
+    fn append_swf_data_past_capacity() {
+        let data = SwfData::with_capacity(4);
+        data.append(b"abc");
+        data.append(b"def");
+        data.append(b"g");
+        assert_eq!(data.get().as_slice(), b"abcdefg");
+        assert_eq!(data.len(), 7);
+    }
+
+    #[test]
+    fn append_swf_data_while_snapshot_is_held() {
+        let data = SwfData::with_capacity(0);
+        data.append(b"abcd");
+        let loaded = data.get();
+
+        // Small appends are held back until they are worth copying the
+        // buffer for.
+        data.append(b"e");
+        assert_eq!(data.get().as_slice(), b"abcd");
+        assert_eq!(data.len(), 5);
+        data.append(b"f");
+        assert_eq!(data.get().as_slice(), b"abcdef");
+        assert_eq!(loaded.as_slice(), b"abcd");
+
+        data.append(b"g");
+        data.finish();
+        assert_eq!(data.get().as_slice(), b"abcdefg");
+    }
+
+    #[test]
+    fn swf_data_from_vec() {
+        let data = SwfData::from(b"abc".to_vec());
+        assert_eq!(data.get().as_slice(), b"abc");
+        assert!(data.is_finished());
+    }
+
+    #[test]
+    fn stream_movie() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../swf/tests/swfs/DefineSprite.swf"
+        );
+        let swf = std::fs::read(path).unwrap();
+        let full_movie = SwfMovie::from_data(&swf, None).unwrap();
+
+        let mut decompressor = swf::read::SwfDecompressor::new();
+        decompressor.push(&swf[..8]).unwrap();
+        assert!(SwfMovie::from_partial_data(&decompressor, None, false).is_none());
+
+        decompressor.push(&swf[8..40]).unwrap();
+        let movie = SwfMovie::from_partial_data(&decompressor, None, false).unwrap();
+        assert_eq!(movie.header(), full_movie.header());
+        assert_eq!(movie.data().as_slice(), decompressor.data());
+        assert_eq!(movie.data_length(), full_movie.data().len());
+        assert_eq!(movie.bytes_loaded(), 40);
+        assert_eq!(movie.bytes_total(), swf.len());
+        assert!(!movie.is_loaded());
+
+        decompressor.push(&swf[40..]).unwrap();
+        movie.append_data(&decompressor.data()[movie.data().len()..]);
+        assert_eq!(movie.data(), full_movie.data());
+        assert_eq!(movie.bytes_loaded(), swf.len());
+        assert!(!movie.is_loaded());
+
+        movie.finish_loading();
+        assert!(movie.is_loaded());
+    }
+
+    #[test]
+    fn stream_movie_longer_than_header() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../swf/tests/swfs/DefineSprite.swf"
+        );
+        let mut swf = std::fs::read(path).unwrap();
+        let full_movie = SwfMovie::from_data(&swf, None).unwrap();
+        let full_length = swf.len();
+        // Understate the length of the movie in its header.
+        let length = (full_length as u32 - 10).to_le_bytes();
+        swf[4..8].copy_from_slice(&length);
+
+        let mut decompressor = swf::read::SwfDecompressor::new();
+        decompressor.push(&swf[..40]).unwrap();
+        let movie = SwfMovie::from_partial_data(&decompressor, None, false).unwrap();
+        assert_eq!(movie.bytes_total(), full_length - 10);
+
+        decompressor.push(&swf[40..]).unwrap();
+        movie.append_data(&decompressor.data()[movie.data().len()..]);
+        movie.finish_loading();
+        assert_eq!(movie.data(), full_movie.data());
+        assert_eq!(movie.bytes_loaded(), full_length);
+        assert_eq!(movie.bytes_total(), full_length);
+
+        let slice = SwfSlice::from(Arc::new(movie));
+        assert_eq!(slice.data(), full_movie.data().as_slice());
+    }
+}
@@ -3,17 +3,24 @@
 //! Trace output can be compared with correct output from the official Flash Payer.
 
 use approx::assert_abs_diff_eq;
-use log::{Metadata, Record};
-use ruffle_core::backend::navigator::{NullExecutor, NullNavigatorBackend};
+use ruffle_core::backend::local_connection::LocalConnectionHub;
+use ruffle_core::backend::log::MemoryLogBackend;
+use ruffle_core::backend::navigator::{
+    ChunkStream, NavigationMethod, NavigatorBackend, NullExecutor, NullNavigatorBackend,
+    OwnedFuture, RequestOptions,
+};
 use ruffle_core::backend::storage::MemoryStorageBackend;
 use ruffle_core::backend::{
     audio::NullAudioBackend, input::NullInputBackend, render::NullRenderer,
 };
+use ruffle_core::loader::Error as LoaderError;
 use ruffle_core::tag_utils::SwfMovie;
 use ruffle_core::Player;
-use std::cell::RefCell;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 type Error = Box<dyn std::error::Error>;
 
@@ -200,6 +207,7 @@ swf_tests! {
     (xml_load, "avm1/xml_load", 1),
     (with_return, "avm1/with_return", 1),
     (watch, "avm1/watch", 1),
+    (wait_for_frame, "avm1/wait_for_frame", 1),
     #[ignore] (watch_virtual_property, "avm1/watch_virtual_property", 1),
     (cross_movie_root, "avm1/cross_movie_root", 5),
     (roots_and_levels, "avm1/roots_and_levels", 1),
@@ -315,6 +323,41 @@ swf_tests_approx! {
     (as3_coerce_string_precision, "avm2/coerce_string_precision", 1, 10_000_000.0),
 }
 
+#[test]
+fn mcl_loadclip_in_chunks() -> Result<(), Error> {
+    // The 68 byte child movie arrives in 5 chunks, each reported by an
+    // `onLoadProgress` event.
+    let trace_log = run_swf_with_chunk_size("tests/swfs/avm1/mcl_loadclip/test.swf", 11, Some(16))?;
+    assert_eq!(
+        trace_log,
+        "Event: onLoadStart\n".to_string()
+            + &"Event: onLoadProgress\n".repeat(5)
+            + "Event: onLoadComplete\nChild movie loaded!\nEvent: onLoadInit\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn local_connection_between_players() -> Result<(), Error> {
+    // Movies in different players, such as different windows of the desktop
+    // player, reach each other through a hub that the players share.
+    let hub = LocalConnectionHub::new();
+    let receiver = hub_player("tests/swfs/avm1/local_connection_hub/receiver.swf", &hub)?;
+    let sender = hub_player("tests/swfs/avm1/local_connection_hub/sender.swf", &hub)?;
+
+    for _ in 0..3 {
+        receiver.lock().unwrap().run_frame();
+        sender.lock().unwrap().run_frame();
+    }
+
+    assert_eq!(
+        trace_output(&receiver),
+        "connect: true\nreceived: hello 2\n"
+    );
+    assert_eq!(trace_output(&sender), "send: true\n");
+    Ok(())
+}
+
 /// Wrapper around string slice that makes debug output `{:?}` to print string same way as `{}`.
 /// Used in different `assert*!` macros in combination with `pretty_assertions` crate to make
 /// test failures to show nice diffs.
@@ -389,19 +432,39 @@ fn test_swf_approx(
 /// Loads an SWF and runs it through the Ruffle core for a number of frames.
 /// Tests that the trace output matches the given expected output.
 fn run_swf(swf_path: &str, num_frames: u32) -> Result<String, Error> {
-    let _ = log::set_logger(&TRACE_LOGGER).map(|()| log::set_max_level(log::LevelFilter::Info));
+    run_swf_with_chunk_size(swf_path, num_frames, None)
+}
 
+/// Like `run_swf`, but movies loaded by the SWF arrive `chunk_size` bytes at
+/// a time, if given.
+fn run_swf_with_chunk_size(
+    swf_path: &str,
+    num_frames: u32,
+    chunk_size: Option<usize>,
+) -> Result<String, Error> {
     let base_path = Path::new(swf_path).parent().unwrap();
     let (mut executor, channel) = NullExecutor::new();
     let movie = SwfMovie::from_path(swf_path)?;
     let frame_time = 1000.0 / movie.header().frame_rate as f64;
+    let navigator = NullNavigatorBackend::with_base_path(base_path, channel);
+    let navigator: Box<dyn NavigatorBackend> = match chunk_size {
+        Some(chunk_size) => Box::new(ChunkedNavigatorBackend {
+            navigator,
+            chunk_size,
+        }),
+        None => Box::new(navigator),
+    };
     let player = Player::new(
         Box::new(NullRenderer),
         Box::new(NullAudioBackend::new()),
-        Box::new(NullNavigatorBackend::with_base_path(base_path, channel)),
+        navigator,
         Box::new(NullInputBackend::new()),
         Box::new(MemoryStorageBackend::default()),
     )?;
+    player
+        .lock()
+        .unwrap()
+        .set_log_backend(Box::new(MemoryLogBackend::new()));
     player.lock().unwrap().set_root_movie(Arc::new(movie));
 
     for _ in 0..num_frames {
@@ -412,32 +475,83 @@ fn run_swf(swf_path: &str, num_frames: u32) -> Result<String, Error> {
 
     executor.block_all().unwrap();
 
-    Ok(trace_log())
+    Ok(trace_output(&player))
 }
 
-thread_local! {
-    static TRACE_LOG: RefCell<String> = RefCell::new(String::new());
+/// Start a player running an SWF, with its own transport on `hub`.
+fn hub_player(swf_path: &str, hub: &LocalConnectionHub) -> Result<Arc<Mutex<Player>>, Error> {
+    let movie = SwfMovie::from_path(swf_path)?;
+    let player = Player::new(
+        Box::new(NullRenderer),
+        Box::new(NullAudioBackend::new()),
+        Box::new(NullNavigatorBackend::new()),
+        Box::new(NullInputBackend::new()),
+        Box::new(MemoryStorageBackend::default()),
+    )?;
+    {
+        let mut player = player.lock().unwrap();
+        player.set_log_backend(Box::new(MemoryLogBackend::new()));
+        player.set_local_connection_backend(Box::new(hub.transport()));
+        player.set_root_movie(Arc::new(movie));
+    }
+    Ok(player)
 }
 
-static TRACE_LOGGER: TraceLogger = TraceLogger;
-
-/// `TraceLogger` captures output from AVM trace actions into a String.
-struct TraceLogger;
+/// The trace output of a player whose log backend is a `MemoryLogBackend`.
+fn trace_output(player: &Mutex<Player>) -> String {
+    player
+        .lock()
+        .unwrap()
+        .log()
+        .downcast_ref::<MemoryLogBackend>()
+        .unwrap()
+        .trace_output()
+}
 
-fn trace_log() -> String {
-    TRACE_LOG.with(|log| log.borrow().clone())
+/// A navigator that streams fetched files in chunks of a fixed size.
+struct ChunkedNavigatorBackend {
+    navigator: NullNavigatorBackend,
+    chunk_size: usize,
 }
 
-impl log::Log for TraceLogger {
-    fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.target() == "avm_trace"
+impl NavigatorBackend for ChunkedNavigatorBackend {
+    fn navigate_to_url(
+        &self,
+        url: String,
+        window: Option<String>,
+        vars_method: Option<(NavigationMethod, HashMap<String, String>)>,
+    ) {
+        self.navigator.navigate_to_url(url, window, vars_method)
     }
 
-    fn log(&self, record: &Record) {
-        if self.enabled(record.metadata()) {
-            TRACE_LOG.with(|log| log.borrow_mut().push_str(&format!("{}\n", record.args())));
-        }
+    fn fetch(&self, url: &str, options: RequestOptions) -> OwnedFuture<Vec<u8>, LoaderError> {
+        self.navigator.fetch(url, options)
     }
 
-    fn flush(&self) {}
+    fn fetch_stream(&self, url: &str, options: RequestOptions) -> ChunkStream {
+        let fetch = self.navigator.fetch(url, options);
+        let chunk_size = self.chunk_size;
+        ChunkStream::new(|sink| {
+            Box::pin(async move {
+                let data = fetch.await?;
+                sink.set_total_length(data.len());
+                for chunk in data.chunks(chunk_size) {
+                    sink.send(chunk.to_vec());
+                }
+                Ok(())
+            })
+        })
+    }
+
+    fn time_since_launch(&mut self) -> Duration {
+        self.navigator.time_since_launch()
+    }
+
+    fn spawn_future(&mut self, future: OwnedFuture<(), LoaderError>) {
+        self.navigator.spawn_future(future)
+    }
+
+    fn resolve_relative_url<'a>(&mut self, url: &'a str) -> Cow<'a, str> {
+        self.navigator.resolve_relative_url(url)
+    }
 }
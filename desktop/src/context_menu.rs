@@ -0,0 +1,97 @@
+//! Context menu for the desktop player.
+//!
+//! Windows has a native popup menu. Elsewhere, the player draws the menu over
+//! the movie itself.
+
+use ruffle_core::Player;
+use std::sync::Mutex;
+use winit::window::Window;
+
+/// Show the context menu of `player` at the mouse cursor, and run the action
+/// of the chosen item.
+#[cfg(windows)]
+pub fn show_context_menu(window: &Window, player: &Mutex<Player>) {
+    // The player must stay unlocked while the menu is open, as the window
+    // keeps handling events meanwhile.
+    let items = player.lock().unwrap().prepare_context_menu();
+    let chosen = track_popup_menu(window, &items);
+    let mut player_lock = player.lock().unwrap();
+    match chosen {
+        Some(index) => player_lock.run_context_menu_callback(index),
+        None => player_lock.clear_context_menu(),
+    }
+}
+
+/// Show `items` as a native popup menu at the mouse cursor.
+///
+/// Returns the index of the chosen item, or `None` if the menu was dismissed.
+#[cfg(windows)]
+fn track_popup_menu(
+    window: &Window,
+    items: &[ruffle_core::context_menu::ContextMenuItem],
+) -> Option<usize> {
+    use std::ptr::{null, null_mut};
+    use winapi::shared::windef::{HWND, POINT};
+    use winapi::um::winuser::*;
+    use winit::platform::windows::WindowExtWindows;
+
+    if items.is_empty() {
+        return None;
+    }
+
+    unsafe {
+        let menu = CreatePopupMenu();
+        if menu.is_null() {
+            return None;
+        }
+
+        for (i, item) in items.iter().enumerate() {
+            if item.separator_before {
+                AppendMenuW(menu, MF_SEPARATOR, 0, null_mut());
+            }
+            let mut flags = MF_STRING;
+            if !item.enabled {
+                flags |= MF_GRAYED;
+            }
+            if item.checked {
+                flags |= MF_CHECKED;
+            }
+            let caption: Vec<u16> = item
+                .caption
+                .encode_utf16()
+                .chain(std::iter::once(0))
+                .collect();
+            // Item IDs start at 1, because `TrackPopupMenu` returns 0 when the
+            // menu is dismissed.
+            AppendMenuW(menu, flags, i + 1, caption.as_ptr());
+        }
+
+        let mut cursor = POINT { x: 0, y: 0 };
+        GetCursorPos(&mut cursor);
+        let chosen = TrackPopupMenu(
+            menu,
+            TPM_RETURNCMD | TPM_RIGHTBUTTON,
+            cursor.x,
+            cursor.y,
+            0,
+            window.hwnd() as HWND,
+            null(),
+        );
+        DestroyMenu(menu);
+
+        if chosen > 0 {
+            Some(chosen as usize - 1)
+        } else {
+            None
+        }
+    }
+}
+
+/// Show the context menu of `player` at the mouse cursor.
+///
+/// There is no native menu on this platform, so the player draws the menu
+/// itself and handles the choice as input events arrive.
+#[cfg(not(windows))]
+pub fn show_context_menu(_window: &Window, player: &Mutex<Player>) {
+    player.lock().unwrap().show_drawn_context_menu();
+}
@@ -0,0 +1,148 @@
+//! Command-line frontend for the AVM1 debugger.
+
+use ruffle_core::debugger::{
+    Breakpoint, DebugCommand, DebugSession, DebuggerFrontend, PauseReason,
+};
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "\
+Commands:
+  c, continue          Run until the next breakpoint
+  s, step              Step into the next action
+  n, next              Step over the next action
+  f, finish            Run until the current function returns
+  b, break <offset>    Break at an action offset, such as 0x1a2
+  b, break <clip> <n>  Break at frame n of a clip, such as _level0.menu 3
+  b, break <file>:<n>  Break at line n of a source file
+  d, delete <index>    Delete a breakpoint
+  l, list              List the breakpoints
+  w, where             Show where execution is paused
+  bt, backtrace        Show the call stack
+  scope                Show the variables of the scope chain
+  locals               Show the local variables
+  reg, registers       Show the registers
+  stack                Show the operand stack
+  p, print <expr>      Evaluate a variable path or literal
+  h, help              Show this help";
+
+/// Pauses on the terminal, reading debugger commands from stdin.
+pub struct CliDebugger;
+
+impl DebuggerFrontend for CliDebugger {
+    fn on_pause(&mut self, session: &mut DebugSession<'_, '_, '_, '_>) -> DebugCommand {
+        match session.reason() {
+            PauseReason::Start => {
+                println!("Paused before the first action. Type `help` for commands.")
+            }
+            PauseReason::Breakpoint => println!("Hit a breakpoint."),
+            PauseReason::Step => (),
+        }
+        print_location(session);
+
+        let stdin = io::stdin();
+        loop {
+            print!("(ruffle) ");
+            let _ = io::stdout().flush();
+
+            let mut line = String::new();
+            match stdin.lock().read_line(&mut line) {
+                // Resume if stdin is closed, rather than pausing forever.
+                Ok(0) | Err(_) => return DebugCommand::Continue,
+                Ok(_) => (),
+            }
+
+            let line = line.trim();
+            let (command, args) = match line.find(char::is_whitespace) {
+                Some(i) => (&line[..i], line[i..].trim()),
+                None => (line, ""),
+            };
+            match command {
+                "" => (),
+                "c" | "continue" => return DebugCommand::Continue,
+                "s" | "step" => return DebugCommand::StepIn,
+                "n" | "next" => return DebugCommand::StepOver,
+                "f" | "finish" => return DebugCommand::StepOut,
+                "b" | "break" => match parse_breakpoint(args) {
+                    Some(breakpoint) => {
+                        println!("Breakpoint set: {}", breakpoint);
+                        session.state().add_breakpoint(breakpoint);
+                    }
+                    None => println!("Usage: break <offset> | <clip> <frame> | <file>:<line>"),
+                },
+                "d" | "delete" => {
+                    let removed = args
+                        .parse()
+                        .ok()
+                        .and_then(|index| session.state().remove_breakpoint(index));
+                    match removed {
+                        Some(breakpoint) => println!("Breakpoint deleted: {}", breakpoint),
+                        None => println!("No breakpoint {}", args),
+                    }
+                }
+                "l" | "list" => {
+                    let breakpoints = session.state().breakpoints();
+                    if breakpoints.is_empty() {
+                        println!("No breakpoints");
+                    }
+                    for (i, breakpoint) in breakpoints.iter().enumerate() {
+                        println!("{}: {}", i, breakpoint);
+                    }
+                }
+                "w" | "where" => print_location(session),
+                "bt" | "backtrace" => {
+                    for (i, name) in session.call_stack().iter().enumerate() {
+                        println!("#{} {}", i, name);
+                    }
+                }
+                "scope" => print!("{}", session.scope_chain()),
+                "locals" => print!("{}", session.locals()),
+                "reg" | "registers" => print!("{}", session.registers()),
+                "stack" => print!("{}", session.stack()),
+                "p" | "print" => match session.evaluate(args) {
+                    Ok(value) => println!("{}", value),
+                    Err(e) => println!("Error: {}", e),
+                },
+                "h" | "help" => println!("{}", HELP),
+                _ => println!("Unknown command `{}`. Type `help` for commands.", command),
+            }
+        }
+    }
+}
+
+fn print_location(session: &DebugSession<'_, '_, '_, '_>) {
+    println!("{}", session.location());
+    if let Some(action) = session.action() {
+        println!("  {}", action);
+    }
+}
+
+/// Parse the arguments of the `break` command.
+fn parse_breakpoint(args: &str) -> Option<Breakpoint> {
+    let mut words = args.split_whitespace();
+    match (words.next(), words.next(), words.next()) {
+        (Some(path), Some(frame), None) => Some(Breakpoint::Frame {
+            path: path.to_string(),
+            frame: frame.parse().ok()?,
+        }),
+        (Some(word), None, None) => {
+            if let Some(offset) = parse_offset(word) {
+                Some(Breakpoint::Action { url: None, offset })
+            } else {
+                let colon = word.rfind(':')?;
+                Some(Breakpoint::Line {
+                    file: word[..colon].to_string(),
+                    line: word[colon + 1..].parse().ok()?,
+                })
+            }
+        }
+        _ => None,
+    }
+}
+
+fn parse_offset(word: &str) -> Option<usize> {
+    if let Some(hex) = word.strip_prefix("0x") {
+        usize::from_str_radix(hex, 16).ok()
+    } else {
+        word.parse().ok()
+    }
+}
@@ -0,0 +1,573 @@
+//! Debugger frontend speaking the Flash Player debugger protocol, so that
+//! Flex `fdb` and IDE debuggers can debug the movie.
+//!
+//! Like the Flash debug player, Ruffle first tries to connect to a debugger
+//! waiting on the debugger port. If none is waiting, it listens on that port
+//! until a debugger connects.
+//!
+//! Every message is framed as the little-endian length of its payload and its
+//! type, followed by the payload. Only the messages needed for breakpoints,
+//! stepping, variables and traces are supported; anything else is ignored.
+
+use ruffle_core::debugger::{
+    avm2_source_files, debugger_password_hash, Breakpoint, DebugCommand, DebugSession,
+    DebuggerFrontend, DebuggerState, LineInfo, PauseReason, SourceLine,
+};
+use ruffle_core::tag_utils::SwfMovie;
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::thread;
+
+/// The port debuggers listen on.
+const DEBUGGER_PORT: u16 = 7935;
+
+/// The protocol version reported to the debugger.
+const PROTOCOL_VERSION: u32 = 10;
+
+// Messages from the player to the debugger.
+const IN_EXIT: u32 = 2;
+const IN_TRACE: u32 = 5;
+const IN_SCRIPT: u32 = 14;
+const IN_BREAK_AT: u32 = 16;
+const IN_SET_BREAKPOINT: u32 = 19;
+const IN_NUM_SCRIPT: u32 = 20;
+const IN_REMOVE_BREAKPOINT: u32 = 22;
+const IN_VERSION: u32 = 26;
+const IN_SQUELCH: u32 = 29;
+const IN_GET_VARIABLE: u32 = 30;
+const IN_BREAK_REASON: u32 = 40;
+
+// Messages from the debugger to the player.
+const OUT_CONTINUE: u32 = 15;
+const OUT_STOP_DEBUG: u32 = 16;
+const OUT_SET_BREAKPOINTS: u32 = 17;
+const OUT_REMOVE_BREAKPOINTS: u32 = 18;
+const OUT_REMOVE_ALL_BREAKPOINTS: u32 = 19;
+const OUT_STEP_OVER: u32 = 20;
+const OUT_STEP_INTO: u32 = 21;
+const OUT_STEP_OUT: u32 = 22;
+const OUT_SET_SQUELCH: u32 = 24;
+const OUT_GET_VARIABLE: u32 = 25;
+const OUT_GET_BREAK_REASON: u32 = 35;
+
+// Break reasons.
+const REASON_BREAKPOINT: u16 = 1;
+const REASON_STOP_REQUEST: u16 = 4;
+const REASON_STEP: u16 = 5;
+
+/// The type of a variable sent as a string.
+const VARIABLE_TYPE_STRING: u16 = 2;
+
+type Error = Box<dyn std::error::Error>;
+
+/// A message from the debugger.
+#[derive(Debug, PartialEq)]
+enum Request {
+    Continue,
+    StopDebug,
+    SetBreakpoints(Vec<u32>),
+    RemoveBreakpoints(Vec<u32>),
+    RemoveAllBreakpoints,
+    Step(DebugCommand),
+    SetSquelch(bool),
+    GetVariable {
+        id: u32,
+        name: String,
+    },
+    GetBreakReason,
+
+    /// A message this frontend doesn't handle.
+    Unknown,
+
+    /// The debugger has gone away.
+    Disconnected,
+}
+
+impl Request {
+    fn decode(kind: u32, mut payload: &[u8]) -> Self {
+        let input = &mut payload;
+        let breakpoints = |input: &mut &[u8]| -> Vec<u32> {
+            let count = read_u32(input).unwrap_or(0);
+            (0..count).filter_map(|_| read_u32(input)).collect()
+        };
+        match kind {
+            OUT_CONTINUE => Request::Continue,
+            OUT_STOP_DEBUG => Request::StopDebug,
+            OUT_SET_BREAKPOINTS => Request::SetBreakpoints(breakpoints(input)),
+            OUT_REMOVE_BREAKPOINTS => Request::RemoveBreakpoints(breakpoints(input)),
+            OUT_REMOVE_ALL_BREAKPOINTS => Request::RemoveAllBreakpoints,
+            OUT_STEP_OVER => Request::Step(DebugCommand::StepOver),
+            OUT_STEP_INTO => Request::Step(DebugCommand::StepIn),
+            OUT_STEP_OUT => Request::Step(DebugCommand::StepOut),
+            OUT_SET_SQUELCH => Request::SetSquelch(read_u32(input).unwrap_or(0) != 0),
+            OUT_GET_VARIABLE => Request::GetVariable {
+                id: read_u32(input).unwrap_or(0),
+                name: read_c_string(input),
+            },
+            OUT_GET_BREAK_REASON => Request::GetBreakReason,
+            _ => Request::Unknown,
+        }
+    }
+}
+
+fn read_u32(input: &mut &[u8]) -> Option<u32> {
+    if input.len() < 4 {
+        return None;
+    }
+    let value = u32::from_le_bytes([input[0], input[1], input[2], input[3]]);
+    *input = &input[4..];
+    Some(value)
+}
+
+fn read_c_string(input: &mut &[u8]) -> String {
+    let len = input.iter().position(|&c| c == 0).unwrap_or(input.len());
+    let string = String::from_utf8_lossy(&input[..len]).into_owned();
+    *input = &input[(len + 1).min(input.len())..];
+    string
+}
+
+/// A message to the debugger.
+struct Message {
+    kind: u32,
+    payload: Vec<u8>,
+}
+
+impl Message {
+    fn new(kind: u32) -> Self {
+        Self {
+            kind,
+            payload: vec![],
+        }
+    }
+
+    fn u16(mut self, value: u16) -> Self {
+        self.payload.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn u32(mut self, value: u32) -> Self {
+        self.payload.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn u8(mut self, value: u8) -> Self {
+        self.payload.push(value);
+        self
+    }
+
+    fn string(mut self, value: &str) -> Self {
+        self.payload.extend(value.bytes().filter(|&c| c != 0));
+        self.payload.push(0);
+        self
+    }
+
+    /// The framed message, as it's sent to the debugger.
+    fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(8 + self.payload.len());
+        data.extend_from_slice(&(self.payload.len() as u32).to_le_bytes());
+        data.extend_from_slice(&self.kind.to_le_bytes());
+        data.extend_from_slice(&self.payload);
+        data
+    }
+}
+
+/// Reads messages from the debugger until the connection closes.
+fn read_requests(mut stream: impl Read, sender: Sender<Request>) {
+    loop {
+        let mut header = [0; 8];
+        if stream.read_exact(&mut header).is_err() {
+            break;
+        }
+        let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let kind = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        let mut payload = vec![0; len as usize];
+        if stream.read_exact(&mut payload).is_err() {
+            break;
+        }
+        if sender.send(Request::decode(kind, &payload)).is_err() {
+            return;
+        }
+    }
+    let _ = sender.send(Request::Disconnected);
+}
+
+/// Serves a connected Flash debugger.
+pub struct FdbFrontend {
+    stream: TcpStream,
+    requests: Receiver<Request>,
+
+    /// The source files known to the debugger, whose IDs count from 1.
+    files: Vec<String>,
+
+    /// The line of the last pause, so that steps run whole lines.
+    last_line: Option<SourceLine>,
+
+    /// The step the debugger asked for last, which is repeated until the
+    /// line changes.
+    last_step: DebugCommand,
+
+    /// Whether the debugger asked not to be sent traces.
+    squelch: bool,
+
+    connected: bool,
+}
+
+impl FdbFrontend {
+    /// Wait for a debugger to attach to `movie`.
+    ///
+    /// Fails if the movie doesn't allow debugging, or if it asks for a
+    /// password and `password` doesn't match.
+    pub fn connect(
+        movie: &SwfMovie,
+        line_info: Option<&LineInfo>,
+        password: Option<&str>,
+    ) -> Result<Self, Error> {
+        match debugger_password_hash(movie) {
+            None => return Err("This movie does not allow debugging".into()),
+            Some(hash) if !hash.is_empty() => {
+                if !pwhash::md5_crypt::verify(password.unwrap_or(""), &hash) {
+                    return Err("Wrong debugger password".into());
+                }
+            }
+            Some(_) => (),
+        }
+
+        let stream = match TcpStream::connect((Ipv4Addr::LOCALHOST, DEBUGGER_PORT)) {
+            Ok(stream) => stream,
+            Err(_) => {
+                log::info!("Waiting for a debugger on port {}", DEBUGGER_PORT);
+                let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, DEBUGGER_PORT))?;
+                listener.accept()?.0
+            }
+        };
+        stream.set_nodelay(true)?;
+
+        let (sender, requests) = channel();
+        let reader = stream.try_clone()?;
+        thread::spawn(move || read_requests(reader, sender));
+
+        let mut scripts: Vec<(String, String)> = line_info
+            .map(|info| info.source_files())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, text)| (name.to_string(), text.to_string()))
+            .collect();
+        scripts.extend(
+            avm2_source_files(movie)
+                .into_iter()
+                .map(|name| (name, String::new())),
+        );
+
+        let mut frontend = Self {
+            stream,
+            requests,
+            files: scripts.iter().map(|(name, _)| name.clone()).collect(),
+            last_line: None,
+            last_step: DebugCommand::StepIn,
+            squelch: false,
+            connected: true,
+        };
+
+        frontend.send(
+            Message::new(IN_VERSION)
+                .u32(PROTOCOL_VERSION)
+                .u8(std::mem::size_of::<u32>() as u8),
+        )?;
+        frontend.send(Message::new(IN_NUM_SCRIPT).u32(scripts.len() as u32))?;
+        for (i, (name, text)) in scripts.iter().enumerate() {
+            frontend.send(
+                Message::new(IN_SCRIPT)
+                    .u32(i as u32 + 1)
+                    .u32(0)
+                    .string(name)
+                    .string(text),
+            )?;
+        }
+
+        Ok(frontend)
+    }
+
+    fn send(&mut self, message: Message) -> io::Result<()> {
+        self.stream.write_all(&message.encode())
+    }
+
+    /// Send a message, dropping the connection if it fails.
+    fn send_or_disconnect(&mut self, message: Message) {
+        if self.send(message).is_err() {
+            self.connected = false;
+        }
+    }
+
+    /// The breakpoint ID of a source line, which packs its file ID in the low
+    /// 16 bits and its line in the high 16 bits.
+    fn breakpoint_id(&self, line: &SourceLine) -> u32 {
+        let file_id = self
+            .files
+            .iter()
+            .position(|file| *file == line.file)
+            .map(|i| i as u32 + 1)
+            .unwrap_or(0);
+        file_id | (line.line << 16)
+    }
+
+    fn breakpoint(&self, id: u32) -> Option<Breakpoint> {
+        let file = self.files.get(((id & 0xffff) as usize).checked_sub(1)?)?;
+        Some(Breakpoint::Line {
+            file: file.clone(),
+            line: id >> 16,
+        })
+    }
+
+    /// Handle a request that doesn't need paused code.
+    ///
+    /// Returns the request back if it needs to be handled while paused.
+    fn handle(&mut self, state: &mut DebuggerState, request: Request) -> Option<Request> {
+        match request {
+            Request::SetBreakpoints(ids) => {
+                let mut set = vec![];
+                for id in ids {
+                    if let Some(breakpoint) = self.breakpoint(id) {
+                        state.add_breakpoint(breakpoint);
+                        set.push(id);
+                    }
+                }
+                self.send_breakpoints(IN_SET_BREAKPOINT, &set);
+            }
+            Request::RemoveBreakpoints(ids) => {
+                for id in &ids {
+                    if let Some(breakpoint) = self.breakpoint(*id) {
+                        remove_breakpoint(state, &breakpoint);
+                    }
+                }
+                self.send_breakpoints(IN_REMOVE_BREAKPOINT, &ids);
+            }
+            Request::RemoveAllBreakpoints => while state.remove_breakpoint(0).is_some() {},
+            Request::SetSquelch(squelch) => {
+                self.squelch = squelch;
+                self.send_or_disconnect(Message::new(IN_SQUELCH).u32(squelch.into()));
+            }
+            Request::StopDebug | Request::Disconnected => {
+                // Let the movie run freely once the debugger has gone.
+                while state.remove_breakpoint(0).is_some() {}
+                self.connected = false;
+            }
+            Request::Unknown => (),
+            request => return Some(request),
+        }
+        None
+    }
+
+    fn send_breakpoints(&mut self, kind: u32, ids: &[u32]) {
+        let mut message = Message::new(kind).u32(ids.len() as u32);
+        for id in ids {
+            message = message.u32(*id);
+        }
+        self.send_or_disconnect(message);
+    }
+}
+
+fn remove_breakpoint(state: &mut DebuggerState, breakpoint: &Breakpoint) {
+    if let Some(index) = state.breakpoints().iter().position(|b| b == breakpoint) {
+        state.remove_breakpoint(index);
+    }
+}
+
+impl DebuggerFrontend for FdbFrontend {
+    fn on_pause(&mut self, session: &mut DebugSession<'_, '_, '_, '_>) -> DebugCommand {
+        if !self.connected {
+            return DebugCommand::Continue;
+        }
+
+        // Debuggers step by source line, while the player steps by action.
+        let line = session.location().line.clone();
+        if session.reason() == PauseReason::Step && line.is_some() && line == self.last_line {
+            return self.last_step;
+        }
+
+        let breakpoint_id = line.as_ref().map_or(0, |line| self.breakpoint_id(line));
+        let reason = match session.reason() {
+            PauseReason::Start => REASON_STOP_REQUEST,
+            PauseReason::Breakpoint => REASON_BREAKPOINT,
+            PauseReason::Step => REASON_STEP,
+        };
+        let frame = session.location().frame.unwrap_or(0);
+        let offset = session.location().offset.unwrap_or(0) as u32;
+        self.send_or_disconnect(
+            Message::new(IN_BREAK_AT)
+                .u32(breakpoint_id)
+                .u32(0)
+                .string(&session.call_stack().join("\n")),
+        );
+        self.send_or_disconnect(
+            Message::new(IN_BREAK_REASON)
+                .u16(reason)
+                .u16(0)
+                .u32(offset)
+                .u32(offset)
+                .u32(frame.into()),
+        );
+        self.last_line = line;
+
+        while self.connected {
+            let request = match self.requests.recv() {
+                Ok(request) => request,
+                Err(_) => Request::Disconnected,
+            };
+            match self.handle(session.state(), request) {
+                Some(Request::Continue) => return DebugCommand::Continue,
+                Some(Request::Step(command)) => {
+                    self.last_step = command;
+                    return command;
+                }
+                Some(Request::GetVariable { id, name }) => {
+                    let value = session.evaluate(&name).unwrap_or_else(|e| e);
+                    self.send_or_disconnect(
+                        Message::new(IN_GET_VARIABLE)
+                            .u32(id)
+                            .string(&name)
+                            .u16(VARIABLE_TYPE_STRING)
+                            .u16(0)
+                            .string(&value),
+                    );
+                }
+                Some(Request::GetBreakReason) => self.send_or_disconnect(
+                    Message::new(IN_BREAK_REASON)
+                        .u16(reason)
+                        .u16(0)
+                        .u32(offset)
+                        .u32(offset)
+                        .u32(frame.into()),
+                ),
+                _ => (),
+            }
+        }
+        DebugCommand::Continue
+    }
+
+    fn poll(&mut self, state: &mut DebuggerState) {
+        loop {
+            match self.requests.try_recv() {
+                Ok(request) => {
+                    self.handle(state, request);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.handle(state, Request::Disconnected);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn on_trace(&mut self, message: &str) {
+        if self.connected && !self.squelch {
+            self.send_or_disconnect(Message::new(IN_TRACE).string(message));
+        }
+    }
+}
+
+impl Drop for FdbFrontend {
+    fn drop(&mut self) {
+        if self.connected {
+            let _ = self.send(Message::new(IN_EXIT));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(kind: u32, payload: &[u8]) -> Vec<u8> {
+        let mut data = vec![];
+        data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        data.extend_from_slice(&kind.to_le_bytes());
+        data.extend_from_slice(payload);
+        data
+    }
+
+    #[test]
+    fn encode_message() {
+        let message = Message::new(IN_BREAK_AT)
+            .u32(0x0002_0001)
+            .u16(3)
+            .string("a\0b");
+        assert_eq!(
+            message.encode(),
+            frame(IN_BREAK_AT, &[1, 0, 2, 0, 3, 0, b'a', b'b', 0])
+        );
+        assert_eq!(Message::new(IN_EXIT).encode(), frame(IN_EXIT, &[]));
+    }
+
+    #[test]
+    fn decode_requests() {
+        assert_eq!(Request::decode(OUT_CONTINUE, &[]), Request::Continue);
+        assert_eq!(
+            Request::decode(OUT_STEP_OUT, &[]),
+            Request::Step(DebugCommand::StepOut)
+        );
+        assert_eq!(
+            Request::decode(OUT_SET_BREAKPOINTS, &[2, 0, 0, 0, 1, 0, 5, 0, 2, 0, 7, 0]),
+            Request::SetBreakpoints(vec![0x0005_0001, 0x0007_0002])
+        );
+        assert_eq!(
+            Request::decode(OUT_SET_SQUELCH, &[1, 0, 0, 0]),
+            Request::SetSquelch(true)
+        );
+        assert_eq!(
+            Request::decode(OUT_GET_VARIABLE, b"\x04\0\0\0_root.x\0"),
+            Request::GetVariable {
+                id: 4,
+                name: "_root.x".to_string()
+            }
+        );
+        assert_eq!(Request::decode(999, &[1, 2, 3]), Request::Unknown);
+    }
+
+    #[test]
+    fn decode_truncated_requests() {
+        // A breakpoint count larger than the payload only yields what's there.
+        assert_eq!(
+            Request::decode(OUT_REMOVE_BREAKPOINTS, &[3, 0, 0, 0, 1, 0, 5, 0, 2]),
+            Request::RemoveBreakpoints(vec![0x0005_0001])
+        );
+        assert_eq!(
+            Request::decode(OUT_SET_BREAKPOINTS, &[]),
+            Request::SetBreakpoints(vec![])
+        );
+        assert_eq!(
+            Request::decode(OUT_GET_VARIABLE, b"\x04\0\0\0x"),
+            Request::GetVariable {
+                id: 4,
+                name: "x".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn read_framed_requests() {
+        let mut data = frame(OUT_SET_SQUELCH, &[0, 0, 0, 0]);
+        data.extend(frame(OUT_GET_BREAK_REASON, &[]));
+        // The connection closes in the middle of this one.
+        data.extend(&frame(OUT_CONTINUE, &[0, 0])[..9]);
+
+        let (sender, receiver) = channel();
+        read_requests(&data[..], sender);
+        assert_eq!(
+            receiver.iter().collect::<Vec<_>>(),
+            vec![
+                Request::SetSquelch(false),
+                Request::GetBreakReason,
+                Request::Disconnected
+            ]
+        );
+    }
+
+    #[test]
+    fn verify_password() {
+        let hash = "$1$saltstr$jt4dMUtB952ERKJcUbm6W1";
+        assert!(pwhash::md5_crypt::verify("password", hash));
+        assert!(!pwhash::md5_crypt::verify("passwor", hash));
+    }
+}
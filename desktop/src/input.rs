@@ -1,6 +1,6 @@
 use clipboard::{ClipboardContext, ClipboardProvider};
 use ruffle_core::backend::input::{InputBackend, MouseCursor};
-use ruffle_core::events::{KeyCode, PlayerEvent};
+use ruffle_core::events::{KeyCode, MouseButton, PlayerEvent};
 use std::collections::HashSet;
 use std::rc::Rc;
 use winit::event::{ElementState, VirtualKeyCode, WindowEvent};
@@ -8,6 +8,7 @@ use winit::window::Window;
 
 pub struct WinitInputBackend {
     keys_down: HashSet<VirtualKeyCode>,
+    mouse_buttons_down: HashSet<MouseButton>,
     window: Rc<Window>,
     cursor_visible: bool,
     last_key: KeyCode,
@@ -18,6 +19,7 @@ impl WinitInputBackend {
     pub fn new(window: Rc<Window>) -> Self {
         Self {
             keys_down: HashSet::new(),
+            mouse_buttons_down: HashSet::new(),
             cursor_visible: true,
             last_key: KeyCode::Unknown,
             window,
@@ -25,6 +27,15 @@ impl WinitInputBackend {
         }
     }
 
+    /// Record a mouse button being pressed or released, for `Key.isDown`.
+    pub fn set_mouse_button_down(&mut self, button: MouseButton, is_down: bool) {
+        if is_down {
+            self.mouse_buttons_down.insert(button);
+        } else {
+            self.mouse_buttons_down.remove(&button);
+        }
+    }
+
     /// Process an input event, and returns an event that should be forward to the player, if any.
     pub fn handle_event(&mut self, event: WindowEvent) -> Option<PlayerEvent> {
         match event {
@@ -65,6 +76,9 @@ impl InputBackend for WinitInputBackend {
     fn is_key_down(&self, key: KeyCode) -> bool {
         match key {
             KeyCode::Unknown => false,
+            KeyCode::LeftMouse => self.mouse_buttons_down.contains(&MouseButton::Left),
+            KeyCode::RightMouse => self.mouse_buttons_down.contains(&MouseButton::Right),
+            KeyCode::MiddleMouse => self.mouse_buttons_down.contains(&MouseButton::Middle),
             KeyCode::Backspace => self.keys_down.contains(&VirtualKeyCode::Back),
             KeyCode::Return => self.keys_down.contains(&VirtualKeyCode::Return),
             KeyCode::Shift => {
@@ -1,18 +1,25 @@
 #![allow(clippy::unneeded_field_pattern)]
 
 mod audio;
+mod context_menu;
 mod custom_event;
+mod debugger;
 mod executor;
+mod fdb;
 mod input;
 mod navigator;
+mod socket;
 mod storage;
 mod task;
+mod trace_console;
 
 use crate::custom_event::RuffleEvent;
 use crate::executor::GlutinAsyncExecutor;
 use clap::Clap;
 use ruffle_core::{
     backend::audio::{AudioBackend, NullAudioBackend},
+    backend::local_connection::LocalConnectionHub,
+    debugger::{Debugger, DebuggerFrontend, LineInfo},
     Player,
 };
 use ruffle_render_wgpu::WgpuRenderBackend;
@@ -21,12 +28,15 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use crate::storage::DiskStorageBackend;
+use ruffle_core::events::MouseWheelDelta;
 use ruffle_core::tag_utils::SwfMovie;
 use std::rc::Rc;
 use winit::dpi::{LogicalSize, PhysicalPosition};
-use winit::event::{ElementState, MouseButton, WindowEvent};
+use winit::event::{
+    ElementState, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
+};
 use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::{Icon, WindowBuilder};
+use winit::window::{Fullscreen, Icon, WindowBuilder};
 
 #[derive(Clap, Debug)]
 #[clap(
@@ -37,6 +47,25 @@ use winit::window::{Icon, WindowBuilder};
 struct Opt {
     #[clap(name = "FILE", parse(from_os_str))]
     input_path: PathBuf,
+
+    /// Pause before the first action, and debug the movie's ActionScript 1/2
+    /// code from the terminal. Line info is read from a .swd file next to the
+    /// movie, if there is one.
+    #[clap(long)]
+    debug: bool,
+
+    /// Wait for a Flash debugger, such as Flex `fdb`, to attach on the
+    /// debugger port. The movie must be published with debugging enabled.
+    #[clap(long, conflicts_with = "debug")]
+    fdb: bool,
+
+    /// The password the movie asks debuggers for, if any.
+    #[clap(long, requires = "fdb")]
+    debugger_password: Option<String>,
+
+    /// Show the latest `trace` output over the movie.
+    #[clap(long)]
+    trace_console: bool,
 }
 
 fn main() {
@@ -46,7 +75,12 @@ fn main() {
 
     let opt = Opt::parse();
 
-    let ret = run_player(opt.input_path);
+    // Players in this process take their `LocalConnection` transport from
+    // one hub, which routes messages between them. Only one window is opened
+    // for now, so its movie can only talk to itself.
+    let local_connection_hub = LocalConnectionHub::new();
+
+    let ret = run_player(opt, &local_connection_hub);
 
     if let Err(e) = ret {
         eprintln!("Fatal error:\n{}", e);
@@ -54,7 +88,11 @@ fn main() {
     }
 }
 
-fn run_player(input_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+fn run_player(
+    opt: Opt,
+    local_connection_hub: &LocalConnectionHub,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let input_path = opt.input_path;
     let movie = SwfMovie::from_path(&input_path)?;
     let movie_size = LogicalSize::new(movie.width(), movie.height());
 
@@ -94,10 +132,50 @@ fn run_player(input_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
         event_loop.create_proxy(),
     )); //TODO: actually implement this backend type
     let input = Box::new(input::WinitInputBackend::new(window.clone()));
-    let storage = Box::new(DiskStorageBackend::new(
-        input_path.file_name().unwrap_or_default().as_ref(),
-    ));
+    let storage = DiskStorageBackend::new();
+    storage.migrate_legacy_objects(&input_path, movie.url());
+    let storage = Box::new(storage);
+    let local_connection = Box::new(local_connection_hub.transport());
+    let socket = Box::new(socket::TcpSocketBackend::new());
     let player = Player::new(renderer, audio, navigator, input, storage)?;
+    {
+        let mut player = player.lock().unwrap();
+        player.set_is_projector(true);
+        // Like the standalone Flash Player, trust the movies it's given.
+        player.set_trust_local_content(true);
+        player.set_local_connection_backend(local_connection);
+        player.set_socket_backend(socket);
+        player.set_log_backend(Box::new(trace_console::TraceConsoleBackend::new(
+            opt.trace_console,
+        )));
+    }
+    if opt.debug || opt.fdb {
+        let line_info = match std::fs::read(input_path.with_extension("swd")) {
+            Ok(swd) => match LineInfo::from_swd(&swd, &movie) {
+                Ok(line_info) => Some(line_info),
+                Err(e) => {
+                    log::warn!("Unable to read line info: {}", e);
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+        let frontend: Box<dyn DebuggerFrontend> = if opt.fdb {
+            Box::new(fdb::FdbFrontend::connect(
+                &movie,
+                line_info.as_ref(),
+                opt.debugger_password.as_deref(),
+            )?)
+        } else {
+            Box::new(debugger::CliDebugger)
+        };
+        let mut debugger = Debugger::new(frontend);
+        debugger.pause_on_start();
+        if let Some(line_info) = line_info {
+            debugger.state_mut().add_line_info(line_info);
+        }
+        player.lock().unwrap().set_debugger(Some(debugger));
+    }
     player.lock().unwrap().set_root_movie(Arc::new(movie));
     player.lock().unwrap().set_is_playing(true); // Desktop player will auto-play.
 
@@ -130,6 +208,19 @@ fn run_player(input_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
                         if player_lock.needs_render() {
                             window.request_redraw();
                         }
+
+                        // Projector FSCommands.
+                        if player_lock.quit_requested() {
+                            *control_flow = ControlFlow::Exit;
+                        }
+                        let is_fullscreen = player_lock.is_fullscreen();
+                        if is_fullscreen != window.fullscreen().is_some() {
+                            window.set_fullscreen(if is_fullscreen {
+                                Some(Fullscreen::Borderless(window.current_monitor()))
+                            } else {
+                                None
+                            });
+                        }
                     }
                 }
 
@@ -158,23 +249,66 @@ fn run_player(input_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
                         }
                     }
                     WindowEvent::MouseInput {
-                        button: MouseButton::Left,
+                        button,
                         state: pressed,
                         ..
                     } => {
-                        let mut player_lock = player.lock().unwrap();
-                        let event = if pressed == ElementState::Pressed {
-                            ruffle_core::PlayerEvent::MouseDown {
-                                x: mouse_pos.x,
-                                y: mouse_pos.y,
+                        let button = match button {
+                            MouseButton::Left => Some(ruffle_core::events::MouseButton::Left),
+                            MouseButton::Right => Some(ruffle_core::events::MouseButton::Right),
+                            MouseButton::Middle => Some(ruffle_core::events::MouseButton::Middle),
+                            MouseButton::Other(_) => None,
+                        };
+                        if let Some(button) = button {
+                            let mut player_lock = player.lock().unwrap();
+                            let is_down = pressed == ElementState::Pressed;
+                            player_lock
+                                .input_mut()
+                                .downcast_mut::<input::WinitInputBackend>()
+                                .unwrap()
+                                .set_mouse_button_down(button, is_down);
+                            // A drawn context menu handles its own clicks.
+                            let menu_was_open = player_lock.is_drawn_context_menu_open();
+                            let event = if is_down {
+                                ruffle_core::PlayerEvent::MouseDown {
+                                    x: mouse_pos.x,
+                                    y: mouse_pos.y,
+                                    button,
+                                }
+                            } else {
+                                ruffle_core::PlayerEvent::MouseUp {
+                                    x: mouse_pos.x,
+                                    y: mouse_pos.y,
+                                    button,
+                                }
+                            };
+                            player_lock.handle_event(event);
+                            if player_lock.needs_render() {
+                                window.request_redraw();
                             }
-                        } else {
-                            ruffle_core::PlayerEvent::MouseUp {
-                                x: mouse_pos.x,
-                                y: mouse_pos.y,
+                            drop(player_lock);
+
+                            if button == ruffle_core::events::MouseButton::Right
+                                && !is_down
+                                && !menu_was_open
+                            {
+                                context_menu::show_context_menu(&window, &player);
+                                if player.lock().unwrap().needs_render() {
+                                    window.request_redraw();
+                                }
                             }
+                        }
+                    }
+                    WindowEvent::MouseWheel { delta, .. } => {
+                        let delta = match delta {
+                            // Flash scrolls 3 lines for each notch of the wheel.
+                            MouseScrollDelta::LineDelta(_, y) => {
+                                MouseWheelDelta::Lines(f64::from(y) * 3.0)
+                            }
+                            MouseScrollDelta::PixelDelta(pos) => MouseWheelDelta::Pixels(pos.y),
                         };
-                        player_lock.handle_event(event);
+                        let mut player_lock = player.lock().unwrap();
+                        player_lock.handle_event(ruffle_core::PlayerEvent::MouseWheel { delta });
                         if player_lock.needs_render() {
                             window.request_redraw();
                         }
@@ -189,6 +323,22 @@ fn run_player(input_path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
                     WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                     WindowEvent::KeyboardInput { .. } | WindowEvent::ReceivedCharacter(_) => {
                         let mut player_lock = player.lock().unwrap();
+                        // Like the projector, leave fullscreen on Escape, unless the
+                        // movie traps every key with the `trapallkeys` FSCommand.
+                        if let WindowEvent::KeyboardInput {
+                            input:
+                                KeyboardInput {
+                                    virtual_keycode: Some(VirtualKeyCode::Escape),
+                                    state: ElementState::Pressed,
+                                    ..
+                                },
+                            ..
+                        } = event
+                        {
+                            if player_lock.is_fullscreen() && !player_lock.trap_all_keys() {
+                                player_lock.set_is_fullscreen(false);
+                            }
+                        }
                         if let Some(event) = player_lock
                             .input_mut()
                             .downcast_mut::<input::WinitInputBackend>()
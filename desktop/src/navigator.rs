@@ -1,8 +1,13 @@
 //! Navigator backend for web
 
 use crate::custom_event::RuffleEvent;
+use futures::AsyncReadExt;
+use isahc::config::{Configurable, RedirectPolicy};
+use isahc::http::{Request, Response};
+use isahc::{Body, HttpClient};
 use ruffle_core::backend::navigator::{
-    url_from_relative_path, NavigationMethod, NavigatorBackend, OwnedFuture, RequestOptions,
+    url_from_relative_path, ChunkSink, ChunkStream, NavigationMethod, NavigatorBackend,
+    OwnedFuture, RequestOptions,
 };
 use ruffle_core::loader::Error;
 use std::borrow::Cow;
@@ -10,6 +15,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::Sender;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use url::Url;
 use winit::event_loop::EventLoopProxy;
@@ -28,6 +34,9 @@ pub struct ExternalNavigatorBackend {
 
     /// The time that the SWF was launched.
     start_time: Instant,
+
+    /// The client used for HTTP(S) fetches, if one could be created.
+    client: Option<Arc<HttpClient>>,
 }
 
 impl ExternalNavigatorBackend {
@@ -41,6 +50,7 @@ impl ExternalNavigatorBackend {
             event_loop,
             relative_base_path: PathBuf::new(),
             start_time: Instant::now(),
+            client: create_http_client(),
         }
     }
 
@@ -59,6 +69,7 @@ impl ExternalNavigatorBackend {
             event_loop,
             relative_base_path,
             start_time: Instant::now(),
+            client: create_http_client(),
         }
     }
 }
@@ -112,13 +123,55 @@ impl NavigatorBackend for ExternalNavigatorBackend {
         Instant::now().duration_since(self.start_time)
     }
 
-    fn fetch(&self, url: &str, _options: RequestOptions) -> OwnedFuture<Vec<u8>, Error> {
-        // Load from local filesystem.
-        // TODO: Support network loads, honor sandbox type (local-with-filesystem, local-with-network, remote, ...)
-        let mut path = self.relative_base_path.clone();
-        path.push(url);
+    fn fetch(&self, url: &str, options: RequestOptions) -> OwnedFuture<Vec<u8>, Error> {
+        let url = match Url::parse(url) {
+            Ok(url) => url,
+            Err(_) => {
+                // Anything that isn't an absolute URL is a path relative to the movie.
+                let mut path = self.relative_base_path.clone();
+                path.push(url);
+                return Box::pin(async move { fs::read(path).map_err(Error::NetworkError) });
+            }
+        };
 
-        Box::pin(async move { fs::read(path).map_err(Error::NetworkError) })
+        match url.scheme() {
+            "file" => Box::pin(async move {
+                let path = url
+                    .to_file_path()
+                    .map_err(|_| Error::FetchError(format!("Invalid file URL {}", url)))?;
+                fs::read(path).map_err(Error::NetworkError)
+            }),
+            "http" | "https" => {
+                let client = self.client.clone();
+                Box::pin(async move {
+                    let client = client.ok_or_else(|| {
+                        Error::FetchError("HTTP client is unavailable".to_string())
+                    })?;
+                    fetch_http(&client, url, options).await
+                })
+            }
+            scheme => {
+                let error = Error::FetchError(format!("Unsupported URL scheme {}", scheme));
+                Box::pin(async move { Err(error) })
+            }
+        }
+    }
+
+    fn fetch_stream(&self, url: &str, options: RequestOptions) -> ChunkStream {
+        // Only HTTP responses are worth streaming; local files are read at once.
+        let url = match Url::parse(url) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => url,
+            _ => return self.fetch(url, options).into(),
+        };
+
+        let client = self.client.clone();
+        ChunkStream::new(|sink| {
+            Box::pin(async move {
+                let client = client
+                    .ok_or_else(|| Error::FetchError("HTTP client is unavailable".to_string()))?;
+                fetch_http_stream(&client, url, options, sink).await
+            })
+        })
     }
 
     fn spawn_future(&mut self, future: OwnedFuture<(), Error>) {
@@ -140,3 +193,229 @@ impl NavigatorBackend for ExternalNavigatorBackend {
         }
     }
 }
+
+fn create_http_client() -> Option<Arc<HttpClient>> {
+    match HttpClient::builder()
+        .redirect_policy(RedirectPolicy::Follow)
+        .build()
+    {
+        Ok(client) => Some(Arc::new(client)),
+        Err(e) => {
+            log::error!("Unable to create HTTP client: {}", e);
+            None
+        }
+    }
+}
+
+/// Make an HTTP request and read the whole response, following redirects.
+///
+/// Responses with a status other than success are reported as errors.
+async fn fetch_http(
+    client: &HttpClient,
+    url: Url,
+    options: RequestOptions,
+) -> Result<Vec<u8>, Error> {
+    let mut response = send_http(client, url, options).await?;
+    let mut data = vec![];
+    response
+        .body_mut()
+        .read_to_end(&mut data)
+        .await
+        .map_err(Error::NetworkError)?;
+    Ok(data)
+}
+
+/// Make an HTTP request and send the response to `sink` in chunks as it
+/// arrives.
+async fn fetch_http_stream(
+    client: &HttpClient,
+    url: Url,
+    options: RequestOptions,
+    sink: ChunkSink,
+) -> Result<(), Error> {
+    let mut response = send_http(client, url, options).await?;
+    if let Some(length) = response.body().len() {
+        sink.set_total_length(length as usize);
+    }
+
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let length = response
+            .body_mut()
+            .read(&mut buffer)
+            .await
+            .map_err(Error::NetworkError)?;
+        if length == 0 {
+            return Ok(());
+        }
+        sink.send(buffer[..length].to_vec());
+    }
+}
+
+/// Make an HTTP request, following redirects.
+///
+/// Responses with a status other than success are reported as errors.
+async fn send_http(
+    client: &HttpClient,
+    url: Url,
+    options: RequestOptions,
+) -> Result<Response<Body>, Error> {
+    let request = match options.method() {
+        NavigationMethod::GET => Request::get(url.as_str()),
+        NavigationMethod::POST => Request::post(url.as_str()),
+    };
+    let request = match options.body() {
+        Some((data, mime)) => request
+            .header("Content-Type", mime.as_str())
+            .body(data.clone()),
+        None => request.body(vec![]),
+    }
+    .map_err(|e| Error::FetchError(e.to_string()))?;
+
+    let response = client
+        .send_async(request)
+        .await
+        .map_err(|e| Error::FetchError(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(Error::FetchError(format!(
+            "{} returned HTTP status {}",
+            url,
+            response.status()
+        )));
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// The head and body of a request received by `serve`.
+    type ReceivedRequest = (String, Vec<u8>);
+
+    /// Find the value of a header in the head of a request.
+    fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+        head.lines().find_map(|line| {
+            let mut parts = line.splitn(2, ':');
+            if parts.next()?.eq_ignore_ascii_case(name) {
+                Some(parts.next()?.trim())
+            } else {
+                None
+            }
+        })
+    }
+
+    fn response(status: &str, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{}",
+            status,
+            body.len(),
+            headers,
+            body
+        )
+    }
+
+    /// Answer one connection with each of `responses` in turn, on a local
+    /// port standing in for a web server.
+    ///
+    /// Returns the server's URL, and a handle to the requests it received.
+    fn serve(responses: Vec<String>) -> (Url, thread::JoinHandle<Vec<ReceivedRequest>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let handle = thread::spawn(move || {
+            let mut requests = vec![];
+            for response in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut head = String::new();
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    head.push_str(&line);
+                }
+
+                let content_length = header(&head, "Content-Length")
+                    .and_then(|length| length.parse().ok())
+                    .unwrap_or(0);
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+                requests.push((head, body));
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn get() {
+        let (url, server) = serve(vec![response("200 OK", "", "hello")]);
+        let client = create_http_client().unwrap();
+
+        let data = block_on(fetch_http(
+            &client,
+            url.join("data.txt").unwrap(),
+            RequestOptions::get(),
+        ));
+        assert_eq!(data.unwrap(), b"hello");
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].0.starts_with("GET /data.txt "));
+    }
+
+    #[test]
+    fn post() {
+        let (url, server) = serve(vec![response("200 OK", "", "ok")]);
+        let client = create_http_client().unwrap();
+
+        let body = (
+            b"name=value".to_vec(),
+            "application/x-www-form-urlencoded".to_string(),
+        );
+        let data = block_on(fetch_http(&client, url, RequestOptions::post(Some(body))));
+        assert_eq!(data.unwrap(), b"ok");
+
+        let requests = server.join().unwrap();
+        let (head, body) = &requests[0];
+        assert!(head.starts_with("POST / "));
+        assert_eq!(
+            header(head, "Content-Type"),
+            Some("application/x-www-form-urlencoded")
+        );
+        assert_eq!(body, b"name=value");
+    }
+
+    #[test]
+    fn redirect() {
+        let (url, server) = serve(vec![
+            response("302 Found", "Location: /moved.txt\r\n", ""),
+            response("200 OK", "", "moved"),
+        ]);
+        let client = create_http_client().unwrap();
+
+        let data = block_on(fetch_http(&client, url, RequestOptions::get()));
+        assert_eq!(data.unwrap(), b"moved");
+
+        let requests = server.join().unwrap();
+        assert!(requests[1].0.starts_with("GET /moved.txt "));
+    }
+
+    #[test]
+    fn error_status() {
+        let (url, server) = serve(vec![response("404 Not Found", "", "missing")]);
+        let client = create_http_client().unwrap();
+
+        let data = block_on(fetch_http(&client, url, RequestOptions::get()));
+        assert!(matches!(data, Err(Error::FetchError(_))));
+
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,312 @@
+//! TCP socket backend
+
+use generational_arena::Arena;
+use ruffle_core::backend::socket::{SocketBackend, SocketEvent, SocketHandle};
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait for a connection before giving up.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// A message from a socket's background threads.
+enum Message {
+    /// The connection attempt finished. Holds a handle to the stream if it was
+    /// successful, which is only used to shut the connection down.
+    Connected(Option<TcpStream>),
+    Data(Vec<u8>),
+    Closed,
+}
+
+struct Socket {
+    /// Bytes to be written by the socket's background thread.
+    ///
+    /// Bytes sent before the connection is established wait in the channel.
+    writer: Sender<Vec<u8>>,
+
+    /// A handle to the connection once it's established, used to shut it down.
+    stream: Option<TcpStream>,
+
+    /// Whether the connection failed or was closed by the remote end.
+    closed: bool,
+}
+
+/// Socket backend that makes real TCP connections.
+///
+/// Each socket is connected and written to by its own background thread, and
+/// read by another one which forwards everything it receives to the player
+/// through a channel. The player never blocks on the network.
+pub struct TcpSocketBackend {
+    sockets: Arena<Socket>,
+    sender: Sender<(SocketHandle, Message)>,
+    receiver: Receiver<(SocketHandle, Message)>,
+}
+
+impl TcpSocketBackend {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            sockets: Arena::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Connect to `host:port`, then write everything received from `writes`
+    /// until the player closes the socket.
+    ///
+    /// Reading happens on another thread, which forwards everything read to
+    /// `sender` until the connection closes.
+    fn run_socket(
+        handle: SocketHandle,
+        host: String,
+        port: u16,
+        sender: Sender<(SocketHandle, Message)>,
+        writes: Receiver<Vec<u8>>,
+    ) {
+        let stream = (host.as_str(), port)
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| {
+                addrs.find_map(|addr| TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).ok())
+            });
+
+        let mut stream = match stream {
+            Some(stream) => stream,
+            None => {
+                log::warn!("Unable to connect socket to {}:{}", host, port);
+                let _ = sender.send((handle, Message::Connected(None)));
+                return;
+            }
+        };
+
+        let (read_half, shutdown_handle) = match (stream.try_clone(), stream.try_clone()) {
+            (Ok(read_half), Ok(shutdown_handle)) => (read_half, shutdown_handle),
+            (Err(e), _) | (_, Err(e)) => {
+                log::warn!("Unable to use socket connection: {}", e);
+                let _ = sender.send((handle, Message::Connected(None)));
+                return;
+            }
+        };
+        if sender
+            .send((handle, Message::Connected(Some(shutdown_handle))))
+            .is_err()
+        {
+            return;
+        }
+
+        thread::spawn(move || Self::read_socket(handle, read_half, sender));
+
+        // This ends once the player drops the socket, closing the channel.
+        for data in writes {
+            if let Err(e) = stream.write_all(&data) {
+                log::warn!("Unable to write to socket: {}", e);
+                break;
+            }
+        }
+    }
+
+    /// Forward everything read from `stream` to `sender` until the connection
+    /// closes.
+    fn read_socket(
+        handle: SocketHandle,
+        mut stream: TcpStream,
+        sender: Sender<(SocketHandle, Message)>,
+    ) {
+        let mut buffer = [0; 4096];
+        loop {
+            match stream.read(&mut buffer) {
+                Ok(0) | Err(_) => {
+                    let _ = sender.send((handle, Message::Closed));
+                    return;
+                }
+                Ok(len) => {
+                    if sender
+                        .send((handle, Message::Data(buffer[..len].to_vec())))
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for TcpSocketBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SocketBackend for TcpSocketBackend {
+    fn connect(&mut self, host: &str, port: u16) -> SocketHandle {
+        let (writer, writes) = channel();
+        let handle = self.sockets.insert(Socket {
+            writer,
+            stream: None,
+            closed: false,
+        });
+
+        let host = host.to_string();
+        let sender = self.sender.clone();
+        thread::spawn(move || Self::run_socket(handle, host, port, sender, writes));
+
+        handle
+    }
+
+    fn send(&mut self, handle: SocketHandle, data: Vec<u8>) -> bool {
+        match self.sockets.get(handle) {
+            Some(socket) if !socket.closed => socket.writer.send(data).is_ok(),
+            _ => false,
+        }
+    }
+
+    fn close(&mut self, handle: SocketHandle) {
+        // Dropping the socket also stops its writing thread.
+        if let Some(Socket {
+            stream: Some(stream),
+            ..
+        }) = self.sockets.remove(handle)
+        {
+            // This stops the reading thread.
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    fn poll(&mut self) -> Vec<(SocketHandle, SocketEvent)> {
+        let mut events = vec![];
+        while let Ok((handle, message)) = self.receiver.try_recv() {
+            let socket = match self.sockets.get_mut(handle) {
+                Some(socket) => socket,
+                // The socket was closed in the meantime.
+                None => {
+                    if let Message::Connected(Some(stream)) = message {
+                        let _ = stream.shutdown(Shutdown::Both);
+                    }
+                    continue;
+                }
+            };
+
+            let event = match message {
+                Message::Connected(Some(stream)) => {
+                    socket.stream = Some(stream);
+                    SocketEvent::Connected(true)
+                }
+                Message::Connected(None) => {
+                    socket.closed = true;
+                    SocketEvent::Connected(false)
+                }
+                Message::Data(data) => SocketEvent::Data(data),
+                Message::Closed => {
+                    socket.stream = None;
+                    socket.closed = true;
+                    SocketEvent::Closed
+                }
+            };
+            events.push((handle, event));
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::time::Instant;
+
+    /// Poll `backend` until `count` events arrived, or a few seconds passed.
+    fn poll_events(
+        backend: &mut TcpSocketBackend,
+        count: usize,
+    ) -> Vec<(SocketHandle, SocketEvent)> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut events = vec![];
+        while events.len() < count && Instant::now() < deadline {
+            events.extend(backend.poll());
+            thread::sleep(Duration::from_millis(10));
+        }
+        events
+    }
+
+    #[test]
+    fn echo() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = [0; 64];
+            while let Ok(len) = stream.read(&mut buffer) {
+                if len == 0 || stream.write_all(&buffer[..len]).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut backend = TcpSocketBackend::new();
+        let handle = backend.connect("127.0.0.1", port);
+        // Sent before the connection is established.
+        assert!(backend.send(handle, b"<hello/>\0".to_vec()));
+
+        let mut connected = false;
+        let mut received = vec![];
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while received.len() < 9 && Instant::now() < deadline {
+            for (event_handle, event) in poll_events(&mut backend, 1) {
+                assert_eq!(event_handle, handle);
+                match event {
+                    SocketEvent::Connected(success) => connected = success,
+                    SocketEvent::Data(data) => received.extend(data),
+                    SocketEvent::Closed => panic!("Socket closed unexpectedly"),
+                }
+            }
+        }
+        assert!(connected);
+        assert_eq!(received, b"<hello/>\0");
+
+        backend.close(handle);
+        assert!(backend.poll().is_empty());
+        assert!(!backend.send(handle, b"<bye/>\0".to_vec()));
+    }
+
+    #[test]
+    fn send_after_remote_close() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            // Hang up as soon as the client connects.
+            let _ = listener.accept().unwrap();
+        });
+
+        let mut backend = TcpSocketBackend::new();
+        let handle = backend.connect("127.0.0.1", port);
+        let events = poll_events(&mut backend, 2);
+        assert_eq!(
+            events,
+            vec![
+                (handle, SocketEvent::Connected(true)),
+                (handle, SocketEvent::Closed)
+            ]
+        );
+        assert!(!backend.send(handle, b"<hello/>\0".to_vec()));
+    }
+
+    #[test]
+    fn connection_refused() {
+        // Bind and drop a listener to find a port that nobody listens on.
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let mut backend = TcpSocketBackend::new();
+        let handle = backend.connect("127.0.0.1", port);
+        let events = poll_events(&mut backend, 1);
+        assert_eq!(events, vec![(handle, SocketEvent::Connected(false))]);
+        assert!(!backend.send(handle, b"<hello/>\0".to_vec()));
+    }
+}
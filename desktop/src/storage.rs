@@ -1,19 +1,26 @@
-use ruffle_core::backend::storage::StorageBackend;
+use ruffle_core::backend::storage::{shared_object_key, StorageBackend};
 use std::fs;
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
+/// The file extension of a stored shared object.
+///
+/// Keys may contain `/`, so a shared object named `save` and one named
+/// `save/slot1` need to live side by side as `save.sol` and `save/slot1.sol`.
+const EXTENSION: &str = "sol";
+
+/// Characters that would let a key segment escape its directory, or that
+/// aren't allowed in file names on some platforms.
+const ESCAPED_CHARS: &[char] = &['%', '/', '\\', ':'];
+
 pub struct DiskStorageBackend {
     base_path: PathBuf,
 }
 
 impl DiskStorageBackend {
-    pub fn new(scope: &Path) -> Self {
-        let base_path = dirs::data_local_dir()
-            .unwrap()
-            .join(Path::new("ruffle"))
-            .join(scope);
+    pub fn new() -> Self {
+        let base_path = ruffle_data_dir().join(Path::new("SharedObjects"));
 
         // Create a base dir if one doesn't exist yet
         if !base_path.exists() {
@@ -25,11 +32,176 @@ impl DiskStorageBackend {
 
         DiskStorageBackend { base_path }
     }
+
+    /// Map a storage key to a file path under the base directory.
+    fn key_path(&self, name: &str) -> PathBuf {
+        key_path(&self.base_path, name)
+    }
+
+    /// Move the shared objects of a movie out of the directory that older
+    /// versions of Ruffle stored them in.
+    ///
+    /// Those versions kept every object of a movie directly in a directory
+    /// named after the movie file, regardless of where the movie came from.
+    pub fn migrate_legacy_objects(&self, movie_path: &Path, movie_url: Option<&str>) {
+        let legacy_dir = match movie_path.file_name() {
+            Some(file_name) => ruffle_data_dir().join(file_name),
+            None => return,
+        };
+        if legacy_dir == self.base_path || !legacy_dir.is_dir() {
+            return;
+        }
+
+        let entries = match fs::read_dir(&legacy_dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            let old_path = entry.path();
+            if !old_path.is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let key = match shared_object_key(movie_url, &name, None, false) {
+                Some(key) => key,
+                None => continue,
+            };
+
+            let new_path = self.key_path(&key);
+            if new_path.exists() {
+                continue;
+            }
+            if let Some(parent) = new_path.parent() {
+                if let Err(r) = fs::create_dir_all(parent) {
+                    log::warn!("Unable to create storage dir {}", r);
+                    continue;
+                }
+            }
+            match fs::rename(&old_path, &new_path) {
+                Ok(()) => log::info!("Migrated shared object {} to {:?}", name, new_path),
+                Err(r) => log::warn!("Unable to migrate shared object {}: {}", name, r),
+            }
+        }
+
+        // Only succeeds once every object has been moved out.
+        let _ = fs::remove_dir(&legacy_dir);
+    }
+
+    /// Sum the size of every stored shared object in `dir` whose key starts
+    /// with `prefix`.
+    fn dir_usage(&self, dir: &Path, prefix: &str) -> usize {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return 0,
+        };
+
+        let mut usage = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                usage += self.dir_usage(&path, prefix);
+            } else if path
+                .extension()
+                .map(|ext| ext == EXTENSION)
+                .unwrap_or(false)
+            {
+                let key = path
+                    .strip_prefix(&self.base_path)
+                    .ok()
+                    .map(|key| key.with_extension(""))
+                    .map(|key| {
+                        key.components()
+                            .map(|c| unescape_segment(&c.as_os_str().to_string_lossy()))
+                            .collect::<Vec<_>>()
+                            .join("/")
+                    });
+                if key.map(|key| key.starts_with(prefix)).unwrap_or(false) {
+                    usage += entry.metadata().map(|m| m.len() as usize).unwrap_or(0);
+                }
+            }
+        }
+        usage
+    }
+}
+
+/// The directory that Ruffle keeps its data in.
+fn ruffle_data_dir() -> PathBuf {
+    dirs::data_local_dir().unwrap().join(Path::new("ruffle"))
+}
+
+/// Map a storage key to a file path under `base_path`.
+///
+/// Every `/`-separated segment of the key becomes a directory, and the
+/// final one becomes the file name. Segments are escaped so that none of
+/// them can leave `base_path`, such as `..` or a Windows drive like `C:`.
+fn key_path(base_path: &Path, name: &str) -> PathBuf {
+    let mut path = base_path.to_path_buf();
+    let mut segments = name
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .peekable();
+    while let Some(segment) = segments.next() {
+        let segment = escape_segment(segment);
+        if segments.peek().is_some() {
+            path.push(segment);
+        } else {
+            path.push(format!("{}.{}", segment, EXTENSION));
+        }
+    }
+    path
+}
+
+/// Percent-encode the characters of a key segment that have a meaning in
+/// file paths, as well as the dots of a `.` or `..` segment.
+fn escape_segment(segment: &str) -> String {
+    if segment == "." || segment == ".." {
+        return segment.replace('.', "%2E");
+    }
+
+    let mut escaped = String::with_capacity(segment.len());
+    for c in segment.chars() {
+        if ESCAPED_CHARS.contains(&c) {
+            escaped.push_str(&format!("%{:02X}", c as u32));
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Reverse `escape_segment`.
+fn unescape_segment(segment: &str) -> String {
+    let mut unescaped = String::with_capacity(segment.len());
+    let mut rest = segment;
+    while let Some(index) = rest.find('%') {
+        unescaped.push_str(&rest[..index]);
+        let code = rest
+            .get(index + 1..index + 3)
+            .and_then(|code| u8::from_str_radix(code, 16).ok());
+        match code {
+            Some(code) => {
+                unescaped.push(char::from(code));
+                rest = &rest[index + 3..];
+            }
+            None => {
+                unescaped.push('%');
+                rest = &rest[index + 1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+impl Default for DiskStorageBackend {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StorageBackend for DiskStorageBackend {
     fn get_string(&self, name: &str) -> Option<String> {
-        let full_path = self.base_path.join(Path::new(name));
+        let full_path = self.key_path(name);
 
         match File::open(full_path) {
             Ok(mut file) => {
@@ -49,7 +221,14 @@ impl StorageBackend for DiskStorageBackend {
     }
 
     fn put_string(&mut self, name: &str, value: String) -> bool {
-        let full_path = self.base_path.join(Path::new(name));
+        let full_path = self.key_path(name);
+
+        if let Some(parent) = full_path.parent() {
+            if let Err(r) = fs::create_dir_all(parent) {
+                log::warn!("Unable to create storage dir {}", r);
+                return false;
+            }
+        }
 
         match File::create(full_path) {
             Ok(mut file) => {
@@ -68,7 +247,50 @@ impl StorageBackend for DiskStorageBackend {
     }
 
     fn remove_key(&mut self, name: &str) {
-        let full_path = self.base_path.join(Path::new(name));
+        let full_path = self.key_path(name);
         let _ = fs::remove_file(full_path);
     }
+
+    fn get_usage(&self, prefix: &str) -> usize {
+        // Only walk the directory of the first key segment, which is the domain.
+        let first_segment = prefix.split('/').next().unwrap_or_default();
+        if prefix.contains('/') && !first_segment.is_empty() {
+            self.dir_usage(&self.base_path.join(first_segment), prefix)
+        } else {
+            self.dir_usage(&self.base_path, prefix)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_path_stays_in_base_path() {
+        let base = Path::new("base");
+        assert_eq!(
+            key_path(base, "example.com/games/foo.swf/save"),
+            base.join("example.com/games/foo.swf/save.sol")
+        );
+        assert_eq!(
+            key_path(base, "localhost/C:/games/foo.swf/save"),
+            base.join("localhost/C%3A/games/foo.swf/save.sol")
+        );
+        assert_eq!(
+            key_path(base, "localhost/../../save"),
+            base.join("localhost/%2E%2E/%2E%2E/save.sol")
+        );
+        assert_eq!(
+            key_path(base, "localhost//./a\\b"),
+            base.join("localhost/%2E/a%5Cb.sol")
+        );
+    }
+
+    #[test]
+    fn escaped_segments_round_trip() {
+        for segment in &["save", "C:", "..", ".", "a\\b", "100%"] {
+            assert_eq!(unescape_segment(&escape_segment(segment)), *segment);
+        }
+    }
 }
@@ -0,0 +1,54 @@
+//! Log backend that shows `trace` output on the terminal and over the movie.
+
+use ruffle_core::backend::log::{LogBackend, LogSource};
+use std::collections::VecDeque;
+
+/// How many lines of trace output are shown over the movie.
+const SHOWN_LINES: usize = 10;
+
+/// Prints traces to stdout, and optionally keeps the latest ones to be drawn
+/// over the movie.
+pub struct TraceConsoleBackend {
+    show_on_screen: bool,
+    lines: VecDeque<String>,
+
+    /// The shown lines, oldest first, as handed to the player.
+    overlay: Vec<String>,
+}
+
+impl TraceConsoleBackend {
+    pub fn new(show_on_screen: bool) -> Self {
+        Self {
+            show_on_screen,
+            lines: VecDeque::with_capacity(SHOWN_LINES),
+            overlay: vec![],
+        }
+    }
+}
+
+impl LogBackend for TraceConsoleBackend {
+    fn trace(&mut self, _source: &LogSource, message: &str) {
+        println!("{}", message);
+
+        if self.show_on_screen {
+            for line in message.lines() {
+                if self.lines.len() == SHOWN_LINES {
+                    self.lines.pop_front();
+                }
+                self.lines.push_back(line.to_string());
+            }
+            self.overlay = self.lines.iter().cloned().collect();
+        }
+    }
+
+    fn avm_warning(&mut self, source: &LogSource, message: &str) {
+        match source.frame {
+            Some(frame) => log::warn!("{} (frame {})", message, frame),
+            None => log::warn!("{}", message),
+        }
+    }
+
+    fn overlay(&self) -> &[String] {
+        &self.overlay
+    }
+}
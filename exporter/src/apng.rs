@@ -0,0 +1,191 @@
+//! Writing captured frames as an animated PNG.
+//!
+//! Each frame is encoded as a regular PNG, and its image data is then moved
+//! into the frame chunks of the animation.
+
+use crate::frame_rate_fraction;
+use image::png::PNGEncoder;
+use image::{ColorType, RgbaImage};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+pub fn write_apng(
+    path: &Path,
+    images: &[RgbaImage],
+    frame_rate: f64,
+) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    encode_apng(&mut writer, images, frame_rate)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn encode_apng(
+    writer: &mut impl Write,
+    images: &[RgbaImage],
+    frame_rate: f64,
+) -> Result<(), Box<dyn Error>> {
+    let first = images.first().ok_or("No frames to write")?;
+    let (delay_num, delay_den) = frame_delay(frame_rate);
+    writer.write_all(PNG_SIGNATURE)?;
+
+    // Frame control and frame data chunks share one sequence.
+    let mut sequence_number = 0u32;
+    for (i, image) in images.iter().enumerate() {
+        let png = encode_png(image)?;
+        let chunks = read_chunks(&png)?;
+        if i == 0 {
+            let header = chunks
+                .iter()
+                .find(|(chunk_type, _)| chunk_type == b"IHDR")
+                .ok_or("Encoded PNG has no header")?;
+            write_chunk(writer, b"IHDR", header.1)?;
+
+            let mut animation_control = Vec::with_capacity(8);
+            animation_control.extend_from_slice(&(images.len() as u32).to_be_bytes());
+            animation_control.extend_from_slice(&0u32.to_be_bytes()); // Loop forever
+            write_chunk(writer, b"acTL", &animation_control)?;
+        }
+
+        let mut frame_control = Vec::with_capacity(26);
+        frame_control.extend_from_slice(&sequence_number.to_be_bytes());
+        frame_control.extend_from_slice(&first.width().to_be_bytes());
+        frame_control.extend_from_slice(&first.height().to_be_bytes());
+        frame_control.extend_from_slice(&0u32.to_be_bytes()); // x offset
+        frame_control.extend_from_slice(&0u32.to_be_bytes()); // y offset
+        frame_control.extend_from_slice(&delay_num.to_be_bytes());
+        frame_control.extend_from_slice(&delay_den.to_be_bytes());
+        frame_control.push(0); // APNG_DISPOSE_OP_NONE
+        frame_control.push(0); // APNG_BLEND_OP_SOURCE
+        write_chunk(writer, b"fcTL", &frame_control)?;
+        sequence_number += 1;
+
+        for (_, data) in chunks
+            .iter()
+            .filter(|(chunk_type, _)| chunk_type == b"IDAT")
+        {
+            if i == 0 {
+                // The first frame doubles as the default image.
+                write_chunk(writer, b"IDAT", data)?;
+            } else {
+                let mut frame_data = Vec::with_capacity(data.len() + 4);
+                frame_data.extend_from_slice(&sequence_number.to_be_bytes());
+                frame_data.extend_from_slice(data);
+                write_chunk(writer, b"fdAT", &frame_data)?;
+                sequence_number += 1;
+            }
+        }
+    }
+
+    write_chunk(writer, b"IEND", &[])?;
+    Ok(())
+}
+
+/// The delay between frames as a fraction of a second.
+fn frame_delay(frame_rate: f64) -> (u16, u16) {
+    let (num, den) = frame_rate_fraction(frame_rate);
+    let clamp = |value: u32| value.min(u32::from(u16::MAX)) as u16;
+    (clamp(den), clamp(num))
+}
+
+fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut png = Vec::new();
+    PNGEncoder::new(&mut png).encode(image, image.width(), image.height(), ColorType::Rgba8)?;
+    Ok(png)
+}
+
+/// The type and data of a PNG chunk.
+type Chunk<'a> = ([u8; 4], &'a [u8]);
+
+/// Splits an encoded PNG into its chunks.
+fn read_chunks(png: &[u8]) -> Result<Vec<Chunk<'_>>, Box<dyn Error>> {
+    let mut chunks = Vec::new();
+    let mut rest = png
+        .get(PNG_SIGNATURE.len()..)
+        .ok_or("Encoded PNG is truncated")?;
+    while rest.len() >= 12 {
+        let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let chunk_type = [rest[4], rest[5], rest[6], rest[7]];
+        let data = rest.get(8..8 + length).ok_or("Encoded PNG is truncated")?;
+        chunks.push((chunk_type, data));
+        rest = rest.get(12 + length..).ok_or("Encoded PNG is truncated")?;
+    }
+    Ok(chunks)
+}
+
+fn write_chunk(writer: &mut impl Write, chunk_type: &[u8; 4], data: &[u8]) -> std::io::Result<()> {
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(chunk_type);
+    crc.update(data);
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(chunk_type)?;
+    writer.write_all(data)?;
+    writer.write_all(&crc.finalize().to_be_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::png::PngDecoder;
+    use image::{AnimationDecoder, Rgba};
+
+    #[test]
+    fn frame_delays() {
+        assert_eq!(frame_delay(24.0), (1, 24));
+        assert_eq!(frame_delay(12.5), (2, 25));
+        assert_eq!(frame_delay(0.0), (256, 1));
+    }
+
+    #[test]
+    fn chunks() {
+        let images = vec![
+            RgbaImage::from_pixel(2, 1, Rgba([255, 0, 0, 255])),
+            RgbaImage::from_pixel(2, 1, Rgba([0, 0, 255, 128])),
+        ];
+        let mut apng = vec![];
+        encode_apng(&mut apng, &images, 12.5).unwrap();
+        assert!(apng.starts_with(PNG_SIGNATURE));
+
+        let chunks = read_chunks(&apng).unwrap();
+        let types: Vec<&[u8]> = chunks
+            .iter()
+            .map(|(chunk_type, _)| &chunk_type[..])
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                &b"IHDR"[..],
+                b"acTL",
+                b"fcTL",
+                b"IDAT",
+                b"fcTL",
+                b"fdAT",
+                b"IEND"
+            ]
+        );
+        // Two frames, looping forever.
+        assert_eq!(chunks[1].1, [0, 0, 0, 2, 0, 0, 0, 0]);
+        // The sequence numbers of the second frame's chunks, and its delay.
+        assert_eq!(chunks[4].1[..4], [0, 0, 0, 1]);
+        assert_eq!(chunks[4].1[20..24], [0, 2, 0, 25]);
+        assert_eq!(chunks[5].1[..4], [0, 0, 0, 2]);
+
+        let decoder = PngDecoder::new(&apng[..]).unwrap();
+        assert!(decoder.is_apng());
+        let frames = decoder.apng().into_frames().collect_frames().unwrap();
+        assert_eq!(frames.len(), 2);
+        for (frame, image) in frames.iter().zip(&images) {
+            assert_eq!(frame.buffer(), image);
+            assert_eq!(frame.delay().numer_denom_ms(), (80, 1));
+        }
+    }
+
+    #[test]
+    fn no_frames() {
+        assert!(encode_apng(&mut vec![], &[], 24.0).is_err());
+    }
+}
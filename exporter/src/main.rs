@@ -1,8 +1,16 @@
+mod apng;
+mod symbols;
+mod wav;
+mod y4m;
+
+use crate::y4m::Y4mWriter;
 use clap::Clap;
 use futures::executor::block_on;
-use image::RgbaImage;
+use image::gif::Encoder as GifEncoder;
+use image::{Delay, Frame, RgbaImage};
 use indicatif::{ProgressBar, ProgressStyle};
-use ruffle_core::backend::audio::NullAudioBackend;
+use ruffle_core::backend::audio::offline::OfflineAudioBackend;
+use ruffle_core::backend::audio::{AudioBackend, NullAudioBackend};
 use ruffle_core::backend::input::NullInputBackend;
 use ruffle_core::backend::navigator::NullNavigatorBackend;
 use ruffle_core::backend::storage::MemoryStorageBackend;
@@ -11,10 +19,12 @@ use ruffle_core::Player;
 use ruffle_render_wgpu::target::TextureTarget;
 use ruffle_render_wgpu::WgpuRenderBackend;
 use std::error::Error;
-use std::fs::create_dir_all;
+use std::fs::{create_dir_all, File};
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::sync::Arc;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use walkdir::{DirEntry, WalkDir};
 
 #[derive(Clap, Debug, Copy, Clone)]
@@ -32,6 +42,196 @@ struct SizeOpt {
     height: Option<u32>,
 }
 
+#[derive(Clap, Debug, Clone)]
+struct FrameOpt {
+    /// Number of frames to capture per file. Defaults to 1, or to no limit
+    /// with `--stop-label` or `--until-end`
+    #[clap(short = "f", long = "frames")]
+    frames: Option<u32>,
+
+    /// Number of frames to skip
+    #[clap(long = "skipframes", default_value = "0")]
+    skipframes: u32,
+
+    /// Start capturing when the root timeline reaches this frame label
+    #[clap(long = "start-label")]
+    start_label: Option<String>,
+
+    /// Stop capturing when the root timeline reaches this frame label. Without
+    /// `--frames`, capturing also stops if the timeline stops or loops first
+    #[clap(long = "stop-label")]
+    stop_label: Option<String>,
+
+    /// Stop capturing when the root timeline stops or loops
+    #[clap(long = "until-end")]
+    until_end: bool,
+}
+
+impl FrameOpt {
+    /// The most frames to capture, or `None` to capture until a stop
+    /// condition.
+    fn limit(&self) -> Option<u32> {
+        if self.frames.is_some() || self.stop_label.is_some() || self.until_end {
+            self.frames
+        } else {
+            Some(1)
+        }
+    }
+}
+
+/// What to do with a frame the root timeline has reached.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum FrameAction {
+    Skip,
+    Capture,
+    Stop,
+}
+
+/// Decides which frames to capture as the movie runs.
+struct FrameSelection {
+    start_frame: Option<u16>,
+    stop_frame: Option<u16>,
+    skip_frames: u32,
+    limit: Option<u32>,
+
+    /// Whether to stop once the root timeline stops or loops. This is always
+    /// the case without a frame limit, so that a stop label that's never
+    /// reached doesn't run the movie forever.
+    stop_at_end: bool,
+
+    /// How many frames have been run.
+    frames_run: u32,
+
+    /// How many frames have been captured.
+    frame_count: u32,
+
+    /// Whether the start label has been reached.
+    started: bool,
+
+    /// The frame the root timeline was on after the last frame ran.
+    last_frame: u16,
+}
+
+impl FrameSelection {
+    fn new(opt: &FrameOpt, start_frame: Option<u16>, stop_frame: Option<u16>) -> Self {
+        let limit = opt.limit();
+        Self {
+            start_frame,
+            stop_frame,
+            skip_frames: opt.skipframes,
+            limit,
+            stop_at_end: opt.until_end || limit.is_none(),
+            frames_run: 0,
+            frame_count: 0,
+            started: start_frame.is_none(),
+            last_frame: 0,
+        }
+    }
+
+    /// Whether to run another frame.
+    fn wants_more(&self) -> bool {
+        self.limit.map_or(true, |limit| self.frame_count < limit)
+    }
+
+    /// Decide what to do with the frame that just ran, given the frame the
+    /// root timeline is now on.
+    ///
+    /// Returns `None` if the timeline ended before it reached the start label.
+    fn select(&mut self, frame: u16) -> Option<FrameAction> {
+        self.frames_run += 1;
+
+        // The timeline has ended if it went back to an earlier frame, or
+        // stayed on the same one because it stopped.
+        let has_ended = frame <= self.last_frame;
+        self.last_frame = frame;
+        if !self.started {
+            if has_ended {
+                return None;
+            }
+            self.started = self.start_frame == Some(frame);
+        }
+
+        if !self.started || self.frames_run <= self.skip_frames {
+            return Some(FrameAction::Skip);
+        }
+        if self.stop_frame == Some(frame) || (self.stop_at_end && has_ended && self.frame_count > 0)
+        {
+            return Some(FrameAction::Stop);
+        }
+        self.frame_count += 1;
+        Some(FrameAction::Capture)
+    }
+}
+
+/// The frame rate of a movie as a fraction in lowest terms. SWF frame rates
+/// are 8.8 fixed point, so they're exact in 256ths.
+fn frame_rate_fraction(frame_rate: f64) -> (u32, u32) {
+    let num = (frame_rate * 256.0).round().max(1.0) as u32;
+    let gcd = gcd(num, 256);
+    (num / gcd, 256 / gcd)
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// How captured frames are written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum OutputFormat {
+    /// A PNG image, or a directory of them if there are multiple frames.
+    Png,
+
+    /// An animated PNG.
+    Apng,
+
+    /// An animated GIF.
+    Gif,
+
+    /// The RGBA bytes of each frame, written to stdout.
+    Raw,
+
+    /// A YUV4MPEG2 video, written to stdout.
+    Y4m,
+}
+
+impl OutputFormat {
+    /// Whether frames are streamed to stdout rather than saved to a file.
+    fn is_stream(self) -> bool {
+        self == OutputFormat::Raw || self == OutputFormat::Y4m
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Png | OutputFormat::Apng => "png",
+            OutputFormat::Gif => "gif",
+            OutputFormat::Raw => "rgba",
+            OutputFormat::Y4m => "y4m",
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "png" => Ok(OutputFormat::Png),
+            "apng" => Ok(OutputFormat::Apng),
+            "gif" => Ok(OutputFormat::Gif),
+            "raw" => Ok(OutputFormat::Raw),
+            "y4m" => Ok(OutputFormat::Y4m),
+            _ => Err(format!(
+                "Unknown format {}; expected png, apng, gif, raw or y4m",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(Clap, Debug)]
 #[clap(name = "Ruffle Exporter", author, version)]
 struct Opt {
@@ -43,91 +243,245 @@ struct Opt {
     /// The default value will either be:
     /// - If given one swf and one frame, the name of the swf + ".png"
     /// - If given one swf and multiple frames, the name of the swf as a directory
+    /// - If given one swf and an animated format, the name of the swf + its extension
     /// - If given multiple swfs, this field is required.
     #[clap(name = "output", parse(from_os_str))]
     output_path: Option<PathBuf>,
 
-    /// Number of frames to capture per file
-    #[clap(short = "f", long = "frames", default_value = "1")]
-    frames: u32,
+    #[clap(flatten)]
+    frames: FrameOpt,
 
-    /// Number of frames to skip
-    #[clap(long = "skipframes", default_value = "0")]
-    skipframes: u32,
+    /// How to write the frames: "png" for an image per frame, "apng" or "gif"
+    /// for an animation, or "raw" (RGBA) or "y4m" for a video stream on stdout
+    #[clap(long = "format", default_value = "png")]
+    format: OutputFormat,
+
+    /// Export each character in the movie's library instead of capturing
+    /// frames: shapes, sprites, morph shapes and font glyph sheets as PNG,
+    /// shapes as SVG, bitmaps as PNG and sounds as WAV or MP3
+    #[clap(long = "symbols")]
+    symbols: bool,
 
     /// Don't show a progress bar
     #[clap(short, long)]
     silent: bool,
 
+    /// Also mix the movie's sounds into a WAV file covering the captured
+    /// frames, named after the output, or `audio.wav` in the frames directory
+    #[clap(short, long)]
+    audio: bool,
+
     #[clap(flatten)]
     size: SizeOpt,
 }
 
-fn take_screenshot(
-    device: Rc<wgpu::Device>,
-    queue: Rc<wgpu::Queue>,
-    swf_path: &Path,
-    frames: u32,
-    skipframes: u32,
-    progress: &Option<ProgressBar>,
-    size: SizeOpt,
-) -> Result<Vec<RgbaImage>, Box<dyn std::error::Error>> {
-    let movie = SwfMovie::from_path(&swf_path)?;
+/// The sample rate of exported audio.
+const SAMPLE_RATE: u32 = 44100;
 
-    let width = size.width.unwrap_or_else(|| movie.width());
-    let width = (width as f32 * size.scale).round() as u32;
+/// The result of capturing frames from a movie.
+struct Capture {
+    /// How many frames were captured.
+    frame_count: u32,
 
-    let height = size.height.unwrap_or_else(|| movie.height());
-    let height = (height as f32 * size.scale).round() as u32;
+    /// The frame rate of the movie, for the delay between frames.
+    frame_rate: f64,
 
+    /// The audio played during the captured frames, if it was mixed.
+    audio: Option<Vec<[i16; 2]>>,
+}
+
+/// Creates a player that renders to a texture of the given size.
+fn create_player(
+    device: Rc<wgpu::Device>,
+    queue: Rc<wgpu::Queue>,
+    (width, height): (u32, u32),
+    audio: Box<dyn AudioBackend>,
+) -> Result<Arc<Mutex<Player>>, Box<dyn Error>> {
     let target = TextureTarget::new(&device, (width, height));
     let player = Player::new(
         Box::new(WgpuRenderBackend::new(device, queue, target)?),
-        Box::new(NullAudioBackend::new()),
+        audio,
         Box::new(NullNavigatorBackend::new()),
         Box::new(NullInputBackend::new()),
         Box::new(MemoryStorageBackend::default()),
     )?;
-
     player
         .lock()
         .unwrap()
         .set_viewport_dimensions(width, height);
-    player.lock().unwrap().set_root_movie(Arc::new(movie));
+    Ok(player)
+}
+
+/// Captures what the player last rendered.
+fn capture(player: &mut Player) -> Option<RgbaImage> {
+    let renderer = player
+        .renderer_mut()
+        .downcast_mut::<WgpuRenderBackend<TextureTarget>>()
+        .unwrap();
+    renderer.target().capture(renderer.device())
+}
 
-    let mut result = Vec::new();
-    let totalframes = frames + skipframes;
+/// Runs a movie and passes each captured frame to `on_frame`.
+fn take_screenshot(
+    device: Rc<wgpu::Device>,
+    queue: Rc<wgpu::Queue>,
+    swf_path: &Path,
+    movie: SwfMovie,
+    opt: &Opt,
+    progress: &Option<ProgressBar>,
+    on_frame: &mut dyn FnMut(RgbaImage) -> Result<(), Box<dyn Error>>,
+) -> Result<Capture, Box<dyn std::error::Error>> {
+    let size = opt.size;
+    let width = size.width.unwrap_or_else(|| movie.width());
+    let width = (width as f32 * size.scale).round() as u32;
+
+    let height = size.height.unwrap_or_else(|| movie.height());
+    let height = (height as f32 * size.scale).round() as u32;
 
-    for i in 0..totalframes {
+    let audio: Box<dyn AudioBackend> = if opt.audio {
+        Box::new(OfflineAudioBackend::new(SAMPLE_RATE))
+    } else {
+        Box::new(NullAudioBackend::new())
+    };
+    let player = create_player(device, queue, (width, height), audio)?;
+    player.lock().unwrap().set_root_movie(Arc::new(movie));
+
+    let frame_opt = &opt.frames;
+    let label_frame = |label: &Option<String>| -> Result<Option<u16>, Box<dyn Error>> {
+        match label {
+            Some(label) => match player.lock().unwrap().root_frame_label(label) {
+                Some(frame) => Ok(Some(frame)),
+                None => Err(format!("No frame label {} in {:?}", label, swf_path).into()),
+            },
+            None => Ok(None),
+        }
+    };
+    let mut selection = FrameSelection::new(
+        frame_opt,
+        label_frame(&frame_opt.start_label)?,
+        label_frame(&frame_opt.stop_label)?,
+    );
+    let mut audio = if opt.audio { Some(Vec::new()) } else { None };
+    let mut skipped_audio = Vec::new();
+
+    while selection.wants_more() {
         if let Some(progress) = &progress {
             progress.set_message(&format!(
                 "{} frame {}",
                 swf_path.file_stem().unwrap().to_string_lossy(),
-                i
+                selection.frames_run
             ));
         }
         player.lock().unwrap().run_frame();
-        if i >= skipframes {
-            player.lock().unwrap().render();
+
+        let frame = player.lock().unwrap().root_frame().unwrap_or(0);
+        let capturing = match selection.select(frame) {
+            Some(FrameAction::Capture) => true,
+            Some(FrameAction::Skip) => false,
+            Some(FrameAction::Stop) => break,
+            None => {
+                return Err(format!("{:?} ended before reaching its start label", swf_path).into())
+            }
+        };
+
+        if let Some(audio) = &mut audio {
+            // Skipped frames are still mixed, so that sounds they start carry
+            // on into the captured frames.
+            let output = if capturing { audio } else { &mut skipped_audio };
             let mut player = player.lock().unwrap();
-            let renderer = player
-                .renderer_mut()
-                .downcast_mut::<WgpuRenderBackend<TextureTarget>>()
-                .unwrap();
-            let target = renderer.target();
-            if let Some(image) = target.capture(renderer.device()) {
-                result.push(image);
-            } else {
-                return Err(format!("Unable to capture frame {} of {:?}", i, swf_path).into());
+            if let Some(mixer) = player.audio_mut().downcast_mut::<OfflineAudioBackend>() {
+                mixer.mix_frame(output);
             }
+            skipped_audio.clear();
         }
 
-        if let Some(progress) = &progress {
-            progress.inc(1);
+        if capturing {
+            player.lock().unwrap().render();
+            let image = capture(&mut player.lock().unwrap());
+            match image {
+                Some(image) => on_frame(image)?,
+                None => {
+                    return Err(format!(
+                        "Unable to capture frame {} of {:?}",
+                        selection.frames_run, swf_path
+                    )
+                    .into())
+                }
+            }
+
+            if let Some(progress) = &progress {
+                progress.inc(1);
+            }
         }
     }
 
-    Ok(result)
+    let frame_rate = player.lock().unwrap().frame_rate();
+    Ok(Capture {
+        frame_count: selection.frame_count,
+        frame_rate,
+        audio,
+    })
+}
+
+/// Writes frames as an animated GIF.
+fn write_gif(path: &Path, images: Vec<RgbaImage>, frame_rate: f64) -> Result<(), Box<dyn Error>> {
+    let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+    // GIF delays are in hundredths of a second, so each frame's end time is
+    // rounded rather than its delay, to stop the animation drifting.
+    let end_time = |frame: usize| (frame as f64 * 100.0 / frame_rate).round() as u32;
+    for (i, image) in images.into_iter().enumerate() {
+        let delay = Delay::from_numer_denom_ms((end_time(i + 1) - end_time(i)) * 10, 1);
+        encoder.encode_frame(Frame::from_parts(image, 0, 0, delay))?;
+    }
+    Ok(())
+}
+
+/// Saves captured frames to `destination`, as one image, a directory of
+/// images, or an animation.
+fn save_frames(
+    format: OutputFormat,
+    images: Vec<RgbaImage>,
+    frame_rate: f64,
+    destination: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = destination.parent() {
+        let _ = create_dir_all(parent);
+    }
+    match format {
+        OutputFormat::Png if images.len() == 1 => images[0].save(destination)?,
+        OutputFormat::Png => {
+            let _ = create_dir_all(destination);
+            for (frame, image) in images.iter().enumerate() {
+                image.save(&destination.join(format!("{}.png", frame)))?;
+            }
+        }
+        OutputFormat::Apng => apng::write_apng(destination, &images, frame_rate)?,
+        OutputFormat::Gif => write_gif(destination, images, frame_rate)?,
+        OutputFormat::Raw | OutputFormat::Y4m => unreachable!("Streams aren't saved"),
+    }
+    Ok(())
+}
+
+/// Where the audio of a capture is saved: next to the output file, or inside
+/// the output directory.
+fn audio_path(output: &Path, is_directory: bool) -> PathBuf {
+    if is_directory {
+        output.join("audio.wav")
+    } else {
+        output.with_extension("wav")
+    }
+}
+
+fn new_progress_bar(length: u64) -> ProgressBar {
+    let progress = ProgressBar::new(length);
+    progress.set_style(
+        ProgressStyle::default_bar()
+            .template(
+                "[{elapsed_precise}] {bar:40.cyan/blue} [{eta_precise}] {pos:>7}/{len:7} {msg}",
+            )
+            .progress_chars("##-"),
+    );
+    progress
 }
 
 fn find_files(root: &Path, with_progress: bool) -> Vec<DirEntry> {
@@ -165,78 +519,106 @@ fn capture_single_swf(
     queue: Rc<wgpu::Queue>,
     opt: &Opt,
 ) -> Result<(), Box<dyn Error>> {
+    let is_directory = opt.format == OutputFormat::Png && opt.frames.limit() != Some(1);
     let output = opt.output_path.clone().unwrap_or_else(|| {
         let mut result = PathBuf::new();
-        if opt.frames == 1 {
-            result.set_file_name(opt.swf.file_stem().unwrap());
-            result.set_extension("png");
-        } else {
-            result.set_file_name(opt.swf.file_stem().unwrap());
+        result.set_file_name(opt.swf.file_stem().unwrap());
+        if !is_directory {
+            result.set_extension(opt.format.extension());
         }
         result
     });
 
-    if opt.frames > 1 {
-        let _ = create_dir_all(&output);
-    }
-
     let progress = if !opt.silent {
-        let progress = ProgressBar::new(opt.frames as u64);
-        progress.set_style(
-            ProgressStyle::default_bar()
-                .template(
-                    "[{elapsed_precise}] {bar:40.cyan/blue} [{eta_precise}] {pos:>7}/{len:7} {msg}",
-                )
-                .progress_chars("##-"),
-        );
-        Some(progress)
+        Some(new_progress_bar(opt.frames.limit().unwrap_or(0).into()))
     } else {
         None
     };
 
-    let frames = take_screenshot(
-        device,
-        queue,
-        &opt.swf,
-        opt.frames,
-        opt.skipframes,
-        &progress,
-        opt.size,
-    )?;
+    let movie = SwfMovie::from_path(&opt.swf)?;
+    let mut images = Vec::new();
+    let capture = if opt.format.is_stream() {
+        let stdout = std::io::stdout();
+        let mut stdout = BufWriter::new(stdout.lock());
+        let capture = if opt.format == OutputFormat::Y4m {
+            let mut writer = Y4mWriter::new(&mut stdout, movie.header().frame_rate.into());
+            let capture = take_screenshot(
+                device,
+                queue,
+                &opt.swf,
+                movie,
+                opt,
+                &progress,
+                &mut |image| Ok(writer.write_frame(&image)?),
+            )?;
+            writer.flush()?;
+            capture
+        } else {
+            take_screenshot(
+                device,
+                queue,
+                &opt.swf,
+                movie,
+                opt,
+                &progress,
+                &mut |image| Ok(stdout.write_all(&image)?),
+            )?
+        };
+        stdout.flush()?;
+        capture
+    } else {
+        let capture = take_screenshot(
+            device,
+            queue,
+            &opt.swf,
+            movie,
+            opt,
+            &progress,
+            &mut |image| {
+                images.push(image);
+                Ok(())
+            },
+        )?;
+        if images.is_empty() {
+            return Err(format!("No frames were captured from {:?}", opt.swf).into());
+        }
+        save_frames(opt.format, images, capture.frame_rate, &output)?;
+        capture
+    };
 
     if let Some(progress) = &progress {
         progress.set_message(&opt.swf.file_stem().unwrap().to_string_lossy());
     }
 
-    if frames.len() == 1 {
-        frames.get(0).unwrap().save(&output)?;
-    } else {
-        for (frame, image) in frames.iter().enumerate() {
-            let mut path = PathBuf::from(&output);
-            path.push(format!("{}.png", frame));
-            image.save(&path)?;
-        }
+    if let Some(audio) = &capture.audio {
+        wav::write_wav(&audio_path(&output, is_directory), SAMPLE_RATE, audio)?;
     }
 
-    let message = if frames.len() == 1 {
+    let destination = if opt.format.is_stream() {
+        "stdout".into()
+    } else {
+        output.to_string_lossy()
+    };
+    let message = if capture.frame_count == 1 {
         format!(
             "Saved first frame of {} to {}",
             opt.swf.to_string_lossy(),
-            output.to_string_lossy()
+            destination
         )
     } else {
         format!(
-            "Saved first {} frames of {} to {}",
-            frames.len(),
+            "Saved {} frames of {} to {}",
+            capture.frame_count,
             opt.swf.to_string_lossy(),
-            output.to_string_lossy()
+            destination
         )
     };
 
     if let Some(progress) = progress {
         progress.finish_with_message(&message);
     } else {
-        println!("{}", message);
+        // Streams keep stdout to themselves.
+        eprintln!("{}", message);
     }
 
     Ok(())
@@ -247,78 +629,78 @@ fn capture_multiple_swfs(
     queue: Rc<wgpu::Queue>,
     opt: &Opt,
 ) -> Result<(), Box<dyn Error>> {
+    if opt.format.is_stream() {
+        return Err("Only a single file can be streamed to stdout.".into());
+    }
     let output = opt.output_path.clone().unwrap();
     let files = find_files(&opt.swf, !opt.silent);
 
     let progress = if !opt.silent {
-        let progress = ProgressBar::new((files.len() as u64) * (opt.frames as u64));
-        progress.set_style(
-            ProgressStyle::default_bar()
-                .template(
-                    "[{elapsed_precise}] {bar:40.cyan/blue} [{eta_precise}] {pos:>7}/{len:7} {msg}",
-                )
-                .progress_chars("##-"),
-        );
-        Some(progress)
+        let frames = u64::from(opt.frames.limit().unwrap_or(0));
+        Some(new_progress_bar((files.len() as u64) * frames))
     } else {
         None
     };
 
     for file in &files {
-        let frames = take_screenshot(
+        let movie = SwfMovie::from_path(file.path())?;
+        let mut images = Vec::new();
+        let capture = take_screenshot(
             device.clone(),
             queue.clone(),
             &file.path(),
-            opt.frames,
-            opt.skipframes,
+            movie,
+            opt,
             &progress,
-            opt.size,
+            &mut |image| {
+                images.push(image);
+                Ok(())
+            },
         )?;
 
         if let Some(progress) = &progress {
             progress.set_message(&file.path().file_stem().unwrap().to_string_lossy());
         }
+        if images.is_empty() {
+            continue;
+        }
 
-        let mut relative_path = file
-            .path()
-            .strip_prefix(&opt.swf)
-            .unwrap_or_else(|_| &file.path())
-            .to_path_buf();
-
-        if frames.len() == 1 {
-            let mut destination = PathBuf::from(&output);
-            relative_path.set_extension("png");
-            destination.push(relative_path);
-            if let Some(parent) = destination.parent() {
-                let _ = create_dir_all(parent);
-            }
-            frames.get(0).unwrap().save(&destination)?;
+        let mut destination = PathBuf::from(&output);
+        destination.push(
+            file.path()
+                .strip_prefix(&opt.swf)
+                .unwrap_or_else(|_| &file.path()),
+        );
+        let is_directory = opt.format == OutputFormat::Png && images.len() > 1;
+        if is_directory {
+            destination.set_extension("");
         } else {
-            let mut parent = PathBuf::from(&output);
-            relative_path.set_extension("");
-            parent.push(&relative_path);
-            let _ = create_dir_all(&parent);
-            for (frame, image) in frames.iter().enumerate() {
-                let mut destination = parent.clone();
-                destination.push(format!("{}.png", frame));
-                image.save(&destination)?;
-            }
+            destination.set_extension(opt.format.extension());
+        }
+        save_frames(opt.format, images, capture.frame_rate, &destination)?;
+
+        if let Some(audio) = &capture.audio {
+            wav::write_wav(&audio_path(&destination, is_directory), SAMPLE_RATE, audio)?;
         }
     }
 
-    let message = if opt.frames == 1 {
-        format!(
+    let message = match opt.frames.limit() {
+        Some(1) => format!(
             "Saved first frame of {} files to {}",
             files.len(),
             output.to_string_lossy()
-        )
-    } else {
-        format!(
+        ),
+        Some(frames) => format!(
             "Saved first {} frames of {} files to {}",
-            opt.frames,
+            frames,
             files.len(),
             output.to_string_lossy()
-        )
+        ),
+        None => format!(
+            "Saved frames of {} files to {}",
+            files.len(),
+            output.to_string_lossy()
+        ),
     };
 
     if let Some(progress) = progress {
@@ -350,7 +732,9 @@ fn main() -> Result<(), Box<dyn Error>> {
         limits: wgpu::Limits::default(),
     }));
 
-    if opt.swf.is_file() {
+    if opt.symbols {
+        symbols::export_symbols(Rc::new(device), Rc::new(queue), &opt)?;
+    } else if opt.swf.is_file() {
         capture_single_swf(Rc::new(device), Rc::new(queue), &opt)?;
     } else if opt.output_path.is_some() {
         capture_multiple_swfs(Rc::new(device), Rc::new(queue), &opt)?;
@@ -360,3 +744,121 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_opt(frames: Option<u32>, skipframes: u32, until_end: bool) -> FrameOpt {
+        FrameOpt {
+            frames,
+            skipframes,
+            start_label: None,
+            stop_label: None,
+            until_end,
+        }
+    }
+
+    /// Run `selection` over the root timeline's frames, returning the
+    /// frames it captured and whether it stopped before running out.
+    fn run(mut selection: FrameSelection, timeline: &[u16]) -> Option<(Vec<u16>, bool)> {
+        let mut captured = vec![];
+        for &frame in timeline {
+            if !selection.wants_more() {
+                return Some((captured, true));
+            }
+            match selection.select(frame)? {
+                FrameAction::Capture => captured.push(frame),
+                FrameAction::Skip => (),
+                FrameAction::Stop => return Some((captured, true)),
+            }
+        }
+        assert_eq!(selection.frame_count as usize, captured.len());
+        Some((captured, false))
+    }
+
+    #[test]
+    fn first_frame_by_default() {
+        let selection = FrameSelection::new(&frame_opt(None, 0, false), None, None);
+        assert_eq!(run(selection, &[1, 2, 3]), Some((vec![1], true)));
+    }
+
+    #[test]
+    fn skip_frames() {
+        let selection = FrameSelection::new(&frame_opt(Some(2), 2, false), None, None);
+        assert_eq!(run(selection, &[1, 2, 3, 4, 5]), Some((vec![3, 4], true)));
+    }
+
+    #[test]
+    fn frame_limit_outlasts_the_timeline() {
+        // With a frame count, a stopped timeline keeps being captured.
+        let selection = FrameSelection::new(&frame_opt(Some(4), 0, false), None, None);
+        assert_eq!(
+            run(selection, &[1, 2, 2, 2]),
+            Some((vec![1, 2, 2, 2], false))
+        );
+    }
+
+    #[test]
+    fn labels() {
+        let opt = FrameOpt {
+            start_label: Some("start".to_string()),
+            stop_label: Some("stop".to_string()),
+            ..frame_opt(None, 0, false)
+        };
+        let selection = FrameSelection::new(&opt, Some(3), Some(5));
+        assert_eq!(
+            run(selection, &[1, 2, 3, 4, 5, 6]),
+            Some((vec![3, 4], true))
+        );
+    }
+
+    #[test]
+    fn unreached_stop_label_ends_with_the_timeline() {
+        let opt = FrameOpt {
+            stop_label: Some("stop".to_string()),
+            ..frame_opt(None, 0, false)
+        };
+        let selection = FrameSelection::new(&opt, None, Some(9));
+        assert_eq!(
+            run(selection, &[1, 2, 3, 1, 2, 3]),
+            Some((vec![1, 2, 3], true))
+        );
+        let selection = FrameSelection::new(&opt, None, Some(9));
+        assert_eq!(run(selection, &[1, 2, 2, 2]), Some((vec![1, 2], true)));
+    }
+
+    #[test]
+    fn until_end() {
+        let selection = FrameSelection::new(&frame_opt(None, 0, true), None, None);
+        assert_eq!(
+            run(selection, &[1, 2, 3, 3, 3]),
+            Some((vec![1, 2, 3], true))
+        );
+
+        // A single frame movie is captured once.
+        let selection = FrameSelection::new(&frame_opt(None, 0, true), None, None);
+        assert_eq!(run(selection, &[1, 1, 1]), Some((vec![1], true)));
+
+        // The frame limit still applies.
+        let selection = FrameSelection::new(&frame_opt(Some(2), 0, true), None, None);
+        assert_eq!(run(selection, &[1, 2, 3]), Some((vec![1, 2], true)));
+    }
+
+    #[test]
+    fn timeline_ends_before_start_label() {
+        let opt = FrameOpt {
+            start_label: Some("start".to_string()),
+            ..frame_opt(None, 0, false)
+        };
+        let selection = FrameSelection::new(&opt, Some(5), None);
+        assert_eq!(run(selection, &[1, 2, 3, 1]), None);
+    }
+
+    #[test]
+    fn frame_rates() {
+        assert_eq!(frame_rate_fraction(24.0), (24, 1));
+        assert_eq!(frame_rate_fraction(12.5), (25, 2));
+        assert_eq!(frame_rate_fraction(0.0), (1, 256));
+    }
+}
@@ -0,0 +1,370 @@
+//! Exporting the characters in a movie's library as separate files.
+//!
+//! Characters that can be drawn are rendered by the player on their own, while
+//! shapes as SVG, bitmaps and sounds are written straight from their tags.
+
+use crate::{capture, create_player, find_files, new_progress_bar, wav, Opt};
+use image::RgbaImage;
+use indicatif::ProgressBar;
+use ruffle_core::backend::audio::decoders::make_decoder;
+use ruffle_core::backend::audio::NullAudioBackend;
+use ruffle_core::backend::render::{self, BitmapFormat};
+use ruffle_core::shape_utils::DistilledShape;
+use ruffle_core::swf::{self, AudioCompression, CharacterId, Tag};
+use ruffle_core::tag_utils::SwfMovie;
+use ruffle_core::{LibrarySymbol, Player, SymbolKind};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+pub fn export_symbols(
+    device: Rc<wgpu::Device>,
+    queue: Rc<wgpu::Queue>,
+    opt: &Opt,
+) -> Result<(), Box<dyn Error>> {
+    let progress = if !opt.silent {
+        Some(new_progress_bar(0))
+    } else {
+        None
+    };
+
+    let mut num_files = 0;
+    let output = if opt.swf.is_file() {
+        let output = opt.output_path.clone().unwrap_or_else(|| {
+            let mut result = PathBuf::new();
+            result.set_file_name(opt.swf.file_stem().unwrap());
+            result
+        });
+        num_files += export_swf(device, queue, &opt.swf, &output, opt, &progress)?;
+        output
+    } else if let Some(output) = &opt.output_path {
+        for file in find_files(&opt.swf, !opt.silent) {
+            let mut destination = output.clone();
+            destination.push(
+                file.path()
+                    .strip_prefix(&opt.swf)
+                    .unwrap_or_else(|_| &file.path()),
+            );
+            destination.set_extension("");
+            num_files += export_swf(
+                device.clone(),
+                queue.clone(),
+                file.path(),
+                &destination,
+                opt,
+                &progress,
+            )?;
+        }
+        output.clone()
+    } else {
+        return Err("Output directory is required when exporting multiple files.".into());
+    };
+
+    let message = format!("Saved {} files to {}", num_files, output.to_string_lossy());
+    if let Some(progress) = progress {
+        progress.finish_with_message(&message);
+    } else {
+        println!("{}", message);
+    }
+
+    Ok(())
+}
+
+/// Exports every character of one movie into `output`, returning how many
+/// files were written.
+fn export_swf(
+    device: Rc<wgpu::Device>,
+    queue: Rc<wgpu::Queue>,
+    swf_path: &Path,
+    output: &Path,
+    opt: &Opt,
+    progress: &Option<ProgressBar>,
+) -> Result<usize, Box<dyn Error>> {
+    let swf = swf::read_swf(&std::fs::read(swf_path)?[..])?;
+    let movie = SwfMovie::from_path(swf_path)?;
+    let player = create_player(device, queue, (1, 1), Box::new(NullAudioBackend::new()))?;
+    let mut player = player.lock().unwrap();
+    player.set_root_movie(Arc::new(movie));
+
+    let symbols = player.library_symbols();
+    if let Some(progress) = progress {
+        progress.inc_length(symbols.len() as u64);
+    }
+    create_dir_all(output)?;
+
+    let mut jpeg_tables = None;
+    let mut tags = HashMap::new();
+    for tag in &swf.tags {
+        match tag {
+            Tag::JpegTables(data) => jpeg_tables = Some(&data[..]),
+            Tag::DefineBits { id, .. }
+            | Tag::DefineBitsJpeg2 { id, .. }
+            | Tag::DefineBitsJpeg3(swf::DefineBitsJpeg3 { id, .. })
+            | Tag::DefineBitsLossless(swf::DefineBitsLossless { id, .. })
+            | Tag::DefineShape(swf::Shape { id, .. }) => {
+                tags.insert(*id, tag);
+            }
+            Tag::DefineSound(sound) => {
+                tags.insert(sound.id, tag);
+            }
+            _ => (),
+        }
+    }
+
+    // Bitmaps are written first, so that shape SVGs can link to them.
+    let mut num_files = 0;
+    let mut bitmap_files = HashMap::new();
+    for symbol in symbols.iter().filter(|s| s.kind == SymbolKind::Bitmap) {
+        let image = match tags.get(&symbol.id) {
+            Some(tag) => decode_bitmap(tag, jpeg_tables)?,
+            None => continue,
+        };
+        let file_name = format!("{}.png", file_stem(symbol));
+        image.save(output.join(&file_name))?;
+        bitmap_files.insert(symbol.id, (file_name, image.width(), image.height()));
+        num_files += 1;
+    }
+    let bitmaps: HashMap<CharacterId, (&str, u32, u32)> = bitmap_files
+        .iter()
+        .map(|(&id, (file_name, width, height))| (id, (&file_name[..], *width, *height)))
+        .collect();
+
+    for symbol in &symbols {
+        if let Some(progress) = progress {
+            progress.set_message(&format!(
+                "{} character {}",
+                swf_path.file_stem().unwrap().to_string_lossy(),
+                symbol.id
+            ));
+        }
+        let stem = file_stem(symbol);
+        match (symbol.kind, tags.get(&symbol.id)) {
+            (SymbolKind::Bitmap, _) => (),
+            (SymbolKind::Sound, Some(Tag::DefineSound(sound))) => {
+                num_files += export_sound(sound, &output.join(stem))?;
+            }
+            (SymbolKind::Sound, _) => (),
+            (_, tag) => {
+                if let Some(Tag::DefineShape(shape)) = tag {
+                    let svg = ruffle_render_common_svg::swf_shape_to_svg(
+                        DistilledShape::from(shape),
+                        &bitmaps,
+                        "pixelated",
+                    );
+                    std::fs::write(output.join(format!("{}.svg", stem)), svg)?;
+                    num_files += 1;
+                }
+
+                let images = render_frames(&mut player, symbol, opt.size.scale)?;
+                if symbol.num_frames <= 1 {
+                    for image in &images {
+                        image.save(output.join(format!("{}.png", stem)))?;
+                    }
+                } else {
+                    let directory = output.join(stem);
+                    create_dir_all(&directory)?;
+                    for (frame, image) in images.iter().enumerate() {
+                        image.save(directory.join(format!("{}.png", frame)))?;
+                    }
+                }
+                num_files += images.len();
+            }
+        }
+
+        if let Some(progress) = progress {
+            progress.inc(1);
+        }
+    }
+
+    Ok(num_files)
+}
+
+/// The name a character's files are saved under: its ID, followed by its
+/// export name if it has one.
+fn file_stem(symbol: &LibrarySymbol) -> String {
+    match symbol.export_names.first() {
+        Some(name) => {
+            let name: String = name
+                .chars()
+                .map(|c| {
+                    if c.is_alphanumeric() || c == '-' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            format!("{}_{}", symbol.id, name)
+        }
+        None => symbol.id.to_string(),
+    }
+}
+
+/// Draws each frame of a character. Empty frames are left out.
+fn render_frames(
+    player: &mut Player,
+    symbol: &LibrarySymbol,
+    scale: f32,
+) -> Result<Vec<RgbaImage>, Box<dyn Error>> {
+    let mut images = Vec::new();
+    for frame in 0..symbol.num_frames {
+        if player.render_symbol(symbol.id, frame, scale).is_some() {
+            match capture(player) {
+                Some(image) => images.push(image),
+                None => {
+                    return Err(format!(
+                        "Unable to capture frame {} of character {}",
+                        frame, symbol.id
+                    )
+                    .into())
+                }
+            }
+        }
+    }
+    Ok(images)
+}
+
+/// Decodes a bitmap tag to straight alpha RGBA.
+fn decode_bitmap(tag: &Tag, jpeg_tables: Option<&[u8]>) -> Result<RgbaImage, Box<dyn Error>> {
+    let bitmap = match tag {
+        Tag::DefineBits { jpeg_data, .. } => {
+            let data = render::glue_tables_to_jpeg(jpeg_data, jpeg_tables);
+            render::decode_define_bits_jpeg(&data, None)?
+        }
+        Tag::DefineBitsJpeg2 { jpeg_data, .. } => render::decode_define_bits_jpeg(jpeg_data, None)?,
+        Tag::DefineBitsJpeg3(jpeg) => {
+            render::decode_define_bits_jpeg(&jpeg.data, Some(&jpeg.alpha_data))?
+        }
+        Tag::DefineBitsLossless(lossless) => render::decode_define_bits_lossless(lossless)?,
+        _ => return Err("Not a bitmap".into()),
+    };
+    let rgba = match bitmap.data {
+        BitmapFormat::Rgb(rgb) => rgb
+            .chunks_exact(3)
+            .flat_map(|rgb| vec![rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        BitmapFormat::Rgba(mut rgba) => {
+            render::unmultiply_alpha_rgba(&mut rgba);
+            rgba
+        }
+    };
+    RgbaImage::from_raw(bitmap.width, bitmap.height, rgba)
+        .ok_or_else(|| "Bitmap data doesn't match its size".into())
+}
+
+/// Writes MP3 sounds as they are, and decodes the other formats to WAV.
+/// Returns how many files were written.
+fn export_sound(sound: &swf::Sound, path: &Path) -> Result<usize, Box<dyn Error>> {
+    if sound.format.compression == AudioCompression::Mp3 {
+        // The sound data starts with the number of samples to skip.
+        if let Some(data) = sound.data.get(2..) {
+            std::fs::write(path.with_extension("mp3"), data)?;
+            return Ok(1);
+        }
+        return Ok(0);
+    }
+
+    match make_decoder(&sound.format, &sound.data[..]) {
+        Ok(decoder) => {
+            let sample_rate = decoder.sample_rate().into();
+            let sample_frames: Vec<_> = decoder.collect();
+            wav::write_wav(&path.with_extension("wav"), sample_rate, &sample_frames)?;
+            Ok(1)
+        }
+        Err(e) => {
+            log::warn!("Unable to decode sound {}: {}", sound.id, e);
+            Ok(0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Read one of the SWFs the `swf` crate is tested with.
+    fn test_swf(name: &str) -> swf::Swf {
+        let path = format!(
+            "{}/../swf/tests/swfs/{}.swf",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        swf::read_swf(&std::fs::read(path).unwrap()[..]).unwrap()
+    }
+
+    #[test]
+    fn file_stems() {
+        let symbol = |export_names: Vec<&str>| LibrarySymbol {
+            id: 7,
+            kind: SymbolKind::Graphic,
+            export_names: export_names.into_iter().map(String::from).collect(),
+            num_frames: 1,
+        };
+        assert_eq!(file_stem(&symbol(vec![])), "7");
+        assert_eq!(file_stem(&symbol(vec!["button-up"])), "7_button-up");
+        assert_eq!(file_stem(&symbol(vec!["../a b.c", "other"])), "7____a_b_c");
+    }
+
+    #[test]
+    fn decode_bitmaps() {
+        for name in &[
+            "DefineBitsLossless",
+            "DefineBitsLossless2",
+            "DefineBitsJpeg2-MX",
+            "DefineBitsJpeg3",
+            "DefineBits-JpegTables-MX",
+        ] {
+            let swf = test_swf(name);
+            let jpeg_tables = swf.tags.iter().find_map(|tag| match tag {
+                Tag::JpegTables(data) => Some(&data[..]),
+                _ => None,
+            });
+            let bitmap = swf
+                .tags
+                .iter()
+                .find_map(|tag| decode_bitmap(tag, jpeg_tables).ok())
+                .unwrap_or_else(|| panic!("No bitmap in {}", name));
+            assert!(bitmap.width() > 0 && bitmap.height() > 0, "{}", name);
+        }
+        assert!(decode_bitmap(&Tag::ShowFrame, None).is_err());
+    }
+
+    #[test]
+    fn export_sounds() {
+        let directory =
+            std::env::temp_dir().join(format!("ruffle_exporter_sounds_{}", std::process::id()));
+        create_dir_all(&directory).unwrap();
+
+        let swf = test_swf("DefineSound");
+        let mut sound = swf
+            .tags
+            .into_iter()
+            .find_map(|tag| match tag {
+                Tag::DefineSound(sound) => Some(sound),
+                _ => None,
+            })
+            .unwrap();
+        let path = directory.join("sound");
+        assert_eq!(export_sound(&sound, &path).unwrap(), 1);
+        let extension = if sound.format.compression == AudioCompression::Mp3 {
+            "mp3"
+        } else {
+            "wav"
+        };
+        assert!(path.with_extension(extension).exists());
+
+        // MP3 data is written as it is, without the samples to skip.
+        sound.format.compression = AudioCompression::Mp3;
+        sound.data = vec![0, 0, 1, 2, 3];
+        assert_eq!(export_sound(&sound, &path).unwrap(), 1);
+        assert_eq!(
+            std::fs::read(path.with_extension("mp3")).unwrap(),
+            [1, 2, 3]
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,35 @@
+//! Writing captured audio as 16-bit stereo PCM WAV files.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const NUM_CHANNELS: u16 = 2;
+const BITS_PER_SAMPLE: u16 = 16;
+
+pub fn write_wav(path: &Path, sample_rate: u32, sample_frames: &[[i16; 2]]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let block_align = NUM_CHANNELS * BITS_PER_SAMPLE / 8;
+    let data_len = (sample_frames.len() * usize::from(block_align)) as u32;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(36 + data_len).to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?; // PCM
+    writer.write_all(&NUM_CHANNELS.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&(sample_rate * u32::from(block_align)).to_le_bytes())?;
+    writer.write_all(&block_align.to_le_bytes())?;
+    writer.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+
+    writer.write_all(b"data")?;
+    writer.write_all(&data_len.to_le_bytes())?;
+    for [left, right] in sample_frames {
+        writer.write_all(&left.to_le_bytes())?;
+        writer.write_all(&right.to_le_bytes())?;
+    }
+    writer.flush()
+}
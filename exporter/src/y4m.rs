@@ -0,0 +1,99 @@
+//! Writing captured frames as a YUV4MPEG2 stream, which most video encoders
+//! can read from a pipe.
+
+use crate::frame_rate_fraction;
+use image::RgbaImage;
+use std::io::{self, Write};
+
+pub struct Y4mWriter<W: Write> {
+    writer: W,
+
+    /// The frame rate as a fraction, for the stream header.
+    frame_rate: (u32, u32),
+
+    wrote_header: bool,
+}
+
+impl<W: Write> Y4mWriter<W> {
+    pub fn new(writer: W, frame_rate: f64) -> Self {
+        Self {
+            writer,
+            frame_rate: frame_rate_fraction(frame_rate),
+            wrote_header: false,
+        }
+    }
+
+    /// Writes a frame as full resolution 4:4:4 YCbCr, ignoring its alpha.
+    pub fn write_frame(&mut self, image: &RgbaImage) -> io::Result<()> {
+        if !self.wrote_header {
+            writeln!(
+                self.writer,
+                "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C444",
+                image.width(),
+                image.height(),
+                self.frame_rate.0,
+                self.frame_rate.1
+            )?;
+            self.wrote_header = true;
+        }
+
+        let num_pixels = (image.width() * image.height()) as usize;
+        let mut planes = vec![0u8; num_pixels * 3];
+        for (i, pixel) in image.pixels().enumerate() {
+            let [r, g, b, _] = pixel.0;
+            let (y, cb, cr) = rgb_to_ycbcr(r, g, b);
+            planes[i] = y;
+            planes[num_pixels + i] = cb;
+            planes[num_pixels * 2 + i] = cr;
+        }
+        self.writer.write_all(b"FRAME\n")?;
+        self.writer.write_all(&planes)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Converts a color to BT.601 studio range YCbCr, which is what Y4M readers
+/// assume.
+fn rgb_to_ycbcr(r: u8, g: u8, b: u8) -> (u8, u8, u8) {
+    let (r, g, b) = (f32::from(r), f32::from(g), f32::from(b));
+    let y = 16.0 + 0.257 * r + 0.504 * g + 0.098 * b;
+    let cb = 128.0 - 0.148 * r - 0.291 * g + 0.439 * b;
+    let cr = 128.0 + 0.439 * r - 0.368 * g - 0.071 * b;
+    (y.round() as u8, cb.round() as u8, cr.round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn frames() {
+        let mut image = RgbaImage::from_pixel(2, 1, Rgba([255, 255, 255, 255]));
+        image.put_pixel(1, 0, Rgba([0, 0, 0, 0]));
+
+        let mut output = vec![];
+        let mut writer = Y4mWriter::new(&mut output, 12.5);
+        writer.write_frame(&image).unwrap();
+        writer.write_frame(&image).unwrap();
+        writer.flush().unwrap();
+
+        let mut expected = b"YUV4MPEG2 W2 H1 F25:2 Ip A1:1 C444\n".to_vec();
+        for _ in 0..2 {
+            expected.extend_from_slice(b"FRAME\n");
+            // The Y, Cb and Cr planes of a white and a black pixel.
+            expected.extend_from_slice(&[235, 16, 128, 128, 128, 128]);
+        }
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(rgb_to_ycbcr(0, 0, 0), (16, 128, 128));
+        assert_eq!(rgb_to_ycbcr(255, 255, 255), (235, 128, 128));
+        assert_eq!(rgb_to_ycbcr(255, 0, 0), (82, 90, 240));
+    }
+}
@@ -0,0 +1,458 @@
+//! SVG generation for SWF shapes, shared by the canvas renderer and the exporter.
+
+use fnv::FnvHashSet;
+use ruffle_core::backend::render::swf::{
+    self, CharacterId, Color, FillStyle, GradientInterpolation, GradientSpread, LineCapStyle,
+    LineJoinStyle, Matrix,
+};
+use ruffle_core::shape_utils::{DistilledShape, DrawCommand, DrawPath};
+use std::collections::HashMap;
+use svg::node::element::{
+    path::Data, Definitions, Filter, Image, LinearGradient, Path as SvgPath, Pattern,
+    RadialGradient, Stop,
+};
+use svg::Document;
+
+/// Converts a shape to an SVG document.
+///
+/// Bitmap fills are drawn from `bitmaps`, which maps a bitmap's character ID
+/// to the URL of its image and its size in pixels.
+#[allow(clippy::cognitive_complexity)]
+pub fn swf_shape_to_svg(
+    shape: DistilledShape,
+    bitmaps: &HashMap<CharacterId, (&str, u32, u32)>,
+    pixelated_property_value: &str,
+) -> String {
+    // Some browsers will vomit if you try to load/draw an image with 0 width/height.
+    // TODO(Herschel): Might be better to just return None in this case and skip
+    // rendering altogether.
+    let (width, height) = (
+        f32::max(
+            (shape.shape_bounds.x_max - shape.shape_bounds.x_min).to_pixels() as f32,
+            1.0,
+        ),
+        f32::max(
+            (shape.shape_bounds.y_max - shape.shape_bounds.y_min).to_pixels() as f32,
+            1.0,
+        ),
+    );
+    let mut document = Document::new()
+        .set("width", width)
+        .set("height", height)
+        .set(
+            "viewBox",
+            (
+                shape.shape_bounds.x_min.get(),
+                shape.shape_bounds.y_min.get(),
+                (shape.shape_bounds.x_max - shape.shape_bounds.x_min).get(),
+                (shape.shape_bounds.y_max - shape.shape_bounds.y_min).get(),
+            ),
+        )
+        // preserveAspectRatio must be off or Firefox will fudge with the dimensions when we draw an image onto canvas.
+        .set("preserveAspectRatio", "none")
+        .set("xmlns:xlink", "http://www.w3.org/1999/xlink");
+
+    let width = (shape.shape_bounds.x_max - shape.shape_bounds.x_min).get() as f32;
+    let height = (shape.shape_bounds.y_max - shape.shape_bounds.y_min).get() as f32;
+
+    let mut bitmap_defs: FnvHashSet<CharacterId> = FnvHashSet::default();
+
+    let mut defs = Definitions::new();
+    let mut num_defs = 0;
+    let mut has_linear_rgb_gradient = false;
+
+    let mut svg_paths = vec![];
+    for path in shape.paths {
+        match path {
+            DrawPath::Fill { style, commands } => {
+                let mut svg_path = SvgPath::new();
+
+                let fill = match style {
+                    FillStyle::Color(Color { r, g, b, a }) => {
+                        format!("rgba({},{},{},{})", r, g, b, f32::from(*a) / 255.0)
+                    }
+                    FillStyle::LinearGradient(gradient) => {
+                        let shift = Matrix {
+                            a: 32768.0 / width,
+                            d: 32768.0 / height,
+                            tx: swf::Twips::new(-16384),
+                            ty: swf::Twips::new(-16384),
+                            ..Default::default()
+                        };
+                        let gradient_matrix = gradient.matrix * shift;
+
+                        let mut svg_gradient = LinearGradient::new()
+                            .set("id", format!("f{}", num_defs))
+                            .set("gradientUnits", "userSpaceOnUse")
+                            .set(
+                                "gradientTransform",
+                                format!(
+                                    "matrix({} {} {} {} {} {})",
+                                    gradient_matrix.a,
+                                    gradient_matrix.b,
+                                    gradient_matrix.c,
+                                    gradient_matrix.d,
+                                    gradient_matrix.tx.get(),
+                                    gradient_matrix.ty.get()
+                                ),
+                            );
+                        svg_gradient = match gradient.spread {
+                            GradientSpread::Pad => svg_gradient, // default
+                            GradientSpread::Reflect => svg_gradient.set("spreadMethod", "reflect"),
+                            GradientSpread::Repeat => svg_gradient.set("spreadMethod", "repeat"),
+                        };
+                        if gradient.interpolation == GradientInterpolation::LinearRGB {
+                            has_linear_rgb_gradient = true;
+                            svg_path = svg_path.set("filter", "url('#_linearrgb')");
+                        }
+                        for record in &gradient.records {
+                            let color =
+                                if gradient.interpolation == GradientInterpolation::LinearRGB {
+                                    srgb_to_linear(record.color.clone())
+                                } else {
+                                    record.color.clone()
+                                };
+                            let stop = Stop::new()
+                                .set("offset", format!("{}%", f32::from(record.ratio) / 2.55))
+                                .set(
+                                    "stop-color",
+                                    format!(
+                                        "rgba({},{},{},{})",
+                                        color.r,
+                                        color.g,
+                                        color.b,
+                                        f32::from(color.a) / 255.0
+                                    ),
+                                );
+                            svg_gradient = svg_gradient.add(stop);
+                        }
+                        defs = defs.add(svg_gradient);
+
+                        let fill_id = format!("url(#f{})", num_defs);
+                        num_defs += 1;
+                        fill_id
+                    }
+                    FillStyle::RadialGradient(gradient) => {
+                        let shift = Matrix {
+                            a: 32768.0,
+                            d: 32768.0,
+                            ..Default::default()
+                        };
+                        let gradient_matrix = gradient.matrix * shift;
+
+                        let mut svg_gradient = RadialGradient::new()
+                            .set("id", format!("f{}", num_defs))
+                            .set("gradientUnits", "userSpaceOnUse")
+                            .set("cx", "0")
+                            .set("cy", "0")
+                            .set("r", "0.5")
+                            .set(
+                                "gradientTransform",
+                                format!(
+                                    "matrix({} {} {} {} {} {})",
+                                    gradient_matrix.a,
+                                    gradient_matrix.b,
+                                    gradient_matrix.c,
+                                    gradient_matrix.d,
+                                    gradient_matrix.tx.get(),
+                                    gradient_matrix.ty.get()
+                                ),
+                            );
+                        svg_gradient = match gradient.spread {
+                            GradientSpread::Pad => svg_gradient, // default
+                            GradientSpread::Reflect => svg_gradient.set("spreadMethod", "reflect"),
+                            GradientSpread::Repeat => svg_gradient.set("spreadMethod", "repeat"),
+                        };
+                        if gradient.interpolation == GradientInterpolation::LinearRGB {
+                            has_linear_rgb_gradient = true;
+                            svg_path = svg_path.set("filter", "url('#_linearrgb')");
+                        }
+                        for record in &gradient.records {
+                            let color =
+                                if gradient.interpolation == GradientInterpolation::LinearRGB {
+                                    srgb_to_linear(record.color.clone())
+                                } else {
+                                    record.color.clone()
+                                };
+                            let stop = Stop::new()
+                                .set("offset", format!("{}%", f32::from(record.ratio) / 2.55))
+                                .set(
+                                    "stop-color",
+                                    format!(
+                                        "rgba({},{},{},{})",
+                                        color.r,
+                                        color.g,
+                                        color.b,
+                                        f32::from(color.a) / 255.0
+                                    ),
+                                );
+                            svg_gradient = svg_gradient.add(stop);
+                        }
+                        defs = defs.add(svg_gradient);
+
+                        let fill_id = format!("url(#f{})", num_defs);
+                        num_defs += 1;
+                        fill_id
+                    }
+                    FillStyle::FocalGradient {
+                        gradient,
+                        focal_point,
+                    } => {
+                        let shift = Matrix {
+                            a: 32768.0,
+                            d: 32768.0,
+                            ..Default::default()
+                        };
+                        let gradient_matrix = gradient.matrix * shift;
+
+                        let mut svg_gradient = RadialGradient::new()
+                            .set("id", format!("f{}", num_defs))
+                            .set("fx", focal_point / 2.0)
+                            .set("gradientUnits", "userSpaceOnUse")
+                            .set("cx", "0")
+                            .set("cy", "0")
+                            .set("r", "0.5")
+                            .set(
+                                "gradientTransform",
+                                format!(
+                                    "matrix({} {} {} {} {} {})",
+                                    gradient_matrix.a,
+                                    gradient_matrix.b,
+                                    gradient_matrix.c,
+                                    gradient_matrix.d,
+                                    gradient_matrix.tx.get(),
+                                    gradient_matrix.ty.get()
+                                ),
+                            );
+                        svg_gradient = match gradient.spread {
+                            GradientSpread::Pad => svg_gradient, // default
+                            GradientSpread::Reflect => svg_gradient.set("spreadMethod", "reflect"),
+                            GradientSpread::Repeat => svg_gradient.set("spreadMethod", "repeat"),
+                        };
+                        if gradient.interpolation == GradientInterpolation::LinearRGB {
+                            has_linear_rgb_gradient = true;
+                            svg_path = svg_path.set("filter", "url('#_linearrgb')");
+                        }
+                        for record in &gradient.records {
+                            let color =
+                                if gradient.interpolation == GradientInterpolation::LinearRGB {
+                                    srgb_to_linear(record.color.clone())
+                                } else {
+                                    record.color.clone()
+                                };
+                            let stop = Stop::new()
+                                .set("offset", format!("{}%", f32::from(record.ratio) / 2.55))
+                                .set(
+                                    "stop-color",
+                                    format!(
+                                        "rgba({},{},{},{})",
+                                        color.r,
+                                        color.g,
+                                        color.b,
+                                        f32::from(color.a) / 255.0
+                                    ),
+                                );
+                            svg_gradient = svg_gradient.add(stop);
+                        }
+                        defs = defs.add(svg_gradient);
+
+                        let fill_id = format!("url(#f{})", num_defs);
+                        num_defs += 1;
+                        fill_id
+                    }
+                    FillStyle::Bitmap {
+                        id,
+                        matrix,
+                        is_smoothed,
+                        is_repeating,
+                    } => {
+                        let (bitmap_data, bitmap_width, bitmap_height) =
+                            bitmaps.get(&id).unwrap_or(&("", 0, 0));
+
+                        if !bitmap_defs.contains(&id) {
+                            let mut image = Image::new()
+                                .set("width", *bitmap_width)
+                                .set("height", *bitmap_height)
+                                .set("xlink:href", *bitmap_data);
+
+                            if !*is_smoothed {
+                                image = image.set("image-rendering", pixelated_property_value);
+                            }
+
+                            let mut bitmap_pattern = Pattern::new()
+                                .set("id", format!("b{}", id))
+                                .set("patternUnits", "userSpaceOnUse");
+
+                            if !*is_repeating {
+                                bitmap_pattern = bitmap_pattern
+                                    .set("width", *bitmap_width)
+                                    .set("height", *bitmap_height);
+                            } else {
+                                bitmap_pattern = bitmap_pattern
+                                    .set("width", *bitmap_width)
+                                    .set("height", *bitmap_height)
+                                    .set(
+                                        "viewBox",
+                                        format!("0 0 {} {}", bitmap_width, bitmap_height),
+                                    );
+                            }
+
+                            bitmap_pattern = bitmap_pattern.add(image);
+
+                            defs = defs.add(bitmap_pattern);
+                            bitmap_defs.insert(*id);
+                        }
+
+                        let svg_pattern = Pattern::new()
+                            .set("id", format!("f{}", num_defs))
+                            .set("xlink:href", format!("#b{}", id))
+                            .set(
+                                "patternTransform",
+                                format!(
+                                    "matrix({} {} {} {} {} {})",
+                                    matrix.a,
+                                    matrix.b,
+                                    matrix.c,
+                                    matrix.d,
+                                    matrix.tx.get(),
+                                    matrix.ty.get()
+                                ),
+                            );
+
+                        defs = defs.add(svg_pattern);
+
+                        let fill_id = format!("url(#f{})", num_defs);
+                        num_defs += 1;
+                        fill_id
+                    }
+                };
+                svg_path = svg_path.set("fill", fill);
+
+                let mut data = Data::new();
+                for command in commands {
+                    data = match command {
+                        DrawCommand::MoveTo { x, y } => data.move_to((x.get(), y.get())),
+                        DrawCommand::LineTo { x, y } => data.line_to((x.get(), y.get())),
+                        DrawCommand::CurveTo { x1, y1, x2, y2 } => {
+                            data.quadratic_curve_to((x1.get(), y1.get(), x2.get(), y2.get()))
+                        }
+                    };
+                }
+
+                svg_path = svg_path.set("d", data);
+                svg_paths.push(svg_path);
+            }
+            DrawPath::Stroke {
+                style,
+                commands,
+                is_closed,
+            } => {
+                // Flash always renders strokes with a minimum width of 1 pixel (20 twips).
+                // Additionally, many SWFs use the "hairline" stroke setting, which sets the stroke's width
+                // to 1 twip. Because of the minimum, this will effectively make the stroke nearly-always render
+                // as 1 pixel wide.
+                // SVG doesn't have a minimum and can render strokes at fractional widths, so these hairline
+                // strokes end up rendering very faintly if we use the actual width of 1 twip.
+                // Therefore, we clamp the stroke width to 1 pixel (20 twips). This won't be 100% accurate
+                // if the shape is scaled, but it looks much closer to the Flash Player.
+                let stroke_width = std::cmp::max(style.width.get(), 20);
+                let mut svg_path = SvgPath::new();
+                svg_path = svg_path
+                    .set("fill", "none")
+                    .set(
+                        "stroke",
+                        format!(
+                            "rgba({},{},{},{})",
+                            style.color.r, style.color.g, style.color.b, style.color.a
+                        ),
+                    )
+                    .set("stroke-width", stroke_width)
+                    .set(
+                        "stroke-linecap",
+                        match style.start_cap {
+                            LineCapStyle::Round => "round",
+                            LineCapStyle::Square => "square",
+                            LineCapStyle::None => "butt",
+                        },
+                    )
+                    .set(
+                        "stroke-linejoin",
+                        match style.join_style {
+                            LineJoinStyle::Round => "round",
+                            LineJoinStyle::Bevel => "bevel",
+                            LineJoinStyle::Miter(_) => "miter",
+                        },
+                    );
+
+                if let LineJoinStyle::Miter(miter_limit) = style.join_style {
+                    svg_path = svg_path.set("stroke-miterlimit", miter_limit);
+                }
+
+                let mut data = Data::new();
+                for command in commands {
+                    data = match command {
+                        DrawCommand::MoveTo { x, y } => data.move_to((x.get(), y.get())),
+                        DrawCommand::LineTo { x, y } => data.line_to((x.get(), y.get())),
+                        DrawCommand::CurveTo { x1, y1, x2, y2 } => {
+                            data.quadratic_curve_to((x1.get(), y1.get(), x2.get(), y2.get()))
+                        }
+                    };
+                }
+                if is_closed {
+                    data = data.close();
+                }
+
+                svg_path = svg_path.set("d", data);
+                svg_paths.push(svg_path);
+            }
+        }
+    }
+
+    // If this shape contains a gradient in linear RGB space, add a filter to do the color space adjustment.
+    // We have to use a filter because browser don't seem to implement the `color-interpolation` SVG property.
+    if has_linear_rgb_gradient {
+        // Add a filter to convert from linear space to sRGB space.
+        let mut filter = Filter::new();
+        filter = filter.set("id", "_linearrgb");
+        filter = filter.set("color-interpolation-filters", "sRGB");
+        let text = svg::node::Text::new(
+            r#"
+            <feComponentTransfer>
+                <feFuncR type="gamma" exponent="0.4545454545"></feFuncR>
+                <feFuncG type="gamma" exponent="0.4545454545"></feFuncG>
+                <feFuncB type="gamma" exponent="0.4545454545"></feFuncB>
+            </feComponentTransfer>
+            "#,
+        );
+        filter = filter.add(text);
+        defs = defs.add(filter);
+        num_defs += 1;
+    }
+
+    if num_defs > 0 {
+        document = document.add(defs);
+    }
+
+    for svg_path in svg_paths {
+        document = document.add(svg_path);
+    }
+
+    document.to_string()
+}
+
+/// Converts an SWF color from sRGB space to linear color space.
+pub fn srgb_to_linear(mut color: swf::Color) -> swf::Color {
+    fn to_linear_channel(n: u8) -> u8 {
+        let mut n = f32::from(n) / 255.0;
+        n = if n <= 0.04045 {
+            n / 12.92
+        } else {
+            f32::powf((n + 0.055) / 1.055, 2.4)
+        };
+        (n.max(0.0).min(1.0) * 255.0).round() as u8
+    }
+    color.r = to_linear_channel(color.r);
+    color.g = to_linear_channel(color.g);
+    color.b = to_linear_channel(color.b);
+    color
+}
@@ -5,8 +5,9 @@ use lyon::tessellation::{
     FillAttributes, FillTessellator, StrokeAttributes, StrokeTessellator, StrokeVertexConstructor,
 };
 use lyon::tessellation::{FillOptions, StrokeOptions};
-use ruffle_core::backend::render::swf::{self, FillStyle, GradientInterpolation, Twips};
-use ruffle_core::shape_utils::{DistilledShape, DrawCommand, DrawPath};
+use ruffle_core::backend::render::gradient_lookup_table;
+use ruffle_core::backend::render::swf::{self, FillStyle, Twips};
+use ruffle_core::shape_utils::{stroke_width, DistilledShape, DrawCommand, DrawPath, StrokeScale};
 
 pub struct ShapeTessellator {
     fill_tess: FillTessellator,
@@ -21,7 +22,13 @@ impl ShapeTessellator {
         }
     }
 
-    pub fn tessellate_shape<F>(&mut self, shape: DistilledShape, get_bitmap_dimensions: F) -> Mesh
+    /// Tessellate `shape`, with its strokes sized for drawing at `stroke_scale`.
+    pub fn tessellate_shape<F>(
+        &mut self,
+        shape: DistilledShape,
+        stroke_scale: StrokeScale,
+        get_bitmap_dimensions: F,
+    ) -> Mesh
     where
         F: Fn(swf::CharacterId) -> Option<(u32, u32)>,
     {
@@ -82,27 +89,12 @@ impl ShapeTessellator {
                             continue;
                         }
 
-                        let mut colors: Vec<[f32; 4]> = Vec::with_capacity(8);
-                        let mut ratios: Vec<f32> = Vec::with_capacity(8);
-                        for record in &gradient.records {
-                            colors.push([
-                                f32::from(record.color.r) / 255.0,
-                                f32::from(record.color.g) / 255.0,
-                                f32::from(record.color.b) / 255.0,
-                                f32::from(record.color.a) / 255.0,
-                            ]);
-                            ratios.push(f32::from(record.ratio) / 255.0);
-                        }
-
                         let gradient = Gradient {
                             gradient_type: GradientType::Linear,
-                            ratios,
-                            colors,
-                            num_colors: gradient.records.len() as u32,
+                            lookup_table: gradient_lookup_table(gradient),
                             matrix: swf_to_gl_matrix(gradient.matrix),
                             repeat_mode: gradient.spread,
                             focal_point: 0.0,
-                            interpolation: gradient.interpolation,
                         };
 
                         flush_draw(DrawType::Gradient(gradient), &mut mesh, &mut lyon_mesh);
@@ -125,27 +117,12 @@ impl ShapeTessellator {
                             continue;
                         }
 
-                        let mut colors: Vec<[f32; 4]> = Vec::with_capacity(8);
-                        let mut ratios: Vec<f32> = Vec::with_capacity(8);
-                        for record in &gradient.records {
-                            colors.push([
-                                f32::from(record.color.r) / 255.0,
-                                f32::from(record.color.g) / 255.0,
-                                f32::from(record.color.b) / 255.0,
-                                f32::from(record.color.a) / 255.0,
-                            ]);
-                            ratios.push(f32::from(record.ratio) / 255.0);
-                        }
-
                         let gradient = Gradient {
                             gradient_type: GradientType::Radial,
-                            ratios,
-                            colors,
-                            num_colors: gradient.records.len() as u32,
+                            lookup_table: gradient_lookup_table(gradient),
                             matrix: swf_to_gl_matrix(gradient.matrix),
                             repeat_mode: gradient.spread,
                             focal_point: 0.0,
-                            interpolation: gradient.interpolation,
                         };
 
                         flush_draw(DrawType::Gradient(gradient), &mut mesh, &mut lyon_mesh);
@@ -171,27 +148,12 @@ impl ShapeTessellator {
                             continue;
                         }
 
-                        let mut colors: Vec<[f32; 4]> = Vec::with_capacity(8);
-                        let mut ratios: Vec<f32> = Vec::with_capacity(8);
-                        for record in &gradient.records {
-                            colors.push([
-                                f32::from(record.color.r) / 255.0,
-                                f32::from(record.color.g) / 255.0,
-                                f32::from(record.color.b) / 255.0,
-                                f32::from(record.color.a) / 255.0,
-                            ]);
-                            ratios.push(f32::from(record.ratio) / 255.0);
-                        }
-
                         let gradient = Gradient {
                             gradient_type: GradientType::Focal,
-                            ratios,
-                            colors,
-                            num_colors: gradient.records.len() as u32,
+                            lookup_table: gradient_lookup_table(gradient),
                             matrix: swf_to_gl_matrix(gradient.matrix),
                             repeat_mode: gradient.spread,
                             focal_point: *focal_point,
-                            interpolation: gradient.interpolation,
                         };
 
                         flush_draw(DrawType::Gradient(gradient), &mut mesh, &mut lyon_mesh);
@@ -245,15 +207,8 @@ impl ShapeTessellator {
                     let mut buffers_builder =
                         BuffersBuilder::new(&mut lyon_mesh, RuffleVertexCtor { color });
 
-                    // TODO(Herschel): 0 width indicates "hairline".
-                    let width = if style.width.to_pixels() >= 1.0 {
-                        style.width.to_pixels() as f32
-                    } else {
-                        1.0
-                    };
-
                     let mut options = StrokeOptions::default()
-                        .with_line_width(width)
+                        .with_line_width(stroke_width(style, stroke_scale))
                         .with_line_join(match style.join_style {
                             swf::LineJoinStyle::Round => tessellation::LineJoin::Round,
                             swf::LineJoinStyle::Bevel => tessellation::LineJoin::Bevel,
@@ -317,12 +272,11 @@ pub enum DrawType {
 pub struct Gradient {
     pub matrix: [[f32; 3]; 3],
     pub gradient_type: GradientType,
-    pub ratios: Vec<f32>,
-    pub colors: Vec<[f32; 4]>,
-    pub num_colors: u32,
+
+    /// The colors of the gradient, as baked by `gradient_lookup_table`.
+    pub lookup_table: Vec<u8>,
     pub repeat_mode: GradientSpread,
     pub focal_point: f32,
-    pub interpolation: GradientInterpolation,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -8,22 +8,13 @@ uniform vec4 add_color;
 uniform mat3 u_matrix;
 
 uniform int u_gradient_type;
-uniform float u_ratios[8];
-uniform vec4 u_colors[8];
-uniform int u_num_colors;
 uniform int u_repeat_mode;
 uniform float u_focal_point;
-uniform int u_interpolation;
 
-varying vec2 frag_uv;
+// The colors of the gradient, baked into a 256x1 lookup table.
+uniform sampler2D u_gradient;
 
-vec3 linear_to_srgb(vec3 linear)
-{
-    vec3 a = 12.92 * linear;
-    vec3 b = 1.055 * pow(linear, vec3(1.0 / 2.4)) - 0.055;
-    vec3 c = step(vec3(0.0031308), linear);
-    return mix(a, b, c);
-}
+varying vec2 frag_uv;
 
 void main() {
     float t;
@@ -35,17 +26,29 @@ void main() {
     {
         t = length(frag_uv * 2.0 - 1.0);
     }
-    else if( u_gradient_type == 2 )
+    else
     {
+        // Cast a ray from the focal point through this point, and find where
+        // it leaves the unit circle. Flash keeps the focal point just inside
+        // the circle, so the ray always hits it.
+        float focal_point = clamp(u_focal_point, -0.98, 0.98);
         vec2 uv = frag_uv * 2.0 - 1.0;
-        vec2 d = vec2(u_focal_point, 0.0) - uv;
+        vec2 d = vec2(focal_point, 0.0) - uv;
         float l = length(d);
-        d /= l;
-        t = l / (sqrt(1.0 -  u_focal_point*u_focal_point*d.y*d.y) + u_focal_point*d.x);
+        if( l > 0.0 )
+        {
+            d /= l;
+            t = l / (sqrt(1.0 - focal_point * focal_point * d.y * d.y) + focal_point * d.x);
+        }
+        else
+        {
+            t = 0.0;
+        }
     }
+
     if( u_repeat_mode == 0 )
     {
-        // Clamp
+        // Pad
         t = clamp(t, 0.0, 1.0);
     }
     else if( u_repeat_mode == 1 )
@@ -55,54 +58,12 @@ void main() {
     }
     else
     {
-        // Mirror
-        if( t < 0.0 )
-        {
-            t = -t;
-        }
-
-        if( int(mod(t, 2.0)) == 0 ) {
-            t = fract(t);
-        } else {
-            t = 1.0 - fract(t);
-        }
+        // Reflect
+        t = 1.0 - abs(mod(t, 2.0) - 1.0);
     }
 
-    // TODO: No non-constant array access in WebGL 1, so the following is kind of painful.
-    // We'd probably be better off passing in the gradient as a texture and sampling from there.
-    vec4 color;
-    float a;
-    if( t <= u_ratios[0] ) {
-        color = u_colors[0];
-    } else if( t <= u_ratios[1] ) {    
-        a = (t - u_ratios[0]) / (u_ratios[1] - u_ratios[0]);
-        color = mix(u_colors[0], u_colors[1], a);
-    } else if( t <= u_ratios[2] ) {    
-        a = (t - u_ratios[1]) / (u_ratios[2] - u_ratios[1]);
-        color = mix(u_colors[1], u_colors[2], a);
-    } else if( t <= u_ratios[3] ) {    
-        a = (t - u_ratios[2]) / (u_ratios[3] - u_ratios[2]);
-        color = mix(u_colors[2], u_colors[3], a);
-    } else if( t <= u_ratios[4] ) {    
-        a = (t - u_ratios[3]) / (u_ratios[4] - u_ratios[3]);
-        color = mix(u_colors[3], u_colors[4], a);
-    } else if( t <= u_ratios[5] ) {    
-        a = (t - u_ratios[4]) / (u_ratios[5] - u_ratios[4]);
-        color = mix(u_colors[4], u_colors[5], a);
-    } else if( t <= u_ratios[6] ) {    
-        a = (t - u_ratios[5]) / (u_ratios[6] - u_ratios[5]);
-        color = mix(u_colors[5], u_colors[6], a);
-    } else if( t <= u_ratios[7] ) {
-        a = (t - u_ratios[6]) / (u_ratios[7] - u_ratios[6]);
-        color = mix(u_colors[6], u_colors[7], a);
-    } else {
-        color = u_colors[7];
-    }
-
-    if( u_interpolation != 0 ) {
-        color = vec4(linear_to_srgb(vec3(color)), color.a);
-    }
-
-    gl_FragColor = mult_color * color + add_color;;
+    // Sample the center of each texel, so that t = 0 and t = 1 hit the first
+    // and last entries exactly.
+    vec4 color = texture2D(u_gradient, vec2((t * 255.0 + 0.5) / 256.0, 0.5));
+    gl_FragColor = mult_color * color + add_color;
 }
-
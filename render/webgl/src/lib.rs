@@ -1,11 +1,12 @@
 use ruffle_core::backend::render::swf::{self, FillStyle};
 use ruffle_core::backend::render::{
-    srgb_to_linear, Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, Letterbox,
-    RenderBackend, ShapeHandle, Transform,
+    Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, Letterbox, RenderBackend, ShapeHandle,
+    StageQuality, Transform, GRADIENT_SIZE,
 };
-use ruffle_core::shape_utils::DistilledShape;
+use ruffle_core::shape_utils::{DistilledShape, OwnedShape, StrokeScale, StrokeScaling};
 use ruffle_render_common_tess::{GradientSpread, GradientType, ShapeTessellator, Vertex};
 use ruffle_web_common::JsResult;
+use std::collections::HashMap;
 use wasm_bindgen::{JsCast, JsValue};
 use web_sys::{
     HtmlCanvasElement, OesVertexArrayObject, WebGl2RenderingContext as Gl2, WebGlBuffer,
@@ -22,6 +23,9 @@ const GRADIENT_FRAGMENT_GLSL: &str = include_str!("../shaders/gradient.frag");
 const BITMAP_FRAGMENT_GLSL: &str = include_str!("../shaders/bitmap.frag");
 const NUM_VERTEX_ATTRIBUTES: u32 = 2;
 
+/// The number of stroke scales to keep tessellations of for each shape.
+const MAX_STROKE_SCALES: usize = 8;
+
 pub struct WebGlRenderBackend {
     /// WebGL1 context
     gl: Gl,
@@ -36,6 +40,10 @@ pub struct WebGlRenderBackend {
     msaa_buffers: Option<MsaaBuffers>,
     msaa_sample_count: u32,
 
+    /// The MSAA sample count used at high quality, which is the most this
+    /// device is allowed to use.
+    max_msaa_sample_count: u32,
+
     color_program: ShaderProgram,
     bitmap_program: ShaderProgram,
     gradient_program: ShaderProgram,
@@ -162,6 +170,7 @@ impl WebGlRenderBackend {
 
             msaa_buffers: None,
             msaa_sample_count,
+            max_msaa_sample_count: msaa_sample_count,
 
             color_program,
             gradient_program,
@@ -290,6 +299,7 @@ impl WebGlRenderBackend {
                 index_buffer,
                 num_indices: 6,
             }],
+            strokes: None,
         };
         Ok(quad_mesh)
     }
@@ -306,16 +316,9 @@ impl WebGlRenderBackend {
     }
 
     fn build_msaa_buffers(&mut self) -> Result<(), Error> {
-        if self.gl2.is_none() || self.msaa_sample_count <= 1 {
-            self.gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
-            self.gl.bind_renderbuffer(Gl::RENDERBUFFER, None);
-            return Ok(());
-        }
-
-        let gl = self.gl2.as_ref().unwrap();
-
         // Delete previous buffers, if they exist.
         if let Some(msaa_buffers) = self.msaa_buffers.take() {
+            let gl = &self.gl;
             gl.delete_renderbuffer(Some(&msaa_buffers.color_renderbuffer));
             gl.delete_renderbuffer(Some(&msaa_buffers.stencil_renderbuffer));
             gl.delete_framebuffer(Some(&msaa_buffers.render_framebuffer));
@@ -323,6 +326,14 @@ impl WebGlRenderBackend {
             gl.delete_texture(Some(&msaa_buffers.framebuffer_texture));
         }
 
+        if self.gl2.is_none() || self.msaa_sample_count <= 1 {
+            self.gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
+            self.gl.bind_renderbuffer(Gl::RENDERBUFFER, None);
+            return Ok(());
+        }
+
+        let gl = self.gl2.as_ref().unwrap();
+
         // Create frame and render buffers.
         let render_framebuffer = gl
             .create_framebuffer()
@@ -341,7 +352,7 @@ impl WebGlRenderBackend {
         gl.bind_renderbuffer(Gl2::RENDERBUFFER, Some(&color_renderbuffer));
         gl.renderbuffer_storage_multisample(
             Gl2::RENDERBUFFER,
-            4,
+            self.msaa_sample_count as i32,
             Gl2::RGB8,
             self.viewport_width as i32,
             self.viewport_height as i32,
@@ -353,7 +364,7 @@ impl WebGlRenderBackend {
         gl.bind_renderbuffer(Gl2::RENDERBUFFER, Some(&stencil_renderbuffer));
         gl.renderbuffer_storage_multisample(
             Gl2::RENDERBUFFER,
-            4,
+            self.msaa_sample_count as i32,
             Gl2::STENCIL_INDEX8,
             self.viewport_width as i32,
             self.viewport_height as i32,
@@ -423,15 +434,49 @@ impl WebGlRenderBackend {
     }
 
     fn register_shape_internal(&mut self, shape: DistilledShape) -> Mesh {
+        let scaling = StrokeScaling::from_shape(&shape);
+        let strokes = if scaling.has_strokes() {
+            Some(Box::new(ScaledStrokes {
+                shape: OwnedShape::from(&shape),
+                scaling,
+                draws: HashMap::new(),
+            }))
+        } else {
+            None
+        };
+        let draws = self.tessellate_shape(shape, StrokeScale::IDENTITY);
+        Mesh { draws, strokes }
+    }
+
+    /// Tessellates the strokes of `shape` for `stroke_scale`, if they haven't been already.
+    fn prepare_stroke_scale(&mut self, shape: ShapeHandle, stroke_scale: StrokeScale) {
+        let mut strokes = match self.meshes[shape.0].strokes.take() {
+            Some(strokes) => strokes,
+            None => return,
+        };
+        if !strokes.draws.contains_key(&stroke_scale) {
+            // Forget old scales once there are too many, e.g. during a zoom animation.
+            if strokes.draws.len() >= MAX_STROKE_SCALES {
+                strokes.draws.clear();
+            }
+            let draws = self.tessellate_shape(strokes.shape.as_distilled(), stroke_scale);
+            strokes.draws.insert(stroke_scale, draws);
+        }
+        self.meshes[shape.0].strokes = Some(strokes);
+    }
+
+    fn tessellate_shape(&mut self, shape: DistilledShape, stroke_scale: StrokeScale) -> Vec<Draw> {
         use ruffle_render_common_tess::DrawType as TessDrawType;
 
         let textures = &self.textures;
-        let lyon_mesh = self.shape_tessellator.tessellate_shape(shape, |id| {
-            textures
-                .iter()
-                .find(|(other_id, _tex)| *other_id == id)
-                .map(|tex| (tex.1.width, tex.1.height))
-        });
+        let lyon_mesh = self
+            .shape_tessellator
+            .tessellate_shape(shape, stroke_scale, |id| {
+                textures
+                    .iter()
+                    .find(|(other_id, _tex)| *other_id == id)
+                    .map(|tex| (tex.1.width, tex.1.height))
+            });
 
         let mut draws = Vec::with_capacity(lyon_mesh.len());
 
@@ -478,21 +523,6 @@ impl WebGlRenderBackend {
                     },
                 ),
                 TessDrawType::Gradient(gradient) => {
-                    let mut ratios = [0.0; 8];
-                    let mut colors = [[0.0; 4]; 8];
-                    let num_colors = gradient.num_colors as usize;
-                    ratios[..num_colors].copy_from_slice(&gradient.ratios[..num_colors]);
-                    colors[..num_colors].copy_from_slice(&gradient.colors[..num_colors]);
-                    // Convert to linear color space if this is a linear-interpolated gradient.
-                    if gradient.interpolation == swf::GradientInterpolation::LinearRGB {
-                        for color in &mut colors[..num_colors] {
-                            *color = srgb_to_linear(*color);
-                        }
-                    }
-                    for i in num_colors..8 {
-                        ratios[i] = ratios[i - 1];
-                        colors[i] = colors[i - 1];
-                    }
                     let out_gradient = Gradient {
                         matrix: gradient.matrix,
                         gradient_type: match gradient.gradient_type {
@@ -500,16 +530,13 @@ impl WebGlRenderBackend {
                             GradientType::Radial => 1,
                             GradientType::Focal => 2,
                         },
-                        ratios,
-                        colors,
-                        num_colors: gradient.num_colors,
                         repeat_mode: match gradient.repeat_mode {
                             GradientSpread::Pad => 0,
                             GradientSpread::Repeat => 1,
                             GradientSpread::Reflect => 2,
                         },
                         focal_point: gradient.focal_point,
-                        interpolation: gradient.interpolation,
+                        texture: self.register_gradient_texture(&gradient.lookup_table),
                     };
                     (
                         &self.gradient_program,
@@ -577,7 +604,7 @@ impl WebGlRenderBackend {
             }
         }
 
-        Mesh { draws }
+        draws
     }
 
     fn build_matrices(&mut self) {
@@ -644,6 +671,34 @@ impl WebGlRenderBackend {
         }
     }
 
+    /// Uploads a gradient lookup table as a texture.
+    fn register_gradient_texture(&mut self, lookup_table: &[u8]) -> WebGlTexture {
+        let texture = self.gl.create_texture().unwrap();
+        self.gl.bind_texture(Gl::TEXTURE_2D, Some(&texture));
+        self.gl
+            .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                Gl::TEXTURE_2D,
+                0,
+                Gl::RGBA as i32,
+                GRADIENT_SIZE as i32,
+                1,
+                0,
+                Gl::RGBA,
+                Gl::UNSIGNED_BYTE,
+                Some(lookup_table),
+            )
+            .unwrap();
+        self.gl
+            .tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE as i32);
+        self.gl
+            .tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE as i32);
+        self.gl
+            .tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MIN_FILTER, Gl::LINEAR as i32);
+        self.gl
+            .tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MAG_FILTER, Gl::LINEAR as i32);
+        texture
+    }
+
     fn register_bitmap(
         &mut self,
         id: swf::CharacterId,
@@ -962,8 +1017,17 @@ impl RenderBackend for WebGlRenderBackend {
 
         self.set_stencil_state();
 
+        let stroke_scale = self.meshes[shape.0]
+            .strokes
+            .as_ref()
+            .map(|strokes| strokes.scaling.stroke_scale(&transform.matrix))
+            .unwrap_or(StrokeScale::IDENTITY);
+        if stroke_scale != StrokeScale::IDENTITY {
+            self.prepare_stroke_scale(shape, stroke_scale);
+        }
+
         let mesh = &self.meshes[shape.0];
-        for draw in &mesh.draws {
+        for draw in mesh.draws(stroke_scale) {
             self.bind_vertex_array(Some(&draw.vao));
 
             let (program, src_blend, dst_blend) = match &draw.draw_type {
@@ -1019,15 +1083,6 @@ impl RenderBackend for WebGlRenderBackend {
                         ShaderUniform::GradientType,
                         gradient.gradient_type,
                     );
-                    program.uniform1fv(&self.gl, ShaderUniform::GradientRatios, &gradient.ratios);
-                    let colors =
-                        unsafe { std::slice::from_raw_parts(gradient.colors[0].as_ptr(), 32) };
-                    program.uniform4fv(&self.gl, ShaderUniform::GradientColors, &colors);
-                    program.uniform1i(
-                        &self.gl,
-                        ShaderUniform::GradientNumColors,
-                        gradient.num_colors as i32,
-                    );
                     program.uniform1i(
                         &self.gl,
                         ShaderUniform::GradientRepeatMode,
@@ -1038,11 +1093,12 @@ impl RenderBackend for WebGlRenderBackend {
                         ShaderUniform::GradientFocalPoint,
                         gradient.focal_point,
                     );
-                    program.uniform1i(
-                        &self.gl,
-                        ShaderUniform::GradientInterpolation,
-                        (gradient.interpolation == swf::GradientInterpolation::LinearRGB) as i32,
-                    );
+
+                    // Bind the lookup table.
+                    self.gl.active_texture(Gl::TEXTURE0);
+                    self.gl
+                        .bind_texture(Gl::TEXTURE_2D, Some(&gradient.texture));
+                    program.uniform1i(&self.gl, ShaderUniform::GradientTexture, 0);
                 }
                 DrawType::Bitmap(bitmap) => {
                     let texture = &self
@@ -1180,6 +1236,18 @@ impl RenderBackend for WebGlRenderBackend {
             log::warn!("Mask stack underflow\n");
         }
     }
+
+    fn set_quality(&mut self, quality: StageQuality) {
+        let msaa_sample_count = match quality {
+            StageQuality::Low => 1,
+            StageQuality::Medium => self.max_msaa_sample_count.min(2),
+            StageQuality::High => self.max_msaa_sample_count,
+        };
+        if msaa_sample_count != self.msaa_sample_count {
+            self.msaa_sample_count = msaa_sample_count;
+            self.build_msaa_buffers().unwrap();
+        }
+    }
 }
 
 struct Texture {
@@ -1192,12 +1260,11 @@ struct Texture {
 struct Gradient {
     matrix: [[f32; 3]; 3],
     gradient_type: i32,
-    ratios: [f32; 8],
-    colors: [[f32; 4]; 8],
-    num_colors: u32,
     repeat_mode: i32,
     focal_point: f32,
-    interpolation: swf::GradientInterpolation,
+
+    /// The colors of the gradient, as a `GRADIENT_SIZE`x1 lookup texture.
+    texture: WebGlTexture,
 }
 
 #[derive(Clone, Debug)]
@@ -1210,6 +1277,26 @@ struct BitmapDraw {
 
 struct Mesh {
     draws: Vec<Draw>,
+
+    /// The strokes of the shape, if it has any.
+    strokes: Option<Box<ScaledStrokes>>,
+}
+
+impl Mesh {
+    /// The draws to use when the shape's strokes are drawn at `stroke_scale`.
+    fn draws(&self, stroke_scale: StrokeScale) -> &[Draw] {
+        self.strokes
+            .as_ref()
+            .and_then(|strokes| strokes.draws.get(&stroke_scale))
+            .unwrap_or(&self.draws)
+    }
+}
+
+/// The tessellations of a shape for each scale that changes how its strokes look.
+struct ScaledStrokes {
+    shape: OwnedShape,
+    scaling: StrokeScaling,
+    draws: HashMap<StrokeScale, Vec<Draw>>,
 }
 
 #[allow(dead_code)]
@@ -1247,7 +1334,7 @@ struct ShaderProgram {
 }
 
 // These should match the uniform names in the shaders.
-const NUM_UNIFORMS: usize = 13;
+const NUM_UNIFORMS: usize = 10;
 const UNIFORM_NAMES: [&str; NUM_UNIFORMS] = [
     "world_matrix",
     "view_matrix",
@@ -1255,12 +1342,9 @@ const UNIFORM_NAMES: [&str; NUM_UNIFORMS] = [
     "add_color",
     "u_matrix",
     "u_gradient_type",
-    "u_ratios",
-    "u_colors",
-    "u_num_colors",
     "u_repeat_mode",
     "u_focal_point",
-    "u_interpolation",
+    "u_gradient",
     "u_texture",
 ];
 
@@ -1271,12 +1355,9 @@ enum ShaderUniform {
     AddColor,
     TextureMatrix,
     GradientType,
-    GradientRatios,
-    GradientColors,
-    GradientNumColors,
     GradientRepeatMode,
     GradientFocalPoint,
-    GradientInterpolation,
+    GradientTexture,
     BitmapTexture,
 }
 
@@ -5,30 +5,36 @@ use lyon::tessellation::{
 };
 use ruffle_core::backend::render::swf::{self, FillStyle};
 use ruffle_core::backend::render::{
-    srgb_to_linear, Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, Letterbox,
-    RenderBackend, ShapeHandle, Transform,
+    gradient_lookup_table, Bitmap, BitmapFormat, BitmapHandle, BitmapInfo, Color, Letterbox,
+    RenderBackend, ShapeHandle, StageQuality, Transform, GRADIENT_SIZE,
+};
+use ruffle_core::shape_utils::{
+    stroke_width, DistilledShape, DrawPath, OwnedShape, StrokeScale, StrokeScaling,
 };
-use ruffle_core::shape_utils::{DistilledShape, DrawPath};
 use std::convert::TryInto;
-use swf::{CharacterId, DefineBitsLossless, Glyph, GradientInterpolation};
+use swf::{CharacterId, DefineBitsLossless, Glyph};
 
 use bytemuck::{Pod, Zeroable};
 use futures::executor::block_on;
 use raw_window_handle::HasRawWindowHandle;
 
 use crate::pipelines::Pipelines;
-use crate::shapes::{Draw, DrawType, GradientUniforms, IncompleteDrawType, Mesh};
+use crate::shapes::{Draw, DrawType, GradientUniforms, IncompleteDrawType, Mesh, ScaledStrokes};
 use crate::target::{RenderTarget, RenderTargetFrame, SwapChainTarget};
 use crate::utils::{
     build_view_matrix, create_buffer_with_data, gradient_spread_mode_index,
     ruffle_path_to_lyon_path, swf_bitmap_to_gl_matrix, swf_to_gl_matrix,
 };
 use ruffle_core::color_transform::ColorTransform;
+use std::collections::HashMap;
 use std::mem::replace;
 use std::rc::Rc;
 
 type Error = Box<dyn std::error::Error>;
 
+/// The number of stroke scales to keep tessellations of for each shape.
+const MAX_STROKE_SCALES: usize = 8;
+
 #[macro_use]
 mod utils;
 
@@ -225,8 +231,41 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
         })
     }
 
-    #[allow(clippy::cognitive_complexity)]
     fn register_shape_internal(&mut self, shape: DistilledShape) -> Mesh {
+        let scaling = StrokeScaling::from_shape(&shape);
+        let strokes = if scaling.has_strokes() {
+            Some(Box::new(ScaledStrokes {
+                shape: OwnedShape::from(&shape),
+                scaling,
+                meshes: HashMap::new(),
+            }))
+        } else {
+            None
+        };
+        let mut mesh = self.tessellate_shape(shape, StrokeScale::IDENTITY);
+        mesh.strokes = strokes;
+        mesh
+    }
+
+    /// Tessellates the strokes of `shape` for `stroke_scale`, if they haven't been already.
+    fn prepare_stroke_scale(&mut self, shape: ShapeHandle, stroke_scale: StrokeScale) {
+        let mut strokes = match self.meshes[shape.0].strokes.take() {
+            Some(strokes) => strokes,
+            None => return,
+        };
+        if !strokes.meshes.contains_key(&stroke_scale) {
+            // Forget old scales once there are too many, e.g. during a zoom animation.
+            if strokes.meshes.len() >= MAX_STROKE_SCALES {
+                strokes.meshes.clear();
+            }
+            let mesh = self.tessellate_shape(strokes.shape.as_distilled(), stroke_scale);
+            strokes.meshes.insert(stroke_scale, mesh);
+        }
+        self.meshes[shape.0].strokes = Some(strokes);
+    }
+
+    #[allow(clippy::cognitive_complexity)]
+    fn tessellate_shape(&mut self, shape: DistilledShape, stroke_scale: StrokeScale) -> Mesh {
         use lyon::tessellation::{FillOptions, StrokeOptions};
 
         let transforms_label = create_debug_label!("Shape {} transforms ubo", shape.id);
@@ -353,12 +392,14 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
 
                         let uniforms = swf_gradient_to_uniforms(0, gradient, 0.0);
                         let matrix = swf_to_gl_matrix(gradient.matrix);
+                        let texture_view = self.register_gradient_texture(shape.id, gradient);
 
                         flush_draw(
                             shape.id,
                             IncompleteDrawType::Gradient {
                                 texture_transform: matrix,
                                 gradient: uniforms,
+                                texture_view,
                             },
                             &mut draws,
                             &mut lyon_mesh,
@@ -399,12 +440,14 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
 
                         let uniforms = swf_gradient_to_uniforms(1, gradient, 0.0);
                         let matrix = swf_to_gl_matrix(gradient.matrix);
+                        let texture_view = self.register_gradient_texture(shape.id, gradient);
 
                         flush_draw(
                             shape.id,
                             IncompleteDrawType::Gradient {
                                 texture_transform: matrix,
                                 gradient: uniforms,
+                                texture_view,
                             },
                             &mut draws,
                             &mut lyon_mesh,
@@ -448,12 +491,14 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
 
                         let uniforms = swf_gradient_to_uniforms(2, gradient, *focal_point);
                         let matrix = swf_to_gl_matrix(gradient.matrix);
+                        let texture_view = self.register_gradient_texture(shape.id, gradient);
 
                         flush_draw(
                             shape.id,
                             IncompleteDrawType::Gradient {
                                 texture_transform: matrix,
                                 gradient: uniforms,
+                                texture_view,
                             },
                             &mut draws,
                             &mut lyon_mesh,
@@ -547,15 +592,8 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
                     let mut buffers_builder =
                         BuffersBuilder::new(&mut lyon_mesh, RuffleVertexCtor { color });
 
-                    // TODO(Herschel): 0 width indicates "hairline".
-                    let width = if style.width.to_pixels() >= 1.0 {
-                        style.width.to_pixels() as f32
-                    } else {
-                        1.0
-                    };
-
                     let mut options = StrokeOptions::default()
-                        .with_line_width(width)
+                        .with_line_width(stroke_width(style, stroke_scale))
                         .with_line_join(match style.join_style {
                             swf::LineJoinStyle::Round => tessellation::LineJoin::Round,
                             swf::LineJoinStyle::Bevel => tessellation::LineJoin::Bevel,
@@ -606,6 +644,7 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
             colors_buffer: colors_ubo,
             colors_last: ColorTransform::default(),
             shape_id: shape.id,
+            strokes: None,
         }
     }
 
@@ -689,6 +728,56 @@ impl<T: RenderTarget> WgpuRenderBackend<T> {
         })
     }
 
+    /// Uploads the lookup table of `gradient` as a texture.
+    fn register_gradient_texture(
+        &mut self,
+        shape_id: CharacterId,
+        gradient: &swf::Gradient,
+    ) -> wgpu::TextureView {
+        let extent = wgpu::Extent3d {
+            width: GRADIENT_SIZE as u32,
+            height: 1,
+            depth: 1,
+        };
+
+        let texture_label = create_debug_label!("Shape {} gradient texture", shape_id);
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: texture_label.as_deref(),
+            size: extent,
+            array_layer_count: 1,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+        });
+
+        let buffer = create_buffer_with_data(
+            &self.device,
+            &gradient_lookup_table(gradient),
+            wgpu::BufferUsage::COPY_SRC,
+            create_debug_label!("Shape {} gradient transfer buffer", shape_id),
+        );
+
+        self.register_encoder.copy_buffer_to_texture(
+            wgpu::BufferCopyView {
+                buffer: &buffer,
+                offset: 0,
+                bytes_per_row: 4 * extent.width,
+                rows_per_image: 0,
+            },
+            wgpu::TextureCopyView {
+                texture: &texture,
+                mip_level: 0,
+                array_layer: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            extent,
+        );
+
+        texture.create_default_view()
+    }
+
     pub fn target(&self) -> &T {
         &self.target
     }
@@ -1123,6 +1212,19 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
     }
 
     fn render_shape(&mut self, shape: ShapeHandle, transform: &Transform) {
+        if self.current_frame.is_none() {
+            return;
+        }
+
+        let stroke_scale = self.meshes[shape.0]
+            .strokes
+            .as_ref()
+            .map(|strokes| strokes.scaling.stroke_scale(&transform.matrix))
+            .unwrap_or(StrokeScale::IDENTITY);
+        if stroke_scale != StrokeScale::IDENTITY {
+            self.prepare_stroke_scale(shape, stroke_scale);
+        }
+
         let (frame_output, encoder) = if let Some((frame_output, encoder)) = &mut self.current_frame
         {
             (frame_output, encoder)
@@ -1130,7 +1232,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             return;
         };
 
-        let mesh = &mut self.meshes[shape.0];
+        let mesh = self.meshes[shape.0].for_stroke_scale_mut(stroke_scale);
 
         let world_matrix = [
             [transform.matrix.a, transform.matrix.b, 0.0, 0.0],
@@ -1385,6 +1487,29 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
             self.test_stencil_mask = test;
         }
     }
+
+    fn set_quality(&mut self, quality: StageQuality) {
+        let msaa_sample_count = match quality {
+            StageQuality::Low => 1,
+            StageQuality::Medium => 2,
+            StageQuality::High => 4,
+        };
+        if msaa_sample_count == self.msaa_sample_count {
+            return;
+        }
+
+        if let Err(e) = self
+            .pipelines
+            .set_msaa_sample_count(&self.device, msaa_sample_count)
+        {
+            log::error!("Unable to change the MSAA sample count: {}", e);
+            return;
+        }
+        self.msaa_sample_count = msaa_sample_count;
+
+        // Recreate the framebuffer and depth textures with the new sample count.
+        self.set_viewport_dimensions(self.viewport_width as u32, self.viewport_height as u32);
+    }
 }
 
 fn create_quad_buffers(device: &wgpu::Device) -> (wgpu::Buffer, wgpu::Buffer, wgpu::Buffer) {
@@ -1440,42 +1565,18 @@ fn create_quad_buffers(device: &wgpu::Device) -> (wgpu::Buffer, wgpu::Buffer, wg
 }
 
 /// Converts a gradient to the uniforms used by the shader.
+///
+/// The colors of the gradient are passed separately, as a lookup texture.
 fn swf_gradient_to_uniforms(
     gradient_type: i32,
     gradient: &swf::Gradient,
     focal_point: f32,
 ) -> GradientUniforms {
-    let mut colors: [[f32; 4]; 16] = Default::default();
-    let mut ratios: [f32; 16] = Default::default();
-    for (i, record) in gradient.records.iter().enumerate() {
-        if i >= 16 {
-            // TODO: we need to support these!
-            break;
-        }
-        colors[i] = [
-            f32::from(record.color.r) / 255.0,
-            f32::from(record.color.g) / 255.0,
-            f32::from(record.color.b) / 255.0,
-            f32::from(record.color.a) / 255.0,
-        ];
-        ratios[i] = f32::from(record.ratio) / 255.0;
-    }
-
-    // Convert colors from sRGB to linear space if necessary.
-    if gradient.interpolation == GradientInterpolation::LinearRGB {
-        for color in &mut colors[0..gradient.records.len()] {
-            *color = srgb_to_linear(*color);
-        }
-    }
-
     GradientUniforms {
         gradient_type,
-        ratios,
-        colors,
-        interpolation: (gradient.interpolation == GradientInterpolation::LinearRGB) as i32,
-        num_colors: gradient.records.len() as u32,
         repeat_mode: gradient_spread_mode_index(gradient.spread),
         focal_point,
+        _padding: 0.0,
     }
 }
 
@@ -16,6 +16,14 @@ pub struct Pipelines {
 }
 
 impl ShapePipeline {
+    fn new(bind_layout: wgpu::BindGroupLayout) -> Self {
+        Self {
+            write_mask_pipelines: Vec::new(),
+            read_mask_pipelines: Vec::new(),
+            bind_layout,
+        }
+    }
+
     pub fn pipeline_for(
         &self,
         num_masks: u32,
@@ -33,6 +41,24 @@ impl ShapePipeline {
 
 impl Pipelines {
     pub fn new(device: &wgpu::Device, msaa_sample_count: u32) -> Result<Self, Error> {
+        let mut pipelines = Self {
+            color: ShapePipeline::new(create_color_bind_layout(device)),
+            bitmap: ShapePipeline::new(create_bitmap_bind_layout(device)),
+            gradient: ShapePipeline::new(create_gradient_bind_layout(device)),
+        };
+        pipelines.set_msaa_sample_count(device, msaa_sample_count)?;
+        Ok(pipelines)
+    }
+
+    /// Recreate every pipeline to render with the given number of samples.
+    ///
+    /// The bind group layouts are kept, so the bind groups of shapes that were
+    /// already registered stay valid.
+    pub fn set_msaa_sample_count(
+        &mut self,
+        device: &wgpu::Device,
+        msaa_sample_count: u32,
+    ) -> Result<(), Error> {
         let color_vs_bytes = include_bytes!("../shaders/color.vert.spv");
         let color_vs = device.create_shader_module(&wgpu::read_spirv(std::io::Cursor::new(
             &color_vs_bytes[..],
@@ -54,16 +80,37 @@ impl Pipelines {
             &bitmap_fs_bytes[..],
         ))?);
 
-        Ok(Self {
-            color: create_color_pipelines(&device, &color_vs, &color_fs, msaa_sample_count),
-            bitmap: create_bitmap_pipeline(&device, &texture_vs, &bitmap_fs, msaa_sample_count),
-            gradient: create_gradient_pipeline(
-                &device,
-                &texture_vs,
-                &gradient_fs,
-                msaa_sample_count,
-            ),
-        })
+        let (write_mask_pipelines, read_mask_pipelines) = create_color_pipelines(
+            device,
+            &color_vs,
+            &color_fs,
+            &self.color.bind_layout,
+            msaa_sample_count,
+        );
+        self.color.write_mask_pipelines = write_mask_pipelines;
+        self.color.read_mask_pipelines = read_mask_pipelines;
+
+        let (write_mask_pipelines, read_mask_pipelines) = create_bitmap_pipeline(
+            device,
+            &texture_vs,
+            &bitmap_fs,
+            &self.bitmap.bind_layout,
+            msaa_sample_count,
+        );
+        self.bitmap.write_mask_pipelines = write_mask_pipelines;
+        self.bitmap.read_mask_pipelines = read_mask_pipelines;
+
+        let (write_mask_pipelines, read_mask_pipelines) = create_gradient_pipeline(
+            device,
+            &texture_vs,
+            &gradient_fs,
+            &self.gradient.bind_layout,
+            msaa_sample_count,
+        );
+        self.gradient.write_mask_pipelines = write_mask_pipelines;
+        self.gradient.read_mask_pipelines = read_mask_pipelines;
+
+        Ok(())
     }
 }
 
@@ -112,14 +159,9 @@ fn create_pipeline_descriptor<'a>(
     }
 }
 
-fn create_color_pipelines(
-    device: &wgpu::Device,
-    vertex_shader: &wgpu::ShaderModule,
-    fragment_shader: &wgpu::ShaderModule,
-    msaa_sample_count: u32,
-) -> ShapePipeline {
+fn create_color_bind_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
     let label = create_debug_label!("Color shape bind group");
-    let bind_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
         bindings: &[
             wgpu::BindGroupLayoutEntry {
                 binding: 0,
@@ -133,10 +175,18 @@ fn create_color_pipelines(
             },
         ],
         label: label.as_deref(),
-    });
+    })
+}
 
+fn create_color_pipelines(
+    device: &wgpu::Device,
+    vertex_shader: &wgpu::ShaderModule,
+    fragment_shader: &wgpu::ShaderModule,
+    bind_layout: &wgpu::BindGroupLayout,
+    msaa_sample_count: u32,
+) -> (Vec<wgpu::RenderPipeline>, Vec<wgpu::RenderPipeline>) {
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        bind_group_layouts: &[&bind_layout],
+        bind_group_layouts: &[bind_layout],
     });
 
     let mut write_mask_pipelines = Vec::new();
@@ -226,21 +276,12 @@ fn create_color_pipelines(
         )));
     }
 
-    ShapePipeline {
-        write_mask_pipelines,
-        read_mask_pipelines,
-        bind_layout,
-    }
+    (write_mask_pipelines, read_mask_pipelines)
 }
 
-fn create_bitmap_pipeline(
-    device: &wgpu::Device,
-    vertex_shader: &wgpu::ShaderModule,
-    fragment_shader: &wgpu::ShaderModule,
-    msaa_sample_count: u32,
-) -> ShapePipeline {
+fn create_bitmap_bind_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
     let label = create_debug_label!("Bitmap shape bind group");
-    let bind_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
         bindings: &[
             wgpu::BindGroupLayoutEntry {
                 binding: 0,
@@ -273,10 +314,18 @@ fn create_bitmap_pipeline(
             },
         ],
         label: label.as_deref(),
-    });
+    })
+}
 
+fn create_bitmap_pipeline(
+    device: &wgpu::Device,
+    vertex_shader: &wgpu::ShaderModule,
+    fragment_shader: &wgpu::ShaderModule,
+    bind_layout: &wgpu::BindGroupLayout,
+    msaa_sample_count: u32,
+) -> (Vec<wgpu::RenderPipeline>, Vec<wgpu::RenderPipeline>) {
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        bind_group_layouts: &[&bind_layout],
+        bind_group_layouts: &[bind_layout],
     });
 
     let mut write_mask_pipelines = Vec::new();
@@ -366,21 +415,12 @@ fn create_bitmap_pipeline(
         )));
     }
 
-    ShapePipeline {
-        write_mask_pipelines,
-        read_mask_pipelines,
-        bind_layout,
-    }
+    (write_mask_pipelines, read_mask_pipelines)
 }
 
-fn create_gradient_pipeline(
-    device: &wgpu::Device,
-    vertex_shader: &wgpu::ShaderModule,
-    fragment_shader: &wgpu::ShaderModule,
-    msaa_sample_count: u32,
-) -> ShapePipeline {
+fn create_gradient_bind_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
     let label = create_debug_label!("Gradient shape bind group");
-    let bind_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
         bindings: &[
             wgpu::BindGroupLayoutEntry {
                 binding: 0,
@@ -400,17 +440,36 @@ fn create_gradient_pipeline(
             wgpu::BindGroupLayoutEntry {
                 binding: 3,
                 visibility: wgpu::ShaderStage::FRAGMENT,
-                ty: wgpu::BindingType::StorageBuffer {
-                    dynamic: false,
-                    readonly: true,
+                ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 4,
+                visibility: wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::SampledTexture {
+                    multisampled: false,
+                    component_type: wgpu::TextureComponentType::Float,
+                    dimension: wgpu::TextureViewDimension::D2,
                 },
             },
+            wgpu::BindGroupLayoutEntry {
+                binding: 5,
+                visibility: wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::Sampler { comparison: false },
+            },
         ],
         label: label.as_deref(),
-    });
+    })
+}
 
+fn create_gradient_pipeline(
+    device: &wgpu::Device,
+    vertex_shader: &wgpu::ShaderModule,
+    fragment_shader: &wgpu::ShaderModule,
+    bind_layout: &wgpu::BindGroupLayout,
+    msaa_sample_count: u32,
+) -> (Vec<wgpu::RenderPipeline>, Vec<wgpu::RenderPipeline>) {
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        bind_group_layouts: &[&bind_layout],
+        bind_group_layouts: &[bind_layout],
     });
 
     let mut write_mask_pipelines = Vec::new();
@@ -500,9 +559,5 @@ fn create_gradient_pipeline(
         )));
     }
 
-    ShapePipeline {
-        write_mask_pipelines,
-        read_mask_pipelines,
-        bind_layout,
-    }
+    (write_mask_pipelines, read_mask_pipelines)
 }
@@ -4,17 +4,16 @@ use crate::{ColorAdjustments, TextureTransforms, Transforms};
 use bytemuck::{Pod, Zeroable};
 use ruffle_core::backend::audio::swf::CharacterId;
 use ruffle_core::color_transform::ColorTransform;
+use ruffle_core::shape_utils::{OwnedShape, StrokeScale, StrokeScaling};
+use std::collections::HashMap;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
 pub struct GradientUniforms {
-    pub colors: [[f32; 4]; 16],
-    pub ratios: [f32; 16],
     pub gradient_type: i32,
-    pub num_colors: u32,
     pub repeat_mode: i32,
-    pub interpolation: i32,
     pub focal_point: f32,
+    pub _padding: f32,
 }
 
 unsafe impl Pod for GradientUniforms {}
@@ -27,6 +26,37 @@ pub struct Mesh {
     pub colors_buffer: wgpu::Buffer,
     pub colors_last: ColorTransform,
     pub shape_id: CharacterId,
+
+    /// The strokes of the shape, if it has any.
+    pub strokes: Option<Box<ScaledStrokes>>,
+}
+
+impl Mesh {
+    /// The mesh to use when the shape's strokes are drawn at `stroke_scale`.
+    pub fn for_stroke_scale_mut(&mut self, stroke_scale: StrokeScale) -> &mut Mesh {
+        let scaled = self
+            .strokes
+            .as_ref()
+            .map_or(false, |strokes| strokes.meshes.contains_key(&stroke_scale));
+        if scaled {
+            self.strokes
+                .as_mut()
+                .unwrap()
+                .meshes
+                .get_mut(&stroke_scale)
+                .unwrap()
+        } else {
+            self
+        }
+    }
+}
+
+/// The tessellations of a shape for each scale that changes how its strokes look.
+#[derive(Debug)]
+pub struct ScaledStrokes {
+    pub shape: OwnedShape,
+    pub scaling: StrokeScaling,
+    pub meshes: HashMap<StrokeScale, Mesh>,
 }
 
 #[derive(Debug)]
@@ -44,6 +74,7 @@ pub enum DrawType {
     Gradient {
         texture_transforms: wgpu::Buffer,
         gradient: wgpu::Buffer,
+        texture_view: wgpu::TextureView,
     },
     Bitmap {
         texture_transforms: wgpu::Buffer,
@@ -59,6 +90,7 @@ pub enum IncompleteDrawType {
     Gradient {
         texture_transform: [[f32; 4]; 4],
         gradient: GradientUniforms,
+        texture_view: wgpu::TextureView,
     },
     Bitmap {
         texture_transform: [[f32; 4]; 4],
@@ -127,6 +159,7 @@ impl IncompleteDrawType {
             IncompleteDrawType::Gradient {
                 texture_transform,
                 gradient,
+                texture_view,
             } => {
                 let tex_transforms_ubo = create_buffer_with_data(
                     device,
@@ -142,7 +175,7 @@ impl IncompleteDrawType {
                 let gradient_ubo = create_buffer_with_data(
                     device,
                     bytemuck::cast_slice(&[gradient]),
-                    wgpu::BufferUsage::STORAGE_READ,
+                    wgpu::BufferUsage::UNIFORM,
                     create_debug_label!(
                         "Shape {} draw {} gradient ubo transfer buffer",
                         shape_id,
@@ -150,6 +183,20 @@ impl IncompleteDrawType {
                     ),
                 );
 
+                // The lookup table already has an entry for every ratio, so
+                // filtering just smooths between neighbouring entries.
+                let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+                    address_mode_u: wgpu::AddressMode::ClampToEdge,
+                    address_mode_v: wgpu::AddressMode::ClampToEdge,
+                    address_mode_w: wgpu::AddressMode::ClampToEdge,
+                    mag_filter: wgpu::FilterMode::Linear,
+                    min_filter: wgpu::FilterMode::Linear,
+                    mipmap_filter: wgpu::FilterMode::Nearest,
+                    lod_min_clamp: 0.0,
+                    lod_max_clamp: 100.0,
+                    compare: wgpu::CompareFunction::Undefined,
+                });
+
                 let bind_group_label =
                     create_debug_label!("Shape {} (gradient) draw {} bindgroup", shape_id, draw_id);
                 let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -183,6 +230,14 @@ impl IncompleteDrawType {
                                 range: 0..std::mem::size_of::<GradientUniforms>() as u64,
                             },
                         },
+                        wgpu::Binding {
+                            binding: 4,
+                            resource: wgpu::BindingResource::TextureView(&texture_view),
+                        },
+                        wgpu::Binding {
+                            binding: 5,
+                            resource: wgpu::BindingResource::Sampler(&sampler),
+                        },
                     ],
                     label: bind_group_label.as_deref(),
                 });
@@ -191,6 +246,7 @@ impl IncompleteDrawType {
                     draw_type: DrawType::Gradient {
                         texture_transforms: tex_transforms_ubo,
                         gradient: gradient_ubo,
+                        texture_view,
                     },
                     vertex_buffer,
                     index_buffer,
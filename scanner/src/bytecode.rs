@@ -0,0 +1,430 @@
+//! Decoding a movie's ActionScript bytecode, to count the opcodes and native
+//! APIs it uses.
+
+use crate::features::variant_name;
+use ruffle_core::swf::avm1::read::Reader as Avm1Reader;
+use ruffle_core::swf::avm1::types::{Action, Value};
+use ruffle_core::swf::avm2::read::Reader as AbcReader;
+use ruffle_core::swf::avm2::types::{AbcFile, Multiname, Op, Trait};
+use ruffle_core::swf::{Swf, Tag};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Cursor;
+
+/// The bytecode statistics of a movie.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Bytecode {
+    /// The ActionScript version the movie was written in: 3 if it has AVM2
+    /// code, 2 if its AVM1 code defines classes, and 1 otherwise.
+    pub action_script_version: u8,
+
+    /// How many times each AVM1 action appears in the movie's code.
+    pub avm1_opcodes: BTreeMap<String, u32>,
+
+    /// How many times each AVM2 opcode appears in the movie's code.
+    pub avm2_opcodes: BTreeMap<String, u32>,
+
+    /// The names of the variables, properties and methods the movie's code
+    /// uses without defining them itself, such as `gotoAndPlay` or
+    /// `addEventListener`. These are mostly the native API of the player.
+    pub names: Vec<String>,
+
+    /// How many blocks of code couldn't be fully decoded.
+    pub decode_errors: u32,
+}
+
+#[derive(Default)]
+struct Decoder {
+    swf_version: u8,
+    is_action_script_2: bool,
+    is_action_script_3: bool,
+    avm1_opcodes: BTreeMap<String, u32>,
+    avm2_opcodes: BTreeMap<String, u32>,
+    referenced: BTreeSet<String>,
+    defined: BTreeSet<String>,
+    decode_errors: u32,
+}
+
+impl Bytecode {
+    pub fn from_swf(swf: &Swf) -> Self {
+        let mut decoder = Decoder {
+            swf_version: swf.header.version,
+            ..Default::default()
+        };
+        decoder.add_tags(&swf.tags);
+
+        let action_script_version = if decoder.is_action_script_3 {
+            3
+        } else if decoder.is_action_script_2 {
+            2
+        } else {
+            1
+        };
+        let defined = decoder.defined;
+        Self {
+            action_script_version,
+            avm1_opcodes: decoder.avm1_opcodes,
+            avm2_opcodes: decoder.avm2_opcodes,
+            names: decoder
+                .referenced
+                .into_iter()
+                .filter(|name| !defined.contains(name))
+                .collect(),
+            decode_errors: decoder.decode_errors,
+        }
+    }
+}
+
+impl Decoder {
+    fn add_tags(&mut self, tags: &[Tag]) {
+        for tag in tags {
+            match tag {
+                Tag::DefineSprite(sprite) => self.add_tags(&sprite.tags),
+                Tag::DoAction(action_data) => self.add_avm1(action_data),
+                Tag::DoInitAction { action_data, .. } => self.add_avm1(action_data),
+                Tag::DefineButton(button) | Tag::DefineButton2(button) => {
+                    for action in &button.actions {
+                        self.add_avm1(&action.action_data);
+                    }
+                }
+                Tag::PlaceObject(place_object) => {
+                    for clip_action in &place_object.clip_actions {
+                        self.add_avm1(&clip_action.action_data);
+                    }
+                }
+                Tag::FileAttributes(attributes) if attributes.is_action_script_3 => {
+                    self.is_action_script_3 = true;
+                }
+                Tag::DoAbc(do_abc) => {
+                    self.is_action_script_3 = true;
+                    match AbcReader::new(Cursor::new(&do_abc.data[..])).read() {
+                        Ok(abc) => self.add_abc(&abc),
+                        Err(_) => self.decode_errors += 1,
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn add_avm1(&mut self, action_data: &[u8]) {
+        self.add_avm1_block(action_data, &mut vec![]);
+    }
+
+    /// Count the actions in a block of AVM1 code, and in the functions and
+    /// blocks nested in it. `constant_pool` is the pool in use at the start
+    /// of the block.
+    fn add_avm1_block<'a>(&mut self, action_data: &'a [u8], constant_pool: &mut Vec<&'a str>) {
+        let mut reader = Avm1Reader::new(action_data, self.swf_version);
+        // The strings pushed by the previous action, as names are pushed just
+        // before the action that uses them.
+        let mut pushed: Vec<Option<&'a str>> = vec![];
+        while reader.pos() < action_data.len() {
+            let action = match reader.read_action() {
+                Ok(Some(action)) => action,
+                Ok(None) => break,
+                Err(_) => {
+                    self.decode_errors += 1;
+                    break;
+                }
+            };
+
+            let name = match &action {
+                Action::Unknown { opcode, .. } => format!("Unknown({:#x})", opcode),
+                action => variant_name(action),
+            };
+            *self.avm1_opcodes.entry(name).or_default() += 1;
+
+            match &action {
+                Action::ConstantPool(constants) => *constant_pool = constants.clone(),
+                Action::GetVariable
+                | Action::GetMember
+                | Action::CallFunction
+                | Action::CallMethod
+                | Action::NewObject
+                | Action::NewMethod => {
+                    if let Some(Some(name)) = pushed.last() {
+                        self.referenced.insert(name.to_string());
+                    }
+                }
+                Action::SetVariable | Action::SetMember | Action::DefineLocal => {
+                    // The name is pushed just before the value being set.
+                    if let Some(Some(name)) = pushed.iter().rev().nth(1) {
+                        self.defined.insert(name.to_string());
+                    }
+                }
+                Action::DefineLocal2 => {
+                    if let Some(Some(name)) = pushed.last() {
+                        self.defined.insert(name.to_string());
+                    }
+                }
+                Action::DefineFunction { name, actions, .. } => {
+                    self.defined.insert(name.to_string());
+                    self.add_avm1_block(actions, &mut constant_pool.clone());
+                }
+                Action::DefineFunction2(function) => {
+                    self.defined.insert(function.name.to_string());
+                    self.add_avm1_block(function.actions, &mut constant_pool.clone());
+                }
+                Action::With { actions } => self.add_avm1_block(actions, constant_pool),
+                Action::Try(try_block) => {
+                    self.add_avm1_block(try_block.try_actions, constant_pool);
+                    if let Some((_, catch_actions)) = &try_block.catch {
+                        self.add_avm1_block(catch_actions, constant_pool);
+                    }
+                    if let Some(finally_actions) = try_block.finally {
+                        self.add_avm1_block(finally_actions, constant_pool);
+                    }
+                }
+                Action::Extends | Action::ImplementsOp | Action::CastOp => {
+                    self.is_action_script_2 = true;
+                }
+                _ => (),
+            }
+
+            pushed = match &action {
+                Action::Push(values) => values
+                    .iter()
+                    .map(|value| match value {
+                        Value::Str(string) => Some(*string),
+                        Value::ConstantPool(index) => constant_pool.get(*index as usize).copied(),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => vec![],
+            };
+        }
+    }
+
+    fn add_abc(&mut self, abc: &AbcFile) {
+        let pool = &abc.constant_pool;
+        // Index 0 of each pool means "none", so the pools start at 1.
+        let multiname_name = |index: u32| {
+            let name = match pool.multinames.get(index.checked_sub(1)? as usize)? {
+                Multiname::QName { name, .. }
+                | Multiname::QNameA { name, .. }
+                | Multiname::RTQName { name }
+                | Multiname::RTQNameA { name }
+                | Multiname::Multiname { name, .. }
+                | Multiname::MultinameA { name, .. } => name,
+                _ => return None,
+            };
+            pool.strings.get(name.0.checked_sub(1)? as usize)
+        };
+
+        let traits = abc
+            .instances
+            .iter()
+            .flat_map(|instance| &instance.traits)
+            .chain(abc.classes.iter().flat_map(|class| &class.traits))
+            .chain(abc.scripts.iter().flat_map(|script| &script.traits))
+            .chain(abc.method_bodies.iter().flat_map(|body| &body.traits));
+        for Trait { name, .. } in traits {
+            if let Some(name) = multiname_name(name.0) {
+                self.defined.insert(name.clone());
+            }
+        }
+        for instance in &abc.instances {
+            if let Some(name) = multiname_name(instance.name.0) {
+                self.defined.insert(name.clone());
+            }
+        }
+
+        for body in &abc.method_bodies {
+            let mut reader = AbcReader::new(Cursor::new(&body.code[..]));
+            loop {
+                let op = match reader.read_op() {
+                    Ok(Some(op)) => op,
+                    Ok(None) => break,
+                    Err(_) => {
+                        // Running off the end of the code is how it ends.
+                        if reader
+                            .seek(0)
+                            .map_or(true, |pos| pos < body.code.len() as u64)
+                        {
+                            self.decode_errors += 1;
+                        }
+                        break;
+                    }
+                };
+                *self.avm2_opcodes.entry(variant_name(&op)).or_default() += 1;
+
+                match op {
+                    Op::CallProperty { index, .. }
+                    | Op::CallPropLex { index, .. }
+                    | Op::CallPropVoid { index, .. }
+                    | Op::CallSuper { index, .. }
+                    | Op::CallSuperVoid { index, .. }
+                    | Op::ConstructProp { index, .. }
+                    | Op::FindProperty { index }
+                    | Op::FindPropStrict { index }
+                    | Op::GetLex { index }
+                    | Op::GetProperty { index }
+                    | Op::GetSuper { index }
+                    | Op::SetProperty { index }
+                    | Op::InitProperty { index } => {
+                        if let Some(name) = multiname_name(index.0) {
+                            self.referenced.insert(name.clone());
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruffle_core::swf::avm1::assemble::assemble;
+    use ruffle_core::swf::avm2::types::{
+        ConstantPool, Index, Method, MethodBody, Namespace, TraitKind,
+    };
+    use ruffle_core::swf::avm2::write::Writer as AbcWriter;
+    use ruffle_core::swf::{Compression, DoAbc, Header, Rectangle};
+
+    fn swf(tags: Vec<Tag>) -> Swf {
+        Swf {
+            header: Header {
+                version: 8,
+                compression: Compression::None,
+                stage_size: Rectangle::default(),
+                frame_rate: 24.0,
+                num_frames: 1,
+            },
+            tags,
+        }
+    }
+
+    fn do_action(text: &str) -> Tag {
+        Tag::DoAction(assemble(text, 8).unwrap())
+    }
+
+    #[test]
+    fn avm1_opcodes_and_names() {
+        let bytecode = Bytecode::from_swf(&swf(vec![do_action(
+            r#"ConstantPool "gotoAndPlay", "score"
+Push c:1, 0
+SetVariable
+Push 1, 1, c:0
+CallFunction
+Pop
+Push "score"
+GetVariable
+Trace
+DefineFunction "helper" () {
+    Push "Stage"
+    GetVariable
+    Push "width"
+    GetMember
+    Trace
+}
+Push 0, "helper"
+CallFunction
+Pop
+"#,
+        )]));
+        assert_eq!(bytecode.action_script_version, 1);
+        assert_eq!(bytecode.names, vec!["Stage", "gotoAndPlay", "width"]);
+        assert_eq!(bytecode.avm1_opcodes["Push"], 6);
+        assert_eq!(bytecode.avm1_opcodes["CallFunction"], 2);
+        assert_eq!(bytecode.avm1_opcodes["GetVariable"], 2);
+        assert_eq!(bytecode.avm1_opcodes["DefineFunction"], 1);
+        assert_eq!(bytecode.avm1_opcodes["ConstantPool"], 1);
+        assert_eq!(bytecode.decode_errors, 0);
+    }
+
+    #[test]
+    fn avm1_versions_and_errors() {
+        let bytecode = Bytecode::from_swf(&swf(vec![
+            do_action("Push \"Base\"\nGetVariable\nPush \"Derived\"\nGetVariable\nExtends"),
+            // A truncated `Push`.
+            Tag::DoAction(vec![0x96, 0x05]),
+        ]));
+        assert_eq!(bytecode.action_script_version, 2);
+        assert_eq!(bytecode.avm1_opcodes["Extends"], 1);
+        assert_eq!(bytecode.names, vec!["Base", "Derived"]);
+        assert_eq!(bytecode.decode_errors, 1);
+    }
+
+    #[test]
+    fn avm2_opcodes_and_names() {
+        let multiname = |name| Multiname::QName {
+            namespace: Index::new(1),
+            name: Index::new(name),
+        };
+        let abc = AbcFile {
+            major_version: 46,
+            minor_version: 16,
+            constant_pool: ConstantPool {
+                ints: vec![],
+                uints: vec![],
+                doubles: vec![],
+                strings: ["", "addEventListener", "Main", "handler"]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                namespaces: vec![Namespace::Package(Index::new(1))],
+                namespace_sets: vec![],
+                multinames: vec![multiname(2), multiname(3), multiname(4)],
+            },
+            methods: vec![Method {
+                name: Index::new(0),
+                params: vec![],
+                return_type: Index::new(0),
+                needs_arguments_object: false,
+                needs_activation: false,
+                needs_rest: false,
+                needs_dxns: false,
+            }],
+            metadata: vec![],
+            instances: vec![],
+            classes: vec![],
+            scripts: vec![],
+            method_bodies: vec![MethodBody {
+                method: Index::new(0),
+                max_stack: 1,
+                num_locals: 1,
+                init_scope_depth: 0,
+                max_scope_depth: 0,
+                code: vec![
+                    0x60, 2,    // getlex Main
+                    0x29, // pop
+                    0x5d, 1, // findpropstrict addEventListener
+                    0x4f, 1, 0, // callpropvoid addEventListener, 0
+                    0x60, 3,    // getlex handler
+                    0x29, // pop
+                    0x47, // returnvoid
+                ],
+                exceptions: vec![],
+                traits: vec![Trait {
+                    name: Index::new(3),
+                    kind: TraitKind::Slot {
+                        slot_id: 0,
+                        type_name: Index::new(0),
+                        value: None,
+                    },
+                    metadata: vec![],
+                    is_final: false,
+                    is_override: false,
+                }],
+            }],
+        };
+        let mut data = vec![];
+        AbcWriter::new(&mut data).write(abc).unwrap();
+
+        let bytecode = Bytecode::from_swf(&swf(vec![Tag::DoAbc(DoAbc {
+            name: String::new(),
+            is_lazy_initialize: true,
+            data,
+        })]));
+        assert_eq!(bytecode.action_script_version, 3);
+        assert_eq!(bytecode.names, vec!["Main", "addEventListener"]);
+        assert_eq!(bytecode.avm2_opcodes["GetLex"], 2);
+        assert_eq!(bytecode.avm2_opcodes["Pop"], 2);
+        assert_eq!(bytecode.avm2_opcodes["CallPropVoid"], 1);
+        assert_eq!(bytecode.avm2_opcodes.len(), 5);
+        assert_eq!(bytecode.decode_errors, 0);
+    }
+}
@@ -0,0 +1,340 @@
+//! Running movies headlessly, to find the ActionScript features they need.
+//!
+//! Each movie runs in a child process of the scanner, so that a panic or a
+//! hang can't stop the scan. The child prints every distinct AVM warning it
+//! hits as a line on stdout, so that warnings are kept even if it's killed.
+//!
+//! Reading a movie's tags and decoding its code happens in a child process
+//! too, which prints its `Analysis` as a line of JSON.
+
+use crate::bytecode::Bytecode;
+use crate::features::Features;
+use ruffle_core::backend::audio::NullAudioBackend;
+use ruffle_core::backend::input::NullInputBackend;
+use ruffle_core::backend::log::{LogBackend, LogSource};
+use ruffle_core::backend::navigator::{NullExecutor, NullNavigatorBackend};
+use ruffle_core::backend::render::NullRenderer;
+use ruffle_core::backend::storage::MemoryStorageBackend;
+use ruffle_core::swf::read_swf_lenient;
+use ruffle_core::tag_utils::SwfMovie;
+use ruffle_core::Player;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::ffi::OsStr;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// The argument that makes the scanner run a single movie as a child process.
+pub const CHILD_ARG: &str = "--execute-child";
+
+/// The argument that makes the scanner analyze a single movie as a child
+/// process.
+pub const ANALYZE_CHILD_ARG: &str = "--analyze-child";
+
+/// How often to check whether a child process has finished.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How a headless run of a movie ended.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// Every frame ran.
+    Completed,
+
+    /// The player panicked, with the given message.
+    Panicked(String),
+
+    /// The movie didn't finish running in time.
+    TimedOut,
+
+    /// The movie couldn't be loaded or run.
+    Failed(String),
+}
+
+/// The results of running a movie headlessly.
+#[derive(Serialize, Debug, Clone)]
+pub struct Execution {
+    pub outcome: Outcome,
+
+    /// The distinct AVM warnings hit while running, such as
+    /// `Unimplemented property _quality`.
+    pub warnings: Vec<String>,
+}
+
+/// What was found by reading a movie's tags, without running it.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Analysis {
+    /// Problems found in the movie's data that it could be read past.
+    pub diagnostics: Vec<String>,
+
+    pub features: Features,
+
+    pub bytecode: Bytecode,
+}
+
+/// The output of a child process.
+struct ChildOutput {
+    /// How the child exited, or `None` if it was killed for running too long.
+    status: Option<ExitStatus>,
+
+    stdout: Vec<String>,
+
+    stderr: Vec<String>,
+}
+
+/// Run the movie at `path` for `frames` frames, giving up after `timeout`.
+pub fn execute(path: &Path, frames: u32, timeout: Duration) -> Execution {
+    let frames = frames.to_string();
+    let args = [OsStr::new(CHILD_ARG), path.as_os_str(), OsStr::new(&frames)];
+    let output = match run_child_process(&args, timeout) {
+        Ok(output) => output,
+        Err(message) => {
+            return Execution {
+                outcome: Outcome::Failed(message),
+                warnings: vec![],
+            }
+        }
+    };
+
+    let warnings = output
+        .stdout
+        .iter()
+        .map(|line| line.replace("\\n", "\n"))
+        .collect();
+    let outcome = outcome(output.status, &output.stderr);
+
+    Execution { outcome, warnings }
+}
+
+/// Read the movie at `path` and analyze its tags and code, giving up after
+/// `timeout`.
+pub fn analyze(path: &Path, timeout: Duration) -> Result<Analysis, String> {
+    let args = [OsStr::new(ANALYZE_CHILD_ARG), path.as_os_str()];
+    let output = run_child_process(&args, timeout)?;
+    match outcome(output.status, &output.stderr) {
+        Outcome::Completed => output
+            .stdout
+            .last()
+            .and_then(|line| serde_json::from_str(line).ok())
+            .ok_or_else(|| "Analysis produced no results".to_string()),
+        Outcome::Panicked(message) => Err(format!("PANIC: {}", message)),
+        Outcome::TimedOut => Err("Timed out while reading the movie".to_string()),
+        Outcome::Failed(message) => Err(message),
+    }
+}
+
+/// Run the scanner as a child process with `args`, killing it after
+/// `timeout`.
+fn run_child_process(args: &[&OsStr], timeout: Duration) -> Result<ChildOutput, String> {
+    let exe =
+        std::env::current_exe().map_err(|e| format!("Can't find the scanner executable: {}", e))?;
+    let mut child = Command::new(exe)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Can't start a child process: {}", e))?;
+
+    // Read the pipes while waiting, so that the child never blocks on them.
+    let stdout = read_lines(child.stdout.take());
+    let stderr = read_lines(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if Instant::now() < deadline => thread::sleep(POLL_INTERVAL),
+            Ok(None) | Err(_) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+        }
+    };
+
+    Ok(ChildOutput {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// How a child process ended, given its exit status, or `None` if it was
+/// killed for running too long, and what it printed to stderr.
+fn outcome(status: Option<ExitStatus>, stderr: &[String]) -> Outcome {
+    match status {
+        None => Outcome::TimedOut,
+        Some(status) if status.success() => Outcome::Completed,
+        Some(status) => {
+            if let Some(panic) = stderr.iter().find(|line| line.contains("panicked at")) {
+                Outcome::Panicked(panic.clone())
+            } else if let Some(error) = stderr.last() {
+                Outcome::Failed(error.clone())
+            } else {
+                Outcome::Failed(format!("Player exited with {}", status))
+            }
+        }
+    }
+}
+
+fn read_lines(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<String>> {
+    thread::spawn(move || match pipe {
+        Some(pipe) => BufReader::new(pipe)
+            .lines()
+            // Stop at the first read error, which would otherwise repeat.
+            .take_while(|line| line.is_ok())
+            .filter_map(|line| line.ok())
+            .collect(),
+        None => vec![],
+    })
+}
+
+/// Log backend of a child process, which prints each new warning.
+#[derive(Default)]
+struct WarningPrinter {
+    seen: BTreeSet<String>,
+}
+
+impl LogBackend for WarningPrinter {
+    fn trace(&mut self, _source: &LogSource, _message: &str) {}
+
+    fn avm_warning(&mut self, _source: &LogSource, message: &str) {
+        if self.seen.insert(message.to_string()) {
+            println!("{}", message.replace('\n', "\\n"));
+        }
+    }
+}
+
+/// Run a movie with null backends, as the child process of `execute`.
+pub fn run_child(path: &Path, frames: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let base_path = path.parent().unwrap_or_else(|| Path::new(""));
+    let (mut executor, channel) = NullExecutor::new();
+    let movie = SwfMovie::from_path(path)?;
+    let frame_time = 1000.0 / movie.header().frame_rate as f64;
+    let player = Player::new(
+        Box::new(NullRenderer),
+        Box::new(NullAudioBackend::new()),
+        Box::new(NullNavigatorBackend::with_base_path(base_path, channel)),
+        Box::new(NullInputBackend::new()),
+        Box::new(MemoryStorageBackend::default()),
+    )?;
+    player
+        .lock()
+        .unwrap()
+        .set_log_backend(Box::new(WarningPrinter::default()));
+    player.lock().unwrap().set_root_movie(Arc::new(movie));
+
+    for _ in 0..frames {
+        player.lock().unwrap().run_frame();
+        player.lock().unwrap().update_timers(frame_time);
+        executor.poll_all()?;
+    }
+
+    Ok(())
+}
+
+/// Read and analyze a movie.
+fn analyze_data(path: &Path) -> Result<Analysis, String> {
+    let data = std::fs::read(path).map_err(|e| format!("File error: {}", e))?;
+    let (swf, diagnostics) =
+        read_swf_lenient(&data[..]).map_err(|e| format!("Parse error: {}", e))?;
+    Ok(Analysis {
+        diagnostics: diagnostics.iter().map(|d| d.to_string()).collect(),
+        features: Features::from_swf(&swf),
+        bytecode: Bytecode::from_swf(&swf),
+    })
+}
+
+/// Analyze a movie, as the child process of `analyze`.
+pub fn analyze_child(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let analysis = analyze_data(path)?;
+    println!("{}", serde_json::to_string(&analysis)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruffle_core::backend::log::AvmVersion;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn outcomes() {
+        use std::os::unix::process::ExitStatusExt;
+
+        assert_eq!(outcome(None, &[]), Outcome::TimedOut);
+        assert_eq!(
+            outcome(Some(ExitStatus::from_raw(0)), &lines(&["noise"])),
+            Outcome::Completed
+        );
+        // Exit code 101, as after a panic.
+        let failed = ExitStatus::from_raw(101 << 8);
+        assert_eq!(
+            outcome(
+                Some(failed),
+                &lines(&[
+                    "thread 'main' panicked at 'oops', core/src/lib.rs:1:1",
+                    "note: backtrace"
+                ])
+            ),
+            Outcome::Panicked("thread 'main' panicked at 'oops', core/src/lib.rs:1:1".to_string())
+        );
+        assert_eq!(
+            outcome(Some(failed), &lines(&["warning", "Error: Invalid SWF"])),
+            Outcome::Failed("Error: Invalid SWF".to_string())
+        );
+        assert!(matches!(outcome(Some(failed), &[]), Outcome::Failed(_)));
+    }
+
+    #[test]
+    fn warnings_are_printed_once() {
+        let source = LogSource {
+            movie_url: None,
+            avm: AvmVersion::Avm1,
+            frame: None,
+        };
+        let mut printer = WarningPrinter::default();
+        printer.avm_warning(&source, "Unimplemented property _quality");
+        printer.avm_warning(&source, "Unimplemented property _quality");
+        printer.avm_warning(&source, "Unimplemented method\nwith a newline");
+        assert_eq!(
+            printer.seen.into_iter().collect::<Vec<_>>(),
+            vec![
+                "Unimplemented method\nwith a newline",
+                "Unimplemented property _quality"
+            ]
+        );
+    }
+
+    #[test]
+    fn run_child_movie() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../core/tests/swfs/avm1/goto_frame/test.swf");
+        assert!(run_child(&path, 5).is_ok());
+        assert!(run_child(Path::new("missing.swf"), 5).is_err());
+    }
+
+    #[test]
+    fn analyze_child_movie() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../core/tests/swfs/avm1/goto_frame/test.swf");
+        let analysis = analyze_data(&path).unwrap();
+        let json = serde_json::to_string(&analysis).unwrap();
+        assert!(!json.contains('\n'));
+        let read: Analysis = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.features.swf_version, analysis.features.swf_version);
+        assert_eq!(read.bytecode.avm1_opcodes, analysis.bytecode.avm1_opcodes);
+
+        assert!(analyze_data(Path::new("missing.swf"))
+            .unwrap_err()
+            .starts_with("File error"));
+    }
+}
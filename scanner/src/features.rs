@@ -0,0 +1,516 @@
+//! Finding out which Flash features a movie uses, by reading its tags.
+
+use ruffle_core::swf::avm1::read::Reader as Avm1Reader;
+use ruffle_core::swf::avm1::types::{Action, Value};
+use ruffle_core::swf::avm2::read::Reader as AbcReader;
+use ruffle_core::swf::avm2::types::{AbcFile, Multiname, Namespace};
+use ruffle_core::swf::{Swf, Tag};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::{self, Write};
+use std::io::Cursor;
+
+/// The features used by a movie, each listed once in sorted order.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Features {
+    /// The ActionScript VM the movie runs on, 1 or 2.
+    pub avm_version: u8,
+
+    /// The SWF version of the movie.
+    pub swf_version: u8,
+
+    /// The types of tags used anywhere in the movie.
+    pub tags: Vec<String>,
+
+    /// The display filters used on placed objects.
+    pub filters: Vec<String>,
+
+    /// The sound and video codecs of embedded media.
+    pub codecs: Vec<String>,
+
+    /// The blend modes used on placed objects, other than `Normal`.
+    pub blend_modes: Vec<String>,
+
+    /// The Flash Player API classes named by the movie's code, such as
+    /// `flash.display.Sprite`, and the AVM1 classes it binds to symbols with
+    /// `Object.registerClass`.
+    ///
+    /// AVM1 code is only followed as far as it can be without running it, so
+    /// classes reached through registers or computed names are missed.
+    pub classes: Vec<String>,
+}
+
+#[derive(Default)]
+struct FeatureSets {
+    swf_version: u8,
+    tags: BTreeSet<String>,
+    filters: BTreeSet<String>,
+    codecs: BTreeSet<String>,
+    blend_modes: BTreeSet<String>,
+    classes: BTreeSet<String>,
+}
+
+impl Features {
+    pub fn from_swf(swf: &Swf) -> Self {
+        let mut sets = FeatureSets {
+            swf_version: swf.header.version,
+            ..Default::default()
+        };
+        let mut is_action_script_3 = false;
+        for tag in &swf.tags {
+            if let Tag::FileAttributes(attributes) = tag {
+                is_action_script_3 = attributes.is_action_script_3;
+            }
+        }
+        sets.add_tags(&swf.tags);
+
+        Self {
+            avm_version: if is_action_script_3 { 2 } else { 1 },
+            swf_version: swf.header.version,
+            tags: sets.tags.into_iter().collect(),
+            filters: sets.filters.into_iter().collect(),
+            codecs: sets.codecs.into_iter().collect(),
+            blend_modes: sets.blend_modes.into_iter().collect(),
+            classes: sets.classes.into_iter().collect(),
+        }
+    }
+}
+
+impl FeatureSets {
+    fn add_tags(&mut self, tags: &[Tag]) {
+        for tag in tags {
+            self.tags.insert(tag_name(tag));
+            match tag {
+                Tag::DefineSprite(sprite) => self.add_tags(&sprite.tags),
+                Tag::PlaceObject(place_object) => {
+                    for filter in &place_object.filters {
+                        self.filters.insert(variant_name(filter));
+                    }
+                    if place_object.blend_mode != ruffle_core::swf::BlendMode::Normal {
+                        self.blend_modes
+                            .insert(format!("{:?}", place_object.blend_mode));
+                    }
+                }
+                Tag::DefineSound(sound) => {
+                    self.codecs
+                        .insert(format!("{:?}", sound.format.compression));
+                }
+                Tag::SoundStreamHead(head) | Tag::SoundStreamHead2(head) => {
+                    self.codecs
+                        .insert(format!("{:?}", head.stream_format.compression));
+                }
+                Tag::DefineVideoStream(video) => {
+                    self.codecs.insert(format!("{:?}", video.codec));
+                }
+                Tag::DoAbc(do_abc) => {
+                    if let Ok(abc) = AbcReader::new(Cursor::new(&do_abc.data[..])).read() {
+                        self.add_classes(&abc);
+                    }
+                }
+                _ => (),
+            }
+            match tag {
+                Tag::DoAction(action_data) | Tag::DoInitAction { action_data, .. } => {
+                    self.add_avm1_classes(action_data, &[]);
+                }
+                Tag::DefineButton(button) | Tag::DefineButton2(button) => {
+                    for action in &button.actions {
+                        self.add_avm1_classes(&action.action_data, &[]);
+                    }
+                }
+                Tag::PlaceObject(place_object) => {
+                    for clip_action in &place_object.clip_actions {
+                        self.add_avm1_classes(&clip_action.action_data, &[]);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Record the classes used by a block of AVM1 code, and by the functions
+    /// and blocks nested in it. `constant_pool` is the pool in use at the
+    /// start of the block.
+    ///
+    /// The names and paths on the stack are followed from action to action,
+    /// until an action that isn't understood here forgets them.
+    fn add_avm1_classes<'a>(&mut self, action_data: &'a [u8], constant_pool: &[&'a str]) {
+        let mut constant_pool = constant_pool.to_vec();
+        let mut stack: Vec<Operand> = vec![];
+        let mut reader = Avm1Reader::new(action_data, self.swf_version);
+        while reader.pos() < action_data.len() {
+            let action = match reader.read_action() {
+                Ok(Some(action)) => action,
+                Ok(None) | Err(_) => break,
+            };
+            match action {
+                Action::ConstantPool(constants) => constant_pool = constants,
+                Action::Push(values) => {
+                    stack.extend(values.iter().map(|value| {
+                        match value {
+                            Value::Str(string) => Operand::Name(string.to_string()),
+                            Value::ConstantPool(index) => constant_pool
+                                .get(*index as usize)
+                                .map_or(Operand::Unknown, |name| Operand::Name(name.to_string())),
+                            Value::Int(value) => Operand::Number(f64::from(*value)),
+                            Value::Float(value) => Operand::Number(f64::from(*value)),
+                            Value::Double(value) => Operand::Number(*value),
+                            _ => Operand::Unknown,
+                        }
+                    }));
+                }
+                Action::Pop => {
+                    stack.pop();
+                }
+                Action::GetVariable => {
+                    let name = stack.pop().unwrap_or(Operand::Unknown);
+                    if let Operand::Name(path) = &name {
+                        self.add_avm1_class(path);
+                    }
+                    stack.push(name);
+                }
+                Action::GetMember => {
+                    let member = stack.pop().unwrap_or(Operand::Unknown);
+                    let object = stack.pop().unwrap_or(Operand::Unknown);
+                    let path = object.member(&member);
+                    if let Operand::Name(path) = &path {
+                        self.add_avm1_class(path);
+                    }
+                    stack.push(path);
+                }
+                Action::CallMethod | Action::NewMethod => {
+                    let method = stack.pop().unwrap_or(Operand::Unknown);
+                    let object = stack.pop().unwrap_or(Operand::Unknown);
+                    let args = match stack.pop() {
+                        Some(Operand::Number(count)) if count >= 0.0 => {
+                            let start = stack.len().saturating_sub(count as usize);
+                            stack.split_off(start)
+                        }
+                        _ => {
+                            stack.clear();
+                            vec![]
+                        }
+                    };
+                    if let Operand::Name(path) = object.member(&method) {
+                        if action == Action::NewMethod {
+                            self.add_avm1_class(&path);
+                        } else if path == "Object.registerClass" && args.len() == 2 {
+                            // Arguments are pushed last first, so the class
+                            // comes before the linkage name.
+                            if let Operand::Name(class) = &args[0] {
+                                self.classes.insert(global_path(class).to_string());
+                            }
+                        }
+                    }
+                    stack.push(Operand::Unknown);
+                }
+                Action::DefineFunction { name, actions, .. } => {
+                    self.add_avm1_classes(actions, &constant_pool);
+                    if name.is_empty() {
+                        stack.push(Operand::Unknown);
+                    }
+                }
+                Action::DefineFunction2(function) => {
+                    self.add_avm1_classes(function.actions, &constant_pool);
+                    if function.name.is_empty() {
+                        stack.push(Operand::Unknown);
+                    }
+                }
+                Action::With { actions } => {
+                    self.add_avm1_classes(actions, &constant_pool);
+                    stack.clear();
+                }
+                Action::Try(try_block) => {
+                    self.add_avm1_classes(try_block.try_actions, &constant_pool);
+                    if let Some((_, catch_actions)) = try_block.catch {
+                        self.add_avm1_classes(catch_actions, &constant_pool);
+                    }
+                    if let Some(finally_actions) = try_block.finally {
+                        self.add_avm1_classes(finally_actions, &constant_pool);
+                    }
+                    stack.clear();
+                }
+                _ => stack.clear(),
+            }
+        }
+    }
+
+    /// Record `path` if it names a Flash Player class, such as
+    /// `flash.geom.Matrix`.
+    fn add_avm1_class(&mut self, path: &str) {
+        let path = global_path(path);
+        if let Some(package) = path.strip_prefix("flash.") {
+            let name = package.rsplit('.').next().unwrap_or_default();
+            if package.contains('.') && name.starts_with(|c: char| c.is_ascii_uppercase()) {
+                self.classes.insert(path.to_string());
+            }
+        }
+    }
+
+    /// Record the Flash Player classes named by qualified names in `abc`.
+    fn add_classes(&mut self, abc: &AbcFile) {
+        let pool = &abc.constant_pool;
+        // Index 0 of each pool means "none", so the pools start at 1.
+        let string = |index: u32| pool.strings.get(index.checked_sub(1)? as usize);
+        for multiname in &pool.multinames {
+            if let Multiname::QName { namespace, name } = multiname {
+                let namespace = (namespace.0 as usize)
+                    .checked_sub(1)
+                    .and_then(|i| pool.namespaces.get(i));
+                let package = match namespace {
+                    Some(Namespace::Package(package)) => string(package.0),
+                    _ => None,
+                };
+                if let (Some(package), Some(name)) = (package, string(name.0)) {
+                    let is_class = name.starts_with(|c: char| c.is_ascii_uppercase());
+                    if package.starts_with("flash.") && is_class {
+                        self.classes.insert(format!("{}.{}", package, name));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A value on the AVM1 stack, as far as it's known without running the code.
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    /// A string, or the dotted path of the variable or member it was read
+    /// from.
+    Name(String),
+    Number(f64),
+    Unknown,
+}
+
+impl Operand {
+    /// The path of the member `member` of this object.
+    fn member(&self, member: &Operand) -> Operand {
+        match (self, member) {
+            (Operand::Name(object), Operand::Name(member)) => {
+                Operand::Name(format!("{}.{}", object, member))
+            }
+            _ => Operand::Unknown,
+        }
+    }
+}
+
+/// `path` without the `_global.` that AVM1 classes are often reached through.
+fn global_path(path: &str) -> &str {
+    path.strip_prefix("_global.").unwrap_or(path)
+}
+
+/// The name of a tag, such as `DefineShape`, or `Unknown(tag_code)`.
+fn tag_name(tag: &Tag) -> String {
+    match tag {
+        Tag::Unknown { tag_code, .. } => format!("Unknown({})", tag_code),
+        tag => variant_name(tag),
+    }
+}
+
+/// The name of the enum variant of `value`, read from its `Debug` output.
+pub(crate) fn variant_name(value: &impl fmt::Debug) -> String {
+    let mut name = VariantName(String::new());
+    // The writer stops the output once the name is complete, so that large
+    // tags aren't formatted in full.
+    let _ = write!(name, "{:?}", value);
+    name.0
+}
+
+struct VariantName(String);
+
+impl Write for VariantName {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match s.find(|c: char| !c.is_alphanumeric() && c != '_') {
+            Some(end) => {
+                self.0.push_str(&s[..end]);
+                Err(fmt::Error)
+            }
+            None => {
+                self.0.push_str(s);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruffle_core::swf::avm1::assemble::assemble;
+    use ruffle_core::swf::avm2::types::{ConstantPool, Index};
+    use ruffle_core::swf::avm2::write::Writer as AbcWriter;
+    use ruffle_core::swf::{Compression, DoAbc, FileAttributes, Header, Rectangle, Sprite, Twips};
+
+    fn swf(version: u8, tags: Vec<Tag>) -> Swf {
+        Swf {
+            header: Header {
+                version,
+                compression: Compression::None,
+                stage_size: Rectangle {
+                    x_min: Twips::new(0),
+                    x_max: Twips::from_pixels(550.0),
+                    y_min: Twips::new(0),
+                    y_max: Twips::from_pixels(400.0),
+                },
+                frame_rate: 24.0,
+                num_frames: 1,
+            },
+            tags,
+        }
+    }
+
+    fn do_action(text: &str) -> Tag {
+        Tag::DoAction(assemble(text, 8).unwrap())
+    }
+
+    #[test]
+    fn tags_in_sprites() {
+        let features = Features::from_swf(&swf(
+            8,
+            vec![
+                Tag::DefineSprite(Sprite {
+                    id: 1,
+                    num_frames: 1,
+                    tags: vec![do_action("Stop"), Tag::ShowFrame],
+                }),
+                Tag::Unknown {
+                    tag_code: 255,
+                    data: vec![],
+                },
+                Tag::ShowFrame,
+            ],
+        ));
+        assert_eq!(features.avm_version, 1);
+        assert_eq!(features.swf_version, 8);
+        assert_eq!(
+            features.tags,
+            vec!["DefineSprite", "DoAction", "ShowFrame", "Unknown(255)"]
+        );
+    }
+
+    #[test]
+    fn avm1_classes() {
+        let features = Features::from_swf(&swf(
+            8,
+            vec![do_action(
+                r#"ConstantPool "Object", "registerClass"
+Push "com"
+GetVariable
+Push "example"
+GetMember
+Push "Widget"
+GetMember
+Push "widget", 2, c:0
+GetVariable
+Push c:1
+CallMethod
+Pop
+DefineFunction "blur" () {
+    Push 0, "_global.flash.filters"
+    GetVariable
+    Push "BlurFilter"
+    NewMethod
+    Return
+}
+Push "flash"
+GetVariable
+Push "external"
+GetMember
+Push "ExternalInterface"
+GetMember
+Push "available"
+GetMember
+Pop
+"#,
+            )],
+        ));
+        assert_eq!(
+            features.classes,
+            vec![
+                "com.example.Widget",
+                "flash.external.ExternalInterface",
+                "flash.filters.BlurFilter"
+            ]
+        );
+    }
+
+    #[test]
+    fn avm2_classes() {
+        let string = |i| Index::new(i);
+        let abc = AbcFile {
+            major_version: 46,
+            minor_version: 16,
+            constant_pool: ConstantPool {
+                ints: vec![],
+                uints: vec![],
+                doubles: vec![],
+                strings: [
+                    "flash.display",
+                    "Sprite",
+                    "",
+                    "Main",
+                    "flash.events",
+                    "type",
+                ]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+                namespaces: vec![
+                    Namespace::Package(string(1)),
+                    Namespace::Package(string(3)),
+                    Namespace::Package(string(5)),
+                ],
+                namespace_sets: vec![],
+                multinames: vec![
+                    Multiname::QName {
+                        namespace: Index::new(1),
+                        name: string(2),
+                    },
+                    Multiname::QName {
+                        namespace: Index::new(2),
+                        name: string(4),
+                    },
+                    Multiname::QName {
+                        namespace: Index::new(3),
+                        name: string(6),
+                    },
+                ],
+            },
+            methods: vec![],
+            metadata: vec![],
+            instances: vec![],
+            classes: vec![],
+            scripts: vec![],
+            method_bodies: vec![],
+        };
+        let mut data = vec![];
+        AbcWriter::new(&mut data).write(abc).unwrap();
+
+        let features = Features::from_swf(&swf(
+            10,
+            vec![
+                Tag::FileAttributes(FileAttributes {
+                    use_direct_blit: false,
+                    use_gpu: false,
+                    has_metadata: false,
+                    is_action_script_3: true,
+                    use_network_sandbox: false,
+                }),
+                Tag::DoAbc(DoAbc {
+                    name: String::new(),
+                    is_lazy_initialize: true,
+                    data,
+                }),
+            ],
+        ));
+        assert_eq!(features.avm_version, 2);
+        assert_eq!(features.classes, vec!["flash.display.Sprite"]);
+    }
+
+    #[test]
+    fn variant_names() {
+        assert_eq!(variant_name(&Tag::ShowFrame), "ShowFrame");
+        assert_eq!(variant_name(&Some(1)), "Some");
+        assert_eq!(variant_name(&Action::Push(vec![Value::Int(1)])), "Push");
+    }
+}
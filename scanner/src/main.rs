@@ -1,18 +1,60 @@
+mod bytecode;
+mod execute;
+mod features;
+
+use crate::bytecode::Bytecode;
+use crate::execute::{Execution, Outcome};
+use crate::features::Features;
 use clap::Clap;
 use indicatif::{ProgressBar, ProgressStyle};
 use path_slash::PathExt;
-use ruffle_core::swf::read_swf;
 
 use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
-use std::panic::catch_unwind;
+use std::time::Duration;
 use walkdir::{DirEntry, WalkDir};
 
 #[derive(Serialize, Debug)]
 struct FileResults {
     name: String,
     error: Option<String>,
+
+    /// Problems found in the movie's data that it could be read past, such
+    /// as malformed tags or a truncated file.
+    diagnostics: Vec<String>,
+
+    /// The features found in the movie's tags, if it could be parsed.
+    features: Option<Features>,
+
+    /// The opcodes and native APIs used by the movie's code, if it could be
+    /// parsed.
+    bytecode: Option<Bytecode>,
+
+    /// The results of running the movie, if it was run.
+    execution: Option<Execution>,
+}
+
+impl FileResults {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            error: None,
+            diagnostics: Vec::new(),
+            features: None,
+            bytecode: None,
+            execution: None,
+        }
+    }
+
+    fn error(name: String, error: String) -> Self {
+        Self {
+            error: Some(error),
+            ..Self::new(name)
+        }
+    }
 }
 
 #[derive(Clap, Debug)]
@@ -22,13 +64,24 @@ struct Opt {
     #[clap(name = "directory", parse(from_os_str))]
     input_path: PathBuf,
 
-    /// The file to store results in CSV format
+    /// The file to store results in, as JSON if it ends in `.json` and
+    /// as CSV otherwise
     #[clap(name = "results", parse(from_os_str))]
     output_path: PathBuf,
 
     /// Filenames to ignore
     #[clap(short = "i", long = "ignore")]
     ignore: Vec<String>,
+
+    /// Also run each movie headlessly for this many frames, recording the
+    /// ActionScript warnings it hits
+    #[clap(long = "run-frames")]
+    run_frames: Option<u32>,
+
+    /// How many seconds a movie may be read or run for before it's given up
+    /// on
+    #[clap(long = "timeout", default_value = "10")]
+    timeout: u64,
 }
 
 fn find_files(root: &Path, ignore: &[String]) -> Vec<DirEntry> {
@@ -52,41 +105,131 @@ fn find_files(root: &Path, ignore: &[String]) -> Vec<DirEntry> {
     results
 }
 
-fn scan_file(file: DirEntry, name: String) -> FileResults {
-    let data = match std::fs::read(file.path()) {
-        Ok(data) => data,
+fn scan_file(file: DirEntry, name: String, opt: &Opt) -> FileResults {
+    let timeout = Duration::from_secs(opt.timeout);
+    let mut results = match execute::analyze(file.path(), timeout) {
+        Ok(analysis) => FileResults {
+            diagnostics: analysis.diagnostics,
+            features: Some(analysis.features),
+            bytecode: Some(analysis.bytecode),
+            ..FileResults::new(name)
+        },
+        Err(e) => return FileResults::error(name, e),
+    };
+
+    if let Some(frames) = opt.run_frames {
+        results.execution = Some(execute::execute(file.path(), frames, timeout));
+    }
+
+    results
+}
+
+/// Writes results as CSV, with each list of features in a single column.
+fn write_csv_record(
+    writer: &mut csv::Writer<std::fs::File>,
+    results: &FileResults,
+) -> Result<(), csv::Error> {
+    let features = results.features.as_ref();
+    let list = |f: fn(&Features) -> &Vec<String>| features.map(f).map(|list| list.join("; "));
+    let bytecode = results.bytecode.as_ref();
+    let execution = results.execution.as_ref();
+    writer.write_record(&[
+        results.name.clone(),
+        results.error.clone().unwrap_or_default(),
+        results.diagnostics.join("; "),
+        features
+            .map(|f| f.avm_version.to_string())
+            .unwrap_or_default(),
+        features
+            .map(|f| f.swf_version.to_string())
+            .unwrap_or_default(),
+        list(|f| &f.tags).unwrap_or_default(),
+        list(|f| &f.filters).unwrap_or_default(),
+        list(|f| &f.codecs).unwrap_or_default(),
+        list(|f| &f.blend_modes).unwrap_or_default(),
+        list(|f| &f.classes).unwrap_or_default(),
+        bytecode
+            .map(|b| b.action_script_version.to_string())
+            .unwrap_or_default(),
+        bytecode
+            .map(|b| opcode_list(&b.avm1_opcodes))
+            .unwrap_or_default(),
+        bytecode
+            .map(|b| opcode_list(&b.avm2_opcodes))
+            .unwrap_or_default(),
+        bytecode.map(|b| b.names.join("; ")).unwrap_or_default(),
+        bytecode
+            .map(|b| b.decode_errors.to_string())
+            .unwrap_or_default(),
+        execution
+            .map(|e| format!("{:?}", e.outcome))
+            .unwrap_or_default(),
+        execution.map(|e| e.warnings.join("; ")).unwrap_or_default(),
+    ])
+}
+
+/// Formats opcode counts as a single column, such as `Push: 3; Trace: 1`.
+fn opcode_list(opcodes: &BTreeMap<String, u32>) -> String {
+    opcodes
+        .iter()
+        .map(|(opcode, count)| format!("{}: {}", opcode, count))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Run a single movie, when the scanner was started as a child process by
+/// `execute::execute`.
+fn run_child(args: &[OsString]) -> ! {
+    let frames = args.get(1).and_then(|frames| frames.to_str()?.parse().ok());
+    let result = match (args.get(0), frames) {
+        (Some(path), Some(frames)) => execute::run_child(Path::new(path), frames),
+        _ => Err("Usage: --execute-child <file> <frames>".into()),
+    };
+    exit_child(result)
+}
+
+/// Analyze a single movie, when the scanner was started as a child process by
+/// `execute::analyze`.
+fn analyze_child(args: &[OsString]) -> ! {
+    let result = match args.first() {
+        Some(path) => execute::analyze_child(Path::new(path)),
+        None => Err("Usage: --analyze-child <file>".into()),
+    };
+    exit_child(result)
+}
+
+/// Exit a child process, printing its error if it failed.
+fn exit_child(result: Result<(), Box<dyn std::error::Error>>) -> ! {
+    match result {
+        Ok(()) => std::process::exit(0),
         Err(e) => {
-            return {
-                FileResults {
-                    name,
-                    error: Some(format!("File error: {}", e.to_string())),
-                }
-            }
+            eprintln!("{}", e);
+            std::process::exit(2);
         }
-    };
+    }
+}
 
-    match catch_unwind(|| read_swf(&data[..])) {
-        Ok(swf) => match swf {
-            Ok(_swf) => FileResults { name, error: None },
-            Err(e) => FileResults {
-                name,
-                error: Some(format!("Parse error: {}", e.to_string())),
-            },
-        },
-        Err(e) => match e.downcast::<String>() {
-            Ok(e) => FileResults {
-                name,
-                error: Some(format!("PANIC: {}", e.to_string())),
-            },
-            Err(_) => FileResults {
-                name,
-                error: Some("PANIC".to_string()),
-            },
-        },
+/// Prints the `limit` highest counts, if there are any.
+fn print_most_common(title: &str, counts: HashMap<String, usize>, limit: usize) {
+    if counts.is_empty() {
+        return;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    println!("{}:", title);
+    for (item, count) in counts.iter().take(limit) {
+        println!("{:>7} {}", count, item);
     }
 }
 
 fn main() -> Result<(), std::io::Error> {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    match args.get(1) {
+        Some(arg) if arg == execute::CHILD_ARG => run_child(&args[2..]),
+        Some(arg) if arg == execute::ANALYZE_CHILD_ARG => analyze_child(&args[2..]),
+        _ => {}
+    }
+
     env_logger::init();
 
     let opt = Opt::parse();
@@ -94,8 +237,27 @@ fn main() -> Result<(), std::io::Error> {
     let total = to_scan.len() as u64;
     let mut good = 0;
     let mut bad = 0;
+    // How many of the good movies could only be read past problems.
+    let mut recovered = 0;
     let progress = ProgressBar::new(total);
-    let mut writer = csv::Writer::from_path(opt.output_path)?;
+    let is_json = opt
+        .output_path
+        .extension()
+        .map_or(false, |ext| ext == "json");
+    let mut writer = if is_json {
+        None
+    } else {
+        Some(csv::Writer::from_path(&opt.output_path)?)
+    };
+    let mut all_results = vec![];
+    // How many movies hit each warning, to rank the missing features.
+    let mut warning_counts: HashMap<String, usize> = HashMap::new();
+    // How often each opcode is used, and how many movies use each name, to
+    // rank the missing parts of the AVMs.
+    let mut avm1_opcode_counts: HashMap<String, usize> = HashMap::new();
+    let mut avm2_opcode_counts: HashMap<String, usize> = HashMap::new();
+    let mut name_counts: HashMap<String, usize> = HashMap::new();
+    let mut version_counts: HashMap<String, usize> = HashMap::new();
 
     progress.set_style(
         ProgressStyle::default_bar()
@@ -105,7 +267,27 @@ fn main() -> Result<(), std::io::Error> {
             .progress_chars("##-"),
     );
 
-    writer.write_record(&["Filename", "Error"])?;
+    if let Some(writer) = &mut writer {
+        writer.write_record(&[
+            "Filename",
+            "Error",
+            "Diagnostics",
+            "AVM",
+            "SWF Version",
+            "Tags",
+            "Filters",
+            "Codecs",
+            "Blend Modes",
+            "Classes",
+            "AS Version",
+            "AVM1 Opcodes",
+            "AVM2 Opcodes",
+            "Names",
+            "Decode Errors",
+            "Outcome",
+            "Warnings",
+        ])?;
+    }
 
     for file in to_scan {
         let name = file
@@ -115,21 +297,60 @@ fn main() -> Result<(), std::io::Error> {
             .to_slash_lossy();
         progress.inc(1);
         progress.set_message(&name);
-        let result = scan_file(file, name);
+        let result = scan_file(file, name, &opt);
 
-        if result.error.is_none() {
+        let ran_cleanly = result
+            .execution
+            .as_ref()
+            .map_or(true, |e| e.outcome == Outcome::Completed);
+        if result.error.is_none() && ran_cleanly {
             good += 1;
+            if !result.diagnostics.is_empty() {
+                recovered += 1;
+            }
         } else {
             bad += 1;
         }
+        if let Some(bytecode) = &result.bytecode {
+            for (opcode, count) in &bytecode.avm1_opcodes {
+                *avm1_opcode_counts.entry(opcode.clone()).or_default() += *count as usize;
+            }
+            for (opcode, count) in &bytecode.avm2_opcodes {
+                *avm2_opcode_counts.entry(opcode.clone()).or_default() += *count as usize;
+            }
+            for name in &bytecode.names {
+                *name_counts.entry(name.clone()).or_default() += 1;
+            }
+            let version = format!("ActionScript {}", bytecode.action_script_version);
+            *version_counts.entry(version).or_default() += 1;
+        }
+        if let Some(execution) = &result.execution {
+            for warning in &execution.warnings {
+                *warning_counts.entry(warning.clone()).or_default() += 1;
+            }
+        }
+
+        match &mut writer {
+            Some(writer) => write_csv_record(writer, &result)?,
+            None => all_results.push(result),
+        }
+    }
 
-        writer.serialize(result)?;
+    if is_json {
+        let file = std::fs::File::create(&opt.output_path)?;
+        serde_json::to_writer_pretty(file, &all_results)?;
     }
 
     progress.finish_with_message(&format!(
-        "Scanned {} swf files. {} successfully parsed, {} encountered errors",
-        total, good, bad
+        "Scanned {} swf files. {} successfully parsed ({} with recoverable problems), {} encountered errors",
+        total, good, recovered, bad
     ));
 
+    print_most_common("Movies by ActionScript version", version_counts, usize::MAX);
+    print_most_common("Most common AVM1 actions", avm1_opcode_counts, 50);
+    print_most_common("Most common AVM2 opcodes", avm2_opcode_counts, 50);
+    print_most_common("Most used names", name_counts, 50);
+    print_most_common("Most common warnings", warning_counts, 20);
+
     Ok(())
 }
@@ -0,0 +1,31 @@
+//! Lists the ABC code of every `DoAbc` tag in a SWF.
+//!
+//! Usage:
+//!   abc <file.swf>
+
+use std::fs::File;
+use std::io::BufReader;
+use swf::avm2::{disassemble::disassemble, read::Reader};
+use swf::Tag;
+
+fn main() {
+    let path = match std::env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: abc <file.swf>");
+            std::process::exit(1);
+        }
+    };
+    let file = File::open(path).unwrap();
+    let swf = swf::read_swf(BufReader::new(file)).unwrap();
+    for tag in &swf.tags {
+        if let Tag::DoAbc(do_abc) = tag {
+            println!("; DoAbc {}", do_abc.name);
+            match Reader::new(&do_abc.data[..]).read() {
+                Ok(abc) => print!("{}", disassemble(&abc)),
+                Err(e) => println!("; Unable to read ABC: {}", e),
+            }
+            println!();
+        }
+    }
+}
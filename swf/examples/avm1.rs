@@ -0,0 +1,86 @@
+//! Converts the AVM1 code of a SWF to text and back.
+//!
+//! Usage:
+//!   avm1 disassemble <file.swf>
+//!   avm1 assemble <code.txt> <file.swf>
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use swf::avm1::{assemble::assemble, disassemble::disassemble};
+use swf::*;
+
+/// The SWF version that assembled code is written for.
+const VERSION: u8 = 10;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("disassemble") if args.len() == 3 => {
+            let file = File::open(&args[2]).unwrap();
+            let swf = swf::read_swf(BufReader::new(file)).unwrap();
+            print_actions(&swf.tags, swf.header.version, "");
+        }
+        Some("assemble") if args.len() == 4 => {
+            let text = std::fs::read_to_string(&args[2]).unwrap();
+            let action_data = match assemble(&text, VERSION) {
+                Ok(data) => data,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+            let swf = Swf {
+                header: Header {
+                    version: VERSION,
+                    compression: Compression::Zlib,
+                    stage_size: Rectangle {
+                        x_min: Twips::from_pixels(0.0),
+                        x_max: Twips::from_pixels(550.0),
+                        y_min: Twips::from_pixels(0.0),
+                        y_max: Twips::from_pixels(400.0),
+                    },
+                    frame_rate: 24.0,
+                    num_frames: 1,
+                },
+                tags: vec![Tag::DoAction(action_data), Tag::ShowFrame],
+            };
+            let file = File::create(&args[3]).unwrap();
+            swf::write_swf(&swf, BufWriter::new(file)).unwrap();
+        }
+        _ => {
+            eprintln!("Usage:");
+            eprintln!("  avm1 disassemble <file.swf>");
+            eprintln!("  avm1 assemble <code.txt> <file.swf>");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints the code of every `DoAction` and `DoInitAction` tag, including the
+/// ones inside sprites.
+fn print_actions(tags: &[Tag], version: u8, location: &str) {
+    let mut frame = 1;
+    for tag in tags {
+        match tag {
+            Tag::DoAction(action_data) => {
+                println!("; {}frame {}", location, frame);
+                print!("{}", disassemble(action_data, version));
+                println!();
+            }
+            Tag::DoInitAction { id, action_data } => {
+                println!("; init actions for character {}", id);
+                print!("{}", disassemble(action_data, version));
+                println!();
+            }
+            Tag::DefineSprite(sprite) => {
+                print_actions(
+                    &sprite.tags,
+                    version,
+                    &format!("{}sprite {} ", location, sprite.id),
+                );
+            }
+            Tag::ShowFrame => frame += 1,
+            _ => (),
+        }
+    }
+}
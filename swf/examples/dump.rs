@@ -0,0 +1,62 @@
+//! Dumps a SWF to JSON or YAML, and rebuilds a SWF from a dump.
+//!
+//! The format is picked from the extension of the dump file. Dumps can be
+//! diffed or edited by hand before being rebuilt. JSON can't hold floats that
+//! are NaN or infinite, so use YAML to write those in by hand.
+//!
+//! Usage:
+//!   dump dump <file.swf> <dump.json|dump.yaml>
+//!   dump rebuild <dump.json|dump.yaml> <file.swf>
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use swf::Swf;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let result = match args.get(1).map(String::as_str) {
+        Some("dump") if args.len() == 4 => dump(Path::new(&args[2]), Path::new(&args[3])),
+        Some("rebuild") if args.len() == 4 => rebuild(Path::new(&args[2]), Path::new(&args[3])),
+        _ => {
+            eprintln!("Usage:");
+            eprintln!("  dump dump <file.swf> <dump.json|dump.yaml>");
+            eprintln!("  dump rebuild <dump.json|dump.yaml> <file.swf>");
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn is_yaml(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("yaml") | Some("yml")
+    )
+}
+
+fn dump(swf_path: &Path, dump_path: &Path) -> Result<(), Box<dyn Error>> {
+    let swf = swf::read_swf(BufReader::new(File::open(swf_path)?))?;
+    let writer = BufWriter::new(File::create(dump_path)?);
+    if is_yaml(dump_path) {
+        serde_yaml::to_writer(writer, &swf)?;
+    } else {
+        serde_json::to_writer_pretty(writer, &swf)?;
+    }
+    Ok(())
+}
+
+fn rebuild(dump_path: &Path, swf_path: &Path) -> Result<(), Box<dyn Error>> {
+    let reader = BufReader::new(File::open(dump_path)?);
+    let swf: Swf = if is_yaml(dump_path) {
+        serde_yaml::from_reader(reader)?
+    } else {
+        serde_json::from_reader(reader)?
+    };
+    swf::write_swf(&swf, BufWriter::new(File::create(swf_path)?))?;
+    Ok(())
+}
@@ -1,3 +1,5 @@
+pub mod assemble;
+pub mod disassemble;
 pub(crate) mod opcode;
 pub mod read;
 pub mod types;
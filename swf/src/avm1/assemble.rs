@@ -0,0 +1,884 @@
+//! Assembling the text form of AVM1 bytecode written by `disassemble`.
+//!
+//! Lines hold an action and its operands, or a label such as `loop:` that
+//! `If` and `Jump` can name instead of a byte offset. Comments start with `;`.
+//! Blocks of code belonging to an action are written in braces.
+
+use crate::avm1::opcode::OpCode;
+use crate::avm1::types::*;
+use crate::avm1::write::Writer;
+use crate::error::{Error, Result};
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
+use std::str::Lines;
+
+/// Assembles text into a block of AVM1 bytecode.
+pub fn assemble(text: &str, version: u8) -> Result<Vec<u8>> {
+    let mut assembler = Assembler {
+        lines: text.lines(),
+        line_number: 0,
+        version,
+    };
+    let (data, closing) = assembler.block()?;
+    match closing {
+        Some(line) => Err(line.error("Unexpected }")),
+        None => Ok(data),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Str(String),
+    Word(String),
+    Punct(char),
+}
+
+/// The remaining tokens of a line.
+struct Line {
+    number: usize,
+    tokens: VecDeque<Token>,
+}
+
+/// A piece of a block, which is laid out once all of its labels are known.
+enum Item {
+    Label(String),
+    Bytes(Vec<u8>),
+    Branch {
+        opcode: OpCode,
+        target: Target,
+        line: usize,
+    },
+}
+
+enum Target {
+    Label(String),
+    Offset(i16),
+}
+
+/// The length of an `If` or `Jump` action.
+const BRANCH_LENGTH: usize = 5;
+
+struct Assembler<'a> {
+    lines: Lines<'a>,
+    line_number: usize,
+    version: u8,
+}
+
+impl<'a> Assembler<'a> {
+    /// Assembles lines up to the end of the text or a closing brace, and
+    /// returns the rest of the line after the brace.
+    fn block(&mut self) -> Result<(Vec<u8>, Option<Line>)> {
+        let mut items = Vec::new();
+        let closing = loop {
+            let mut line = match self.next_line()? {
+                Some(line) => line,
+                None => break None,
+            };
+            match line.tokens.front() {
+                None => continue,
+                Some(Token::Punct('}')) => {
+                    line.tokens.pop_front();
+                    break Some(line);
+                }
+                _ => (),
+            }
+            let name = line.word()?;
+            if name.ends_with(':') && line.tokens.is_empty() {
+                items.push(Item::Label(name[..name.len() - 1].to_string()));
+                continue;
+            }
+            items.push(self.action(&name, line)?);
+        };
+        Ok((self.layout(items)?, closing))
+    }
+
+    /// Works out the offsets of branches and joins the block together.
+    fn layout(&self, items: Vec<Item>) -> Result<Vec<u8>> {
+        let mut labels = HashMap::new();
+        let mut pos = 0;
+        for item in &items {
+            match item {
+                Item::Label(name) => {
+                    if labels.insert(name.as_str(), pos).is_some() {
+                        return Err(Error::invalid_data(format!("Duplicate label {}", name)));
+                    }
+                }
+                Item::Bytes(bytes) => pos += bytes.len(),
+                Item::Branch { .. } => pos += BRANCH_LENGTH,
+            }
+        }
+
+        let mut data = Vec::with_capacity(pos);
+        for item in &items {
+            match item {
+                Item::Label(_) => (),
+                Item::Bytes(bytes) => data.extend_from_slice(bytes),
+                Item::Branch {
+                    opcode,
+                    target,
+                    line,
+                } => {
+                    let offset = match target {
+                        Target::Offset(offset) => *offset,
+                        Target::Label(name) => {
+                            let target = *labels.get(name.as_str()).ok_or_else(|| {
+                                Error::invalid_data(format!(
+                                    "Line {}: Unknown label {}",
+                                    line, name
+                                ))
+                            })?;
+                            let end = data.len() + BRANCH_LENGTH;
+                            i16::try_from(target as isize - end as isize).map_err(|_| {
+                                Error::invalid_data(format!(
+                                    "Line {}: Label {} is too far away",
+                                    line, name
+                                ))
+                            })?
+                        }
+                    };
+                    let action = if *opcode == OpCode::If {
+                        Action::If { offset }
+                    } else {
+                        Action::Jump { offset }
+                    };
+                    self.write(&action, &mut data)?;
+                }
+            }
+        }
+        Ok(data)
+    }
+
+    fn action(&mut self, name: &str, mut line: Line) -> Result<Item> {
+        let mut data = Vec::new();
+        match name {
+            "Bytes" => {
+                while !line.tokens.is_empty() {
+                    data.push(line.hex_byte()?);
+                }
+            }
+            "ConstantPool" => {
+                let constants = line.list(Line::string)?;
+                line.end()?;
+                let constants = constants.iter().map(|c| c.as_str()).collect();
+                self.write(&Action::ConstantPool(constants), &mut data)?;
+            }
+            "DefineFunction" => {
+                let name = line.string()?;
+                line.punct('(')?;
+                let params = line.list(Line::string)?;
+                line.punct(')')?;
+                line.punct('{')?;
+                line.end()?;
+                let actions = self.inner_block(line.number)?;
+                self.write(
+                    &Action::DefineFunction {
+                        name: &name,
+                        params: params.iter().map(|p| p.as_str()).collect(),
+                        actions: &actions,
+                    },
+                    &mut data,
+                )?;
+            }
+            "DefineFunction2" => {
+                let name = line.string()?;
+                line.punct('(')?;
+                let params = line.list(|line| {
+                    let register_index = if line.peek_string() {
+                        None
+                    } else {
+                        Some(line.prefixed("r:")?)
+                    };
+                    Ok((register_index, line.string()?))
+                })?;
+                line.punct(')')?;
+                let register_count = line.prefixed("registers=")?;
+                let mut function = Function {
+                    name: &name,
+                    register_count,
+                    params: params
+                        .iter()
+                        .map(|(register_index, name)| FunctionParam {
+                            name,
+                            register_index: *register_index,
+                        })
+                        .collect(),
+                    preload_parent: false,
+                    preload_root: false,
+                    suppress_super: false,
+                    preload_super: false,
+                    suppress_arguments: false,
+                    preload_arguments: false,
+                    suppress_this: false,
+                    preload_this: false,
+                    preload_global: false,
+                    actions: &[],
+                };
+                while !line.peek_punct('{') {
+                    let flag = line.word()?;
+                    *match flag.as_str() {
+                        "preload_parent" => &mut function.preload_parent,
+                        "preload_root" => &mut function.preload_root,
+                        "suppress_super" => &mut function.suppress_super,
+                        "preload_super" => &mut function.preload_super,
+                        "suppress_arguments" => &mut function.suppress_arguments,
+                        "preload_arguments" => &mut function.preload_arguments,
+                        "suppress_this" => &mut function.suppress_this,
+                        "preload_this" => &mut function.preload_this,
+                        "preload_global" => &mut function.preload_global,
+                        _ => return Err(line.error(&format!("Unknown flag {}", flag))),
+                    } = true;
+                }
+                line.punct('{')?;
+                line.end()?;
+                let actions = self.inner_block(line.number)?;
+                function.actions = &actions;
+                self.write(&Action::DefineFunction2(function), &mut data)?;
+            }
+            "End" => {
+                line.end()?;
+                data.push(OpCode::End as u8);
+            }
+            "GetUrl" => {
+                let url = line.string()?;
+                line.punct(',')?;
+                let target = line.string()?;
+                line.end()?;
+                self.write(
+                    &Action::GetUrl {
+                        url: &url,
+                        target: &target,
+                    },
+                    &mut data,
+                )?;
+            }
+            "GetUrl2" => {
+                let send_vars_method = match line.word()?.as_str() {
+                    "none" => SendVarsMethod::None,
+                    "get" => SendVarsMethod::Get,
+                    "post" => SendVarsMethod::Post,
+                    method => return Err(line.error(&format!("Unknown method {}", method))),
+                };
+                let is_target_sprite = line.flag("target_sprite");
+                let is_load_vars = line.flag("load_vars");
+                line.end()?;
+                self.write(
+                    &Action::GetUrl2 {
+                        send_vars_method,
+                        is_target_sprite,
+                        is_load_vars,
+                    },
+                    &mut data,
+                )?;
+            }
+            "GotoFrame" => {
+                let frame = line.number()?;
+                line.end()?;
+                self.write(&Action::GotoFrame(frame), &mut data)?;
+            }
+            "GotoFrame2" => {
+                let set_playing = match line.word()?.as_str() {
+                    "play" => true,
+                    "stop" => false,
+                    word => return Err(line.error(&format!("Expected play or stop, got {}", word))),
+                };
+                let scene_offset = if line.tokens.is_empty() {
+                    0
+                } else {
+                    line.prefixed("scene_offset=")?
+                };
+                line.end()?;
+                self.write(
+                    &Action::GotoFrame2 {
+                        set_playing,
+                        scene_offset,
+                    },
+                    &mut data,
+                )?;
+            }
+            "GotoLabel" => {
+                let label = line.string()?;
+                line.end()?;
+                self.write(&Action::GotoLabel(&label), &mut data)?;
+            }
+            "If" | "Jump" => {
+                let target = line.word()?;
+                line.end()?;
+                let target = if target.starts_with('+') || target.starts_with('-') {
+                    Target::Offset(line.parse(&target)?)
+                } else {
+                    Target::Label(target)
+                };
+                return Ok(Item::Branch {
+                    opcode: if name == "If" {
+                        OpCode::If
+                    } else {
+                        OpCode::Jump
+                    },
+                    target,
+                    line: line.number,
+                });
+            }
+            "Push" => {
+                let values = line.list(Line::value)?;
+                line.end()?;
+                let values = values.iter().map(OwnedValue::as_value).collect();
+                self.write(&Action::Push(values), &mut data)?;
+            }
+            "SetTarget" => {
+                let target = line.string()?;
+                line.end()?;
+                self.write(&Action::SetTarget(&target), &mut data)?;
+            }
+            "StoreRegister" => {
+                let register = line.number()?;
+                line.end()?;
+                self.write(&Action::StoreRegister(register), &mut data)?;
+            }
+            "Try" => {
+                line.punct('{')?;
+                line.end()?;
+                let (try_actions, mut closing) = self.block_until_brace(line.number)?;
+                let mut catch = None;
+                if closing.flag("catch") {
+                    let catch_var = if closing.peek_string() {
+                        OwnedCatchVar::Var(closing.string()?)
+                    } else {
+                        OwnedCatchVar::Register(closing.prefixed("r:")?)
+                    };
+                    closing.punct('{')?;
+                    closing.end()?;
+                    let (catch_actions, next) = self.block_until_brace(closing.number)?;
+                    catch = Some((catch_var, catch_actions));
+                    closing = next;
+                }
+                let mut finally = None;
+                if closing.flag("finally") {
+                    closing.punct('{')?;
+                    closing.end()?;
+                    let (finally_actions, next) = self.block_until_brace(closing.number)?;
+                    finally = Some(finally_actions);
+                    closing = next;
+                }
+                closing.end()?;
+
+                let catch = catch.as_ref().map(|(catch_var, catch_actions)| {
+                    let catch_var = match catch_var {
+                        OwnedCatchVar::Var(name) => CatchVar::Var(name),
+                        OwnedCatchVar::Register(register) => CatchVar::Register(*register),
+                    };
+                    (catch_var, &catch_actions[..])
+                });
+                self.write(
+                    &Action::Try(TryBlock {
+                        try_actions: &try_actions,
+                        catch,
+                        finally: finally.as_deref(),
+                    }),
+                    &mut data,
+                )?;
+            }
+            "WaitForFrame" => {
+                let frame = line.number()?;
+                let num_actions_to_skip = line.prefixed("skip=")?;
+                line.end()?;
+                self.write(
+                    &Action::WaitForFrame {
+                        frame,
+                        num_actions_to_skip,
+                    },
+                    &mut data,
+                )?;
+            }
+            "WaitForFrame2" => {
+                let num_actions_to_skip = line.prefixed("skip=")?;
+                line.end()?;
+                self.write(
+                    &Action::WaitForFrame2 {
+                        num_actions_to_skip,
+                    },
+                    &mut data,
+                )?;
+            }
+            "With" => {
+                line.punct('{')?;
+                line.end()?;
+                let actions = self.inner_block(line.number)?;
+                self.write(&Action::With { actions: &actions }, &mut data)?;
+            }
+            "Unknown" => {
+                let opcode = line.word()?;
+                let opcode = opcode
+                    .strip_prefix("0x")
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| line.error(&format!("Invalid opcode {}", opcode)))?;
+                let mut unknown_data = Vec::new();
+                while !line.tokens.is_empty() {
+                    unknown_data.push(line.hex_byte()?);
+                }
+                if opcode < 0x80 && !unknown_data.is_empty() {
+                    return Err(line.error("Opcodes less than 0x80 can't have data"));
+                }
+                self.write(
+                    &Action::Unknown {
+                        opcode,
+                        data: &unknown_data,
+                    },
+                    &mut data,
+                )?;
+            }
+            _ => {
+                let action = simple_action(name)
+                    .ok_or_else(|| line.error(&format!("Unknown action {}", name)))?;
+                line.end()?;
+                self.write(&action, &mut data)?;
+            }
+        }
+        Ok(Item::Bytes(data))
+    }
+
+    /// Assembles a block that must be closed with a brace on its own.
+    fn inner_block(&mut self, start_line: usize) -> Result<Vec<u8>> {
+        let (data, mut closing) = self.block_until_brace(start_line)?;
+        closing.end()?;
+        Ok(data)
+    }
+
+    /// Assembles a block that must be closed with a brace, and returns what
+    /// follows the brace.
+    fn block_until_brace(&mut self, start_line: usize) -> Result<(Vec<u8>, Line)> {
+        match self.block()? {
+            (data, Some(closing)) => Ok((data, closing)),
+            (_, None) => Err(Error::invalid_data(format!(
+                "Line {}: Block is never closed",
+                start_line
+            ))),
+        }
+    }
+
+    fn write(&self, action: &Action, data: &mut Vec<u8>) -> Result<()> {
+        Writer::new(data, self.version).write_action(action)?;
+        Ok(())
+    }
+
+    fn next_line(&mut self) -> Result<Option<Line>> {
+        let text = match self.lines.next() {
+            Some(text) => text,
+            None => return Ok(None),
+        };
+        self.line_number += 1;
+        let mut line = Line {
+            number: self.line_number,
+            tokens: VecDeque::new(),
+        };
+        let mut chars = text.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            match c {
+                ';' => break,
+                c if c.is_whitespace() => {
+                    chars.next();
+                }
+                ',' | '(' | ')' | '{' | '}' => {
+                    chars.next();
+                    line.tokens.push_back(Token::Punct(c));
+                }
+                '"' => {
+                    chars.next();
+                    let mut s = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some('\\') => s.push(match chars.next() {
+                                Some('"') => '"',
+                                Some('\\') => '\\',
+                                Some('n') => '\n',
+                                Some('r') => '\r',
+                                Some('t') => '\t',
+                                Some('u') => {
+                                    let mut hex = String::new();
+                                    if chars.next() != Some('{') {
+                                        return Err(line.error("Invalid escape"));
+                                    }
+                                    loop {
+                                        match chars.next() {
+                                            Some('}') => break,
+                                            Some(c) => hex.push(c),
+                                            None => return Err(line.error("Invalid escape")),
+                                        }
+                                    }
+                                    u32::from_str_radix(&hex, 16)
+                                        .ok()
+                                        .and_then(std::char::from_u32)
+                                        .ok_or_else(|| line.error("Invalid escape"))?
+                                }
+                                _ => return Err(line.error("Invalid escape")),
+                            }),
+                            Some(c) => s.push(c),
+                            None => return Err(line.error("Unterminated string")),
+                        }
+                    }
+                    line.tokens.push_back(Token::Str(s));
+                }
+                _ => {
+                    let mut word = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if c.is_whitespace() || ";,(){}\"".contains(c) {
+                            break;
+                        }
+                        word.push(c);
+                        chars.next();
+                    }
+                    line.tokens.push_back(Token::Word(word));
+                }
+            }
+        }
+        Ok(Some(line))
+    }
+}
+
+impl Line {
+    fn error(&self, message: &str) -> Error {
+        Error::invalid_data(format!("Line {}: {}", self.number, message))
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        self.tokens
+            .pop_front()
+            .ok_or_else(|| self.error("Unexpected end of line"))
+    }
+
+    fn end(&mut self) -> Result<()> {
+        match self.tokens.front() {
+            None => Ok(()),
+            Some(token) => Err(self.error(&format!("Unexpected {:?}", token))),
+        }
+    }
+
+    fn word(&mut self) -> Result<String> {
+        match self.next()? {
+            Token::Word(word) => Ok(word),
+            token => Err(self.error(&format!("Expected a word, got {:?}", token))),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        match self.next()? {
+            Token::Str(s) => Ok(s),
+            token => Err(self.error(&format!("Expected a string, got {:?}", token))),
+        }
+    }
+
+    fn punct(&mut self, expected: char) -> Result<()> {
+        match self.next()? {
+            Token::Punct(c) if c == expected => Ok(()),
+            token => Err(self.error(&format!("Expected {}, got {:?}", expected, token))),
+        }
+    }
+
+    fn peek_punct(&self, expected: char) -> bool {
+        self.tokens.front() == Some(&Token::Punct(expected))
+    }
+
+    fn peek_string(&self) -> bool {
+        matches!(self.tokens.front(), Some(Token::Str(_)))
+    }
+
+    /// Takes a word if it's the given flag.
+    fn flag(&mut self, flag: &str) -> bool {
+        if let Some(Token::Word(word)) = self.tokens.front() {
+            if word == flag {
+                self.tokens.pop_front();
+                return true;
+            }
+        }
+        false
+    }
+
+    fn parse<T: std::str::FromStr>(&self, text: &str) -> Result<T> {
+        text.parse()
+            .map_err(|_| self.error(&format!("Invalid number {}", text)))
+    }
+
+    fn number<T: std::str::FromStr>(&mut self) -> Result<T> {
+        let word = self.word()?;
+        self.parse(&word)
+    }
+
+    /// Reads a number written after a prefix, such as `r:1`.
+    fn prefixed<T: std::str::FromStr>(&mut self, prefix: &str) -> Result<T> {
+        let word = self.word()?;
+        match word.strip_prefix(prefix) {
+            Some(number) => self.parse(number),
+            None => Err(self.error(&format!("Expected {}, got {}", prefix, word))),
+        }
+    }
+
+    fn hex_byte(&mut self) -> Result<u8> {
+        let word = self.word()?;
+        u8::from_str_radix(&word, 16).map_err(|_| self.error(&format!("Invalid byte {}", word)))
+    }
+
+    /// Reads items separated by commas up to the end of the list, which ends
+    /// at the end of the line or at a `)`. The list may be empty.
+    fn list<T>(&mut self, mut item: impl FnMut(&mut Self) -> Result<T>) -> Result<Vec<T>> {
+        if self.tokens.is_empty() || self.peek_punct(')') {
+            return Ok(vec![]);
+        }
+        let mut items = vec![item(self)?];
+        while self.peek_punct(',') {
+            self.tokens.pop_front();
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    fn value(&mut self) -> Result<OwnedValue> {
+        let word = match self.next()? {
+            Token::Str(s) => return Ok(OwnedValue::Str(s)),
+            Token::Word(word) => word,
+            token => return Err(self.error(&format!("Expected a value, got {:?}", token))),
+        };
+        let value = match word.as_str() {
+            "undefined" => OwnedValue::Undefined,
+            "null" => OwnedValue::Null,
+            "true" => OwnedValue::Bool(true),
+            "false" => OwnedValue::Bool(false),
+            _ => {
+                if let Some(register) = word.strip_prefix("r:") {
+                    OwnedValue::Register(self.parse(register)?)
+                } else if let Some(index) = word.strip_prefix("c:") {
+                    OwnedValue::ConstantPool(self.parse(index)?)
+                } else if let Some(number) = word.strip_suffix('f') {
+                    OwnedValue::Float(
+                        parse_number(number)
+                            .ok_or_else(|| self.error(&format!("Invalid number {}", word)))?
+                            as f32,
+                    )
+                } else if word.contains(&['.', 'e', 'N', 'I'][..]) {
+                    OwnedValue::Double(
+                        parse_number(&word)
+                            .ok_or_else(|| self.error(&format!("Invalid number {}", word)))?,
+                    )
+                } else {
+                    OwnedValue::Int(self.parse(&word)?)
+                }
+            }
+        };
+        Ok(value)
+    }
+}
+
+/// Parses a number, with the spellings of infinity and NaN that
+/// `disassemble` uses.
+fn parse_number(text: &str) -> Option<f64> {
+    match text {
+        "NaN" => Some(f64::NAN),
+        "Infinity" => Some(f64::INFINITY),
+        "-Infinity" => Some(f64::NEG_INFINITY),
+        _ if text.contains(|c: char| c.is_ascii_alphabetic() && c != 'e') => None,
+        _ => text.parse().ok(),
+    }
+}
+
+/// A pushed value with its string owned by the assembler.
+enum OwnedValue {
+    Undefined,
+    Null,
+    Bool(bool),
+    Int(i32),
+    Float(f32),
+    Double(f64),
+    Str(String),
+    Register(u8),
+    ConstantPool(u16),
+}
+
+impl OwnedValue {
+    fn as_value(&self) -> Value<'_> {
+        match *self {
+            OwnedValue::Undefined => Value::Undefined,
+            OwnedValue::Null => Value::Null,
+            OwnedValue::Bool(v) => Value::Bool(v),
+            OwnedValue::Int(v) => Value::Int(v),
+            OwnedValue::Float(v) => Value::Float(v),
+            OwnedValue::Double(v) => Value::Double(v),
+            OwnedValue::Str(ref v) => Value::Str(v),
+            OwnedValue::Register(v) => Value::Register(v),
+            OwnedValue::ConstantPool(v) => Value::ConstantPool(v),
+        }
+    }
+}
+
+/// The variable a `Try` block catches into, with its name owned by the
+/// assembler.
+enum OwnedCatchVar {
+    Var(String),
+    Register(u8),
+}
+
+/// Looks up an action without operands by name.
+fn simple_action(name: &str) -> Option<Action<'static>> {
+    let action = match name {
+        "Add" => Action::Add,
+        "Add2" => Action::Add2,
+        "And" => Action::And,
+        "AsciiToChar" => Action::AsciiToChar,
+        "BitAnd" => Action::BitAnd,
+        "BitLShift" => Action::BitLShift,
+        "BitOr" => Action::BitOr,
+        "BitRShift" => Action::BitRShift,
+        "BitURShift" => Action::BitURShift,
+        "BitXor" => Action::BitXor,
+        "Call" => Action::Call,
+        "CallFunction" => Action::CallFunction,
+        "CallMethod" => Action::CallMethod,
+        "CastOp" => Action::CastOp,
+        "CharToAscii" => Action::CharToAscii,
+        "CloneSprite" => Action::CloneSprite,
+        "Decrement" => Action::Decrement,
+        "DefineLocal" => Action::DefineLocal,
+        "DefineLocal2" => Action::DefineLocal2,
+        "Delete" => Action::Delete,
+        "Delete2" => Action::Delete2,
+        "Divide" => Action::Divide,
+        "EndDrag" => Action::EndDrag,
+        "Enumerate" => Action::Enumerate,
+        "Enumerate2" => Action::Enumerate2,
+        "Equals" => Action::Equals,
+        "Equals2" => Action::Equals2,
+        "Extends" => Action::Extends,
+        "GetMember" => Action::GetMember,
+        "GetProperty" => Action::GetProperty,
+        "GetTime" => Action::GetTime,
+        "GetVariable" => Action::GetVariable,
+        "Greater" => Action::Greater,
+        "ImplementsOp" => Action::ImplementsOp,
+        "Increment" => Action::Increment,
+        "InitArray" => Action::InitArray,
+        "InitObject" => Action::InitObject,
+        "InstanceOf" => Action::InstanceOf,
+        "Less" => Action::Less,
+        "Less2" => Action::Less2,
+        "MBAsciiToChar" => Action::MBAsciiToChar,
+        "MBCharToAscii" => Action::MBCharToAscii,
+        "MBStringExtract" => Action::MBStringExtract,
+        "MBStringLength" => Action::MBStringLength,
+        "Modulo" => Action::Modulo,
+        "Multiply" => Action::Multiply,
+        "NewMethod" => Action::NewMethod,
+        "NewObject" => Action::NewObject,
+        "NextFrame" => Action::NextFrame,
+        "Not" => Action::Not,
+        "Or" => Action::Or,
+        "Play" => Action::Play,
+        "Pop" => Action::Pop,
+        "PreviousFrame" => Action::PreviousFrame,
+        "PushDuplicate" => Action::PushDuplicate,
+        "RandomNumber" => Action::RandomNumber,
+        "RemoveSprite" => Action::RemoveSprite,
+        "Return" => Action::Return,
+        "SetMember" => Action::SetMember,
+        "SetProperty" => Action::SetProperty,
+        "SetTarget2" => Action::SetTarget2,
+        "SetVariable" => Action::SetVariable,
+        "StackSwap" => Action::StackSwap,
+        "StartDrag" => Action::StartDrag,
+        "Stop" => Action::Stop,
+        "StopSounds" => Action::StopSounds,
+        "StrictEquals" => Action::StrictEquals,
+        "StringAdd" => Action::StringAdd,
+        "StringEquals" => Action::StringEquals,
+        "StringExtract" => Action::StringExtract,
+        "StringGreater" => Action::StringGreater,
+        "StringLength" => Action::StringLength,
+        "StringLess" => Action::StringLess,
+        "Subtract" => Action::Subtract,
+        "TargetPath" => Action::TargetPath,
+        "Throw" => Action::Throw,
+        "ToInteger" => Action::ToInteger,
+        "ToNumber" => Action::ToNumber,
+        "ToString" => Action::ToString,
+        "ToggleQuality" => Action::ToggleQuality,
+        "Trace" => Action::Trace,
+        "TypeOf" => Action::TypeOf,
+        _ => return None,
+    };
+    Some(action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::disassemble::disassemble;
+    use crate::test_data;
+
+    #[test]
+    fn round_trip_actions() {
+        for (swf_version, action, bytes) in test_data::avm1_tests() {
+            let text = disassemble(&bytes, swf_version);
+            match assemble(&text, swf_version) {
+                Ok(assembled) if assembled == bytes => (),
+                result => panic!(
+                    "Action didn't survive a round trip.\nAction:\n{:?}\n\nText:\n{}\nAssembled:\n{:?}",
+                    action, text, result
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn labels_and_nested_blocks() {
+        let text = r#"ConstantPool "i", "trace"
+DefineFunction2 "count" (r:1 "n") registers=2 suppress_super preload_this {
+    Push 0
+    StoreRegister 1
+    Pop
+loop:
+    Push r:1, 10.0
+    Less2
+    Not
+    If done
+    Push r:1, c:0 ; c:0 = "i"
+    Trace
+    Jump loop
+done:
+}
+Push "tab\tquote\" \u{1}", -1.5f, NaN, -Infinity, undefined, null, true
+End
+"#;
+        let bytes = assemble(text, 7).unwrap();
+        let disassembled = disassemble(&bytes, 7);
+        assert_eq!(assemble(&disassembled, 7).unwrap(), bytes);
+        assert!(disassembled.contains("    If label2\n"));
+        assert!(disassembled.contains("    Jump label1\n"));
+        assert!(disassembled.contains("Push r:1, c:0 ; c:0 = \"i\"\n"));
+    }
+
+    #[test]
+    fn empty_lists() {
+        let text = "ConstantPool\nPush\nDefineFunction \"f\" () {\n}\n";
+        let bytes = assemble(text, 5).unwrap();
+        assert_eq!(
+            bytes,
+            [0x88, 2, 0, 0, 0, 0x96, 0, 0, 0x9B, 6, 0, b'f', 0, 0, 0, 0, 0]
+        );
+        assert_eq!(disassemble(&bytes, 5), text);
+    }
+
+    #[test]
+    fn odd_encodings_are_kept() {
+        // A constant pushed with a 16-bit index, followed by a truncated action.
+        let bytes = [0x96, 0x03, 0x00, 0x09, 0x01, 0x00, 0x07, 0x96, 0x05];
+        let text = disassemble(&bytes, 5);
+        assert_eq!(text, "Bytes 96 03 00 09 01 00\nStop\nBytes 96 05\n");
+        assert_eq!(assemble(&text, 5).unwrap(), bytes);
+    }
+
+    #[test]
+    fn assemble_errors() {
+        assert!(assemble("Jump nowhere", 5).is_err());
+        assert!(assemble("With {\nStop", 5).is_err());
+        assert!(assemble("Stop\n}", 5).is_err());
+        assert!(assemble("Push \"unterminated", 5).is_err());
+        assert!(assemble("Florp", 5).is_err());
+    }
+}
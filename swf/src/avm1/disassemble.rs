@@ -0,0 +1,377 @@
+//! A human-readable text form of AVM1 bytecode.
+//!
+//! Each action is written on its own line, with its operands after it.
+//! Branch targets get labels, pushed constants are resolved in comments, and
+//! the code inside functions, `With` and `Try` blocks is indented in braces.
+//! Anything that wouldn't be written back to the same bytes is kept as raw
+//! `Bytes`, so `assemble` always reproduces the original bytecode.
+
+use crate::avm1::read::Reader;
+use crate::avm1::types::*;
+use crate::avm1::write::Writer;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Disassembles a block of AVM1 bytecode, such as the contents of a
+/// `DoAction` tag.
+pub fn disassemble(data: &[u8], version: u8) -> String {
+    let mut disassembler = Disassembler {
+        version,
+        output: String::new(),
+        constant_pool: Vec::new(),
+    };
+    disassembler.block(data, 0);
+    disassembler.output
+}
+
+/// An action read from a block, or bytes that couldn't be read as one.
+enum Item<'a> {
+    Action(Action<'a>),
+    End,
+    Bytes(&'a [u8]),
+}
+
+struct Disassembler<'a> {
+    version: u8,
+    output: String,
+
+    /// The constants of the last `ConstantPool`, used to resolve pushes.
+    constant_pool: Vec<&'a str>,
+}
+
+impl<'a> Disassembler<'a> {
+    fn block(&mut self, data: &'a [u8], indent: usize) {
+        let items = self.read_items(data);
+
+        // Label every branch target that starts an action in this block.
+        let mut labels = BTreeMap::new();
+        for (_, end, item) in &items {
+            if let Item::Action(Action::If { offset }) | Item::Action(Action::Jump { offset }) =
+                item
+            {
+                let target = *end as isize + isize::from(*offset);
+                let is_action = items.iter().any(|(start, _, _)| *start as isize == target);
+                if is_action || target == data.len() as isize {
+                    labels.insert(target as usize, String::new());
+                }
+            }
+        }
+        for (i, name) in labels.values_mut().enumerate() {
+            *name = format!("label{}", i + 1);
+        }
+
+        for (start, end, item) in &items {
+            if let Some(label) = labels.get(start) {
+                self.line(indent, &format!("{}:", label));
+            }
+            match item {
+                Item::Action(action) => self.action(action, *end, &labels, indent),
+                Item::End => self.line(indent, "End"),
+                Item::Bytes(bytes) => {
+                    let mut line = "Bytes".to_string();
+                    for byte in *bytes {
+                        let _ = write!(line, " {:02x}", byte);
+                    }
+                    self.line(indent, &line);
+                }
+            }
+        }
+        if let Some(label) = labels.get(&data.len()) {
+            self.line(indent, &format!("{}:", label));
+        }
+    }
+
+    /// Reads the actions of a block with their start and end positions.
+    fn read_items(&self, data: &'a [u8]) -> Vec<(usize, usize, Item<'a>)> {
+        let mut items = Vec::new();
+        let mut reader = Reader::new(data, self.version);
+        while reader.pos() < data.len() {
+            let start = reader.pos();
+            let item = match reader.read_action() {
+                Ok(Some(action)) => {
+                    let bytes = &data[start..reader.pos()];
+                    if self.is_exact(&action, bytes) {
+                        Item::Action(action)
+                    } else {
+                        Item::Bytes(bytes)
+                    }
+                }
+                Ok(None) => Item::End,
+                Err(_) => {
+                    items.push((start, data.len(), Item::Bytes(&data[start..])));
+                    break;
+                }
+            };
+            items.push((start, reader.pos(), item));
+        }
+        items
+    }
+
+    /// Whether an action's text form would be assembled back to `bytes`.
+    fn is_exact(&self, action: &Action, bytes: &[u8]) -> bool {
+        // Only the usual NaN survives being written as text.
+        if let Action::Push(values) = action {
+            let is_odd_nan = |value: &Value| match *value {
+                Value::Float(v) => v.is_nan() && v.to_bits() != f32::NAN.to_bits(),
+                Value::Double(v) => v.is_nan() && v.to_bits() != f64::NAN.to_bits(),
+                _ => false,
+            };
+            if values.iter().any(is_odd_nan) {
+                return false;
+            }
+        }
+        let mut written = Vec::with_capacity(bytes.len());
+        Writer::new(&mut written, self.version)
+            .write_action(action)
+            .is_ok()
+            && written == bytes
+    }
+
+    fn action(
+        &mut self,
+        action: &Action<'a>,
+        end: usize,
+        labels: &BTreeMap<usize, String>,
+        indent: usize,
+    ) {
+        let branch_target = |offset: i16| {
+            let target = end as isize + isize::from(offset);
+            if target >= 0 {
+                if let Some(label) = labels.get(&(target as usize)) {
+                    return label.clone();
+                }
+            }
+            format!("{:+}", offset)
+        };
+
+        match action {
+            Action::ConstantPool(constants) => {
+                self.constant_pool = constants.clone();
+                let constants: Vec<_> = constants.iter().map(|c| quote(c)).collect();
+                self.line(indent, &operands("ConstantPool", &constants));
+            }
+            Action::DefineFunction {
+                name,
+                params,
+                actions,
+            } => {
+                let params: Vec<_> = params.iter().map(|p| quote(p)).collect();
+                self.line(
+                    indent,
+                    &format!("DefineFunction {} ({}) {{", quote(name), params.join(", ")),
+                );
+                self.block(actions, indent + 1);
+                self.line(indent, "}");
+            }
+            Action::DefineFunction2(function) => {
+                let params: Vec<_> = function
+                    .params
+                    .iter()
+                    .map(|param| match param.register_index {
+                        Some(register) => format!("r:{} {}", register, quote(param.name)),
+                        None => quote(param.name),
+                    })
+                    .collect();
+                let mut line = format!(
+                    "DefineFunction2 {} ({}) registers={}",
+                    quote(function.name),
+                    params.join(", "),
+                    function.register_count
+                );
+                for &(flag, is_set) in &[
+                    ("preload_parent", function.preload_parent),
+                    ("preload_root", function.preload_root),
+                    ("suppress_super", function.suppress_super),
+                    ("preload_super", function.preload_super),
+                    ("suppress_arguments", function.suppress_arguments),
+                    ("preload_arguments", function.preload_arguments),
+                    ("suppress_this", function.suppress_this),
+                    ("preload_this", function.preload_this),
+                    ("preload_global", function.preload_global),
+                ] {
+                    if is_set {
+                        line.push(' ');
+                        line.push_str(flag);
+                    }
+                }
+                line.push_str(" {");
+                self.line(indent, &line);
+                self.block(function.actions, indent + 1);
+                self.line(indent, "}");
+            }
+            Action::GetUrl { url, target } => {
+                self.line(indent, &format!("GetUrl {}, {}", quote(url), quote(target)))
+            }
+            Action::GetUrl2 {
+                send_vars_method,
+                is_target_sprite,
+                is_load_vars,
+            } => {
+                let mut line = format!(
+                    "GetUrl2 {}",
+                    match send_vars_method {
+                        SendVarsMethod::None => "none",
+                        SendVarsMethod::Get => "get",
+                        SendVarsMethod::Post => "post",
+                    }
+                );
+                if *is_target_sprite {
+                    line.push_str(" target_sprite");
+                }
+                if *is_load_vars {
+                    line.push_str(" load_vars");
+                }
+                self.line(indent, &line);
+            }
+            Action::GotoFrame(frame) => self.line(indent, &format!("GotoFrame {}", frame)),
+            Action::GotoFrame2 {
+                set_playing,
+                scene_offset,
+            } => {
+                let mut line = format!("GotoFrame2 {}", if *set_playing { "play" } else { "stop" });
+                if *scene_offset != 0 {
+                    let _ = write!(line, " scene_offset={}", scene_offset);
+                }
+                self.line(indent, &line);
+            }
+            Action::GotoLabel(label) => self.line(indent, &format!("GotoLabel {}", quote(label))),
+            Action::If { offset } => self.line(indent, &format!("If {}", branch_target(*offset))),
+            Action::Jump { offset } => {
+                self.line(indent, &format!("Jump {}", branch_target(*offset)))
+            }
+            Action::Push(values) => {
+                let mut constants = Vec::new();
+                let values: Vec<_> = values
+                    .iter()
+                    .map(|value| {
+                        if let Value::ConstantPool(i) = value {
+                            if let Some(constant) = self.constant_pool.get(usize::from(*i)) {
+                                constants.push(format!("c:{} = {}", i, quote(constant)));
+                            }
+                        }
+                        format_value(value)
+                    })
+                    .collect();
+                let mut line = operands("Push", &values);
+                if !constants.is_empty() {
+                    let _ = write!(line, " ; {}", constants.join(", "));
+                }
+                self.line(indent, &line);
+            }
+            Action::SetTarget(target) => self.line(indent, &format!("SetTarget {}", quote(target))),
+            Action::StoreRegister(register) => {
+                self.line(indent, &format!("StoreRegister {}", register))
+            }
+            Action::Try(try_block) => {
+                self.line(indent, "Try {");
+                self.block(try_block.try_actions, indent + 1);
+                if let Some((catch_var, catch_actions)) = &try_block.catch {
+                    let catch_var = match catch_var {
+                        CatchVar::Var(name) => quote(name),
+                        CatchVar::Register(register) => format!("r:{}", register),
+                    };
+                    self.line(indent, &format!("}} catch {} {{", catch_var));
+                    self.block(catch_actions, indent + 1);
+                }
+                if let Some(finally_actions) = try_block.finally {
+                    self.line(indent, "} finally {");
+                    self.block(finally_actions, indent + 1);
+                }
+                self.line(indent, "}");
+            }
+            Action::WaitForFrame {
+                frame,
+                num_actions_to_skip,
+            } => self.line(
+                indent,
+                &format!("WaitForFrame {} skip={}", frame, num_actions_to_skip),
+            ),
+            Action::WaitForFrame2 {
+                num_actions_to_skip,
+            } => self.line(
+                indent,
+                &format!("WaitForFrame2 skip={}", num_actions_to_skip),
+            ),
+            Action::With { actions } => {
+                self.line(indent, "With {");
+                self.block(actions, indent + 1);
+                self.line(indent, "}");
+            }
+            Action::Unknown { opcode, data } => {
+                let mut line = format!("Unknown 0x{:02x}", opcode);
+                for byte in *data {
+                    let _ = write!(line, " {:02x}", byte);
+                }
+                self.line(indent, &line);
+            }
+            // The rest of the actions have no operands.
+            _ => self.line(indent, &format!("{:?}", action)),
+        }
+    }
+
+    fn line(&mut self, indent: usize, text: &str) {
+        for _ in 0..indent {
+            self.output.push_str("    ");
+        }
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+}
+
+/// An action followed by its comma separated operands, if it has any.
+fn operands(name: &str, operands: &[String]) -> String {
+    if operands.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} {}", name, operands.join(", "))
+    }
+}
+
+fn format_value(value: &Value) -> String {
+    match *value {
+        Value::Undefined => "undefined".to_string(),
+        Value::Null => "null".to_string(),
+        Value::Bool(v) => v.to_string(),
+        Value::Int(v) => v.to_string(),
+        Value::Float(v) => format!("{}f", format_number(f64::from(v), format!("{:?}", v))),
+        Value::Double(v) => format_number(v, format!("{:?}", v)),
+        Value::Str(v) => quote(v),
+        Value::Register(v) => format!("r:{}", v),
+        Value::ConstantPool(v) => format!("c:{}", v),
+    }
+}
+
+/// Spells out the numbers that Rust prints in a way that can't be told apart
+/// from other values.
+fn format_number(v: f64, debug: String) -> String {
+    if v.is_nan() {
+        "NaN".to_string()
+    } else if v == f64::INFINITY {
+        "Infinity".to_string()
+    } else if v == f64::NEG_INFINITY {
+        "-Infinity".to_string()
+    } else {
+        debug
+    }
+}
+
+/// Writes a string as a quoted literal.
+pub(crate) fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{{{:x}}}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
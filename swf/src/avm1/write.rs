@@ -160,11 +160,11 @@ impl<W: Write> Writer<W> {
             } => {
                 if scene_offset != 0 {
                     self.write_action_header(OpCode::GotoFrame2, 3)?;
-                    self.write_u8(if set_playing { 0b11 } else { 0b01 })?;
+                    self.write_u8(if set_playing { 0b11 } else { 0b10 })?;
                     self.write_u16(scene_offset)?;
                 } else {
                     self.write_action_header(OpCode::GotoFrame2, 1)?;
-                    self.write_u8(if set_playing { 0b10 } else { 0b00 })?;
+                    self.write_u8(if set_playing { 0b01 } else { 0b00 })?;
                 }
             }
             Action::GotoLabel(ref label) => {
@@ -280,13 +280,12 @@ impl<W: Write> Writer<W> {
                         0
                     };
                 }
-                let len = 7
-                    + action_buf.len()
-                    + if let Some((CatchVar::Var(ref name), _)) = try_block.catch {
-                        name.len() + 1
-                    } else {
-                        1
-                    };
+                // The action's length doesn't include the blocks of code.
+                let len = 7 + if let Some((CatchVar::Var(ref name), _)) = try_block.catch {
+                    name.len() + 1
+                } else {
+                    1
+                };
                 self.write_action_header(OpCode::Try, len)?;
                 self.write_u8(
                     if let Some((CatchVar::Register(_), _)) = try_block.catch {
@@ -302,7 +301,8 @@ impl<W: Write> Writer<W> {
                 match try_block.catch {
                     Some((CatchVar::Var(ref name), _)) => self.write_c_string(name)?,
                     Some((CatchVar::Register(i), _)) => self.write_u8(i)?,
-                    _ => (),
+                    // The catch variable is always present, so leave its name empty.
+                    None => self.write_u8(0)?,
                 }
                 self.inner.write_all(&action_buf)?;
             }
@@ -322,7 +322,9 @@ impl<W: Write> Writer<W> {
                 self.write_u8(num_actions_to_skip)?;
             }
             Action::With { ref actions } => {
-                self.write_action_header(OpCode::With, actions.len())?;
+                // The action's length doesn't include the block of code.
+                self.write_action_header(OpCode::With, 2)?;
+                self.write_u16(actions.len() as u16)?;
                 self.inner.write_all(&actions)?;
             }
             Action::Unknown { opcode, ref data } => {
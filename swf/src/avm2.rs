@@ -1,3 +1,4 @@
+pub mod disassemble;
 pub mod read;
 pub mod types;
 pub mod write;
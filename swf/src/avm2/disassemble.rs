@@ -0,0 +1,653 @@
+//! A human-readable listing of an ABC file.
+//!
+//! Names are resolved through the constant pool, so scripts, classes and
+//! their traits are printed with qualified names and types. Method bodies are
+//! disassembled one instruction per line, prefixed with their byte offset,
+//! which branch targets and exception ranges refer to.
+
+use crate::avm1::disassemble::quote;
+use crate::avm2::read::Reader;
+use crate::avm2::types::*;
+use crate::read::SwfRead;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io::Cursor;
+
+/// Lists the scripts, classes and methods of an ABC file.
+pub fn disassemble(abc: &AbcFile) -> String {
+    let mut disassembler = Disassembler {
+        abc,
+        output: String::new(),
+    };
+    disassembler.file();
+    disassembler.output
+}
+
+/// Writes a multiname with its namespaces, such as `flash.display::Sprite`.
+///
+/// Names that are only known at runtime are written as `<runtime>`, and
+/// attribute names start with `@`. Index 0 is the any name, `*`.
+pub fn multiname_to_string(pool: &ConstantPool, index: &Index<Multiname>) -> String {
+    let multiname = match pool_get(&pool.multinames, index.0) {
+        Some(multiname) => multiname,
+        None => return "*".to_string(),
+    };
+    let name = |index: &Index<String>| {
+        pool_get(&pool.strings, index.0)
+            .cloned()
+            .unwrap_or_else(|| "*".to_string())
+    };
+    let namespace_set = |index: &Index<NamespaceSet>| {
+        let namespaces: Vec<_> = pool_get(&pool.namespace_sets, index.0)
+            .map(|set| {
+                set.iter()
+                    .map(|namespace| namespace_to_string(pool, namespace))
+                    .collect()
+            })
+            .unwrap_or_default();
+        format!("[{}]", namespaces.join(", "))
+    };
+    let qualify = |namespace: String, name: String| {
+        if namespace.is_empty() {
+            name
+        } else {
+            format!("{}::{}", namespace, name)
+        }
+    };
+    let (is_attribute, text) = match multiname {
+        Multiname::QName { namespace, name: n } => (
+            false,
+            qualify(namespace_to_string(pool, namespace), name(n)),
+        ),
+        Multiname::QNameA { namespace, name: n } => {
+            (true, qualify(namespace_to_string(pool, namespace), name(n)))
+        }
+        Multiname::RTQName { name: n } => (false, format!("<runtime>::{}", name(n))),
+        Multiname::RTQNameA { name: n } => (true, format!("<runtime>::{}", name(n))),
+        Multiname::RTQNameL => (false, "<runtime>::<runtime>".to_string()),
+        Multiname::RTQNameLA => (true, "<runtime>::<runtime>".to_string()),
+        Multiname::Multiname {
+            namespace_set: set,
+            name: n,
+        } => (false, format!("{}::{}", namespace_set(set), name(n))),
+        Multiname::MultinameA {
+            namespace_set: set,
+            name: n,
+        } => (true, format!("{}::{}", namespace_set(set), name(n))),
+        Multiname::MultinameL { namespace_set: set } => {
+            (false, format!("{}::<runtime>", namespace_set(set)))
+        }
+        Multiname::MultinameLA { namespace_set: set } => {
+            (true, format!("{}::<runtime>", namespace_set(set)))
+        }
+    };
+    if is_attribute {
+        format!("@{}", text)
+    } else {
+        text
+    }
+}
+
+/// Writes a namespace. Packages are written as their name alone, which is
+/// empty for the top level package, and other kinds of namespace as their
+/// kind followed by their URI, such as `private` or `protected(Foo)`.
+pub fn namespace_to_string(pool: &ConstantPool, index: &Index<Namespace>) -> String {
+    let (kind, uri) = match pool_get(&pool.namespaces, index.0) {
+        Some(Namespace::Package(uri)) => {
+            return pool_get(&pool.strings, uri.0).cloned().unwrap_or_default()
+        }
+        Some(Namespace::Namespace(uri)) => ("namespace", uri),
+        Some(Namespace::PackageInternal(uri)) => ("internal", uri),
+        Some(Namespace::Protected(uri)) => ("protected", uri),
+        Some(Namespace::Explicit(uri)) => ("explicit", uri),
+        Some(Namespace::StaticProtected(uri)) => ("static_protected", uri),
+        Some(Namespace::Private(uri)) => ("private", uri),
+        None => return "*".to_string(),
+    };
+    match pool_get(&pool.strings, uri.0) {
+        Some(uri) if !uri.is_empty() => format!("{}({})", kind, uri),
+        _ => kind.to_string(),
+    }
+}
+
+/// Looks up an entry of the constant pool. Index 0 is reserved and isn't
+/// stored in the pool.
+fn pool_get<T>(items: &[T], index: u32) -> Option<&T> {
+    index.checked_sub(1).and_then(|i| items.get(i as usize))
+}
+
+struct Disassembler<'a> {
+    abc: &'a AbcFile,
+    output: String,
+}
+
+impl<'a> Disassembler<'a> {
+    fn file(&mut self) {
+        let abc = self.abc;
+        self.line(
+            0,
+            &format!("; ABC version {}.{}", abc.major_version, abc.minor_version),
+        );
+
+        for (i, script) in abc.scripts.iter().enumerate() {
+            self.line(0, "");
+            self.line(0, &format!("script {}", i));
+            self.line(1, &format!("init method {}", script.init_method.0));
+            self.traits(&script.traits, false);
+        }
+
+        for (i, (instance, class)) in abc.instances.iter().zip(&abc.classes).enumerate() {
+            let mut line = format!("class {} {}", i, self.multiname(&instance.name));
+            if instance.super_name.0 != 0 {
+                let _ = write!(line, " extends {}", self.multiname(&instance.super_name));
+            }
+            if !instance.interfaces.is_empty() {
+                let interfaces: Vec<_> = instance
+                    .interfaces
+                    .iter()
+                    .map(|interface| self.multiname(interface))
+                    .collect();
+                let _ = write!(line, " implements {}", interfaces.join(", "));
+            }
+            for &(flag, is_set) in &[
+                ("sealed", instance.is_sealed),
+                ("final", instance.is_final),
+                ("interface", instance.is_interface),
+            ] {
+                if is_set {
+                    line.push(' ');
+                    line.push_str(flag);
+                }
+            }
+            self.line(0, "");
+            self.line(0, &line);
+            if let Some(namespace) = &instance.protected_namespace {
+                let namespace = namespace_to_string(&abc.constant_pool, namespace);
+                self.line(1, &format!("protected namespace {}", namespace));
+            }
+            self.line(1, &format!("init method {}", instance.init_method.0));
+            self.line(1, &format!("static init method {}", class.init_method.0));
+            self.traits(&instance.traits, false);
+            self.traits(&class.traits, true);
+        }
+
+        let bodies: HashMap<u32, &MethodBody> = abc
+            .method_bodies
+            .iter()
+            .map(|body| (body.method.0, body))
+            .collect();
+        for (i, method) in abc.methods.iter().enumerate() {
+            self.line(0, "");
+            self.method(i as u32, method, bodies.get(&(i as u32)).copied());
+        }
+    }
+
+    fn traits(&mut self, traits: &[Trait], is_static: bool) {
+        for t in traits {
+            for metadata in &t.metadata {
+                let line = self.metadata(metadata);
+                self.line(1, &line);
+            }
+
+            let mut line = String::new();
+            for &(modifier, is_set) in &[
+                ("static ", is_static),
+                ("final ", t.is_final),
+                ("override ", t.is_override),
+            ] {
+                if is_set {
+                    line.push_str(modifier);
+                }
+            }
+            let name = self.multiname(&t.name);
+            match &t.kind {
+                TraitKind::Slot {
+                    slot_id,
+                    type_name,
+                    value,
+                }
+                | TraitKind::Const {
+                    slot_id,
+                    type_name,
+                    value,
+                } => {
+                    let keyword = if let TraitKind::Slot { .. } = t.kind {
+                        "var"
+                    } else {
+                        "const"
+                    };
+                    let _ = write!(line, "{} {}:{}", keyword, name, self.multiname(type_name));
+                    if let Some(value) = value {
+                        let _ = write!(line, " = {}", self.default_value(value));
+                    }
+                    let _ = write!(line, " slot={}", slot_id);
+                }
+                TraitKind::Method { disp_id, method }
+                | TraitKind::Getter { disp_id, method }
+                | TraitKind::Setter { disp_id, method } => {
+                    let accessor = match t.kind {
+                        TraitKind::Getter { .. } => "get ",
+                        TraitKind::Setter { .. } => "set ",
+                        _ => "",
+                    };
+                    let _ = write!(
+                        line,
+                        "function {}{}{} method={} disp={}",
+                        accessor,
+                        name,
+                        self.signature(method),
+                        method.0,
+                        disp_id
+                    );
+                }
+                TraitKind::Class { slot_id, class } => {
+                    let _ = write!(line, "class {} class={} slot={}", name, class.0, slot_id);
+                }
+                TraitKind::Function { slot_id, function } => {
+                    let _ = write!(
+                        line,
+                        "function {}{} method={} slot={}",
+                        name,
+                        self.signature(function),
+                        function.0,
+                        slot_id
+                    );
+                }
+            }
+            self.line(1, &line);
+        }
+    }
+
+    fn metadata(&self, index: &Index<Metadata>) -> String {
+        let metadata = match self.abc.metadata.get(index.0 as usize) {
+            Some(metadata) => metadata,
+            None => return format!("[<invalid metadata {}>]", index.0),
+        };
+        let items: Vec<_> = metadata
+            .items
+            .iter()
+            .map(|item| {
+                let value = quote(self.string(&item.value));
+                if item.key.0 == 0 {
+                    value
+                } else {
+                    format!("{}={}", self.string(&item.key), value)
+                }
+            })
+            .collect();
+        format!("[{}({})]", self.string(&metadata.name), items.join(", "))
+    }
+
+    /// Writes the parameters and return type of a method, such as
+    /// `(int, name:String = "x", ...):void`.
+    fn signature(&self, index: &Index<Method>) -> String {
+        let method = match self.abc.methods.get(index.0 as usize) {
+            Some(method) => method,
+            None => return "(<invalid method>)".to_string(),
+        };
+        let mut params: Vec<_> = method
+            .params
+            .iter()
+            .map(|param| {
+                let mut text = self.multiname(&param.kind);
+                if let Some(name) = &param.name {
+                    text = format!("{}:{}", self.string(name), text);
+                }
+                if let Some(value) = &param.default_value {
+                    let _ = write!(text, " = {}", self.default_value(value));
+                }
+                text
+            })
+            .collect();
+        if method.needs_rest {
+            params.push("...".to_string());
+        }
+        format!(
+            "({}):{}",
+            params.join(", "),
+            self.multiname(&method.return_type)
+        )
+    }
+
+    fn method(&mut self, index: u32, method: &Method, body: Option<&MethodBody>) {
+        let mut line = format!("method {}", index);
+        if method.name.0 != 0 {
+            let _ = write!(line, " {}", quote(self.string(&method.name)));
+        }
+        line.push_str(&self.signature(&Index::new(index)));
+        for &(flag, is_set) in &[
+            ("needs_arguments", method.needs_arguments_object),
+            ("needs_activation", method.needs_activation),
+            ("needs_dxns", method.needs_dxns),
+        ] {
+            if is_set {
+                line.push(' ');
+                line.push_str(flag);
+            }
+        }
+        let body = match body {
+            Some(body) => body,
+            None => {
+                line.push_str(" native");
+                self.line(0, &line);
+                return;
+            }
+        };
+        self.line(0, &line);
+        self.line(
+            1,
+            &format!(
+                "max_stack={} locals={} scope_depth={}..{}",
+                body.max_stack, body.num_locals, body.init_scope_depth, body.max_scope_depth
+            ),
+        );
+        self.traits(&body.traits, false);
+        self.code(&body.code);
+        for (i, exception) in body.exceptions.iter().enumerate() {
+            let mut line = format!(
+                "exception {} from {} to {} target {} type {}",
+                i,
+                exception.from_offset,
+                exception.to_offset,
+                exception.target_offset,
+                self.multiname(&exception.type_name)
+            );
+            if exception.variable_name.0 != 0 {
+                let _ = write!(line, " var {}", self.exception_variable(exception));
+            }
+            self.line(1, &line);
+        }
+    }
+
+    /// The name of an exception's variable, which is stored as a multiname
+    /// index despite its type.
+    fn exception_variable(&self, exception: &Exception) -> String {
+        multiname_to_string(
+            &self.abc.constant_pool,
+            &Index::new(exception.variable_name.0),
+        )
+    }
+
+    fn code(&mut self, code: &[u8]) {
+        let mut reader = Reader::new(Cursor::new(code));
+        loop {
+            let start = reader.get_inner().position() as usize;
+            if start >= code.len() {
+                break;
+            }
+            match reader.read_op() {
+                Ok(Some(op)) => {
+                    let end = reader.get_inner().position() as usize;
+                    let operands = self.operands(&op, start, end);
+                    let mut line = format!("{:>5}  {}", start, mnemonic(&op));
+                    if !operands.is_empty() {
+                        line.push(' ');
+                        line.push_str(&operands);
+                    }
+                    self.line(1, &line);
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    self.line(1, &format!("{:>5}  ; {}", start, e));
+                    break;
+                }
+            }
+        }
+    }
+
+    fn operands(&self, op: &Op, start: usize, end: usize) -> String {
+        let pool = &self.abc.constant_pool;
+        // Branches are relative to the end of the instruction, and
+        // `lookupswitch` to its start.
+        let target = |base: usize, offset: i32| (base as i64 + i64::from(offset)).to_string();
+        match op {
+            Op::AsType { type_name: index }
+            | Op::Coerce { index }
+            | Op::DeleteProperty { index }
+            | Op::FindProperty { index }
+            | Op::FindPropStrict { index }
+            | Op::GetDescendants { index }
+            | Op::GetLex { index }
+            | Op::GetProperty { index }
+            | Op::GetSuper { index }
+            | Op::InitProperty { index }
+            | Op::IsType { index }
+            | Op::SetProperty { index }
+            | Op::SetSuper { index } => self.multiname(index),
+            Op::CallProperty { index, num_args }
+            | Op::CallPropLex { index, num_args }
+            | Op::CallPropVoid { index, num_args }
+            | Op::CallSuper { index, num_args }
+            | Op::CallSuperVoid { index, num_args }
+            | Op::ConstructProp { index, num_args } => {
+                format!("{}, {}", self.multiname(index), num_args)
+            }
+            Op::CallMethod { index, num_args } | Op::CallStatic { index, num_args } => {
+                format!("method {}, {}", index.0, num_args)
+            }
+            Op::NewFunction { index } => format!("method {}", index.0),
+            Op::NewClass { index } => format!("class {}", index.0),
+            Op::NewCatch { index } => format!("exception {}", index.0),
+            Op::Call { num_args }
+            | Op::Construct { num_args }
+            | Op::ConstructSuper { num_args }
+            | Op::NewArray { num_args }
+            | Op::NewObject { num_args } => num_args.to_string(),
+            Op::DecLocal { index }
+            | Op::DecLocalI { index }
+            | Op::GetLocal { index }
+            | Op::IncLocal { index }
+            | Op::IncLocalI { index }
+            | Op::Kill { index }
+            | Op::SetLocal { index }
+            | Op::GetGlobalSlot { index }
+            | Op::GetSlot { index }
+            | Op::SetGlobalSlot { index }
+            | Op::SetSlot { index }
+            | Op::DebugLine { line_num: index }
+            | Op::PushShort { value: index } => index.to_string(),
+            Op::GetScopeObject { index } => index.to_string(),
+            // The byte is sign extended.
+            Op::PushByte { value } => (*value as i8).to_string(),
+            Op::HasNext2 {
+                object_register,
+                index_register,
+            } => format!("{}, {}", object_register, index_register),
+            Op::IfEq { offset }
+            | Op::IfFalse { offset }
+            | Op::IfGe { offset }
+            | Op::IfGt { offset }
+            | Op::IfLe { offset }
+            | Op::IfLt { offset }
+            | Op::IfNge { offset }
+            | Op::IfNgt { offset }
+            | Op::IfNle { offset }
+            | Op::IfNlt { offset }
+            | Op::IfNe { offset }
+            | Op::IfStrictEq { offset }
+            | Op::IfStrictNe { offset }
+            | Op::IfTrue { offset }
+            | Op::Jump { offset } => target(end, *offset),
+            Op::LookupSwitch {
+                default_offset,
+                case_offsets,
+            } => {
+                let cases: Vec<_> = case_offsets
+                    .iter()
+                    .map(|offset| target(start, *offset))
+                    .collect();
+                format!(
+                    "default {}, [{}]",
+                    target(start, *default_offset),
+                    cases.join(", ")
+                )
+            }
+            Op::DebugFile { file_name: index }
+            | Op::Dxns { index }
+            | Op::PushString { value: index } => quote(self.string(index)),
+            Op::Debug {
+                is_local_register,
+                register_name,
+                register,
+            } => format!(
+                "{}, {}, {}",
+                u8::from(*is_local_register),
+                quote(self.string(register_name)),
+                register
+            ),
+            Op::PushDouble { value } => pool_get(&pool.doubles, value.0)
+                .map(|v| format!("{:?}", v))
+                .unwrap_or_else(|| invalid_index(value.0)),
+            Op::PushInt { value } => pool_get(&pool.ints, value.0)
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| invalid_index(value.0)),
+            Op::PushUint { value } => pool_get(&pool.uints, value.0)
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| invalid_index(value.0)),
+            Op::PushNamespace { value } => namespace_to_string(pool, value),
+            _ => String::new(),
+        }
+    }
+
+    fn default_value(&self, value: &DefaultValue) -> String {
+        let pool = &self.abc.constant_pool;
+        match value {
+            DefaultValue::Int(i) => pool_get(&pool.ints, i.0)
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| invalid_index(i.0)),
+            DefaultValue::Uint(i) => pool_get(&pool.uints, i.0)
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| invalid_index(i.0)),
+            DefaultValue::Double(i) => pool_get(&pool.doubles, i.0)
+                .map(|v| format!("{:?}", v))
+                .unwrap_or_else(|| invalid_index(i.0)),
+            DefaultValue::String(i) => pool_get(&pool.strings, i.0)
+                .map(|v| quote(v))
+                .unwrap_or_else(|| invalid_index(i.0)),
+            DefaultValue::True => "true".to_string(),
+            DefaultValue::False => "false".to_string(),
+            DefaultValue::Null => "null".to_string(),
+            DefaultValue::Undefined => "undefined".to_string(),
+            DefaultValue::Namespace(namespace)
+            | DefaultValue::Package(namespace)
+            | DefaultValue::PackageInternal(namespace)
+            | DefaultValue::Protected(namespace)
+            | DefaultValue::Explicit(namespace)
+            | DefaultValue::StaticProtected(namespace)
+            | DefaultValue::Private(namespace) => namespace_to_string(pool, namespace),
+        }
+    }
+
+    fn multiname(&self, index: &Index<Multiname>) -> String {
+        multiname_to_string(&self.abc.constant_pool, index)
+    }
+
+    fn string(&self, index: &Index<String>) -> &'a str {
+        pool_get(&self.abc.constant_pool.strings, index.0)
+            .map(|s| s.as_str())
+            .unwrap_or("")
+    }
+
+    fn line(&mut self, indent: usize, text: &str) {
+        for _ in 0..indent {
+            self.output.push_str("    ");
+        }
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+}
+
+/// The lowercase name of an instruction, such as `getlocal`.
+fn mnemonic(op: &Op) -> String {
+    let debug = format!("{:?}", op);
+    debug
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+fn invalid_index(index: u32) -> String {
+    format!("<invalid index {}>", index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data;
+
+    #[test]
+    fn disassemble_abc() {
+        for (_, abc_file, _) in test_data::avm2_tests() {
+            let text = disassemble(&abc_file);
+            let expected = [
+                "; ABC version 46.16",
+                "script 0",
+                "    init method 1",
+                "    function Avm2Test():void method=0 disp=1",
+                "method 0():void",
+                "    max_stack=2 locals=1 scope_depth=1..2",
+                "        0  getlocal 0",
+                "        1  pushscope",
+                "        2  findpropstrict trace",
+                "        4  pushstring \"Test\"",
+                "        6  callpropvoid trace, 1",
+                "        9  returnvoid",
+            ];
+            for line in &expected {
+                assert!(
+                    text.lines().any(|l| l == *line),
+                    "Missing line {:?} in:\n{}",
+                    line,
+                    text
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn multinames() {
+        let pool = ConstantPool {
+            ints: vec![],
+            uints: vec![],
+            doubles: vec![],
+            strings: vec![
+                "".to_string(),
+                "flash.display".to_string(),
+                "Sprite".to_string(),
+                "Foo".to_string(),
+            ],
+            namespaces: vec![
+                Namespace::Package(Index::new(2)),
+                Namespace::Private(Index::new(1)),
+                Namespace::Protected(Index::new(4)),
+            ],
+            namespace_sets: vec![vec![Index::new(1), Index::new(3)]],
+            multinames: vec![
+                Multiname::QName {
+                    namespace: Index::new(1),
+                    name: Index::new(3),
+                },
+                Multiname::QNameA {
+                    namespace: Index::new(2),
+                    name: Index::new(4),
+                },
+                Multiname::MultinameL {
+                    namespace_set: Index::new(1),
+                },
+            ],
+        };
+        let names: Vec<_> = (0..=3)
+            .map(|i| multiname_to_string(&pool, &Index::new(i)))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "*",
+                "flash.display::Sprite",
+                "@private::Foo",
+                "[flash.display, protected(Foo)]::<runtime>"
+            ]
+        );
+    }
+}
@@ -30,7 +30,7 @@ pub mod write;
 mod test_data;
 
 /// Reexports
-pub use read::{read_swf, read_swf_header};
+pub use read::{read_swf, read_swf_header, read_swf_lenient};
 pub use tag_code::TagCode;
 pub use types::*;
 pub use write::write_swf;
@@ -5,12 +5,15 @@
 )]
 
 use crate::error::{Error, Result};
+use crate::tag_code::TagCode;
 use crate::types::*;
 use byteorder::{LittleEndian, ReadBytesExt};
 use enumset::EnumSet;
-use std::collections::HashSet;
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
 use std::convert::TryInto;
 use std::io::{self, Read};
+use std::rc::Rc;
 
 /// Convenience method to parse an SWF.
 ///
@@ -25,47 +28,543 @@ use std::io::{self, Read};
 /// println!("Number of frames: {}", swf.header.num_frames);
 /// ```
 pub fn read_swf<R: Read>(input: R) -> Result<Swf> {
+    let swf_buf = decompress_swf(input)?;
+    for diagnostic in &swf_buf.diagnostics {
+        log::warn!("{}", diagnostic);
+    }
+    let mut reader = Reader::new(&swf_buf.data[..], swf_buf.header.version);
+
+    Ok(Swf {
+        header: swf_buf.header,
+        tags: reader.read_tag_list()?,
+    })
+}
+
+/// Parses an SWF, carrying on past malformed data.
+///
+/// Tags that fail to parse are returned as `Tag::Unknown` with their raw data,
+/// and reading resumes at the next tag. The tags of a sprite are read the same
+/// way, so a malformed tag inside a sprite doesn't lose the whole sprite. If
+/// the data ends early, the tags of the last incomplete frame are dropped.
+/// Each problem found along the way is returned as a `Diagnostic`. Only an
+/// unreadable header is an error.
+///
+/// # Example
+/// ```
+/// let data = std::fs::read("tests/swfs/DefineSprite.swf").unwrap();
+/// let (swf, diagnostics) = swf::read_swf_lenient(&data[..]).unwrap();
+/// for diagnostic in &diagnostics {
+///     println!("{}", diagnostic);
+/// }
+/// ```
+pub fn read_swf_lenient<R: Read>(input: R) -> Result<(Swf, Vec<Diagnostic>)> {
+    let swf_buf = decompress_swf(input)?;
+    let mut diagnostics = swf_buf.diagnostics;
+    let tags = read_tag_list_lenient(
+        &swf_buf.data,
+        swf_buf.header.version,
+        swf_buf.header_length,
+        &mut diagnostics,
+    );
+    let swf = Swf {
+        header: swf_buf.header,
+        tags,
+    };
+    Ok((swf, diagnostics))
+}
+
+/// A problem found while reading an SWF leniently.
+#[derive(Debug)]
+pub struct Diagnostic {
+    /// The offset of the problem in the uncompressed SWF, counted from the
+    /// start of the file.
+    pub offset: usize,
+
+    /// The code of the tag the problem was found in, if any.
+    pub tag_code: Option<u16>,
+
+    pub error: Error,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.tag_code {
+            Some(tag_code) => write!(
+                f,
+                "At offset {} in tag {}: {}",
+                self.offset, tag_code, self.error
+            ),
+            None => write!(f, "At offset {}: {}", self.offset, self.error),
+        }
+    }
+}
+
+/// The header and uncompressed tag data of an SWF, returned by
+/// `decompress_swf`.
+pub struct SwfBuf {
+    pub header: Header,
+
+    /// The uncompressed data following the header, starting with the first tag.
+    pub data: Vec<u8>,
+
+    /// The length of the uncompressed header, which is the offset of `data`
+    /// in the uncompressed SWF.
+    pub header_length: usize,
+
+    /// Problems found while decompressing, such as a truncated stream.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Reads the header of an SWF and decompresses the rest of it in memory.
+///
+/// Some SWF streams may not be compressed correctly (e.g. incorrect data
+/// length in the stream), so decompressing may throw an error even though
+/// the data otherwise comes through the stream. Whatever data was
+/// decompressed is returned along with a diagnostic in that case.
+pub fn decompress_swf<R: Read>(input: R) -> Result<SwfBuf> {
     let swf_stream = read_swf_header(input)?;
     let header = swf_stream.header;
+    let header_length = swf_stream.header_length;
     let mut reader = swf_stream.reader;
+    let mut diagnostics = Vec::new();
 
-    // Decompress all of SWF into memory at once.
-    let mut data = if header.compression == Compression::Lzma {
+    // The uncompressed length in the header counts the header itself.
+    let data_length = (swf_stream.uncompressed_length + 8).saturating_sub(header_length);
+    let data = if header.compression == Compression::Lzma {
         // TODO: The LZMA decoder is still funky.
         // It always errors, and doesn't return all the data if you use read_to_end,
         // but read_exact at least returns the data... why?
         // Does the decoder need to be flushed somehow?
-        let mut data = vec![0u8; swf_stream.uncompressed_length];
+        let mut data = vec![0u8; data_length];
         let _ = reader.get_mut().read_exact(&mut data);
         data
     } else {
-        let mut data = Vec::with_capacity(swf_stream.uncompressed_length);
+        let mut data = Vec::with_capacity(data_length);
         if let Err(e) = reader.get_mut().read_to_end(&mut data) {
-            log::error!("Error decompressing SWF, may be corrupt: {}", e);
+            diagnostics.push(Diagnostic {
+                offset: header_length + data.len(),
+                tag_code: None,
+                error: Error::invalid_data(format!(
+                    "Error decompressing SWF, may be corrupt: {}",
+                    e
+                )),
+            });
         }
         data
     };
-    let version = header.version;
 
-    // Some SWF streams may not be compressed correctly,
-    // (e.g. incorrect data length in the stream), so decompressing
-    // may throw an error even though the data otherwise comes
-    // through the stream.
-    // We'll still try to parse what we get if the full decompression fails.
-    if let Err(e) = reader.get_mut().read_to_end(&mut data) {
-        log::warn!("Error decompressing SWF stream, may be corrupt: {}", e);
-    }
-    if data.len() != swf_stream.uncompressed_length {
-        log::warn!("SWF length doesn't match header, may be corrupt");
+    if data.len() != data_length {
+        diagnostics.push(Diagnostic {
+            offset: header_length + data.len(),
+            tag_code: None,
+            error: Error::invalid_data(format!(
+                "SWF length is {} bytes but the header says {}",
+                header_length + data.len(),
+                data_length + header_length
+            )),
+        });
     }
-    let mut reader = Reader::new(&data[..], version);
 
-    Ok(Swf {
+    Ok(SwfBuf {
         header,
-        tags: reader.read_tag_list()?,
+        data,
+        header_length,
+        diagnostics,
     })
 }
 
+/// Decompresses an SWF that arrives in chunks, such as one being downloaded.
+///
+/// Each chunk is passed to `push`, which decompresses as much of the SWF as
+/// the data received so far allows. The header becomes available as soon as
+/// it has been decompressed, and `data` returns the tag data decompressed so
+/// far, so a movie can start playing before it has fully loaded.
+///
+/// # Example
+/// ```
+/// let data = std::fs::read("tests/swfs/DefineSprite.swf").unwrap();
+/// let mut decompressor = swf::read::SwfDecompressor::new();
+/// for chunk in data.chunks(64) {
+///     decompressor.push(chunk).unwrap();
+/// }
+/// decompressor.finish().unwrap();
+/// println!("Number of frames: {}", decompressor.header().unwrap().num_frames);
+/// ```
+pub struct SwfDecompressor {
+    /// The received data that the decompressor has not consumed yet.
+    input: Rc<RefCell<ChunkInput>>,
+
+    /// The first 8 bytes of the SWF, which are never compressed.
+    signature: Vec<u8>,
+
+    /// The decompressor for the rest of the SWF, once the signature has been
+    /// received.
+    reader: Option<Box<dyn Read>>,
+
+    /// Whether the decompressor has reached the end of the SWF.
+    ended: bool,
+
+    /// The header, and its length, once enough of the SWF has been
+    /// decompressed to read it.
+    header: Option<(Header, usize)>,
+
+    /// Everything decompressed so far, starting after the signature.
+    decompressed: Vec<u8>,
+
+    bytes_received: usize,
+
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl SwfDecompressor {
+    pub fn new() -> Self {
+        Self {
+            input: Rc::new(RefCell::new(ChunkInput::default())),
+            signature: Vec::with_capacity(8),
+            reader: None,
+            ended: false,
+            header: None,
+            decompressed: Vec::new(),
+            bytes_received: 0,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Decompresses the next chunk of the SWF.
+    ///
+    /// Returns an error if the SWF header is invalid. Problems with the
+    /// compressed data are noted as diagnostics instead.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<()> {
+        self.bytes_received += chunk.len();
+        let mut chunk = chunk;
+        if self.signature.len() < 8 {
+            let len = chunk.len().min(8 - self.signature.len());
+            self.signature.extend_from_slice(&chunk[..len]);
+            chunk = &chunk[len..];
+        }
+        self.input.borrow_mut().data.extend(chunk);
+        self.decompress()
+    }
+
+    /// Decompresses the rest of the SWF once all of it has been received.
+    ///
+    /// Returns an error if the data ended before the header.
+    pub fn finish(&mut self) -> Result<()> {
+        self.input.borrow_mut().finished = true;
+        self.decompress()?;
+        let header_length = match self.header {
+            Some((_, header_length)) => header_length,
+            None => {
+                return Err(Error::invalid_data(
+                    "SWF ended before the end of its header",
+                ))
+            }
+        };
+
+        let data_length = self.data_length();
+        let data = self.data();
+        if data.len() != data_length {
+            let diagnostic = Diagnostic {
+                offset: header_length + data.len(),
+                tag_code: None,
+                error: Error::invalid_data(format!(
+                    "SWF length is {} bytes but the header says {}",
+                    header_length + data.len(),
+                    data_length + header_length
+                )),
+            };
+            self.diagnostics.push(diagnostic);
+        }
+        Ok(())
+    }
+
+    /// The SWF header, once it has been received.
+    pub fn header(&self) -> Option<&Header> {
+        self.header.as_ref().map(|(header, _)| header)
+    }
+
+    /// The length of the uncompressed header, once it has been received.
+    pub fn header_length(&self) -> Option<usize> {
+        self.header
+            .as_ref()
+            .map(|(_, header_length)| *header_length)
+    }
+
+    /// The length of the uncompressed tag data according to the header.
+    pub fn data_length(&self) -> usize {
+        match self.header {
+            Some((_, header_length)) => {
+                (self.uncompressed_length() + 8).saturating_sub(header_length)
+            }
+            None => 0,
+        }
+    }
+
+    /// The uncompressed tag data received so far.
+    pub fn data(&self) -> &[u8] {
+        match self.header {
+            Some((_, header_length)) => &self.decompressed[header_length - 8..],
+            None => &[],
+        }
+    }
+
+    /// The number of bytes received, before decompression.
+    pub fn bytes_received(&self) -> usize {
+        self.bytes_received
+    }
+
+    /// Problems found while decompressing, such as a truncated stream.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// The uncompressed length of the SWF data after the signature.
+    fn uncompressed_length(&self) -> usize {
+        let mut length = &self.signature[4..];
+        length
+            .read_u32::<LittleEndian>()
+            .map(|length| length.saturating_sub(8) as usize)
+            .unwrap_or(0)
+    }
+
+    fn decompress(&mut self) -> Result<()> {
+        if self.signature.len() < 8 {
+            return Ok(());
+        }
+
+        let compression = Reader::read_compression_type(&self.signature[..])?;
+        if self.reader.is_none() && !self.ended {
+            let input = ChunkReader(self.input.clone());
+            self.reader = match compression {
+                Compression::None => Some(Box::new(input)),
+                Compression::Zlib => Some(make_zlib_stream_reader(input)?),
+                // The LZMA decoder reads its properties straight away, so wait
+                // until they have arrived.
+                Compression::Lzma if self.input.borrow().data.len() < 9 => return Ok(()),
+                Compression::Lzma => Some(make_lzma_reader(
+                    input,
+                    self.uncompressed_length().try_into().unwrap(),
+                )?),
+            };
+        }
+
+        if let Some(reader) = &mut self.reader {
+            let mut buffer = [0; 4096];
+            loop {
+                match reader.read(&mut buffer) {
+                    Ok(0) => {
+                        self.reader = None;
+                        self.ended = true;
+                        break;
+                    }
+                    Ok(len) => self.decompressed.extend_from_slice(&buffer[..len]),
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                    Err(e) => {
+                        // The LZMA decoder errors at the end of the data, even
+                        // when it has all been decompressed.
+                        if compression != Compression::Lzma
+                            || self.decompressed.len() < self.uncompressed_length()
+                        {
+                            self.diagnostics.push(Diagnostic {
+                                offset: 8 + self.decompressed.len(),
+                                tag_code: None,
+                                error: Error::invalid_data(format!(
+                                    "Error decompressing SWF, may be corrupt: {}",
+                                    e
+                                )),
+                            });
+                        }
+                        self.reader = None;
+                        self.ended = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        if self.header.is_none() {
+            self.read_header(compression)?;
+        }
+        Ok(())
+    }
+
+    fn read_header(&mut self, compression: Compression) -> Result<()> {
+        let first_byte = match self.decompressed.first() {
+            Some(&first_byte) => first_byte,
+            None => return Ok(()),
+        };
+        let header_data_length = header_data_length(first_byte);
+        if self.decompressed.len() < header_data_length {
+            return Ok(());
+        }
+
+        let version = self.signature[3];
+        let mut header_reader = Reader::new(&self.decompressed[..], version);
+        let stage_size = header_reader.read_rectangle()?;
+        let frame_rate = header_reader.read_fixed8()?;
+        let num_frames = header_reader.read_u16()?;
+        let header = Header {
+            version,
+            compression,
+            stage_size,
+            frame_rate,
+            num_frames,
+        };
+        self.header = Some((header, 8 + header_data_length));
+        Ok(())
+    }
+}
+
+impl Default for SwfDecompressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Data received by a `SwfDecompressor` that has not been decompressed yet.
+#[derive(Default)]
+struct ChunkInput {
+    data: VecDeque<u8>,
+
+    /// Whether all of the data has been received.
+    finished: bool,
+}
+
+/// Reads the data received by a `SwfDecompressor`.
+///
+/// Reading past the data received so far fails with `WouldBlock` until the
+/// input is finished, which the decompressors handle by picking up where they
+/// left off on the next read.
+struct ChunkReader(Rc<RefCell<ChunkInput>>);
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut input = self.0.borrow_mut();
+        if input.data.is_empty() && !input.finished {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        let len = buf.len().min(input.data.len());
+        for (dst, src) in buf.iter_mut().zip(input.data.drain(..len)) {
+            *dst = src;
+        }
+        Ok(len)
+    }
+}
+
+/// Reads the tags of an SWF, turning tags that fail to parse into
+/// `Tag::Unknown` and noting them in `diagnostics`.
+///
+/// `base_offset` is the offset of `data` in the uncompressed SWF.
+fn read_tag_list_lenient(
+    data: &[u8],
+    version: u8,
+    base_offset: usize,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<Tag> {
+    let mut tags = Vec::new();
+    // The number of tags up to the end of the last complete frame.
+    let mut num_complete_tags = 0;
+    let mut pos = 0;
+    let is_truncated = loop {
+        let mut reader = Reader::new(&data[pos..], version);
+        let (tag_code, length) = match reader.read_tag_code_and_length() {
+            Ok(tag_code_and_length) => tag_code_and_length,
+            Err(error) => {
+                let error = if pos == data.len() {
+                    Error::invalid_data("Missing End tag")
+                } else {
+                    error
+                };
+                diagnostics.push(Diagnostic {
+                    offset: base_offset + pos,
+                    tag_code: None,
+                    error,
+                });
+                break true;
+            }
+        };
+        let start = data.len() - reader.get_ref().len();
+        let end = match start.checked_add(length) {
+            Some(end) if end <= data.len() => end,
+            _ => {
+                diagnostics.push(Diagnostic {
+                    offset: base_offset + pos,
+                    tag_code: Some(tag_code),
+                    error: Error::swf_parse_error_with_source(
+                        tag_code,
+                        Error::invalid_data("Tag is cut off by the end of the SWF"),
+                    ),
+                });
+                break true;
+            }
+        };
+
+        match Reader::new(&data[pos..end], version).read_tag() {
+            Ok(Tag::End) => break false,
+            Ok(tag) => {
+                let is_frame_end = tag == Tag::ShowFrame;
+                tags.push(tag);
+                if is_frame_end {
+                    num_complete_tags = tags.len();
+                }
+            }
+            Err(error) => {
+                let sprite = if tag_code == TagCode::DefineSprite as u16 {
+                    read_define_sprite_lenient(
+                        &data[start..end],
+                        version,
+                        base_offset + start,
+                        diagnostics,
+                    )
+                } else {
+                    None
+                };
+                match sprite {
+                    Some(sprite) => tags.push(sprite),
+                    None => {
+                        diagnostics.push(Diagnostic {
+                            offset: base_offset + pos,
+                            tag_code: Some(tag_code),
+                            error,
+                        });
+                        tags.push(Tag::Unknown {
+                            tag_code,
+                            data: data[start..end].to_vec(),
+                        });
+                    }
+                }
+            }
+        }
+        pos = end;
+    };
+
+    if is_truncated {
+        tags.truncate(num_complete_tags);
+    }
+    tags
+}
+
+/// Reads a `DefineSprite` tag whose nested tags failed to parse, turning only
+/// the broken nested tags into `Tag::Unknown`.
+///
+/// Returns `None` if the sprite's own header can't be read.
+fn read_define_sprite_lenient(
+    data: &[u8],
+    version: u8,
+    base_offset: usize,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<Tag> {
+    let mut reader = Reader::new(data, version);
+    let id = reader.read_u16().ok()?;
+    let num_frames = reader.read_u16().ok()?;
+    let tags = read_tag_list_lenient(&data[4..], version, base_offset + 4, diagnostics);
+    Some(Tag::DefineSprite(Sprite {
+        id,
+        num_frames,
+        tags,
+    }))
+}
+
 /// Parses an SWF header and returns a `Reader` that can be used
 /// to read the SWF tags inside the SWF file.
 ///
@@ -84,7 +583,7 @@ pub fn read_swf_header<'a, R: Read + 'a>(mut input: R) -> Result<SwfStream<'a>>
 
     // Uncompressed length includes the 4-byte header and 4-byte uncompressed length itself,
     // subtract it here.
-    let uncompressed_length = input.read_u32::<LittleEndian>()? - 8;
+    let uncompressed_length = input.read_u32::<LittleEndian>()?.saturating_sub(8);
 
     // Now the SWF switches to a compressed stream.
     let decompressed_input: Box<dyn Read> = match compression {
@@ -109,10 +608,18 @@ pub fn read_swf_header<'a, R: Read + 'a>(mut input: R) -> Result<SwfStream<'a>>
         }
     };
 
-    let mut reader = Reader::new(decompressed_input, version);
-    let stage_size = reader.read_rectangle()?;
-    let frame_rate = reader.read_fixed8()?;
-    let num_frames = reader.read_u16()?;
+    // The stage size is a rectangle of variable length, so read its bytes
+    // first to know where the header ends.
+    let mut decompressed_input = decompressed_input;
+    let first_byte = decompressed_input.read_u8()?;
+    let mut header_data = vec![0; header_data_length(first_byte)];
+    header_data[0] = first_byte;
+    decompressed_input.read_exact(&mut header_data[1..])?;
+
+    let mut header_reader = Reader::new(&header_data[..], version);
+    let stage_size = header_reader.read_rectangle()?;
+    let frame_rate = header_reader.read_fixed8()?;
+    let num_frames = header_reader.read_u16()?;
     let header = Header {
         version,
         compression,
@@ -123,10 +630,19 @@ pub fn read_swf_header<'a, R: Read + 'a>(mut input: R) -> Result<SwfStream<'a>>
     Ok(SwfStream {
         header,
         uncompressed_length: uncompressed_length.try_into().unwrap(),
-        reader,
+        header_length: 8 + header_data.len(),
+        reader: Reader::new(decompressed_input, version),
     })
 }
 
+/// The length of the header fields that follow the uncompressed length, given
+/// their first byte.
+fn header_data_length(first_byte: u8) -> usize {
+    // The stage size rectangle has a 5-bit field size, then four fields,
+    // padded to a whole byte. The frame rate and count follow.
+    (4 * usize::from(first_byte >> 3) + 12) / 8 + 4
+}
+
 #[cfg(feature = "flate2")]
 fn make_zlib_reader<'a, R: Read + 'a>(input: R) -> Result<Box<dyn Read + 'a>> {
     use flate2::read::ZlibDecoder;
@@ -147,6 +663,24 @@ fn make_zlib_reader<'a, R: Read + 'a>(_input: R) -> Result<Box<dyn Read + 'a>> {
     ))
 }
 
+/// Like `make_zlib_reader`, but the returned reader can carry on after the
+/// input fails with `WouldBlock`.
+#[cfg(feature = "flate2")]
+fn make_zlib_stream_reader<'a, R: Read + 'a>(input: R) -> Result<Box<dyn Read + 'a>> {
+    make_zlib_reader(input)
+}
+
+#[cfg(all(feature = "libflate", not(feature = "flate2")))]
+fn make_zlib_stream_reader<'a, R: Read + 'a>(input: R) -> Result<Box<dyn Read + 'a>> {
+    use libflate::non_blocking::zlib::Decoder;
+    Ok(Box::new(Decoder::new(input)))
+}
+
+#[cfg(not(any(feature = "flate2", feature = "libflate")))]
+fn make_zlib_stream_reader<'a, R: Read + 'a>(input: R) -> Result<Box<dyn Read + 'a>> {
+    make_zlib_reader(input)
+}
+
 #[cfg(feature = "lzma")]
 fn make_lzma_reader<'a, R: Read + 'a>(
     mut input: R,
@@ -384,7 +918,6 @@ impl<R: Read> Reader<R> {
 
     fn read_tag_with_code(&mut self, tag_code: u16, length: usize) -> Result<Tag> {
         let mut tag_reader = Reader::new(self.input.by_ref().take(length as u64), self.version);
-        use crate::tag_code::TagCode;
         let tag = match TagCode::from_u16(tag_code) {
             Some(TagCode::End) => Tag::End,
             Some(TagCode::ShowFrame) => Tag::ShowFrame,
@@ -3311,4 +3844,143 @@ pub mod tests {
             }
         }
     }
+
+    #[test]
+    fn read_swf_lenient_recovers() {
+        #[rustfmt::skip]
+        let data = [
+            b'F', b'W', b'S', 10, 28, 0, 0, 0,
+            // Stage size, frame rate and frame count.
+            0, 0, 24, 1, 0,
+            // ShowFrame
+            0x40, 0x00,
+            // SetBackgroundColor that's too short.
+            0x41, 0x02, 0xff,
+            // ShowFrame
+            0x40, 0x00,
+            // SetBackgroundColor
+            0x43, 0x02, 0xff, 0x00, 0x00,
+            // SetBackgroundColor cut off by the end of the file.
+            0x43, 0x02, 0xff,
+        ];
+        assert!(read_swf(&data[..]).is_err());
+
+        let (swf, diagnostics) = read_swf_lenient(&data[..]).unwrap();
+        assert_eq!(
+            swf.tags,
+            [
+                Tag::ShowFrame,
+                Tag::Unknown {
+                    tag_code: 9,
+                    data: vec![0xff],
+                },
+                Tag::ShowFrame,
+            ]
+        );
+        let problems: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.offset, diagnostic.tag_code))
+            .collect();
+        assert_eq!(problems, [(15, Some(9)), (25, Some(9))]);
+    }
+
+    #[test]
+    fn read_swf_lenient_recovers_in_sprites() {
+        #[rustfmt::skip]
+        let data = [
+            b'F', b'W', b'S', 10, 32, 0, 0, 0,
+            // Stage size, frame rate and frame count.
+            0, 0, 24, 1, 0,
+            // DefineSprite with ID 1 and 2 frames.
+            0xcd, 0x09, 1, 0, 2, 0,
+            // ShowFrame
+            0x40, 0x00,
+            // SetBackgroundColor that's too short.
+            0x41, 0x02, 0xff,
+            // ShowFrame
+            0x40, 0x00,
+            // End
+            0x00, 0x00,
+            // ShowFrame
+            0x40, 0x00,
+            // End
+            0x00, 0x00,
+        ];
+        assert!(read_swf(&data[..]).is_err());
+
+        let (swf, diagnostics) = read_swf_lenient(&data[..]).unwrap();
+        assert_eq!(
+            swf.tags,
+            [
+                Tag::DefineSprite(Sprite {
+                    id: 1,
+                    num_frames: 2,
+                    tags: vec![
+                        Tag::ShowFrame,
+                        Tag::Unknown {
+                            tag_code: 9,
+                            data: vec![0xff],
+                        },
+                        Tag::ShowFrame,
+                    ],
+                }),
+                Tag::ShowFrame,
+            ]
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].offset, 21);
+        assert_eq!(diagnostics[0].tag_code, Some(9));
+        assert!(diagnostics[0]
+            .to_string()
+            .starts_with("At offset 21 in tag 9: "));
+    }
+
+    #[test]
+    fn decompress_swf_in_chunks() {
+        #[allow(unused_mut)]
+        let mut paths = vec![
+            "tests/swfs/DefineSprite.swf",
+            "tests/swfs/PlaceObject3-Image.swf",
+            "tests/swfs/zlib.swf",
+        ];
+        #[cfg(feature = "lzma")]
+        paths.push("tests/swfs/lzma.swf");
+        for path in paths {
+            let data = std::fs::read(path).unwrap();
+            let swf_buf = decompress_swf(&data[..]).unwrap();
+            for &chunk_size in &[1, 7, 1024] {
+                let mut decompressor = SwfDecompressor::new();
+                for chunk in data.chunks(chunk_size) {
+                    decompressor.push(chunk).unwrap();
+                    assert!(swf_buf.data.starts_with(decompressor.data()));
+                }
+                decompressor.finish().unwrap();
+                assert_eq!(decompressor.header(), Some(&swf_buf.header));
+                assert_eq!(decompressor.header_length(), Some(swf_buf.header_length));
+                assert_eq!(decompressor.data_length(), swf_buf.data.len());
+                assert_eq!(decompressor.data(), &swf_buf.data[..]);
+                assert_eq!(decompressor.bytes_received(), data.len());
+                assert!(decompressor.diagnostics().is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn decompress_truncated_swf_in_chunks() {
+        let data = std::fs::read("tests/swfs/zlib.swf").unwrap();
+        let mut decompressor = SwfDecompressor::new();
+        decompressor.push(&data[..4]).unwrap();
+        assert!(decompressor.header().is_none());
+        decompressor.push(&data[4..data.len() / 2]).unwrap();
+        assert!(decompressor.header().is_some());
+        let len = decompressor.data().len();
+        decompressor.finish().unwrap();
+        assert!(decompressor.data().len() >= len);
+        assert!(decompressor.data().len() < decompressor.data_length());
+        assert!(!decompressor.diagnostics().is_empty());
+
+        let mut decompressor = SwfDecompressor::new();
+        decompressor.push(&data[..10]).unwrap();
+        assert!(decompressor.finish().is_err());
+    }
 }
@@ -2594,6 +2594,12 @@ pub fn avm1_tests() -> Vec<Avm1TestData> {
         (4, Action::AsciiToChar, vec![0x33]),
         (4, Action::Call, vec![0x9E, 0, 0]),
         (4, Action::CharToAscii, vec![0x32]),
+        (5, Action::ConstantPool(vec![]), vec![0x88, 2, 0, 0, 0]),
+        (
+            5,
+            Action::ConstantPool(vec!["a", "b"]),
+            vec![0x88, 6, 0, 2, 0, 97, 0, 98, 0],
+        ),
         (4, Action::Divide, vec![0x0D]),
         (4, Action::Equals, vec![0x0E]),
         (4, Action::GetTime, vec![0x34]),
@@ -2641,6 +2647,22 @@ pub fn avm1_tests() -> Vec<Avm1TestData> {
             },
             vec![0x9F, 3, 0, 0b11, 3, 1],
         ),
+        (
+            4,
+            Action::GotoFrame2 {
+                set_playing: true,
+                scene_offset: 0,
+            },
+            vec![0x9F, 1, 0, 0b01],
+        ),
+        (
+            4,
+            Action::GotoFrame2 {
+                set_playing: false,
+                scene_offset: 5,
+            },
+            vec![0x9F, 3, 0, 0b10, 5, 0],
+        ),
         (
             3,
             Action::GotoLabel("testb"),
@@ -2676,6 +2698,7 @@ pub fn avm1_tests() -> Vec<Avm1TestData> {
             Action::Push(vec![Value::Double(1.5)]),
             vec![0x96, 9, 0, 6, 0, 0, 248, 63, 0, 0, 0, 0],
         ),
+        (5, Action::Push(vec![]), vec![0x96, 0, 0]),
         (5, Action::Push(vec![Value::Null]), vec![0x96, 1, 0, 2]),
         (5, Action::Push(vec![Value::Undefined]), vec![0x96, 1, 0, 3]),
         (
@@ -2756,6 +2779,29 @@ pub fn avm1_tests() -> Vec<Avm1TestData> {
             },
             vec![0x8D, 1, 0, 34],
         ),
+        (
+            7,
+            Action::Try(TryBlock {
+                try_actions: &[0x07],
+                catch: Some((CatchVar::Var("e"), &[0x06])),
+                finally: None,
+            }),
+            vec![0x8F, 9, 0, 0b1, 1, 0, 1, 0, 0, 0, 101, 0, 0x07, 0x06],
+        ),
+        (
+            7,
+            Action::Try(TryBlock {
+                try_actions: &[0x07],
+                catch: None,
+                finally: Some(&[0x06]),
+            }),
+            vec![0x8F, 8, 0, 0b10, 1, 0, 0, 0, 1, 0, 0, 0x07, 0x06],
+        ),
+        (
+            5,
+            Action::With { actions: &[0x07] },
+            vec![0x94, 2, 0, 1, 0, 0x07],
+        ),
         (
             1,
             Action::Unknown {
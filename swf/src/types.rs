@@ -7,12 +7,15 @@ use enumset::{EnumSet, EnumSetType};
 use std::collections::HashSet;
 
 mod matrix;
+#[cfg(feature = "serde")]
+mod serialization;
 
 pub use matrix::Matrix;
 
 /// A complete header and tags in the SWF file.
 /// This is returned by the `swf::read_swf` convenience method.
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Swf {
     pub header: Header,
     pub tags: Vec<Tag>,
@@ -23,6 +26,11 @@ pub struct Swf {
 pub struct SwfStream<'a> {
     pub header: Header,
     pub uncompressed_length: usize,
+
+    /// The length of the uncompressed header, including the stage size,
+    /// frame rate and frame count.
+    pub header_length: usize,
+
     pub reader: crate::read::Reader<Box<dyn std::io::Read + 'a>>,
 }
 
@@ -32,6 +40,7 @@ pub struct SwfStream<'a> {
 ///
 /// [SWF19 p.27](https://www.adobe.com/content/dam/acom/en/devnet/pdf/swf-file-format-spec.pdf#page=27)
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
     pub version: u8,
     pub compression: Compression,
@@ -45,6 +54,7 @@ pub struct Header {
 /// The vast majority of SWFs will use zlib compression.
 /// [SWF19 p.27](https://www.adobe.com/content/dam/acom/en/devnet/pdf/swf-file-format-spec.pdf#page=27)
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Compression {
     None,
     Zlib,
@@ -60,6 +70,7 @@ pub enum Compression {
 /// Use `Twips::from_pixels` and `Twips::to_pixels` to convert to and from
 /// pixel values.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Default, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Twips(i32);
 
 impl Twips {
@@ -156,6 +167,7 @@ impl std::fmt::Display for Twips {
 }
 
 #[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rectangle {
     pub x_min: Twips,
     pub x_max: Twips,
@@ -164,6 +176,7 @@ pub struct Rectangle {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -183,6 +196,7 @@ impl Color {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColorTransform {
     pub r_multiply: f32,
     pub g_multiply: f32,
@@ -216,6 +230,7 @@ impl Default for ColorTransform {
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Language {
     Unknown,
     Latin,
@@ -226,6 +241,7 @@ pub enum Language {
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FileAttributes {
     pub use_direct_blit: bool,
     pub use_gpu: bool,
@@ -235,18 +251,21 @@ pub struct FileAttributes {
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrameLabel {
     pub label: String,
     pub is_anchor: bool,
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DefineSceneAndFrameLabelData {
     pub scenes: Vec<FrameLabelData>,
     pub frame_labels: Vec<FrameLabelData>,
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrameLabelData {
     pub frame_num: u32,
     pub label: String,
@@ -256,6 +275,7 @@ pub type Depth = u16;
 pub type CharacterId = u16;
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlaceObject {
     pub version: u8,
     pub action: PlaceObjectAction,
@@ -277,6 +297,7 @@ pub struct PlaceObject {
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PlaceObjectAction {
     Place(CharacterId),
     Modify,
@@ -284,6 +305,7 @@ pub enum PlaceObjectAction {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Filter {
     DropShadowFilter(Box<DropShadowFilter>),
     BlurFilter(Box<BlurFilter>),
@@ -296,6 +318,7 @@ pub enum Filter {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DropShadowFilter {
     pub color: Color,
     pub blur_x: f64,
@@ -309,6 +332,7 @@ pub struct DropShadowFilter {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlurFilter {
     pub blur_x: f64,
     pub blur_y: f64,
@@ -316,6 +340,7 @@ pub struct BlurFilter {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlowFilter {
     pub color: Color,
     pub blur_x: f64,
@@ -327,6 +352,7 @@ pub struct GlowFilter {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BevelFilter {
     pub shadow_color: Color,
     pub highlight_color: Color,
@@ -342,6 +368,7 @@ pub struct BevelFilter {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GradientGlowFilter {
     pub colors: Vec<GradientRecord>,
     pub blur_x: f64,
@@ -356,6 +383,7 @@ pub struct GradientGlowFilter {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConvolutionFilter {
     pub num_matrix_rows: u8,
     pub num_matrix_cols: u8,
@@ -368,11 +396,13 @@ pub struct ConvolutionFilter {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColorMatrixFilter {
     pub matrix: [f64; 20],
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GradientBevelFilter {
     pub colors: Vec<GradientRecord>,
     pub blur_x: f64,
@@ -387,6 +417,7 @@ pub struct GradientBevelFilter {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlendMode {
     Normal,
     Layer,
@@ -409,7 +440,9 @@ pub enum BlendMode {
 ///
 /// [SWF19 pp.37-38 ClipActionRecord](https://www.adobe.com/content/dam/acom/en/devnet/pdf/swf-file-format-spec.pdf#page=37)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClipAction {
+    #[cfg_attr(feature = "serde", serde(with = "serialization::enum_set"))]
     pub events: EnumSet<ClipEventFlag>,
     pub key_code: Option<KeyCode>,
     pub action_data: Vec<u8>,
@@ -420,6 +453,7 @@ pub struct ClipAction {
 ///
 /// [SWF19 pp.48-50 ClipEvent](https://www.adobe.com/content/dam/acom/en/devnet/pdf/swf-file-format-spec.pdf#page=38)
 #[derive(Debug, EnumSetType)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ClipEventFlag {
     Construct,
     Data,
@@ -453,6 +487,7 @@ pub type KeyCode = u8;
 ///
 // [SWF19 p.29](https://www.adobe.com/content/dam/acom/en/devnet/pdf/swf-file-format-spec.pdf#page=29)
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Tag {
     ExportAssets(ExportAssets),
     ScriptLimits {
@@ -557,12 +592,14 @@ pub enum Tag {
 pub type ExportAssets = Vec<ExportedAsset>;
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExportedAsset {
     pub id: CharacterId,
     pub name: String,
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RemoveObject {
     pub depth: Depth,
     pub character_id: Option<CharacterId>,
@@ -571,12 +608,14 @@ pub struct RemoveObject {
 pub type SetBackgroundColor = Color;
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SymbolClassLink {
     pub id: CharacterId,
     pub class_name: String,
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Shape {
     pub version: u8,
     pub id: CharacterId,
@@ -590,6 +629,7 @@ pub struct Shape {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sound {
     pub id: CharacterId,
     pub format: SoundFormat,
@@ -598,6 +638,7 @@ pub struct Sound {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SoundInfo {
     pub event: SoundEvent,
     pub in_sample: Option<u32>,
@@ -607,6 +648,7 @@ pub struct SoundInfo {
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SoundEvent {
     Event,
     Start,
@@ -616,6 +658,7 @@ pub enum SoundEvent {
 pub type SoundEnvelope = Vec<SoundEnvelopePoint>;
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SoundEnvelopePoint {
     pub sample: u32,
     pub left_volume: f32,
@@ -623,12 +666,14 @@ pub struct SoundEnvelopePoint {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StartSound {
     pub id: CharacterId,
     pub sound_info: Box<SoundInfo>,
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sprite {
     pub id: CharacterId,
     pub num_frames: u16,
@@ -636,12 +681,14 @@ pub struct Sprite {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShapeStyles {
     pub fill_styles: Vec<FillStyle>,
     pub line_styles: Vec<LineStyle>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ShapeRecord {
     StyleChange(StyleChangeData),
     StraightEdge {
@@ -657,6 +704,7 @@ pub enum ShapeRecord {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StyleChangeData {
     pub move_to: Option<(Twips, Twips)>,
     pub fill_style_0: Option<u32>,
@@ -666,6 +714,7 @@ pub struct StyleChangeData {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FillStyle {
     Color(Color),
     LinearGradient(Gradient),
@@ -683,6 +732,7 @@ pub enum FillStyle {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Gradient {
     pub matrix: Matrix,
     pub spread: GradientSpread,
@@ -691,6 +741,7 @@ pub struct Gradient {
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GradientSpread {
     Pad,
     Reflect,
@@ -698,18 +749,21 @@ pub enum GradientSpread {
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GradientInterpolation {
     RGB,
     LinearRGB,
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GradientRecord {
     pub ratio: u8,
     pub color: Color,
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineStyle {
     pub width: Twips,
     pub color: Color,
@@ -741,6 +795,7 @@ impl LineStyle {
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LineCapStyle {
     Round,
     None,
@@ -748,6 +803,7 @@ pub enum LineCapStyle {
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LineJoinStyle {
     Round,
     Bevel,
@@ -755,6 +811,7 @@ pub enum LineJoinStyle {
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AudioCompression {
     UncompressedUnknownEndian,
     Adpcm,
@@ -767,6 +824,7 @@ pub enum AudioCompression {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SoundFormat {
     pub compression: AudioCompression,
     pub sample_rate: u16,
@@ -775,6 +833,7 @@ pub struct SoundFormat {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SoundStreamHead {
     pub stream_format: SoundFormat,
     pub playback_format: SoundFormat,
@@ -785,6 +844,7 @@ pub struct SoundStreamHead {
 pub type SoundStreamBlock = Vec<u8>;
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Button {
     pub id: CharacterId,
     pub is_track_as_menu: bool,
@@ -793,7 +853,9 @@ pub struct Button {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ButtonRecord {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialization::sorted_set"))]
     pub states: HashSet<ButtonState>,
     pub id: CharacterId,
     pub depth: Depth,
@@ -803,7 +865,8 @@ pub struct ButtonRecord {
     pub blend_mode: BlendMode,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ButtonState {
     Up,
     Over,
@@ -812,12 +875,14 @@ pub enum ButtonState {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ButtonColorTransform {
     pub id: CharacterId,
     pub color_transforms: Vec<ColorTransform>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ButtonSounds {
     pub id: CharacterId,
     pub over_to_up_sound: Option<ButtonSound>,
@@ -829,13 +894,16 @@ pub struct ButtonSounds {
 pub type ButtonSound = (CharacterId, SoundInfo);
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ButtonAction {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialization::sorted_set"))]
     pub conditions: HashSet<ButtonActionCondition>,
     pub key_code: Option<u8>,
     pub action_data: Vec<u8>,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ButtonActionCondition {
     IdleToOverDown,
     OutDownToIdle,
@@ -850,6 +918,7 @@ pub enum ButtonActionCondition {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DefineMorphShape {
     pub version: u8,
     pub id: CharacterId,
@@ -860,6 +929,7 @@ pub struct DefineMorphShape {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MorphShape {
     pub shape_bounds: Rectangle,
     pub edge_bounds: Rectangle,
@@ -869,12 +939,14 @@ pub struct MorphShape {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FontV1 {
     pub id: CharacterId,
     pub glyphs: Vec<Vec<ShapeRecord>>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Font {
     pub version: u8,
     pub id: CharacterId,
@@ -890,6 +962,7 @@ pub struct Font {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Font4 {
     pub id: CharacterId,
     pub is_italic: bool,
@@ -899,6 +972,7 @@ pub struct Font4 {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Glyph {
     pub shape_records: Vec<ShapeRecord>,
     pub code: u16,
@@ -907,6 +981,7 @@ pub struct Glyph {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FontLayout {
     pub ascent: u16,
     pub descent: u16,
@@ -915,6 +990,7 @@ pub struct FontLayout {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KerningRecord {
     pub left_code: u16,
     pub right_code: u16,
@@ -922,6 +998,7 @@ pub struct KerningRecord {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FontInfo {
     pub id: CharacterId,
     pub version: u8,
@@ -936,6 +1013,7 @@ pub struct FontInfo {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Text {
     pub id: CharacterId,
     pub bounds: Rectangle,
@@ -944,6 +1022,7 @@ pub struct Text {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextRecord {
     pub font_id: Option<CharacterId>,
     pub color: Option<Color>,
@@ -954,12 +1033,14 @@ pub struct TextRecord {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlyphEntry {
     pub index: u32,
     pub advance: i32,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EditText {
     pub id: CharacterId,
     pub bounds: Rectangle,
@@ -984,6 +1065,7 @@ pub struct EditText {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextLayout {
     pub align: TextAlign,
     pub left_margin: Twips,
@@ -993,6 +1075,7 @@ pub struct TextLayout {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextAlign {
     Left,
     Center,
@@ -1001,6 +1084,7 @@ pub enum TextAlign {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FontAlignZone {
     // TODO(Herschel): Read these as f16s.
     pub left: i16,
@@ -1010,6 +1094,7 @@ pub struct FontAlignZone {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontThickness {
     Thin,
     Medium,
@@ -1017,6 +1102,7 @@ pub enum FontThickness {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CsmTextSettings {
     pub id: CharacterId,
     pub use_advanced_rendering: bool,
@@ -1026,6 +1112,7 @@ pub struct CsmTextSettings {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextGridFit {
     None,
     Pixel,
@@ -1033,6 +1120,7 @@ pub enum TextGridFit {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DefineBitsLossless {
     pub version: u8,
     pub id: CharacterId,
@@ -1044,6 +1132,7 @@ pub struct DefineBitsLossless {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BitmapFormat {
     ColorMap8,
     Rgb15,
@@ -1051,6 +1140,7 @@ pub enum BitmapFormat {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DefineVideoStream {
     pub id: CharacterId,
     pub num_frames: u16,
@@ -1062,6 +1152,7 @@ pub struct DefineVideoStream {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VideoDeblocking {
     UseVideoPacketValue,
     None,
@@ -1072,6 +1163,7 @@ pub enum VideoDeblocking {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VideoCodec {
     H263,
     ScreenVideo,
@@ -1080,6 +1172,7 @@ pub enum VideoCodec {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VideoFrame {
     pub stream_id: CharacterId,
     pub frame_num: u16,
@@ -1087,6 +1180,7 @@ pub struct VideoFrame {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DefineBitsJpeg3 {
     pub id: CharacterId,
     pub version: u8,
@@ -1096,6 +1190,7 @@ pub struct DefineBitsJpeg3 {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DoAbc {
     pub name: String,
     pub is_lazy_initialize: bool,
@@ -1110,6 +1205,7 @@ pub type JpegTables = Vec<u8>;
 /// Not documented in the SWF19 reference. Emitted by mxmlc.
 /// See http://wahlers.com.br/claus/blog/undocumented-swf-tags-written-by-mxmlc/
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProductInfo {
     pub product_id: u32,
     pub edition: u32,
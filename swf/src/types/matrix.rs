@@ -1,6 +1,7 @@
 use crate::Twips;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Matrix {
     /// Serialized as `scale_x` in SWF files
     pub a: f32,
@@ -0,0 +1,98 @@
+//! Serde helpers for the fields of SWF types that can't be derived.
+//!
+//! Floats that are NaN or infinite can't be written to JSON, which has no
+//! numbers for them, so they only survive formats such as YAML. Tags read from
+//! a SWF never hold them, as their floats are all read from fixed point.
+
+use enumset::{EnumSet, EnumSetType};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeSet, HashSet};
+
+/// Writes a set in a stable order, so that dumps of the same data compare
+/// equal.
+pub fn sorted_set<T, S>(set: &HashSet<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + Ord,
+    S: Serializer,
+{
+    serializer.collect_seq(set.iter().collect::<BTreeSet<_>>())
+}
+
+/// Writes an `EnumSet` as a list of its flags.
+pub mod enum_set {
+    use super::*;
+
+    pub fn serialize<T, S>(set: &EnumSet<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: EnumSetType + Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(set.iter())
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<EnumSet<T>, D::Error>
+    where
+        T: EnumSetType + Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<T>::deserialize(deserializer)?.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_data;
+    use crate::types::{ButtonColorTransform, ColorTransform, Tag};
+
+    #[test]
+    fn round_trip_tags() {
+        for (_, tag, _) in test_data::tag_tests() {
+            let json = serde_json::to_string(&tag).unwrap();
+            let parsed: Tag = serde_json::from_str(&json).unwrap();
+            if parsed != tag {
+                panic!(
+                    "Tag didn't survive a round trip through JSON.\nRead:\n{:?}\n\nExpected:\n{:?}",
+                    parsed, tag
+                );
+            }
+
+            let yaml = serde_yaml::to_string(&tag).unwrap();
+            let parsed: Tag = serde_yaml::from_str(&yaml).unwrap();
+            if parsed != tag {
+                panic!(
+                    "Tag didn't survive a round trip through YAML.\nRead:\n{:?}\n\nExpected:\n{:?}",
+                    parsed, tag
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn non_finite_floats() {
+        let tag = Tag::DefineButtonColorTransform(ButtonColorTransform {
+            id: 1,
+            color_transforms: vec![ColorTransform {
+                r_multiply: f32::NAN,
+                g_multiply: f32::INFINITY,
+                b_multiply: f32::NEG_INFINITY,
+                ..ColorTransform::new()
+            }],
+        });
+
+        let yaml = serde_yaml::to_string(&tag).unwrap();
+        match serde_yaml::from_str(&yaml).unwrap() {
+            Tag::DefineButtonColorTransform(ButtonColorTransform {
+                color_transforms, ..
+            }) => {
+                assert!(color_transforms[0].r_multiply.is_nan());
+                assert_eq!(color_transforms[0].g_multiply, f32::INFINITY);
+                assert_eq!(color_transforms[0].b_multiply, f32::NEG_INFINITY);
+            }
+            parsed => panic!("Unexpected tag {:?}", parsed),
+        }
+
+        // JSON writes them as null, which can't be read back as a float.
+        let json = serde_json::to_string(&tag).unwrap();
+        assert!(serde_json::from_str::<Tag>(&json).is_err());
+    }
+}
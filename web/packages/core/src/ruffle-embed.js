@@ -17,6 +17,7 @@ module.exports = class RuffleEmbed extends RufflePlayer {
 
     connectedCallback() {
         super.connectedCallback();
+        this.allow_script_access = this.getAttribute("allowScriptAccess");
         this.stream_swf_url(this.attributes.src.value);
     }
 
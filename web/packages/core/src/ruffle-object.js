@@ -18,6 +18,12 @@ module.exports = class RuffleObject extends RufflePlayer {
         super.connectedCallback();
 
         this.params = RuffleObject.params_of(this);
+        // Parameter names aren't case sensitive.
+        for (const [name, value] of Object.entries(this.params)) {
+            if (name.toLowerCase() === "allowscriptaccess") {
+                this.allow_script_access = value;
+            }
+        }
 
         //Kick off the SWF download.
         if (this.attributes.data) {
@@ -27,7 +27,25 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
             );
         }
 
+        self.context_menu = self.shadow.getElementById("context_menu");
+        self.container.addEventListener(
+            "contextmenu",
+            self.show_context_menu.bind(self)
+        );
+        // Clicking anywhere but the menu closes it.
+        self.context_menu.addEventListener("pointerdown", (event) =>
+            event.stopPropagation()
+        );
+        window.addEventListener(
+            "pointerdown",
+            self.hide_context_menu.bind(self)
+        );
+        window.addEventListener("blur", self.hide_context_menu.bind(self));
+
         self.instance = null;
+        self.exposed_callbacks = new Set();
+        self.allow_script_access = null;
+        self.swf_url = null;
 
         self.Ruffle = load_ruffle();
 
@@ -128,13 +146,17 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
             this.instance = null;
             console.log("Ruffle instance destroyed.");
         }
+        for (const name of this.exposed_callbacks) {
+            delete this[name];
+        }
+        this.exposed_callbacks.clear();
 
         let Ruffle = await this.Ruffle.catch(function (e) {
             console.error("Serious error loading Ruffle: " + e);
             throw e;
         });
 
-        this.instance = Ruffle.new(this.container);
+        this.instance = Ruffle.new(this.container, this);
         console.log("New Ruffle instance created.");
     }
 
@@ -153,6 +175,8 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
             if (this.isConnected && !this.is_unused_fallback_object()) {
                 console.log("Loading SWF file " + url);
 
+                this.swf_url = new URL(url, document.baseURI).href;
+
                 await this.ensure_fresh_instance();
                 this.instance.stream_from(url);
 
@@ -170,6 +194,69 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
         }
     }
 
+    /**
+     * The movie's `allowScriptAccess` parameter, if it was given one.
+     *
+     * Read by Ruffle Core to decide whether the movie may call the page's
+     * functions with `ExternalInterface.call`.
+     */
+    get allowScriptAccess() {
+        return this.allow_script_access;
+    }
+
+    /**
+     * The absolute URL of the movie, or null if it was loaded from data.
+     *
+     * Read by Ruffle Core to tell whether the movie shares the page's origin.
+     */
+    get swfUrl() {
+        return this.swf_url;
+    }
+
+    /**
+     * Called by Ruffle Core when the movie exposes a callback with
+     * `ExternalInterface.addCallback`.
+     *
+     * The callback becomes a method of this element, so that the page can
+     * call into the movie. Names that the element already has, such as
+     * `play` or `remove`, are refused so that the movie can't replace them.
+     *
+     * @param {string} name The name of the callback.
+     */
+    onCallbackAvailable(name) {
+        if (name in this && !this.exposed_callbacks.has(name)) {
+            console.warn(
+                `Refusing to expose callback ${name}, as it would replace a member of the player`
+            );
+            return;
+        }
+
+        const instance = this.instance;
+        this.exposed_callbacks.add(name);
+        this[name] = (...args) => {
+            return instance.call_exposed_callback(name, args);
+        };
+    }
+
+    /**
+     * Called by Ruffle Core when the movie sends an FSCommand.
+     *
+     * Like the Flash plugin, this calls the page's `<name>_DoFSCommand`
+     * function, where `<name>` is the `id` or `name` of this element.
+     *
+     * @param {string} command The FSCommand.
+     * @param {string} args The arguments of the FSCommand.
+     */
+    onFSCommand(command, args) {
+        const name = this.id || this.getAttribute("name");
+        const handler = name ? window[name + "_DoFSCommand"] : undefined;
+        if (typeof handler === "function") {
+            handler(command, args);
+        } else {
+            console.log(`Unhandled FSCommand: ${command} ${args}`);
+        }
+    }
+
     play_button_clicked() {
         if (this.instance) {
             this.instance.play();
@@ -179,6 +266,72 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
         }
     }
 
+    /**
+     * Show the movie's context menu in place of the browser's own.
+     *
+     * @param {MouseEvent} event The `contextmenu` event.
+     */
+    show_context_menu(event) {
+        event.preventDefault();
+        if (!this.instance || this.context_menu.contains(event.target)) {
+            return;
+        }
+
+        const items = this.instance.prepare_context_menu();
+        this.context_menu.textContent = "";
+        items.forEach((item, index) => {
+            if (item.separatorBefore) {
+                const separator = document.createElement("li");
+                separator.className = "menu_separator";
+                this.context_menu.appendChild(separator);
+            }
+
+            const element = document.createElement("li");
+            element.className = "menu_item";
+            element.textContent = item.caption;
+            element.classList.toggle("disabled", !item.enabled);
+            element.classList.toggle("checked", item.checked);
+            if (item.enabled) {
+                element.addEventListener("click", () => {
+                    this.context_menu.style.display = "none";
+                    this.instance.run_context_menu_callback(index);
+                });
+            }
+            this.context_menu.appendChild(element);
+        });
+
+        if (items.length === 0) {
+            this.instance.clear_context_menu();
+            return;
+        }
+
+        // Open the menu at the cursor, but keep it inside the player.
+        this.context_menu.style.display = "block";
+        const rect = this.container.getBoundingClientRect();
+        const x = Math.min(
+            event.clientX - rect.left,
+            rect.width - this.context_menu.offsetWidth
+        );
+        const y = Math.min(
+            event.clientY - rect.top,
+            rect.height - this.context_menu.offsetHeight
+        );
+        this.context_menu.style.left = Math.max(x, 0) + "px";
+        this.context_menu.style.top = Math.max(y, 0) + "px";
+    }
+
+    /**
+     * Close the context menu without choosing an item.
+     */
+    hide_context_menu() {
+        if (this.context_menu.style.display === "block") {
+            this.context_menu.style.display = "none";
+            if (this.instance) {
+                this.instance.clear_context_menu();
+            }
+        }
+    }
+
     /**
      * Load a movie's data into this Ruffle Player instance.
      *
@@ -194,6 +347,7 @@ exports.RufflePlayer = class RufflePlayer extends HTMLElement {
         if (this.isConnected && !this.is_unused_fallback_object()) {
             console.log("Got SWF data");
 
+            this.swf_url = null;
             await this.ensure_fresh_instance();
             this.instance.load_data(new Uint8Array(data));
             console.log("New Ruffle instance created.");
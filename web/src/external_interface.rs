@@ -0,0 +1,209 @@
+//! `ExternalInterface` calls to and from the page
+
+use crate::JavascriptPlayer;
+use js_sys::{Array, Function, Object, Reflect};
+use ruffle_core::backend::external_interface::{ExternalInterfaceProvider, ExternalValue};
+use std::collections::BTreeMap;
+use url::Url;
+use wasm_bindgen::{JsCast, JsValue};
+
+pub struct WebExternalInterfaceProvider {
+    js_player: JavascriptPlayer,
+}
+
+impl WebExternalInterfaceProvider {
+    pub fn new(js_player: JavascriptPlayer) -> Self {
+        Self { js_player }
+    }
+
+    /// Whether the movie may call into the page, as decided by its
+    /// `allowScriptAccess` parameter.
+    fn script_access_allowed(&self) -> bool {
+        let page_url = web_sys::window().and_then(|window| window.location().href().ok());
+        script_access_allowed(
+            self.js_player.allow_script_access().as_deref(),
+            self.js_player.swf_url().as_deref(),
+            page_url.as_deref(),
+        )
+    }
+
+    /// Find the function named by a dotted path such as `console.log`, along
+    /// with the object it should be called on.
+    fn lookup(name: &str) -> Option<(JsValue, Function)> {
+        let mut this: JsValue = web_sys::window()?.into();
+        let mut value = this.clone();
+        for part in name.split('.') {
+            this = value;
+            value = Reflect::get(&this, &part.into()).ok()?;
+            if value.is_undefined() || value.is_null() {
+                return None;
+            }
+        }
+        value.dyn_into().ok().map(|function| (this, function))
+    }
+}
+
+impl ExternalInterfaceProvider for WebExternalInterfaceProvider {
+    fn available(&self) -> bool {
+        self.script_access_allowed()
+    }
+
+    fn call(&mut self, name: &str, args: &[ExternalValue]) -> Option<ExternalValue> {
+        if !self.script_access_allowed() {
+            log::warn!("allowScriptAccess forbids the movie to call {}", name);
+            return None;
+        }
+
+        let (this, function) = match Self::lookup(name) {
+            Some(found) => found,
+            None => {
+                log::warn!("Movie called unknown external method {}", name);
+                return None;
+            }
+        };
+
+        let args: Array = args.iter().map(to_js_value).collect();
+        match function.apply(&this, &args) {
+            Ok(result) => Some(from_js_value(&result)),
+            Err(e) => {
+                log::warn!("External method {} threw {:?}", name, e);
+                Some(ExternalValue::Undefined)
+            }
+        }
+    }
+
+    fn on_callback_available(&mut self, name: &str) -> bool {
+        // Like Flash, only movies that may script the page can expose
+        // callbacks to it.
+        if !self.script_access_allowed() {
+            log::warn!("allowScriptAccess forbids the movie to expose {}", name);
+            return false;
+        }
+
+        self.js_player.on_callback_available(name);
+        true
+    }
+}
+
+/// Decide whether a movie may script the page that embeds it.
+///
+/// `allowScriptAccess` is one of `always`, `never` or `sameDomain`, which is
+/// also the default. `sameDomain` requires the movie to be served from the
+/// origin of the page; movies without a URL were handed over by the page
+/// itself, so they are considered to share its origin.
+fn script_access_allowed(
+    allow_script_access: Option<&str>,
+    swf_url: Option<&str>,
+    page_url: Option<&str>,
+) -> bool {
+    let allow_script_access = allow_script_access.map(|value| value.to_ascii_lowercase());
+    match allow_script_access.as_deref() {
+        Some("always") => true,
+        Some("never") => false,
+        _ => {
+            let page_url = match page_url.and_then(|url| Url::parse(url).ok()) {
+                Some(page_url) => page_url,
+                None => return false,
+            };
+            match swf_url {
+                Some(swf_url) => page_url
+                    .join(swf_url)
+                    .map(|swf_url| swf_url.origin() == page_url.origin())
+                    .unwrap_or(false),
+                None => true,
+            }
+        }
+    }
+}
+
+pub fn to_js_value(value: &ExternalValue) -> JsValue {
+    match value {
+        ExternalValue::Undefined => JsValue::UNDEFINED,
+        ExternalValue::Null => JsValue::NULL,
+        ExternalValue::Bool(b) => JsValue::from_bool(*b),
+        ExternalValue::Number(n) => JsValue::from_f64(*n),
+        ExternalValue::String(s) => JsValue::from_str(s),
+        ExternalValue::List(elements) => elements.iter().map(to_js_value).collect::<Array>().into(),
+        ExternalValue::Object(properties) => {
+            let object = Object::new();
+            for (key, property) in properties {
+                let _ = Reflect::set(&object, &key.into(), &to_js_value(property));
+            }
+            object.into()
+        }
+    }
+}
+
+pub fn from_js_value(value: &JsValue) -> ExternalValue {
+    if value.is_undefined() || value.is_function() {
+        ExternalValue::Undefined
+    } else if value.is_null() {
+        ExternalValue::Null
+    } else if let Some(b) = value.as_bool() {
+        ExternalValue::Bool(b)
+    } else if let Some(n) = value.as_f64() {
+        ExternalValue::Number(n)
+    } else if let Some(s) = value.as_string() {
+        ExternalValue::String(s)
+    } else if Array::is_array(value) {
+        let array: &Array = value.unchecked_ref();
+        ExternalValue::List(
+            array
+                .iter()
+                .map(|element| from_js_value(&element))
+                .collect(),
+        )
+    } else if let Some(object) = value.dyn_ref::<Object>() {
+        let mut properties = BTreeMap::new();
+        for key in Object::keys(object).iter() {
+            if let (Some(name), Ok(property)) = (key.as_string(), Reflect::get(object, &key)) {
+                properties.insert(name, from_js_value(&property));
+            }
+        }
+        ExternalValue::Object(properties)
+    } else {
+        ExternalValue::Undefined
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: Option<&str> = Some("https://example.com/games/index.html");
+
+    #[test]
+    fn same_domain_is_the_default() {
+        for access in &[None, Some("sameDomain"), Some("SAMEDOMAIN"), Some("bogus")] {
+            assert!(script_access_allowed(*access, Some("movie.swf"), PAGE));
+            assert!(script_access_allowed(
+                *access,
+                Some("https://example.com/other/movie.swf"),
+                PAGE
+            ));
+            assert!(!script_access_allowed(
+                *access,
+                Some("https://cdn.example.net/movie.swf"),
+                PAGE
+            ));
+            assert!(!script_access_allowed(
+                *access,
+                Some("http://example.com/movie.swf"),
+                PAGE
+            ));
+        }
+        assert!(script_access_allowed(None, None, PAGE));
+    }
+
+    #[test]
+    fn always_and_never() {
+        let swf = Some("https://cdn.example.net/movie.swf");
+        assert!(script_access_allowed(Some("always"), swf, PAGE));
+        assert!(!script_access_allowed(
+            Some("never"),
+            Some("movie.swf"),
+            PAGE
+        ));
+        assert!(!script_access_allowed(Some("Never"), None, PAGE));
+    }
+}
@@ -1,5 +1,5 @@
 use ruffle_core::backend::input::{InputBackend, MouseCursor};
-use ruffle_core::events::KeyCode;
+use ruffle_core::events::{KeyCode, MouseButton};
 use ruffle_web_common::JsResult;
 use std::collections::HashSet;
 use web_sys::HtmlCanvasElement;
@@ -8,6 +8,7 @@ use web_sys::HtmlCanvasElement;
 /// APIs
 pub struct WebInputBackend {
     keys_down: HashSet<String>,
+    mouse_buttons_down: HashSet<MouseButton>,
     canvas: HtmlCanvasElement,
     cursor_visible: bool,
     cursor: MouseCursor,
@@ -18,6 +19,7 @@ impl WebInputBackend {
     pub fn new(canvas: &HtmlCanvasElement) -> Self {
         Self {
             keys_down: HashSet::new(),
+            mouse_buttons_down: HashSet::new(),
             canvas: canvas.clone(),
             cursor_visible: true,
             cursor: MouseCursor::Arrow,
@@ -36,6 +38,15 @@ impl WebInputBackend {
         self.keys_down.remove(&code);
     }
 
+    /// Register a mouse button being pressed or released.
+    pub fn set_mouse_button_down(&mut self, button: MouseButton, is_down: bool) {
+        if is_down {
+            self.mouse_buttons_down.insert(button);
+        } else {
+            self.mouse_buttons_down.remove(&button);
+        }
+    }
+
     fn update_mouse_cursor(&self) {
         let cursor = if self.cursor_visible {
             match self.cursor {
@@ -58,6 +69,9 @@ impl InputBackend for WebInputBackend {
     fn is_key_down(&self, key: KeyCode) -> bool {
         match key {
             KeyCode::Unknown => false,
+            KeyCode::LeftMouse => self.mouse_buttons_down.contains(&MouseButton::Left),
+            KeyCode::RightMouse => self.mouse_buttons_down.contains(&MouseButton::Right),
+            KeyCode::MiddleMouse => self.mouse_buttons_down.contains(&MouseButton::Middle),
             KeyCode::Backspace => self.keys_down.contains("Backspace"),
             KeyCode::Return => self.keys_down.contains("Enter"),
             KeyCode::Shift => {
@@ -189,6 +203,17 @@ impl InputBackend for WebInputBackend {
     }
 }
 
+/// Converts a Web `MouseEvent.button` value into a Ruffle `MouseButton`.
+/// Returns `None` for the back and forward buttons, which Flash ignores.
+pub fn web_to_ruffle_mouse_button(button: i16) -> Option<MouseButton> {
+    match button {
+        0 => Some(MouseButton::Left),
+        1 => Some(MouseButton::Middle),
+        2 => Some(MouseButton::Right),
+        _ => None,
+    }
+}
+
 /// Converts a Web `KeyboardEvent.code` value into a Ruffle `KeyCode`.
 /// Returns `None` if there is no matching Flash key key.
 pub fn web_to_ruffle_key_code(key_code: &str) -> Option<KeyCode> {
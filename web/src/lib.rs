@@ -2,24 +2,31 @@
 
 //! Ruffle web frontend.
 mod audio;
+mod external_interface;
 mod input;
+mod log_adapter;
 mod navigator;
 mod storage;
 
+use crate::external_interface::{from_js_value, to_js_value, WebExternalInterfaceProvider};
+use crate::log_adapter::WebLogBackend;
 use crate::storage::LocalStorageBackend;
 use crate::{audio::WebAudioBackend, input::WebInputBackend, navigator::WebNavigatorBackend};
 use generational_arena::{Arena, Index};
-use js_sys::Uint8Array;
+use js_sys::{Array, Object, Reflect, Uint8Array};
 use ruffle_core::backend::render::RenderBackend;
 use ruffle_core::backend::storage::MemoryStorageBackend;
 use ruffle_core::backend::storage::StorageBackend;
+use ruffle_core::events::MouseWheelDelta;
 use ruffle_core::tag_utils::SwfMovie;
 use ruffle_core::PlayerEvent;
 use ruffle_web_common::JsResult;
 use std::sync::{Arc, Mutex};
 use std::{cell::RefCell, error::Error, num::NonZeroI32};
 use wasm_bindgen::{prelude::*, JsCast, JsValue};
-use web_sys::{Element, EventTarget, HtmlCanvasElement, HtmlElement, KeyboardEvent, PointerEvent};
+use web_sys::{
+    Element, EventTarget, HtmlCanvasElement, HtmlElement, KeyboardEvent, PointerEvent, WheelEvent,
+};
 
 thread_local! {
     /// We store the actual instances of the ruffle core in a static pool.
@@ -30,6 +37,30 @@ thread_local! {
 
 type AnimationHandler = Closure<dyn FnMut(f64)>;
 
+#[wasm_bindgen]
+extern "C" {
+    /// The `RufflePlayer` element that owns a player instance.
+    #[derive(Clone)]
+    pub type JavascriptPlayer;
+
+    /// Tell the element that the movie exposed a callback, so that it can be
+    /// made callable on the element.
+    #[wasm_bindgen(method, js_name = "onCallbackAvailable")]
+    pub fn on_callback_available(this: &JavascriptPlayer, name: &str);
+
+    /// The movie's `allowScriptAccess` parameter, if it was given one.
+    #[wasm_bindgen(method, getter, js_name = "allowScriptAccess")]
+    pub fn allow_script_access(this: &JavascriptPlayer) -> Option<String>;
+
+    /// The absolute URL of the movie, unless it was loaded from data.
+    #[wasm_bindgen(method, getter, js_name = "swfUrl")]
+    pub fn swf_url(this: &JavascriptPlayer) -> Option<String>;
+
+    /// Forward an FSCommand to the page's `<name>_DoFSCommand` function.
+    #[wasm_bindgen(method, js_name = "onFSCommand")]
+    pub fn on_fs_command(this: &JavascriptPlayer, command: &str, args: &str);
+}
+
 struct RuffleInstance {
     core: Arc<Mutex<ruffle_core::Player>>,
     canvas: HtmlCanvasElement,
@@ -44,6 +75,7 @@ struct RuffleInstance {
     mouse_down_callback: Option<Closure<dyn FnMut(PointerEvent)>>,
     mouse_up_callback: Option<Closure<dyn FnMut(PointerEvent)>>,
     window_mouse_down_callback: Option<Closure<dyn FnMut(PointerEvent)>>,
+    mouse_wheel_callback: Option<Closure<dyn FnMut(WheelEvent)>>,
     key_down_callback: Option<Closure<dyn FnMut(KeyboardEvent)>>,
     key_up_callback: Option<Closure<dyn FnMut(KeyboardEvent)>>,
     has_focus: bool,
@@ -58,8 +90,8 @@ pub struct Ruffle(Index);
 
 #[wasm_bindgen]
 impl Ruffle {
-    pub fn new(parent: HtmlElement) -> Result<Ruffle, JsValue> {
-        Ruffle::new_internal(parent).map_err(|_| "Error creating player".into())
+    pub fn new(parent: HtmlElement, js_player: JavascriptPlayer) -> Result<Ruffle, JsValue> {
+        Ruffle::new_internal(parent, js_player).map_err(|_| "Error creating player".into())
     }
 
     /// Stream an arbitrary movie file from (presumably) the Internet.
@@ -69,7 +101,9 @@ impl Ruffle {
         INSTANCES.with(|instances| {
             let mut instances = instances.borrow_mut();
             let instance = instances.get_mut(self.0).unwrap();
-            instance.core.lock().unwrap().fetch_root_movie(movie_url);
+            let mut core = instance.core.lock().unwrap();
+            migrate_legacy_objects(&mut core, Some(movie_url));
+            core.fetch_root_movie(movie_url);
         });
     }
 
@@ -77,16 +111,22 @@ impl Ruffle {
     ///
     /// This method should only be called once per player.
     pub fn load_data(&mut self, swf_data: Uint8Array) -> Result<(), JsValue> {
+        // The page handed the movie over itself, so it belongs to the page's
+        // origin.
+        let page_url = web_sys::window().and_then(|window| window.location().href().ok());
         let movie = Arc::new({
             let mut data = vec![0; swf_data.length() as usize];
             swf_data.copy_to(&mut data[..]);
-            SwfMovie::from_data(&data, None).map_err(|e| format!("Error loading movie: {}", e))?
+            SwfMovie::from_data(&data, page_url.clone())
+                .map_err(|e| format!("Error loading movie: {}", e))?
         });
 
         INSTANCES.with(|instances| {
             let mut instances = instances.borrow_mut();
             let instance = instances.get_mut(self.0).unwrap();
-            instance.core.lock().unwrap().set_root_movie(movie);
+            let mut core = instance.core.lock().unwrap();
+            migrate_legacy_objects(&mut core, page_url.as_deref());
+            core.set_root_movie(movie);
         });
 
         Ok(())
@@ -102,6 +142,80 @@ impl Ruffle {
         });
     }
 
+    /// Invoke a callback that the movie exposed with `ExternalInterface.addCallback`.
+    pub fn call_exposed_callback(&self, name: &str, args: Box<[JsValue]>) -> JsValue {
+        let args: Vec<_> = args.iter().map(from_js_value).collect();
+
+        // The player may already be busy if the page calls back into the movie
+        // from inside an `ExternalInterface.call`.
+        let core = INSTANCES.with(|instances| {
+            let instances = instances.try_borrow().ok()?;
+            Some(instances.get(self.0)?.core.clone())
+        });
+        let result = core.and_then(|core| {
+            let mut core = core.try_lock().ok()?;
+            core.call_external_callback(name, &args)
+        });
+
+        match result {
+            Some(result) => to_js_value(&result),
+            None => {
+                log::warn!("Unable to call external callback {}", name);
+                JsValue::UNDEFINED
+            }
+        }
+    }
+
+    /// Build the context menu for a right-click at the current mouse position.
+    ///
+    /// Returns an array of items, each with a `caption`, and `enabled`,
+    /// `checked` and `separatorBefore` flags.
+    pub fn prepare_context_menu(&mut self) -> JsValue {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            let instance = instances.get_mut(self.0).unwrap();
+            let items = instance.core.lock().unwrap().prepare_context_menu();
+            items
+                .iter()
+                .map(|item| {
+                    let object = Object::new();
+                    let _ = Reflect::set(&object, &"caption".into(), &item.caption.as_str().into());
+                    let _ = Reflect::set(&object, &"enabled".into(), &item.enabled.into());
+                    let _ = Reflect::set(&object, &"checked".into(), &item.checked.into());
+                    let _ = Reflect::set(
+                        &object,
+                        &"separatorBefore".into(),
+                        &item.separator_before.into(),
+                    );
+                    JsValue::from(object)
+                })
+                .collect::<Array>()
+                .into()
+        })
+    }
+
+    /// Run the action of the context menu item at `index`, and close the menu.
+    pub fn run_context_menu_callback(&mut self, index: usize) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            let instance = instances.get_mut(self.0).unwrap();
+            instance
+                .core
+                .lock()
+                .unwrap()
+                .run_context_menu_callback(index);
+        });
+    }
+
+    /// Close the context menu without choosing an item.
+    pub fn clear_context_menu(&mut self) {
+        INSTANCES.with(|instances| {
+            let mut instances = instances.borrow_mut();
+            let instance = instances.get_mut(self.0).unwrap();
+            instance.core.lock().unwrap().clear_context_menu();
+        });
+    }
+
     pub fn destroy(&mut self) -> Result<(), JsValue> {
         // Remove instance from the active list.
         if let Some(mut instance) = INSTANCES.with(|instances| {
@@ -122,6 +236,7 @@ impl Ruffle {
             instance.mouse_move_callback = None;
             instance.mouse_up_callback = None;
             instance.window_mouse_down_callback = None;
+            instance.mouse_wheel_callback = None;
 
             // Cancel the animation handler, if it's still active.
             if let Some(id) = instance.animation_handler_id {
@@ -137,7 +252,10 @@ impl Ruffle {
 }
 
 impl Ruffle {
-    fn new_internal(parent: HtmlElement) -> Result<Ruffle, Box<dyn Error>> {
+    fn new_internal(
+        parent: HtmlElement,
+        js_player: JavascriptPlayer,
+    ) -> Result<Ruffle, Box<dyn Error>> {
         console_error_panic_hook::set_once();
         let _ = console_log::init_with_level(log::Level::Trace);
 
@@ -153,17 +271,37 @@ impl Ruffle {
         let navigator = Box::new(WebNavigatorBackend::new());
         let input = Box::new(WebInputBackend::new(&canvas));
 
-        let current_domain = window.location().href().unwrap();
-
+        // Shared object keys are already scoped by the domain and path of the
+        // movie. Older versions scoped them by the page URL instead.
+        let current_url = window.location().href().unwrap_or_default();
         let local_storage = window
             .local_storage()
             .unwrap()
             .map(|s| {
-                Box::new(LocalStorageBackend::new(s, current_domain)) as Box<dyn StorageBackend>
+                Box::new(LocalStorageBackend::new(
+                    s,
+                    "ruffle".to_string(),
+                    current_url,
+                )) as Box<dyn StorageBackend>
             })
             .unwrap_or_else(|| Box::new(MemoryStorageBackend::default()));
 
+        // Browsers don't allow raw TCP connections, so the player keeps its
+        // null socket backend.
+        let external_interface = Box::new(WebExternalInterfaceProvider::new(js_player.clone()));
+
         let core = ruffle_core::Player::new(renderer, audio, navigator, input, local_storage)?;
+        {
+            let mut core = core.lock().unwrap();
+            // Movies on the web are never trusted, even when the page itself
+            // was opened from the filesystem.
+            core.set_trust_local_content(false);
+            core.set_external_interface_backend(external_interface);
+            core.set_log_backend(Box::new(WebLogBackend::new()));
+            core.set_fs_command_handler(Box::new(move |command, args| {
+                js_player.on_fs_command(command, args)
+            }));
+        }
 
         // Create instance.
         let instance = RuffleInstance {
@@ -178,6 +316,7 @@ impl Ruffle {
             mouse_down_callback: None,
             window_mouse_down_callback: None,
             mouse_up_callback: None,
+            mouse_wheel_callback: None,
             key_down_callback: None,
             key_up_callback: None,
             timestamp: None,
@@ -244,11 +383,21 @@ impl Ruffle {
                                     .unchecked_ref::<Element>()
                                     .set_pointer_capture(js_event.pointer_id());
                             }
-                            let event = PlayerEvent::MouseDown {
-                                x: f64::from(js_event.offset_x()) * instance.device_pixel_ratio,
-                                y: f64::from(js_event.offset_y()) * instance.device_pixel_ratio,
-                            };
-                            instance.core.lock().unwrap().handle_event(event);
+                            if let Some(button) =
+                                input::web_to_ruffle_mouse_button(js_event.button())
+                            {
+                                let event = PlayerEvent::MouseDown {
+                                    x: f64::from(js_event.offset_x()) * instance.device_pixel_ratio,
+                                    y: f64::from(js_event.offset_y()) * instance.device_pixel_ratio,
+                                    button,
+                                };
+                                let mut core = instance.core.lock().unwrap();
+                                core.input_mut()
+                                    .downcast_mut::<WebInputBackend>()
+                                    .unwrap()
+                                    .set_mouse_button_down(button, true);
+                                core.handle_event(event);
+                            }
                             js_event.prevent_default();
                         }
                     });
@@ -301,11 +450,21 @@ impl Ruffle {
                                     .unchecked_ref::<Element>()
                                     .release_pointer_capture(js_event.pointer_id());
                             }
-                            let event = PlayerEvent::MouseUp {
-                                x: f64::from(js_event.offset_x()) * instance.device_pixel_ratio,
-                                y: f64::from(js_event.offset_y()) * instance.device_pixel_ratio,
-                            };
-                            instance.core.lock().unwrap().handle_event(event);
+                            if let Some(button) =
+                                input::web_to_ruffle_mouse_button(js_event.button())
+                            {
+                                let event = PlayerEvent::MouseUp {
+                                    x: f64::from(js_event.offset_x()) * instance.device_pixel_ratio,
+                                    y: f64::from(js_event.offset_y()) * instance.device_pixel_ratio,
+                                    button,
+                                };
+                                let mut core = instance.core.lock().unwrap();
+                                core.input_mut()
+                                    .downcast_mut::<WebInputBackend>()
+                                    .unwrap()
+                                    .set_mouse_button_down(button, false);
+                                core.handle_event(event);
+                            }
                             if instance.has_focus {
                                 js_event.prevent_default();
                             }
@@ -324,6 +483,45 @@ impl Ruffle {
                 instance.mouse_up_callback = Some(mouse_up_callback);
             }
 
+            // Create mouse wheel handler.
+            {
+                let mouse_wheel_callback = Closure::wrap(Box::new(move |js_event: WheelEvent| {
+                    INSTANCES.with(move |instances| {
+                        let mut instances = instances.borrow_mut();
+                        if let Some(instance) = instances.get_mut(index) {
+                            // Browsers scroll down for positive deltas, while Flash scrolls up.
+                            let delta = match js_event.delta_mode() {
+                                WheelEvent::DOM_DELTA_LINE => {
+                                    MouseWheelDelta::Lines(-js_event.delta_y())
+                                }
+                                WheelEvent::DOM_DELTA_PAGE => {
+                                    MouseWheelDelta::Lines(-js_event.delta_y() * 3.0)
+                                }
+                                _ => MouseWheelDelta::Pixels(-js_event.delta_y()),
+                            };
+                            instance
+                                .core
+                                .lock()
+                                .unwrap()
+                                .handle_event(PlayerEvent::MouseWheel { delta });
+                            if instance.has_focus {
+                                js_event.prevent_default();
+                            }
+                        }
+                    });
+                })
+                    as Box<dyn FnMut(WheelEvent)>);
+                let canvas_events: &EventTarget = canvas.as_ref();
+                canvas_events
+                    .add_event_listener_with_callback(
+                        "wheel",
+                        mouse_wheel_callback.as_ref().unchecked_ref(),
+                    )
+                    .unwrap();
+                let instance = instances.get_mut(index).unwrap();
+                instance.mouse_wheel_callback = Some(mouse_wheel_callback);
+            }
+
             // Create click event handler.
             // {
             //     let click_callback = Closure::wrap(Box::new(move |_| {
@@ -559,3 +757,11 @@ fn create_renderer(
 
     Err("Unable to create renderer".into())
 }
+
+/// Move the shared objects that older versions of Ruffle saved on this page
+/// to their keys for the movie at `movie_url`.
+fn migrate_legacy_objects(core: &mut ruffle_core::Player, movie_url: Option<&str>) {
+    if let Some(storage) = core.storage_mut().downcast_mut::<LocalStorageBackend>() {
+        storage.migrate_legacy_objects(movie_url);
+    }
+}
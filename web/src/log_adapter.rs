@@ -0,0 +1,21 @@
+use ruffle_core::backend::log::{LogBackend, LogSource};
+use wasm_bindgen::JsValue;
+
+/// Log backend that writes to the browser console.
+pub struct WebLogBackend;
+
+impl WebLogBackend {
+    pub(crate) fn new() -> Self {
+        WebLogBackend
+    }
+}
+
+impl LogBackend for WebLogBackend {
+    fn trace(&mut self, _source: &LogSource, message: &str) {
+        web_sys::console::log_1(&JsValue::from_str(message));
+    }
+
+    fn avm_warning(&mut self, _source: &LogSource, message: &str) {
+        web_sys::console::warn_1(&JsValue::from_str(message));
+    }
+}
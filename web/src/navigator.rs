@@ -1,8 +1,9 @@
 //! Navigator backend for web
 
-use js_sys::{Array, ArrayBuffer, Uint8Array};
+use js_sys::{Array, ArrayBuffer, Function, Promise, Reflect, Uint8Array};
 use ruffle_core::backend::navigator::{
-    url_from_relative_url, NavigationMethod, NavigatorBackend, OwnedFuture, RequestOptions,
+    url_from_relative_url, ChunkStream, NavigationMethod, NavigatorBackend, OwnedFuture,
+    RequestOptions,
 };
 use ruffle_core::loader::Error;
 use std::borrow::Cow;
@@ -97,48 +98,7 @@ impl NavigatorBackend for WebNavigatorBackend {
     fn fetch(&self, url: &str, options: RequestOptions) -> OwnedFuture<Vec<u8>, Error> {
         let url = url.to_string();
         Box::pin(async move {
-            let mut init = RequestInit::new();
-
-            init.method(match options.method() {
-                NavigationMethod::GET => "GET",
-                NavigationMethod::POST => "POST",
-            });
-
-            if let Some((data, mime)) = options.body() {
-                let arraydata = ArrayBuffer::new(data.len() as u32);
-                let u8data = Uint8Array::new(&arraydata);
-
-                for (i, byte) in data.iter().enumerate() {
-                    u8data.fill(*byte, i as u32, i as u32 + 1);
-                }
-
-                let blobparts = Array::new();
-                blobparts.push(&arraydata);
-
-                let mut blobprops = BlobPropertyBag::new();
-                blobprops.type_(mime);
-
-                let datablob =
-                    Blob::new_with_buffer_source_sequence_and_options(&blobparts, &blobprops)
-                        .unwrap()
-                        .dyn_into()
-                        .unwrap();
-
-                init.body(Some(&datablob));
-            }
-
-            let request = Request::new_with_str_and_init(&url, &init).unwrap();
-
-            let window = web_sys::window().unwrap();
-            let fetchval = JsFuture::from(window.fetch_with_request(&request)).await;
-            if fetchval.is_err() {
-                return Err(Error::NetworkError(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "Could not fetch, got JS Error",
-                )));
-            }
-
-            let resp: Response = fetchval.unwrap().dyn_into().unwrap();
+            let resp = fetch_response(&url, options).await?;
             let data: ArrayBuffer = JsFuture::from(resp.array_buffer().unwrap())
                 .await
                 .unwrap()
@@ -152,6 +112,51 @@ impl NavigatorBackend for WebNavigatorBackend {
         })
     }
 
+    fn fetch_stream(&self, url: &str, options: RequestOptions) -> ChunkStream {
+        let url = url.to_string();
+        ChunkStream::new(|sink| {
+            Box::pin(async move {
+                let resp = fetch_response(&url, options).await?;
+                if let Some(length) = resp
+                    .headers()
+                    .get("Content-Length")
+                    .ok()
+                    .flatten()
+                    .and_then(|length| length.parse().ok())
+                {
+                    sink.set_total_length(length);
+                }
+
+                let body = match resp.body() {
+                    Some(body) => body,
+                    None => return Ok(()),
+                };
+                let reader = body.get_reader();
+                let read: Function = Reflect::get(&reader, &"read".into())
+                    .and_then(|read| read.dyn_into())
+                    .map_err(|_| js_error("Could not read response"))?;
+                loop {
+                    let result = read
+                        .call0(&reader)
+                        .and_then(|promise| promise.dyn_into::<Promise>())
+                        .map_err(|_| js_error("Could not read response"))?;
+                    let result = JsFuture::from(result)
+                        .await
+                        .map_err(|_| js_error("Could not read response, got JS Error"))?;
+                    let done = Reflect::get(&result, &"done".into())
+                        .map(|done| done.is_truthy())
+                        .unwrap_or(true);
+                    if done {
+                        return Ok(());
+                    }
+                    if let Ok(value) = Reflect::get(&result, &"value".into()) {
+                        sink.send(Uint8Array::new(&value).to_vec());
+                    }
+                }
+            })
+        })
+    }
+
     fn spawn_future(&mut self, future: OwnedFuture<(), Error>) {
         spawn_local(async move {
             if let Err(e) = future.await {
@@ -173,3 +178,50 @@ impl NavigatorBackend for WebNavigatorBackend {
         url.into()
     }
 }
+
+/// Make a request with the browser's `fetch`, resolving once the response
+/// headers have arrived.
+async fn fetch_response(url: &str, options: RequestOptions) -> Result<Response, Error> {
+    let mut init = RequestInit::new();
+
+    init.method(match options.method() {
+        NavigationMethod::GET => "GET",
+        NavigationMethod::POST => "POST",
+    });
+
+    if let Some((data, mime)) = options.body() {
+        let arraydata = ArrayBuffer::new(data.len() as u32);
+        let u8data = Uint8Array::new(&arraydata);
+
+        for (i, byte) in data.iter().enumerate() {
+            u8data.fill(*byte, i as u32, i as u32 + 1);
+        }
+
+        let blobparts = Array::new();
+        blobparts.push(&arraydata);
+
+        let mut blobprops = BlobPropertyBag::new();
+        blobprops.type_(mime);
+
+        let datablob = Blob::new_with_buffer_source_sequence_and_options(&blobparts, &blobprops)
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+
+        init.body(Some(&datablob));
+    }
+
+    let request = Request::new_with_str_and_init(url, &init).unwrap();
+
+    let window = web_sys::window().unwrap();
+    let fetchval = JsFuture::from(window.fetch_with_request(&request)).await;
+    if fetchval.is_err() {
+        return Err(js_error("Could not fetch, got JS Error"));
+    }
+
+    Ok(fetchval.unwrap().dyn_into().unwrap())
+}
+
+fn js_error(message: &str) -> Error {
+    Error::NetworkError(std::io::Error::new(std::io::ErrorKind::Other, message))
+}
@@ -1,14 +1,52 @@
-use ruffle_core::backend::storage::StorageBackend;
+use ruffle_core::backend::storage::{shared_object_key, StorageBackend};
 use web_sys::Storage;
 
 pub struct LocalStorageBackend {
     storage: Storage,
     prefix: String,
+
+    /// The prefix that older versions of Ruffle stored the shared objects of
+    /// this page under, which is the URL of the page.
+    legacy_prefix: String,
 }
 
 impl LocalStorageBackend {
-    pub(crate) fn new(storage: Storage, prefix: String) -> Self {
-        LocalStorageBackend { storage, prefix }
+    pub(crate) fn new(storage: Storage, prefix: String, legacy_prefix: String) -> Self {
+        LocalStorageBackend {
+            storage,
+            prefix,
+            legacy_prefix,
+        }
+    }
+
+    /// Move the shared objects saved by older versions of Ruffle on this page
+    /// to their keys for the movie at `movie_url`.
+    ///
+    /// Those versions stored every object under the page URL and the bare
+    /// object name, regardless of where the movie came from.
+    pub(crate) fn migrate_legacy_objects(&self, movie_url: Option<&str>) {
+        let legacy_prefix = format!("{}-", self.legacy_prefix);
+        let length = self.storage.length().unwrap_or_default();
+        let legacy_keys: Vec<String> = (0..length)
+            .filter_map(|i| self.storage.key(i).unwrap_or_default())
+            .filter(|key| key.starts_with(&legacy_prefix))
+            .collect();
+
+        for legacy_key in legacy_keys {
+            let name = &legacy_key[legacy_prefix.len()..];
+            let key = match shared_object_key(movie_url, name, None, false) {
+                Some(key) => format!("{}-{}", self.prefix, key),
+                None => continue,
+            };
+            if self.storage.get(&key).unwrap_or_default().is_some() {
+                continue;
+            }
+            if let Some(value) = self.storage.get(&legacy_key).unwrap_or_default() {
+                if self.storage.set(&key, &value).is_ok() {
+                    let _ = self.storage.delete(&legacy_key);
+                }
+            }
+        }
     }
 }
 
@@ -28,4 +66,15 @@ impl StorageBackend for LocalStorageBackend {
     fn remove_key(&mut self, name: &str) {
         let _ = self.storage.delete(&format!("{}-{}", self.prefix, name));
     }
+
+    fn get_usage(&self, prefix: &str) -> usize {
+        let prefix = format!("{}-{}", self.prefix, prefix);
+        let length = self.storage.length().unwrap_or_default();
+        (0..length)
+            .filter_map(|i| self.storage.key(i).unwrap_or_default())
+            .filter(|key| key.starts_with(&prefix))
+            .filter_map(|key| self.storage.get(&key).unwrap_or_default())
+            .map(|value| value.len())
+            .sum()
+    }
 }